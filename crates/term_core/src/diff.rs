@@ -0,0 +1,406 @@
+//! Screen diffing for remote renderers.
+//!
+//! [`ScreenDiffer`] remembers the last visible screen it was shown and
+//! turns the next [`RenderSnapshot`] into a [`ScreenChanges`]: runs of
+//! cells that differ, plus cursor and title. The first snapshot — and
+//! any after a resize — is sent whole. Scrollback is not diffed; a
+//! remote view mirrors the visible screen only.
+//!
+//! [`ScreenChanges::encode`] / [`ScreenChanges::decode`] give a compact
+//! little-endian binary form for shipping a change set over a pipe or
+//! socket, without pulling a serialization crate into `term_core`.
+
+use crate::attrs::CellFlags;
+use crate::color::TermColor;
+use crate::emulator::{CursorState, RenderSnapshot};
+use crate::grid::{Cell, CellExtra, CursorStyle, PromptMarker, Row};
+
+/// Consecutive changed cells in one visible row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellRun {
+    /// Visible row, 0 at the top of the screen.
+    pub row: usize,
+    /// Column of the first cell in `cells`.
+    pub col: usize,
+    pub cells: Vec<Cell>,
+}
+
+/// Everything a remote view needs to catch up with one snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenChanges {
+    /// The runs cover the whole screen; the receiver should drop what it
+    /// has (first update, or the size changed).
+    pub full_redraw: bool,
+    pub cols: usize,
+    pub rows: usize,
+    pub runs: Vec<CellRun>,
+    pub cursor: CursorState,
+    /// The new title, when it changed.
+    pub title: Option<String>,
+}
+
+impl ScreenChanges {
+    /// No cell or title changed (the cursor may still have moved).
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty() && self.title.is_none()
+    }
+
+    /// Number of cells carried by the runs.
+    pub fn cell_count(&self) -> usize {
+        self.runs.iter().map(|run| run.cells.len()).sum()
+    }
+}
+
+/// Tracks the screen last sent and produces minimal updates against it.
+#[derive(Debug, Default)]
+pub struct ScreenDiffer {
+    previous: Option<Screen>,
+}
+
+#[derive(Debug)]
+struct Screen {
+    cols: usize,
+    rows: Vec<Row>,
+    title: String,
+}
+
+impl ScreenDiffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the last screen, so the next [`diff`](Self::diff) is a full
+    /// redraw (e.g. after a new client attaches).
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// The changes from the last snapshot passed in to `snapshot`.
+    pub fn diff(&mut self, snapshot: &RenderSnapshot) -> ScreenChanges {
+        let rows: Vec<Row> = snapshot.visible_iter().cloned().collect();
+        let cols = rows.first().map_or(0, |row| row.cells.len());
+
+        let previous = self
+            .previous
+            .take()
+            .filter(|prev| prev.cols == cols && prev.rows.len() == rows.len());
+        let full_redraw = previous.is_none();
+
+        let mut runs = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            match &previous {
+                Some(prev) => push_changed_runs(&mut runs, index, &prev.rows[index], row),
+                None => runs.push(CellRun {
+                    row: index,
+                    col: 0,
+                    cells: row.cells.clone(),
+                }),
+            }
+        }
+
+        let title_changed = previous
+            .as_ref()
+            .is_none_or(|prev| prev.title != snapshot.title);
+        let changes = ScreenChanges {
+            full_redraw,
+            cols,
+            rows: rows.len(),
+            runs,
+            cursor: snapshot.cursor,
+            title: title_changed.then(|| snapshot.title.clone()),
+        };
+        self.previous = Some(Screen {
+            cols,
+            rows,
+            title: snapshot.title.clone(),
+        });
+        changes
+    }
+}
+
+fn push_changed_runs(runs: &mut Vec<CellRun>, row: usize, old: &Row, new: &Row) {
+    let mut col = 0;
+    while col < new.cells.len() {
+        if old.cells.get(col) == Some(&new.cells[col]) {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        while col < new.cells.len() && old.cells.get(col) != Some(&new.cells[col]) {
+            col += 1;
+        }
+        runs.push(CellRun {
+            row,
+            col: start,
+            cells: new.cells[start..col].to_vec(),
+        });
+    }
+}
+
+// ─── Wire format ──────────────────────────────────────────────────────────
+
+/// Leading byte of an encoded change set; bumped when the layout changes.
+const WIRE_VERSION: u8 = 1;
+
+impl ScreenChanges {
+    /// Compact binary form, readable by [`decode`](Self::decode).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Writer(Vec::with_capacity(32 + self.cell_count() * 12));
+        out.u8(WIRE_VERSION);
+        out.u8(u8::from(self.full_redraw));
+        out.usize(self.cols);
+        out.usize(self.rows);
+        out.usize(self.cursor.row);
+        out.usize(self.cursor.col);
+        out.u8(u8::from(self.cursor.visible));
+        out.u8(cursor_style_code(self.cursor.style));
+        out.opt_str(self.title.as_deref());
+        out.usize(self.runs.len());
+        for run in &self.runs {
+            out.usize(run.row);
+            out.usize(run.col);
+            out.usize(run.cells.len());
+            for cell in &run.cells {
+                out.cell(cell);
+            }
+        }
+        out.0
+    }
+
+    /// Parse the output of [`encode`](Self::encode). `None` for a
+    /// truncated or malformed buffer, or one from another wire version.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut input = Reader(bytes);
+        if input.u8()? != WIRE_VERSION {
+            return None;
+        }
+        let full_redraw = input.bool()?;
+        let cols = input.usize()?;
+        let rows = input.usize()?;
+        let cursor = CursorState {
+            row: input.usize()?,
+            col: input.usize()?,
+            visible: input.bool()?,
+            style: cursor_style_from_code(input.u8()?)?,
+        };
+        let title = input.opt_str()?;
+        let run_count = input.usize()?;
+        let mut runs = Vec::new();
+        for _ in 0..run_count {
+            let row = input.usize()?;
+            let col = input.usize()?;
+            let len = input.usize()?;
+            let mut cells = Vec::new();
+            for _ in 0..len {
+                cells.push(input.cell()?);
+            }
+            runs.push(CellRun { row, col, cells });
+        }
+        if !input.0.is_empty() {
+            return None;
+        }
+        Some(Self {
+            full_redraw,
+            cols,
+            rows,
+            runs,
+            cursor,
+            title,
+        })
+    }
+}
+
+fn cursor_style_code(style: CursorStyle) -> u8 {
+    match style {
+        CursorStyle::BlockSteady => 0,
+        CursorStyle::BlockBlink => 1,
+        CursorStyle::UnderlineSteady => 2,
+        CursorStyle::UnderlineBlink => 3,
+        CursorStyle::BeamSteady => 4,
+        CursorStyle::BeamBlink => 5,
+    }
+}
+
+fn cursor_style_from_code(code: u8) -> Option<CursorStyle> {
+    Some(match code {
+        0 => CursorStyle::BlockSteady,
+        1 => CursorStyle::BlockBlink,
+        2 => CursorStyle::UnderlineSteady,
+        3 => CursorStyle::UnderlineBlink,
+        4 => CursorStyle::BeamSteady,
+        5 => CursorStyle::BeamBlink,
+        _ => return None,
+    })
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u32(value as u32);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn color(&mut self, color: TermColor) {
+        match color {
+            TermColor::Default => self.u8(0),
+            TermColor::Indexed(index) => {
+                self.u8(1);
+                self.u8(index);
+            }
+            TermColor::Rgb(r, g, b) => {
+                self.u8(2);
+                self.0.extend_from_slice(&[r, g, b]);
+            }
+        }
+    }
+
+    fn cell(&mut self, cell: &Cell) {
+        self.u32(cell.c as u32);
+        self.color(cell.fg);
+        self.color(cell.bg);
+        self.0.extend_from_slice(&cell.flags.bits().to_le_bytes());
+        let Some(extra) = &cell.extra else {
+            self.u8(0);
+            return;
+        };
+        self.u8(1);
+        self.usize(extra.zerowidth.len());
+        for &c in &extra.zerowidth {
+            self.u32(c as u32);
+        }
+        self.opt_str(extra.hyperlink.as_deref());
+        match &extra.prompt {
+            None => self.u8(0),
+            Some(PromptMarker::Start) => self.u8(1),
+            Some(PromptMarker::End) => self.u8(2),
+            Some(PromptMarker::Cont(payload)) => {
+                self.u8(3);
+                self.str(payload);
+            }
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn usize(&mut self) -> Option<usize> {
+        self.u32().map(|v| v as usize)
+    }
+
+    fn char(&mut self) -> Option<char> {
+        char::from_u32(self.u32()?)
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.usize()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn opt_str(&mut self) -> Option<Option<String>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => self.str().map(Some),
+            _ => None,
+        }
+    }
+
+    fn color(&mut self) -> Option<TermColor> {
+        Some(match self.u8()? {
+            0 => TermColor::Default,
+            1 => TermColor::Indexed(self.u8()?),
+            2 => {
+                let rgb = self.take(3)?;
+                TermColor::Rgb(rgb[0], rgb[1], rgb[2])
+            }
+            _ => return None,
+        })
+    }
+
+    fn cell(&mut self) -> Option<Cell> {
+        let c = self.char()?;
+        let fg = self.color()?;
+        let bg = self.color()?;
+        let bits = self.take(2)?;
+        let mut flags = CellFlags::empty();
+        flags.set(u16::from_le_bytes([bits[0], bits[1]]));
+        let extra = if self.bool()? {
+            let count = self.usize()?;
+            let mut zerowidth = Vec::new();
+            for _ in 0..count {
+                zerowidth.push(self.char()?);
+            }
+            let hyperlink = self.opt_str()?;
+            let prompt = match self.u8()? {
+                0 => None,
+                1 => Some(PromptMarker::Start),
+                2 => Some(PromptMarker::End),
+                3 => Some(PromptMarker::Cont(self.str()?)),
+                _ => return None,
+            };
+            Some(Box::new(CellExtra {
+                zerowidth,
+                hyperlink,
+                prompt,
+            }))
+        } else {
+            None
+        };
+        Some(Cell {
+            c,
+            fg,
+            bg,
+            flags,
+            extra,
+        })
+    }
+}
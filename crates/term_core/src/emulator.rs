@@ -10,7 +10,7 @@ use crate::parser::{Action, EraseMode, Parser, PromptKind, SgrAction};
 use crate::{CellFlags, TermColor};
 
 /// Cursor state surfaced to the renderer.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CursorState {
     pub row: usize,
     pub col: usize,
@@ -67,6 +67,8 @@ pub trait TerminalEmulator: Send {
     fn mouse_protocol(&self) -> MouseProtocol;
     fn bracketed_paste(&self) -> bool;
     fn cursor_keys_app(&self) -> bool;
+    /// DECKPAM (`ESC =`): the numeric keypad sends `SS3` application codes.
+    fn keypad_app(&self) -> bool;
     fn focus_reporting(&self) -> bool;
     fn title(&self) -> &str;
 
@@ -75,12 +77,40 @@ pub trait TerminalEmulator: Send {
     fn lines_evicted(&self) -> u64;
 }
 
+/// Which terminal queries the emulator answers on the child's behalf. Both
+/// default on — a child blocking on an unanswered `CSI 6 n` hangs — but a host
+/// that forwards queries to a real terminal can turn them off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryReplies {
+    /// DSR 6 → CPR (`CSI row ; col R`).
+    pub cursor_position: bool,
+    /// Primary DA (`CSI c`) → `CSI ? 6 c`.
+    pub device_attributes: bool,
+}
+
+impl Default for QueryReplies {
+    fn default() -> Self {
+        Self { cursor_position: true, device_attributes: true }
+    }
+}
+
+/// Maximum saved titles on the XTWINOPS stack (xterm's limit).
+pub const TITLE_STACK_DEPTH: usize = 10;
+
 pub struct VtEmulator {
     parser: Parser,
     grid: Grid,
     title: String,
+    /// XTWINOPS push/pop title stack (`CSI 22 t` / `CSI 23 t`), capped at
+    /// [`TITLE_STACK_DEPTH`] like xterm — the oldest entry drops first.
+    title_stack: Vec<String>,
     cwd: Option<String>,
     response_buf: Vec<u8>,
+    query_replies: QueryReplies,
+    /// Drop SGR colour changes (`NO_COLOR`); attributes still apply.
+    no_color: bool,
+    /// Parsed-action scratch buffer, reused across `process` calls.
+    action_buf: Vec<Action>,
 }
 
 impl VtEmulator {
@@ -89,11 +119,33 @@ impl VtEmulator {
             parser: Parser::new(),
             grid: Grid::new(cols, rows, max_scrollback),
             title: String::new(),
+            title_stack: Vec::new(),
             cwd: None,
             response_buf: Vec::new(),
+            query_replies: QueryReplies::default(),
+            no_color: false,
+            action_buf: Vec::new(),
         }
     }
 
+    /// Choose which queries get an automatic reply (see [`QueryReplies`]).
+    pub fn set_query_replies(&mut self, replies: QueryReplies) {
+        self.query_replies = replies;
+    }
+
+    /// Recognise 8-bit C1 controls from the child (see
+    /// [`Parser::set_c1_controls`]). Off by default.
+    pub fn set_c1_controls(&mut self, enabled: bool) {
+        self.parser.set_c1_controls(enabled);
+    }
+
+    /// Ignore foreground / background SGR changes, so every cell keeps the
+    /// default colours. Bold, underline, inverse and the other attributes
+    /// are unaffected. Takes effect for text printed from now on.
+    pub fn set_no_color(&mut self, no_color: bool) {
+        self.no_color = no_color;
+    }
+
     pub fn grid(&self) -> &Grid {
         &self.grid
     }
@@ -161,7 +213,7 @@ impl VtEmulator {
             }
 
             // Device replies
-            Action::DeviceStatusReport(6) => {
+            Action::DeviceStatusReport(6) if self.query_replies.cursor_position => {
                 // CPR: report cursor pos (1-based). DECOM-aware? VT100 standard:
                 // report origin-relative when DECOM is on. We report absolute
                 // for simplicity; can be revisited if real apps care.
@@ -177,10 +229,11 @@ impl VtEmulator {
                 self.response_buf.extend_from_slice(b"\x1b[0n");
             }
             Action::DeviceStatusReport(_) => {}
-            Action::DeviceAttributes => {
+            Action::DeviceAttributes if self.query_replies.device_attributes => {
                 // Primary DA — claim VT102 (1;6). vte/Warp also use this.
                 self.response_buf.extend_from_slice(b"\x1b[?6c");
             }
+            Action::DeviceAttributes => {}
 
             // Cursor save/restore
             Action::SaveCursor => self.grid.save_cursor(),
@@ -199,6 +252,7 @@ impl VtEmulator {
             Action::FullReset => {
                 self.grid.reset();
                 self.title.clear();
+                self.title_stack.clear();
                 self.cwd = None;
             }
             Action::SetCursorStyle(n) => {
@@ -208,6 +262,18 @@ impl VtEmulator {
             Action::TabClear(_) => { /* fixed tab=8; TBC ignored */ }
 
             // OSC
+            Action::PushTitle => {
+                if self.title_stack.len() == TITLE_STACK_DEPTH {
+                    self.title_stack.remove(0);
+                }
+                self.title_stack.push(self.title.clone());
+            }
+            Action::PopTitle => {
+                // An unmatched pop leaves the title alone (xterm does too).
+                if let Some(t) = self.title_stack.pop() {
+                    self.title = t;
+                }
+            }
             Action::SetTitle(t) => self.title = t,
             Action::SetCwd(path) => self.cwd = Some(path),
             Action::Hyperlink { params, url } => {
@@ -231,6 +297,11 @@ impl VtEmulator {
     }
 
     fn apply_sgr(&mut self, sgr: SgrAction) {
+        if self.no_color
+            && matches!(sgr, SgrAction::Foreground(_) | SgrAction::Background(_))
+        {
+            return;
+        }
         match sgr {
             SgrAction::Reset => {
                 self.grid.current_fg = TermColor::Default;
@@ -296,6 +367,15 @@ impl VtEmulator {
                 self.grid.mouse.encoding =
                     if enable { MouseEncoding::Sgr } else { MouseEncoding::Default };
             }
+            1015 => {
+                // Only drop back to legacy if urxvt is what's active, so a
+                // stray `?1015l` can't undo a later `?1006h`.
+                if enable {
+                    self.grid.mouse.encoding = MouseEncoding::Urxvt;
+                } else if self.grid.mouse.encoding == MouseEncoding::Urxvt {
+                    self.grid.mouse.encoding = MouseEncoding::Default;
+                }
+            }
             1049 => {
                 if enable {
                     self.grid.enter_alt_screen();
@@ -339,11 +419,12 @@ impl VtEmulator {
 
 impl TerminalEmulator for VtEmulator {
     fn process(&mut self, bytes: &[u8]) {
-        let mut actions = Vec::with_capacity(bytes.len() / 4);
-        self.parser.advance(bytes, |a| actions.push(a));
-        for action in actions {
+        let mut actions = std::mem::take(&mut self.action_buf);
+        self.parser.advance_into(bytes, &mut actions);
+        for action in actions.drain(..) {
             self.apply_action(action);
         }
+        self.action_buf = actions;
     }
 
     fn resize(&mut self, cols: usize, rows: usize) {
@@ -378,6 +459,9 @@ impl TerminalEmulator for VtEmulator {
     fn cursor_keys_app(&self) -> bool {
         self.grid.cursor_keys_app
     }
+    fn keypad_app(&self) -> bool {
+        self.grid.keypad_app
+    }
     fn focus_reporting(&self) -> bool {
         self.grid.focus_reporting
     }
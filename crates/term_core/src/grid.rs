@@ -6,6 +6,7 @@
 //! logically monospace for VT correctness — `CUP row 5 col 10` always
 //! addresses `Row[5].cells[10]`.
 
+use crate::width::char_width;
 use crate::{CellFlags, TermColor};
 
 /// One grid cell.
@@ -113,7 +114,8 @@ pub enum CursorStyle {
 }
 
 impl CursorStyle {
-    /// Decode the DECSCUSR parameter (`CSI Ps SP q`).
+    /// Decode the DECSCUSR parameter (`CSI Ps SP q`). 0 (or an omitted
+    /// parameter) and unknown values restore the default steady block.
     pub fn from_decscusr(n: u16) -> Self {
         match n {
             1 => Self::BlockBlink,
@@ -122,7 +124,7 @@ impl CursorStyle {
             4 => Self::UnderlineSteady,
             5 => Self::BeamBlink,
             6 => Self::BeamSteady,
-            _ => Self::BlockSteady,
+            _ => Self::default(),
         }
     }
 }
@@ -143,8 +145,8 @@ pub enum MouseTracking {
 }
 
 /// Mouse-report byte encoding, set independently of the tracking level.
-/// The UTF-8 (1005) and urxvt (1015) encodings are intentionally
-/// unsupported (deprecated; SGR superseded them, and Warp omits them).
+/// The UTF-8 (1005) encoding is intentionally unsupported (deprecated;
+/// SGR superseded it, and Warp omits it).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum MouseEncoding {
     /// Legacy `CSI M Cb Cx Cy` (byte-offset 32, coords clamp at 223).
@@ -152,11 +154,14 @@ pub enum MouseEncoding {
     Default,
     /// 1006 — SGR `CSI < Cb ; Cx ; Cy M|m` (no coordinate limit).
     Sgr,
+    /// 1015 — urxvt `CSI Cb ; Cx ; Cy M`: decimal, `Cb` keeps the +32
+    /// offset, no coordinate limit, release is button-bits 3 like legacy.
+    Urxvt,
 }
 
 /// The composite mouse-reporting protocol state. The tracking level and
 /// the encoding are set by orthogonal DECSET sequences, so e.g. `1000`
-/// + `1006` compose into click-tracking with SGR encoding rather than
+/// and `1006` compose into click-tracking with SGR encoding rather than
 /// clobbering each other (the bug a single conflated enum had).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct MouseProtocol {
@@ -210,10 +215,33 @@ impl Row {
     pub fn clear_range(&mut self, range: std::ops::Range<usize>) {
         let end = range.end.min(self.cells.len());
         let start = range.start.min(end);
+        if start == end {
+            return;
+        }
+        // A wide character is cleared whole: never leave half of one at
+        // either edge of the range.
+        if self.cells[start].flags.wide_char_spacer() && start > 0 {
+            self.cells[start - 1].reset();
+        }
+        if self.cells[end - 1].flags.wide_char() && end < self.cells.len() {
+            self.cells[end].reset();
+        }
         for cell in &mut self.cells[start..end] {
             cell.reset();
         }
     }
+
+    /// Blank whatever wide character covers `col`, both halves, before
+    /// part of it is overwritten.
+    fn split_wide_at(&mut self, col: usize) {
+        let flags = self.cells[col].flags;
+        if flags.wide_char_spacer() && col > 0 {
+            self.cells[col - 1].reset();
+            self.cells[col].reset();
+        } else if flags.wide_char() && col + 1 < self.cells.len() {
+            self.cells[col + 1].reset();
+        }
+    }
 }
 
 /// Main grid — visible rows plus scrollback. Column count is fixed
@@ -380,20 +408,36 @@ impl Grid {
 
     // ─── Printing ──────────────────────────────────────────────────────────
 
-    /// Print one grapheme base character at the cursor; advances the cursor
-    /// by 1 (callers handle wide-char spacing separately).
+    /// Print one character at the cursor, advancing by its
+    /// [`char_width`](crate::width::char_width): a wide character takes its
+    /// cell plus a `WIDE_CHAR_SPACER` to the right, and a zero-width one
+    /// joins the previously printed cell as a combining mark.
     pub fn print(&mut self, c: char) {
-        if self.auto_wrap && self.cursor_col >= self.cols {
+        let width = char_width(c);
+        if width == 0 {
+            if self.cursor_col > 0 {
+                self.push_zerowidth(c);
+            }
+            return;
+        }
+        let wide = width == 2 && self.cols >= 2;
+        let needed = if wide { 2 } else { 1 };
+        if self.auto_wrap && self.cursor_col + needed > self.cols {
             let cols = self.cols;
             if cols > 0 {
-                self.row_mut(self.cursor_row).cells[cols - 1]
-                    .flags
-                    .set(CellFlags::WRAPLINE);
+                let short = self.cursor_col < cols;
+                let row = self.row_mut(self.cursor_row);
+                // A wide character that doesn't fit leaves the last cell blank.
+                if short {
+                    row.split_wide_at(cols - 1);
+                    row.cells[cols - 1].reset();
+                }
+                row.cells[cols - 1].flags.set(CellFlags::WRAPLINE);
             }
             self.cursor_col = 0;
             self.linefeed();
         }
-        let col = self.cursor_col.min(self.cols.saturating_sub(1));
+        let col = self.cursor_col.min(self.cols.saturating_sub(needed));
         let (fg, bg, flags) = (self.current_fg, self.current_bg, self.current_flags);
 
         // Attach OSC 8 hyperlink (sticky) and OSC 133 prompt marker
@@ -409,26 +453,46 @@ impl Grid {
             }
         }
 
-        let cell = &mut self.row_mut(self.cursor_row).cells[col];
-        *cell = Cell {
+        let row = self.row_mut(self.cursor_row);
+        for covered in col..col + needed {
+            row.split_wide_at(covered);
+        }
+        let mut lead_flags = flags;
+        if wide {
+            lead_flags.set(CellFlags::WIDE_CHAR);
+            let mut spacer_flags = flags;
+            spacer_flags.set(CellFlags::WIDE_CHAR_SPACER);
+            row.cells[col + 1] = Cell {
+                c: ' ',
+                fg,
+                bg,
+                flags: spacer_flags,
+                extra: None,
+            };
+        }
+        row.cells[col] = Cell {
             c,
             fg,
             bg,
-            flags,
+            flags: lead_flags,
             extra,
         };
-        self.cursor_col = col + 1;
+        self.cursor_col = col + needed;
         self.last_printed = Some(c);
     }
 
-    /// Append a combining mark to the most recently printed cell.
+    /// Append a combining mark to the most recently printed cell (the
+    /// left half, when that was a wide character).
     pub fn push_zerowidth(&mut self, c: char) {
         if self.cols == 0 {
             return;
         }
-        let col = self.cursor_col.saturating_sub(1).min(self.cols - 1);
-        let cell = &mut self.row_mut(self.cursor_row).cells[col];
-        cell.push_zerowidth(c);
+        let mut col = self.cursor_col.saturating_sub(1).min(self.cols - 1);
+        let row = self.row_mut(self.cursor_row);
+        if row.cells[col].flags.wide_char_spacer() && col > 0 {
+            col -= 1;
+        }
+        row.cells[col].push_zerowidth(c);
     }
 
     /// **REP** — repeat the last printed character.
@@ -482,7 +546,9 @@ impl Grid {
         } else {
             (self.visible_rows.saturating_sub(1), 0)
         };
-        self.cursor_row = (row + row_offset).min(self.scroll_bottom).min(row_max + row_offset);
+        // Only origin mode confines CUP to the scroll region; otherwise
+        // rows below it stay addressable.
+        self.cursor_row = (row + row_offset).min(row_max + row_offset);
         self.cursor_col = col.min(self.cols.saturating_sub(1));
     }
     pub fn next_tab(&mut self, n: usize) {
@@ -538,10 +604,14 @@ impl Grid {
     }
 
     /// **IL** — insert N blank lines at the cursor row (within scroll region).
+    /// Lines pushed past the region's bottom are discarded, rows outside
+    /// the region don't move, and the cursor returns to column 0 as in
+    /// xterm.
     pub fn insert_lines(&mut self, n: usize) {
         if self.cursor_row < self.scroll_top || self.cursor_row > self.scroll_bottom {
             return;
         }
+        self.cursor_col = 0;
         let cols = self.cols;
         let n = n.min(self.scroll_bottom - self.cursor_row + 1);
         for _ in 0..n {
@@ -554,11 +624,14 @@ impl Grid {
         }
     }
 
-    /// **DL** — delete N lines at the cursor row (within scroll region).
+    /// **DL** — delete N lines at the cursor row (within scroll region),
+    /// pulling the rest of the region up and blanking its bottom. Like
+    /// IL, the cursor returns to column 0.
     pub fn delete_lines(&mut self, n: usize) {
         if self.cursor_row < self.scroll_top || self.cursor_row > self.scroll_bottom {
             return;
         }
+        self.cursor_col = 0;
         let cols = self.cols;
         let n = n.min(self.scroll_bottom - self.cursor_row + 1);
         for _ in 0..n {
@@ -794,17 +867,30 @@ impl Grid {
         //
         // When visible_rows shrinks (or stays the same), scrollback is
         // preserved; trailing blank rows beyond the new visible region
-        // are truncated.
+        // are truncated. The cursor's row is never cut: if it sits below
+        // the new bottom edge, the rows above it spill into scrollback
+        // instead, so the content the cursor is on stays on screen.
         let prev_scrollback = self.rows.len().saturating_sub(self.visible_rows);
         let visible_increment = rows.saturating_sub(self.visible_rows);
         let scrollback_to_keep = prev_scrollback.saturating_sub(visible_increment);
         let target = scrollback_to_keep + rows;
+        let mut cursor_abs = cursor_abs;
         if self.rows.len() < target {
             while self.rows.len() < target {
                 self.rows.push(Row::new(cols));
             }
         } else if self.rows.len() > target {
-            self.rows.truncate(target);
+            let keep = target.max(cursor_abs + 1).min(self.rows.len());
+            self.rows.truncate(keep);
+            let overflow = self
+                .rows
+                .len()
+                .saturating_sub(rows + self.max_scrollback);
+            if overflow > 0 {
+                self.rows.drain(..overflow);
+                self.lines_evicted += overflow as u64;
+                cursor_abs = cursor_abs.saturating_sub(overflow);
+            }
         }
         self.cols = cols;
         self.visible_rows = rows;
@@ -814,8 +900,8 @@ impl Grid {
         self.cursor_row = cursor_abs
             .saturating_sub(visible_start)
             .min(rows.saturating_sub(1));
-        if self.cursor_col >= cols {
-            self.cursor_col = cols.saturating_sub(1);
+        if self.cursor_col > cols {
+            self.cursor_col = cols;
         }
     }
 
@@ -1001,11 +1087,18 @@ fn place_cursor_logical(
         let rows_for_line = if trimmed == 0 {
             1
         } else {
-            (trimmed + new_cols - 1) / new_cols
+            trimmed.div_ceil(new_cols)
         };
         if i == line_idx {
             let capped = offset_in_line.min(trimmed);
-            let row_off = (capped / new_cols).min(rows_for_line.saturating_sub(1));
+            let last_row = rows_for_line.saturating_sub(1);
+            // A cursor just past content that exactly fills its last row
+            // is in the pending-wrap state: keep it at `new_cols` on that
+            // row rather than folding it back to column 0.
+            if capped / new_cols > last_row {
+                return (abs_row + last_row, new_cols);
+            }
+            let row_off = capped / new_cols;
             let col = (capped % new_cols).min(new_cols.saturating_sub(1));
             return (abs_row + row_off, col);
         }
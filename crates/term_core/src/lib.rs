@@ -16,18 +16,23 @@
 
 pub mod attrs;
 pub mod color;
+pub mod diff;
 pub mod emulator;
 pub mod grid;
 pub mod parser;
+pub mod transcript;
+pub mod width;
 
 pub use attrs::CellFlags;
 pub use color::{AnsiPalette, TermColor};
-pub use emulator::{CursorState, RenderSnapshot, TerminalEmulator, VtEmulator};
+pub use diff::{CellRun, ScreenChanges, ScreenDiffer};
+pub use emulator::{CursorState, QueryReplies, RenderSnapshot, TerminalEmulator, VtEmulator};
 pub use grid::{
     Cell, CellExtra, CursorStyle, Grid, MouseEncoding, MouseProtocol, MouseTracking, PromptMarker,
     Row,
 };
 pub use parser::{Action, EraseMode, Parser, PromptKind, SgrAction, TabClear};
+pub use transcript::TranscriptStyle;
 
 /// Create a terminal emulator with the given visible grid size and
 /// scrollback line cap.
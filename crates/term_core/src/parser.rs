@@ -111,6 +111,11 @@ pub enum Action {
     SetTabStop,        // HTS
     TabClear(TabClear),
 
+    // XTWINOPS title stack: `CSI 22 ; Ps t` / `CSI 23 ; Ps t` (Ps 0 or 2;
+    // icon-only Ps 1 is ignored — we keep no icon name).
+    PushTitle,
+    PopTitle,
+
     // OSC
     SetTitle(String),
     SetCwd(String),
@@ -177,6 +182,10 @@ pub struct Parser {
     intermediates: [u8; MAX_INTERMEDIATES],
     intermediate_count: usize,
     osc_buf: Vec<u8>,
+    /// Recognise 8-bit C1 controls (0x80–0x9F) in ground — `0x9B` as CSI,
+    /// `0x9D` as OSC, … Off by default: over a UTF-8 link those bytes are
+    /// continuation bytes, and a stray one must not open a sequence.
+    c1_controls: bool,
 }
 
 impl Default for Parser {
@@ -198,9 +207,26 @@ impl Parser {
             intermediates: [0; MAX_INTERMEDIATES],
             intermediate_count: 0,
             osc_buf: Vec::with_capacity(MAX_OSC_BYTES),
+            c1_controls: false,
         }
     }
 
+    /// Enable 8-bit C1 control recognition for raw 8-bit links. Each C1
+    /// byte behaves like its 7-bit `ESC Fe` equivalent (`0x9B` ≡ `ESC [`).
+    pub fn set_c1_controls(&mut self, enabled: bool) {
+        self.c1_controls = enabled;
+    }
+
+    /// Return to `Ground`, discarding any pending partial sequence —
+    /// collected params, intermediates, an unterminated OSC string, or
+    /// a half-read UTF-8 character. For recovering after the byte stream
+    /// desyncs (child restarted mid-sequence, dropped chunk). Settings
+    /// such as C1 recognition are kept.
+    pub fn reset(&mut self) {
+        self.reset_for_escape();
+        self.state = State::Ground;
+    }
+
     /// Feed a slice of bytes through the state machine; `emit` is called
     /// for every completed `Action`.
     pub fn advance<F: FnMut(Action)>(&mut self, input: &[u8], mut emit: F) {
@@ -209,6 +235,13 @@ impl Parser {
         }
     }
 
+    /// Like [`advance`](Self::advance), but appends to a caller-owned
+    /// buffer. The hot PTY path reuses one `Vec` across reads instead of
+    /// allocating per chunk.
+    pub fn advance_into(&mut self, input: &[u8], out: &mut Vec<Action>) {
+        self.advance(input, |a| out.push(a));
+    }
+
     fn feed<F: FnMut(Action)>(&mut self, byte: u8, emit: &mut F) {
         // Global state escapes (per Paul Williams diagram).
         match byte {
@@ -287,6 +320,12 @@ impl Parser {
             0xC0..=0xDF => self.state = State::Utf8_2(byte),
             0xE0..=0xEF => self.state = State::Utf8_3(byte, 0),
             0xF0..=0xF4 => self.state = State::Utf8_4(byte, 0, 0),
+            0x80..=0x9F if self.c1_controls => {
+                // C1 → ESC Fe: 0x9B - 0x40 = '[' (CSI), 0x9D → ']' (OSC), …
+                self.reset_for_escape();
+                self.state = State::Escape;
+                self.escape(byte - 0x40, emit);
+            }
             _ => { /* invalid UTF-8 lead */ }
         }
     }
@@ -605,13 +644,19 @@ impl Parser {
             b'm' => self.dispatch_sgr(emit),
             b'n' => emit(Action::DeviceStatusReport(self.param(0, 0))),
             b'q' if self.intermediate_count == 1 && self.intermediates[0] == b' ' => {
-                emit(Action::SetCursorStyle(self.param(0, 1)));
+                // Omitted and 0 both mean "reset", distinct from 1.
+                emit(Action::SetCursorStyle(self.param(0, 0)));
             }
             b'r' => emit(Action::SetScrollRegion {
                 top: self.param(0, 1),
                 bottom: self.param(1, u16::MAX),
             }),
             b's' => emit(Action::SaveCursorSco),
+            b't' => match (self.param(0, 0), self.param(1, 0)) {
+                (22, 0 | 2) => emit(Action::PushTitle),
+                (23, 0 | 2) => emit(Action::PopTitle),
+                _ => emit(Action::Unsupported),
+            },
             b'u' => emit(Action::RestoreCursorSco),
             _ => emit(Action::Unsupported),
         }
@@ -0,0 +1,141 @@
+//! Plain-text and ANSI transcripts of a [`RenderSnapshot`].
+//!
+//! Rows are rendered the way a copy would read them: trailing blank
+//! cells trimmed, wide-char spacers skipped, combining marks kept, and
+//! soft-wrapped rows joined into one logical line. Used for history
+//! entries and clipboard copies of whole screens.
+
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::emulator::RenderSnapshot;
+use crate::grid::{Cell, Row};
+use crate::{CellFlags, TermColor};
+
+/// Whether a transcript keeps the cells' styling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptStyle {
+    /// Text only.
+    Plain,
+    /// Text with SGR sequences reproducing colours and attributes; each
+    /// styled line ends with a reset.
+    Ansi,
+}
+
+impl RenderSnapshot {
+    /// Render `rows` (indices into [`rows`](Self::rows), so scrollback
+    /// included) as text. Logical lines are separated by `\n`; there is
+    /// no trailing newline.
+    pub fn transcript(&self, rows: Range<usize>, style: TranscriptStyle) -> String {
+        let rows = rows.start.min(self.rows.len())..rows.end.min(self.rows.len());
+        let mut out = String::new();
+        let last = rows.end.saturating_sub(1);
+        for idx in rows {
+            let row = &self.rows[idx];
+            match style {
+                TranscriptStyle::Plain => push_plain(&mut out, row),
+                TranscriptStyle::Ansi => push_ansi(&mut out, row),
+            }
+            if idx < last && !soft_wrapped(row) {
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// [`transcript`](Self::transcript) of the visible screen.
+    pub fn screen_transcript(&self, style: TranscriptStyle) -> String {
+        self.transcript(self.visible_start()..self.rows.len(), style)
+    }
+}
+
+fn soft_wrapped(row: &Row) -> bool {
+    row.cells.last().is_some_and(|c| c.flags.contains(CellFlags::WRAPLINE))
+}
+
+/// Cells up to the last one that shows something: a glyph, or (when
+/// styled) a non-default background.
+fn content(row: &Row, keep_bg: bool) -> &[Cell] {
+    let end = row
+        .cells
+        .iter()
+        .rposition(|c| c.c != ' ' || c.extra.is_some() || (keep_bg && c.bg != TermColor::Default))
+        .map_or(0, |i| i + 1);
+    &row.cells[..end]
+}
+
+fn push_cell_text(out: &mut String, cell: &Cell) {
+    out.push(cell.c);
+    if let Some(extra) = &cell.extra {
+        out.extend(&extra.zerowidth);
+    }
+}
+
+fn push_plain(out: &mut String, row: &Row) {
+    for cell in content(row, false) {
+        if !cell.flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+            push_cell_text(out, cell);
+        }
+    }
+}
+
+fn push_ansi(out: &mut String, row: &Row) {
+    let mut current = (TermColor::Default, TermColor::Default, CellFlags::empty());
+    for cell in content(row, true) {
+        if cell.flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        let style = (cell.fg, cell.bg, style_flags(cell.flags));
+        if style != current {
+            push_sgr(out, style.0, style.1, style.2);
+            current = style;
+        }
+        push_cell_text(out, cell);
+    }
+    if current != (TermColor::Default, TermColor::Default, CellFlags::empty()) {
+        out.push_str("\x1b[0m");
+    }
+}
+
+/// The flags that affect appearance; layout markers are dropped.
+fn style_flags(flags: CellFlags) -> CellFlags {
+    let mut style = flags;
+    for marker in [CellFlags::WIDE_CHAR, CellFlags::WIDE_CHAR_SPACER, CellFlags::WRAPLINE] {
+        style.clear(marker);
+    }
+    style
+}
+
+/// One complete SGR: reset, then every attribute of the new style.
+fn push_sgr(out: &mut String, fg: TermColor, bg: TermColor, flags: CellFlags) {
+    out.push_str("\x1b[0");
+    for (flag, code) in [
+        (CellFlags::BOLD, 1),
+        (CellFlags::FAINT, 2),
+        (CellFlags::ITALIC, 3),
+        (CellFlags::UNDERLINE, 4),
+        (CellFlags::BLINK_SLOW, 5),
+        (CellFlags::BLINK_FAST, 6),
+        (CellFlags::INVERSE, 7),
+        (CellFlags::HIDDEN, 8),
+        (CellFlags::STRIKE, 9),
+        (CellFlags::DOUBLE_UNDERLINE, 21),
+    ] {
+        if flags.contains(flag) {
+            let _ = write!(out, ";{code}");
+        }
+    }
+    push_color(out, fg, 30, 90, 38);
+    push_color(out, bg, 40, 100, 48);
+    out.push('m');
+}
+
+fn push_color(out: &mut String, color: TermColor, base: u8, bright: u8, extended: u8) {
+    let _ = match color {
+        TermColor::Default => Ok(()),
+        TermColor::Indexed(n @ 0..=7) => write!(out, ";{}", base + n),
+        TermColor::Indexed(n @ 8..=15) => write!(out, ";{}", bright + n - 8),
+        TermColor::Indexed(n) => write!(out, ";{extended};5;{n}"),
+        TermColor::Rgb(r, g, b) => write!(out, ";{extended};2;{r};{g};{b}"),
+    };
+}
@@ -1,7 +1,8 @@
 //! Emulator integration tests — feed bytes, snapshot, check grid state.
 
 use term_core::{
-    AnsiPalette, Cell, CursorStyle, PromptMarker, TermColor, TerminalEmulator, VtEmulator,
+    AnsiPalette, Cell, CursorStyle, PromptMarker, QueryReplies, TermColor, TerminalEmulator,
+    VtEmulator,
 };
 
 fn cells_text(row_cells: &[Cell]) -> String {
@@ -101,6 +102,40 @@ fn dsr_cursor_position_reply() {
     assert_eq!(reply, b"\x1b[3;7R".to_vec());
 }
 
+#[test]
+fn dsr_reply_tracks_the_current_cursor() {
+    let mut em = VtEmulator::new(20, 5, 0);
+    // Printing advances the cursor; the reply reflects where it ended up.
+    em.process(b"\x1b[2;1Habc\x1b[6n");
+    assert_eq!(em.take_responses(), b"\x1b[2;4R".to_vec());
+    // The buffer drains — a second take is empty until the next query.
+    assert!(em.take_responses().is_empty());
+}
+
+#[test]
+fn disabled_query_replies_stay_silent() {
+    let mut em = VtEmulator::new(20, 5, 0);
+    em.set_query_replies(QueryReplies { cursor_position: false, device_attributes: false });
+    em.process(b"\x1b[6n\x1b[c");
+    assert!(em.take_responses().is_empty());
+    // Other replies (DSR 5 status) are unaffected.
+    em.process(b"\x1b[5n");
+    assert_eq!(em.take_responses(), b"\x1b[0n".to_vec());
+}
+
+#[test]
+fn c1_controls_are_read_only_when_enabled() {
+    let mut em = VtEmulator::new(20, 5, 0);
+    // 0x9B 6 n ≡ ESC [ 6 n: a cursor-position query.
+    em.process(&[0x9B, b'6', b'n']);
+    assert!(em.take_responses().is_empty());
+    em.process(b"\x1b[H");
+
+    em.set_c1_controls(true);
+    em.process(&[0x9B, b'6', b'n']);
+    assert_eq!(em.take_responses(), b"\x1b[1;1R".to_vec());
+}
+
 #[test]
 fn alt_screen_swap_preserves_main() {
     let mut em = VtEmulator::new(10, 2, 0);
@@ -218,6 +253,25 @@ fn cursor_style_set_via_decscusr() {
     assert_eq!(snap.cursor.style, CursorStyle::BeamBlink);
 }
 
+#[test]
+fn cursor_style_changes_coalesce_to_the_latest() {
+    let mut em = VtEmulator::new(10, 1, 0);
+    em.process(b"\x1b[2 q\x1b[5 q"); // steady block, then blinking beam
+    assert_eq!(em.snapshot().cursor.style, CursorStyle::BeamBlink);
+}
+
+#[test]
+fn cursor_style_reset_forms_restore_the_default() {
+    let mut em = VtEmulator::new(10, 1, 0);
+    for reset in [&b"\x1b[ q"[..], b"\x1b[0 q"] {
+        em.process(b"\x1b[5 q");
+        em.process(reset);
+        assert_eq!(em.snapshot().cursor.style, CursorStyle::default());
+    }
+    em.process(b"\x1b[1 q");
+    assert_eq!(em.snapshot().cursor.style, CursorStyle::BlockBlink);
+}
+
 #[test]
 fn palette_resolves_indexed_colours() {
     let palette = AnsiPalette::default_dark();
@@ -270,3 +324,53 @@ fn resize_keeps_top_content_anchored_through_shrink_and_grow() {
         "expected first visible row to start with 'hello world', got {first_row:?}"
     );
 }
+
+#[test]
+fn title_stack_push_set_pop_restores() {
+    let mut em = VtEmulator::new(10, 1, 0);
+    em.process(b"\x1b]2;A\x07");
+    em.process(b"\x1b[22;0t"); // push
+    em.process(b"\x1b]2;B\x07");
+    assert_eq!(em.title(), "B");
+    em.process(b"\x1b[23;0t"); // pop
+    assert_eq!(em.title(), "A");
+}
+
+#[test]
+fn unmatched_title_pop_keeps_the_current_title() {
+    let mut em = VtEmulator::new(10, 1, 0);
+    em.process(b"\x1b]0;only\x07\x1b[23t");
+    assert_eq!(em.title(), "only");
+}
+
+#[test]
+fn title_stack_is_capped_dropping_the_oldest() {
+    let mut em = VtEmulator::new(10, 1, 0);
+    for i in 0..=term_core::emulator::TITLE_STACK_DEPTH {
+        em.process(format!("\x1b]2;t{i}\x07\x1b[22t").as_bytes());
+    }
+    for _ in 0..=term_core::emulator::TITLE_STACK_DEPTH {
+        em.process(b"\x1b[23t");
+    }
+    // t0 was pushed first and evicted; the deepest surviving entry is t1.
+    assert_eq!(em.title(), "t1");
+}
+
+#[test]
+fn no_color_drops_colours_but_keeps_attributes() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.set_no_color(true);
+    em.process(b"\x1b[1;4;31;42mhi\x1b[38;2;1;2;3;48;5;200mx");
+    let snap = em.snapshot();
+    for cell in &snap.rows[0].cells[..3] {
+        assert_eq!(cell.fg, TermColor::Default);
+        assert_eq!(cell.bg, TermColor::Default);
+        assert!(cell.flags.bold());
+        assert!(cell.flags.underline());
+    }
+
+    // Switching colour back on applies to what's printed next.
+    em.set_no_color(false);
+    em.process(b"\x1b[31my");
+    assert_eq!(em.snapshot().rows[0].cells[3].fg, TermColor::Indexed(1));
+}
@@ -0,0 +1,55 @@
+//! IL / DL (`CSI Ps L` / `CSI Ps M`) inside a DECSTBM scroll region: only
+//! the region's rows from the cursor down move; everything else stays put.
+
+use term_core::{TerminalEmulator, VtEmulator};
+
+fn row_text(em: &VtEmulator, row: usize) -> String {
+    em.snapshot().rows[row].cells.iter().map(|c| c.c).collect::<String>().trim_end().to_string()
+}
+
+/// Seven rows labelled `r0`..`r6`, with the scroll region set to rows 1–5
+/// (0-based; `CSI 2;6 r`) and the cursor parked on row 3.
+fn labelled() -> VtEmulator {
+    let mut em = VtEmulator::new(8, 7, 0);
+    em.process(b"r0\r\nr1\r\nr2\r\nr3\r\nr4\r\nr5\r\nr6");
+    em.process(b"\x1b[2;6r\x1b[4;2H");
+    em
+}
+
+fn rows(em: &VtEmulator) -> Vec<String> {
+    (0..7).map(|row| row_text(em, row)).collect()
+}
+
+#[test]
+fn insert_line_shifts_the_rest_of_the_region_down() {
+    let mut em = labelled();
+    em.process(b"\x1b[L");
+    assert_eq!(rows(&em), ["r0", "r1", "r2", "", "r3", "r4", "r6"]);
+    let snap = em.snapshot();
+    assert_eq!((snap.cursor.row, snap.cursor.col), (3, 0));
+}
+
+#[test]
+fn delete_line_pulls_the_region_up_and_blanks_its_bottom() {
+    let mut em = labelled();
+    em.process(b"\x1b[M");
+    assert_eq!(rows(&em), ["r0", "r1", "r2", "r4", "r5", "", "r6"]);
+}
+
+#[test]
+fn counts_past_the_region_clear_only_to_its_bottom() {
+    let mut em = labelled();
+    em.process(b"\x1b[9L");
+    assert_eq!(rows(&em), ["r0", "r1", "r2", "", "", "", "r6"]);
+
+    let mut em = labelled();
+    em.process(b"\x1b[9M");
+    assert_eq!(rows(&em), ["r0", "r1", "r2", "", "", "", "r6"]);
+}
+
+#[test]
+fn cursor_outside_the_region_is_a_no_op() {
+    let mut em = labelled();
+    em.process(b"\x1b[7;1H\x1b[L\x1b[1;1H\x1b[M");
+    assert_eq!(rows(&em), ["r0", "r1", "r2", "r3", "r4", "r5", "r6"]);
+}
@@ -1,5 +1,5 @@
 //! Mouse-protocol DECSET handling (§6). The tracking level (1000 / 1002 /
-//! 1003) and the encoding (1006) are orthogonal — the regression these pin is
+//! 1003) and the encoding (1006 / 1015) are orthogonal — the regression these pin is
 //! the old single-enum model where enabling SGR clobbered the tracking level
 //! (and vice versa).
 
@@ -83,3 +83,21 @@ fn resetting_a_nonactive_level_is_a_noop() {
     e.process(b"\x1b[?1002l");
     assert_eq!(e.mouse_protocol().tracking, MouseTracking::AnyEvent);
 }
+
+#[test]
+fn urxvt_encoding_composes_with_tracking() {
+    let mut e = emu();
+    e.process(b"\x1b[?1000h\x1b[?1015h");
+    let p = e.mouse_protocol();
+    assert_eq!(p.tracking, MouseTracking::Normal);
+    assert_eq!(p.encoding, MouseEncoding::Urxvt);
+    e.process(b"\x1b[?1015l");
+    assert_eq!(e.mouse_protocol().encoding, MouseEncoding::Default);
+}
+
+#[test]
+fn resetting_urxvt_does_not_undo_a_later_sgr() {
+    let mut e = emu();
+    e.process(b"\x1b[?1015h\x1b[?1006h\x1b[?1015l");
+    assert_eq!(e.mouse_protocol().encoding, MouseEncoding::Sgr);
+}
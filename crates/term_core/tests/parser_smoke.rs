@@ -132,6 +132,8 @@ fn cursor_style_decscusr() {
     // DECSCUSR — `CSI Ps SP q`. Space is the intermediate.
     assert_eq!(collect(b"\x1b[3 q"), vec![Action::SetCursorStyle(3)]);
     assert_eq!(collect(b"\x1b[6 q"), vec![Action::SetCursorStyle(6)]);
+    // No parameter is the reset form, not 1 (blinking block).
+    assert_eq!(collect(b"\x1b[ q"), vec![Action::SetCursorStyle(0)]);
 }
 
 #[test]
@@ -210,3 +212,115 @@ fn dcs_body_is_eaten() {
         );
     }
 }
+
+fn collect_c1(input: &[u8]) -> Vec<Action> {
+    let mut p = Parser::new();
+    p.set_c1_controls(true);
+    let mut out = Vec::new();
+    p.advance(input, |a| out.push(a));
+    out
+}
+
+#[test]
+fn c1_csi_introducer_in_8bit_mode() {
+    // 0x9B ≡ ESC [ — `0x9B A` is cursor up.
+    assert_eq!(collect_c1(&[0x9B, b'A']), vec![Action::CursorUp(1)]);
+    assert_eq!(collect_c1(&[0x9B, b'3', b'B']), vec![Action::CursorDown(3)]);
+}
+
+#[test]
+fn c1_osc_introducer_in_8bit_mode() {
+    assert_eq!(
+        collect_c1(b"\x9d0;hi\x07"),
+        vec![Action::SetTitle("hi".into())]
+    );
+}
+
+#[test]
+fn c1_bytes_are_ignored_by_default() {
+    // Without 8-bit mode a lone 0x9B is an invalid UTF-8 lead: dropped, and
+    // the following byte prints as-is instead of finishing a CSI.
+    assert_eq!(collect(&[0x9B, b'A']), vec![Action::Print('A')]);
+    // …and 0x9B inside a multibyte char is still just a continuation byte.
+    // U+065B = D9 9B.
+    assert_eq!(collect(&[0xD9, 0x9B]), vec![Action::Print('\u{065B}')]);
+}
+
+#[test]
+fn advance_into_matches_advance() {
+    let inputs: [&[u8]; 5] = [
+        b"abc",
+        b"\x1b[1;31mred\x1b[0m",
+        b"\x1b]0;title\x07\x1b[?1049h",
+        &[0xF0, 0x9F, 0xA6, 0x80, b'\r', b'\n'],
+        b"\x1b[5 q\x1b[6n",
+    ];
+    for input in inputs {
+        let mut p = Parser::new();
+        let mut out = Vec::new();
+        p.advance_into(input, &mut out);
+        assert_eq!(out, collect(input), "input {input:?}");
+    }
+}
+
+#[test]
+fn advance_into_reuses_the_buffer_across_chunks() {
+    // Benchmark-style: a hot loop feeding many chunks through one buffer
+    // stops growing it once warm, i.e. no per-chunk allocation.
+    let chunk = b"\x1b[32mok\x1b[0m line\r\n".repeat(64);
+    let mut p = Parser::new();
+    let mut out = Vec::new();
+    p.advance_into(&chunk, &mut out);
+    let per_chunk = out.len();
+    let warm_capacity = out.capacity();
+    for _ in 0..1_000 {
+        out.clear();
+        p.advance_into(&chunk, &mut out);
+        assert_eq!(out.len(), per_chunk);
+    }
+    assert_eq!(out.capacity(), warm_capacity);
+}
+
+#[test]
+fn reset_discards_a_dangling_escape() {
+    let mut p = Parser::new();
+    let mut out = Vec::new();
+    p.advance(b"\x1b", |a| out.push(a));
+    p.reset();
+    p.advance(b"\x1b[A", |a| out.push(a));
+    assert_eq!(out, vec![Action::CursorUp(1)]);
+}
+
+#[test]
+fn reset_drops_an_unterminated_osc() {
+    let mut p = Parser::new();
+    let mut out = Vec::new();
+    p.advance(b"\x1b]0;half a tit", |a| out.push(a));
+    p.reset();
+    p.advance(b"ok", |a| out.push(a));
+    assert_eq!(out, vec![Action::Print('o'), Action::Print('k')]);
+}
+
+#[test]
+fn csi_param_split_across_feeds_accumulates() {
+    let mut p = Parser::new();
+    let mut out = Vec::new();
+    p.advance(b"\x1b[20", |a| out.push(a));
+    assert!(out.is_empty());
+    p.advance(b"0Cx", |a| out.push(a));
+    assert_eq!(out, vec![Action::CursorForward(200), Action::Print('x')]);
+}
+
+#[test]
+fn paste_start_marker_fed_byte_by_byte_is_one_sequence() {
+    let whole = collect(b"\x1b[200~hi");
+    let mut p = Parser::new();
+    let mut out = Vec::new();
+    for byte in b"\x1b[200~hi" {
+        p.advance(std::slice::from_ref(byte), |a| out.push(a));
+    }
+    assert_eq!(out, whole);
+    // One action for the marker, then the payload prints intact.
+    assert_eq!(out.len(), 3);
+    assert_eq!(&out[1..], &[Action::Print('h'), Action::Print('i')]);
+}
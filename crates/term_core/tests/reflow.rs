@@ -210,3 +210,48 @@ fn shrink_clears_old_wrapline_flags() {
         "stale WRAPLINE leaked into row 2: cell={cell:?}"
     );
 }
+
+#[test]
+fn narrow_then_wide_restores_a_long_wrapped_line() {
+    // A 26-char logical line wraps across three 10-col rows; squeezing
+    // to 4 cols and widening to 30 must give back the single line.
+    let mut em = VtEmulator::new(10, 8, 100);
+    em.process(b"abcdefghijklmnopqrstuvwxyz\r\nnext");
+    em.resize(4, 8);
+    assert_eq!(&row_text(&em, 0)[..4], "abcd");
+    assert_eq!(&row_text(&em, 6)[..4], "yz  ");
+    assert_eq!(&row_text(&em, 7)[..4], "next");
+    em.resize(30, 8);
+    assert_eq!(&row_text(&em, 0)[..26], "abcdefghijklmnopqrstuvwxyz");
+    assert!(!row_wraps(&em, 0));
+    assert_eq!(&row_text(&em, 1)[..4], "next");
+    let snap = em.snapshot();
+    assert_eq!((snap.cursor.row, snap.cursor.col), (1, 4));
+}
+
+#[test]
+fn row_shrink_keeps_the_cursor_line_on_screen() {
+    // Cursor on the last of five lines; shrinking to three rows must
+    // push the top lines into scrollback rather than cut the bottom.
+    let mut em = VtEmulator::new(10, 5, 100);
+    em.process(b"one\r\ntwo\r\nthree\r\nfour\r\nfive");
+    em.resize(10, 3);
+    // Snapshot rows include scrollback; the visible region starts at 2.
+    assert_eq!(em.grid().scrollback_len(), 2);
+    assert_eq!(&row_text(&em, 0)[..3], "one");
+    assert_eq!(&row_text(&em, 2)[..5], "three");
+    assert_eq!(&row_text(&em, 4)[..4], "five");
+    let snap = em.snapshot();
+    assert_eq!((snap.cursor.row, snap.cursor.col), (2, 4));
+}
+
+#[test]
+fn row_shrink_without_scrollback_drops_the_oldest_lines() {
+    let mut em = VtEmulator::new(10, 4, 0);
+    em.process(b"a\r\nb\r\nc\r\nd");
+    em.resize(10, 2);
+    assert_eq!(&row_text(&em, 0)[..1], "c");
+    assert_eq!(&row_text(&em, 1)[..1], "d");
+    assert_eq!(em.grid().scrollback_len(), 0);
+    assert_eq!(em.grid().lines_evicted(), 2);
+}
@@ -0,0 +1,96 @@
+//! Screen diffing: the first diff redraws everything, later ones carry
+//! only the cells that changed, and the wire form round-trips.
+
+use term_core::{ScreenChanges, ScreenDiffer, TerminalEmulator, VtEmulator};
+
+fn run_text(changes: &ScreenChanges, index: usize) -> String {
+    changes.runs[index].cells.iter().map(|c| c.c).collect()
+}
+
+#[test]
+fn first_diff_is_full_redraw() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    em.process(b"hello");
+    let mut differ = ScreenDiffer::new();
+
+    let changes = differ.diff(&em.snapshot());
+    assert!(changes.full_redraw);
+    assert_eq!((changes.cols, changes.rows), (10, 3));
+    assert_eq!(changes.runs.len(), 3);
+    assert_eq!(changes.cell_count(), 30);
+    assert_eq!(run_text(&changes, 0).trim_end(), "hello");
+    assert_eq!(changes.title.as_deref(), Some(""));
+}
+
+#[test]
+fn small_text_change_carries_only_affected_cells() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    em.process(b"hello\r\nworld");
+    let mut differ = ScreenDiffer::new();
+    differ.diff(&em.snapshot());
+
+    // Overwrite "ll" in "hello" with "LL".
+    em.process(b"\x1b[1;3HLL");
+    let changes = differ.diff(&em.snapshot());
+    assert!(!changes.full_redraw);
+    assert_eq!(changes.runs.len(), 1);
+    assert_eq!((changes.runs[0].row, changes.runs[0].col), (0, 2));
+    assert_eq!(run_text(&changes, 0), "LL");
+    assert_eq!(changes.title, None);
+    assert_eq!((changes.cursor.row, changes.cursor.col), (0, 4));
+}
+
+#[test]
+fn unchanged_screen_yields_no_runs() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    em.process(b"hello");
+    let mut differ = ScreenDiffer::new();
+    differ.diff(&em.snapshot());
+
+    assert!(differ.diff(&em.snapshot()).is_empty());
+}
+
+#[test]
+fn resize_or_reset_forces_full_redraw() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    em.process(b"hello");
+    let mut differ = ScreenDiffer::new();
+    differ.diff(&em.snapshot());
+
+    em.resize(12, 4);
+    let changes = differ.diff(&em.snapshot());
+    assert!(changes.full_redraw);
+    assert_eq!((changes.cols, changes.rows), (12, 4));
+    assert_eq!(changes.cell_count(), 48);
+
+    differ.reset();
+    assert!(differ.diff(&em.snapshot()).full_redraw);
+}
+
+#[test]
+fn title_is_sent_only_when_it_changes() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    let mut differ = ScreenDiffer::new();
+    differ.diff(&em.snapshot());
+
+    em.process(b"\x1b]2;build\x07");
+    assert_eq!(differ.diff(&em.snapshot()).title.as_deref(), Some("build"));
+    assert_eq!(differ.diff(&em.snapshot()).title, None);
+}
+
+#[test]
+fn encoded_changes_round_trip() {
+    let mut em = VtEmulator::new(10, 3, 0);
+    em.process(b"\x1b]2;t\x07\x1b[1;31mred\x1b[0m \x1b[38;2;1;2;3m\xe4\xb8\xade\xcc\x81");
+    let mut differ = ScreenDiffer::new();
+    let full = differ.diff(&em.snapshot());
+    assert_eq!(ScreenChanges::decode(&full.encode()), Some(full));
+
+    em.process(b"\x1b[2;1Hx");
+    let partial = differ.diff(&em.snapshot());
+    let bytes = partial.encode();
+    assert_eq!(ScreenChanges::decode(&bytes), Some(partial));
+
+    assert_eq!(ScreenChanges::decode(&bytes[..bytes.len() - 1]), None);
+    assert_eq!(ScreenChanges::decode(&[]), None);
+}
@@ -0,0 +1,50 @@
+//! Screen transcripts: plain text for history/clipboard, or text with the
+//! styling re-encoded as SGR.
+
+use term_core::{TerminalEmulator, TranscriptStyle, VtEmulator};
+
+fn screen(bytes: &[u8], cols: usize, rows: usize) -> VtEmulator {
+    let mut em = VtEmulator::new(cols, rows, 0);
+    em.process(bytes);
+    em
+}
+
+#[test]
+fn plain_drops_styling_and_trailing_blanks() {
+    let em = screen(b"\x1b[1;31mred\x1b[0m plain   \r\n\x1b[4mnext\x1b[0m", 20, 4);
+    let text = em.snapshot().screen_transcript(TranscriptStyle::Plain);
+    assert_eq!(text, "red plain\nnext\n\n");
+}
+
+#[test]
+fn ansi_reencodes_colours_and_attributes() {
+    let em = screen(b"\x1b[1;31mred\x1b[0m plain\r\n\x1b[38;5;200;48;2;1;2;3mx\x1b[0m", 20, 2);
+    let text = em.snapshot().screen_transcript(TranscriptStyle::Ansi);
+    assert_eq!(
+        text,
+        "\x1b[0;1;31mred\x1b[0m plain\n\x1b[0;38;5;200;48;2;1;2;3mx\x1b[0m"
+    );
+}
+
+#[test]
+fn ansi_keeps_trailing_cells_with_a_background() {
+    let em = screen(b"a\x1b[44m  \x1b[0m", 10, 1);
+    let snap = em.snapshot();
+    assert_eq!(snap.screen_transcript(TranscriptStyle::Ansi), "a\x1b[0;44m  \x1b[0m");
+    assert_eq!(snap.screen_transcript(TranscriptStyle::Plain), "a");
+}
+
+#[test]
+fn soft_wrapped_rows_join_and_wide_chars_print_once() {
+    let em = screen("abcdefgh界".as_bytes(), 5, 3);
+    let text = em.snapshot().screen_transcript(TranscriptStyle::Plain);
+    assert_eq!(text, "abcdefgh界\n");
+}
+
+#[test]
+fn line_range_selects_rows() {
+    let em = screen(b"one\r\ntwo\r\nthree", 10, 3);
+    let snap = em.snapshot();
+    assert_eq!(snap.transcript(1..3, TranscriptStyle::Plain), "two\nthree");
+    assert_eq!(snap.transcript(2..9, TranscriptStyle::Plain), "three");
+}
@@ -0,0 +1,111 @@
+//! Double-width and zero-width characters: a wide character takes two
+//! cells (lead + spacer), erasing either half clears both, and combining
+//! marks ride on the previous cell without moving the cursor.
+
+use term_core::{TerminalEmulator, VtEmulator};
+
+fn row_text(em: &VtEmulator, row: usize) -> String {
+    em.snapshot().rows[row]
+        .cells
+        .iter()
+        .filter(|c| !c.flags.wide_char_spacer())
+        .map(|c| c.c)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// True when every wide lead on `row` has its spacer and vice versa.
+fn halves_paired(em: &VtEmulator, row: usize) -> bool {
+    let cells = &em.snapshot().rows[row].cells;
+    cells.iter().enumerate().all(|(i, cell)| {
+        if cell.flags.wide_char() {
+            cells.get(i + 1).is_some_and(|next| next.flags.wide_char_spacer())
+        } else if cell.flags.wide_char_spacer() {
+            i > 0 && cells[i - 1].flags.wide_char()
+        } else {
+            true
+        }
+    })
+}
+
+#[test]
+fn cjk_takes_two_cells_each() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("日本a".as_bytes());
+    let snap = em.snapshot();
+    let cells = &snap.rows[0].cells;
+    assert_eq!(cells[0].c, '日');
+    assert!(cells[0].flags.wide_char());
+    assert!(cells[1].flags.wide_char_spacer());
+    assert_eq!(cells[2].c, '本');
+    assert!(cells[3].flags.wide_char_spacer());
+    assert_eq!(cells[4].c, 'a');
+    assert_eq!(snap.cursor.col, 5);
+    assert_eq!(row_text(&em, 0), "日本a");
+}
+
+#[test]
+fn erase_from_a_spacer_clears_its_lead_too() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("ab日本".as_bytes());
+    // Column 4 (1-based) is the spacer of 日.
+    em.process(b"\x1b[4G\x1b[K");
+    assert_eq!(row_text(&em, 0), "ab");
+    assert!(halves_paired(&em, 0));
+}
+
+#[test]
+fn erase_to_start_ending_on_a_lead_clears_its_spacer() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("ab日本".as_bytes());
+    // Column 5 (1-based) is the lead of 本; EL 1 erases through it.
+    em.process(b"\x1b[5G\x1b[1K");
+    let cells = &em.snapshot().rows[0].cells;
+    assert!(cells[..6].iter().all(|c| c.c == ' ' && !c.flags.wide_char()));
+    assert!(halves_paired(&em, 0));
+}
+
+#[test]
+fn overwriting_half_a_wide_char_blanks_the_other_half() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("日本".as_bytes());
+    em.process(b"\x1b[2Gx");
+    assert_eq!(row_text(&em, 0), " x本");
+    assert!(halves_paired(&em, 0));
+}
+
+#[test]
+fn combining_mark_joins_previous_cell() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("e\u{301}x".as_bytes());
+    let snap = em.snapshot();
+    let cells = &snap.rows[0].cells;
+    assert_eq!(cells[0].c, 'e');
+    assert_eq!(cells[0].extra.as_ref().unwrap().zerowidth, vec!['\u{301}']);
+    assert_eq!(cells[1].c, 'x');
+    assert_eq!(snap.cursor.col, 2);
+}
+
+#[test]
+fn combining_mark_after_wide_char_lands_on_the_lead() {
+    let mut em = VtEmulator::new(10, 2, 0);
+    em.process("日\u{3099}".as_bytes());
+    let snap = em.snapshot();
+    let cells = &snap.rows[0].cells;
+    assert_eq!(cells[0].extra.as_ref().unwrap().zerowidth, vec!['\u{3099}']);
+    assert!(cells[1].extra.is_none());
+    assert_eq!(snap.cursor.col, 2);
+}
+
+#[test]
+fn wide_char_in_last_column_wraps() {
+    let mut em = VtEmulator::new(5, 2, 0);
+    em.process("abcd日".as_bytes());
+    let snap = em.snapshot();
+    assert!(snap.rows[0].cells[4].flags.wrap_line());
+    assert_eq!(snap.rows[0].cells[4].c, ' ');
+    assert_eq!(snap.rows[1].cells[0].c, '日');
+    assert!(snap.rows[1].cells[1].flags.wide_char_spacer());
+    assert_eq!((snap.cursor.row, snap.cursor.col), (1, 2));
+}
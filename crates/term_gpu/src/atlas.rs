@@ -276,8 +276,8 @@ impl GlyphAtlas {
         for e in self.entries.values() {
             layer_live[e.placed.layer as usize] = true;
         }
-        for layer in 0..MAX_LAYERS {
-            if !layer_live[layer] && !self.packers[layer].is_empty() {
+        for (layer, live) in layer_live.iter().enumerate() {
+            if !live && !self.packers[layer].is_empty() {
                 self.packers[layer].reset();
                 let base = layer * layer_bytes();
                 self.cpu_data[base..base + layer_bytes()].fill(0);
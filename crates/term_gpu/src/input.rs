@@ -2,14 +2,19 @@
 //!
 //! Maps `winit::keyboard::Key` + `ModifiersState` to the byte sequence
 //! a typical terminal sends to the PTY. Covers printable text, named
-//! keys (Enter / Tab / arrows / home-end / page up-down / delete),
+//! keys (Enter / Tab / arrows / home-end / page up-down / delete / menu),
 //! `Ctrl+letter` control codes, and `Alt+key` as ESC-prefixed Meta.
 //! Returns `None` for keys that have no terminal-byte equivalent
 //! (modifier keys alone, function keys we don't translate, IME
 //! composition events).
+//!
+//! [`decode_key`] is the inverse: it parses one encoded key back to the
+//! key + modifiers that produce it, for replaying or remapping input.
+//! [`try_decode_key`] also says why bytes didn't decode, for diagnosing
+//! what an unfamiliar terminal sends.
 
-use term_core::{MouseProtocol, MouseTracking};
-use winit::keyboard::{Key, ModifiersState, NamedKey};
+use term_core::{MouseEncoding, MouseProtocol, MouseTracking};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey};
 
 /// The xterm modifier parameter (`1 + shift + alt*2 + ctrl*4`) as its ASCII
 /// digit (`'2'..'8'`), or `None` when no shift / alt / ctrl is held (the
@@ -136,15 +141,227 @@ pub fn encode_key(
             NamedKey::F10 => Some(fn_tilde(b"21", modifiers)),
             NamedKey::F11 => Some(fn_tilde(b"23", modifiers)),
             NamedKey::F12 => Some(fn_tilde(b"24", modifiers)),
+            // The Menu / Application key, sent as xterm's `Do` (`CSI 29 ~`).
+            NamedKey::ContextMenu => Some(fn_tilde(b"29", modifiers)),
             _ => None,
         },
         _ => None,
     }
 }
 
+/// Encode a numeric-keypad key in application-keypad mode (DECKPAM): digits
+/// `SS3 p`..`SS3 y`, Enter `SS3 M`, and the operators (`+` `SS3 k`, `-`
+/// `SS3 m`, `*` `SS3 j`, `/` `SS3 o`, `.` `SS3 n`, `,` `SS3 l`, `=` `SS3 X`).
+/// Returns `None` outside app mode or for a non-keypad key, so the caller
+/// falls through to [`encode_key`] (numeric mode sends the plain characters).
+pub fn encode_keypad_key(code: KeyCode, keypad_app: bool) -> Option<Vec<u8>> {
+    if !keypad_app {
+        return None;
+    }
+    let final_byte = match code {
+        KeyCode::Numpad0 => b'p',
+        KeyCode::Numpad1 => b'q',
+        KeyCode::Numpad2 => b'r',
+        KeyCode::Numpad3 => b's',
+        KeyCode::Numpad4 => b't',
+        KeyCode::Numpad5 => b'u',
+        KeyCode::Numpad6 => b'v',
+        KeyCode::Numpad7 => b'w',
+        KeyCode::Numpad8 => b'x',
+        KeyCode::Numpad9 => b'y',
+        KeyCode::NumpadEnter => b'M',
+        KeyCode::NumpadAdd => b'k',
+        KeyCode::NumpadSubtract => b'm',
+        KeyCode::NumpadMultiply => b'j',
+        KeyCode::NumpadDivide => b'o',
+        KeyCode::NumpadDecimal => b'n',
+        KeyCode::NumpadComma => b'l',
+        KeyCode::NumpadEqual => b'X',
+        _ => return None,
+    };
+    Some(vec![0x1b, b'O', final_byte])
+}
+
+/// Parse one complete key sequence, as produced by [`encode_key`], back to
+/// its key and modifiers. The inverse is canonical where the encoding is
+/// ambiguous: `0x09` / `0x0d` / `0x7f` decode as Tab / Enter / Backspace
+/// (not Ctrl+I / Ctrl+M / Ctrl+Backspace → `0x17` is Ctrl+W), and both
+/// `CSI` and `SS3` cursor forms decode to the same arrow. Returns `None`
+/// for bytes that aren't exactly one key.
+pub fn decode_key(bytes: &[u8]) -> Option<(Key, ModifiersState)> {
+    let plain = ModifiersState::empty();
+    match bytes {
+        [] => None,
+        b"\r" => Some((Key::Named(NamedKey::Enter), plain)),
+        b"\t" => Some((Key::Named(NamedKey::Tab), plain)),
+        b"\x1b[Z" => Some((Key::Named(NamedKey::Tab), ModifiersState::SHIFT)),
+        [0x7f] => Some((Key::Named(NamedKey::Backspace), plain)),
+        [0x1b, 0x7f] => Some((Key::Named(NamedKey::Backspace), ModifiersState::ALT)),
+        [0x1b] => Some((Key::Named(NamedKey::Escape), plain)),
+        b" " => Some((Key::Named(NamedKey::Space), plain)),
+        [0x1b, b'[', rest @ ..] if decode_csi(rest).is_some() => decode_csi(rest),
+        [0x1b, b'O', letter] if decode_ss3(*letter).is_some() => Some((decode_ss3(*letter)?, plain)),
+        [0x1b, rest @ ..] => {
+            // Meta: ESC + a single plain character or control code.
+            let (key, m) = decode_key(rest)?;
+            let meta_able = matches!(&key, Key::Character(s) if s.chars().count() == 1) && !m.alt_key();
+            meta_able.then_some((key, m | ModifiersState::ALT))
+        }
+        [c0] if *c0 < 0x20 => decode_control(*c0),
+        _ => {
+            let text = std::str::from_utf8(bytes).ok()?;
+            if text.chars().any(char::is_control) {
+                return None;
+            }
+            Some((Key::Character(text.into()), plain))
+        }
+    }
+}
+
+/// Why [`try_decode_key`] couldn't decode a byte sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndecodedReason {
+    /// A `CSI` / `SS3` sequence or UTF-8 character cut off before its end —
+    /// what a reader flushing after a timeout is left holding.
+    Incomplete,
+    /// A complete `CSI` / `SS3` sequence whose final byte or parameters no
+    /// key encodes to.
+    Unrecognized,
+    /// Bytes that aren't UTF-8 text.
+    InvalidUtf8,
+    /// Valid, but not exactly one key: several keys, or control codes mixed
+    /// into text.
+    NotOneKey,
+}
+
+/// Bytes [`try_decode_key`] couldn't decode, kept for logging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndecodedKey {
+    pub raw: Vec<u8>,
+    pub reason: UndecodedReason,
+}
+
+/// [`decode_key`], reporting why the bytes aren't a key on failure.
+pub fn try_decode_key(bytes: &[u8]) -> Result<(Key, ModifiersState), UndecodedKey> {
+    decode_key(bytes).ok_or_else(|| UndecodedKey {
+        raw: bytes.to_vec(),
+        reason: undecoded_reason(bytes),
+    })
+}
+
+/// Classify bytes [`decode_key`] rejected.
+fn undecoded_reason(bytes: &[u8]) -> UndecodedReason {
+    // Where a CSI / SS3 body ends: its final byte, or None while still open.
+    let sequence_end = |body: &[u8], is_final: fn(u8) -> bool| {
+        match body.iter().position(|&b| is_final(b)) {
+            None => UndecodedReason::Incomplete,
+            Some(i) if i + 1 == body.len() => UndecodedReason::Unrecognized,
+            Some(_) => UndecodedReason::NotOneKey,
+        }
+    };
+    match bytes {
+        [] => UndecodedReason::Incomplete,
+        [0x1b, b'[', body @ ..] => sequence_end(body, |b| (0x40..=0x7e).contains(&b)),
+        [0x1b, b'O', body @ ..] => sequence_end(body, |_| true),
+        // ESC before something that decodes alone: two keys, or a Meta
+        // form decode_key doesn't produce.
+        [0x1b, rest @ ..] if decode_key(rest).is_some() => UndecodedReason::NotOneKey,
+        [0x1b, rest @ ..] => undecoded_reason(rest),
+        _ => match std::str::from_utf8(bytes) {
+            Err(e) if e.error_len().is_none() => UndecodedReason::Incomplete,
+            Err(_) => UndecodedReason::InvalidUtf8,
+            Ok(_) => UndecodedReason::NotOneKey,
+        },
+    }
+}
+
+/// `Ctrl+key` for a C0 byte other than the named Tab / Enter / Escape.
+fn decode_control(c0: u8) -> Option<(Key, ModifiersState)> {
+    let ch = match c0 {
+        0x00 => ' ',
+        0x01..=0x1a => (b'a' + c0 - 1) as char,
+        0x1c => '\\',
+        0x1d => ']',
+        0x1e => '^',
+        0x1f => '/',
+        _ => return None,
+    };
+    Some((Key::Character(ch.to_string().into()), ModifiersState::CONTROL))
+}
+
+/// The key behind an `SS3 <letter>` sequence (application cursor / F1–F4).
+fn decode_ss3(letter: u8) -> Option<Key> {
+    let named = match letter {
+        b'A' => NamedKey::ArrowUp,
+        b'B' => NamedKey::ArrowDown,
+        b'C' => NamedKey::ArrowRight,
+        b'D' => NamedKey::ArrowLeft,
+        b'H' => NamedKey::Home,
+        b'F' => NamedKey::End,
+        b'P' => NamedKey::F1,
+        b'Q' => NamedKey::F2,
+        b'R' => NamedKey::F3,
+        b'S' => NamedKey::F4,
+        _ => return None,
+    };
+    Some(Key::Named(named))
+}
+
+/// Decode the part of a `CSI` key sequence after `ESC [`: `<letter>`,
+/// `1 ; <mod> <letter>`, `<n> ~` or `<n> ; <mod> ~`.
+fn decode_csi(rest: &[u8]) -> Option<(Key, ModifiersState)> {
+    let (&final_byte, params) = rest.split_last()?;
+    let text = std::str::from_utf8(params).ok()?;
+    let (number, modifiers) = match text.split_once(';') {
+        Some((n, m)) => (n, decode_modifier_param(m)?),
+        None => (text, ModifiersState::empty()),
+    };
+    let key = if final_byte == b'~' {
+        let named = match number {
+            "2" => NamedKey::Insert,
+            "3" => NamedKey::Delete,
+            "5" => NamedKey::PageUp,
+            "6" => NamedKey::PageDown,
+            "15" => NamedKey::F5,
+            "17" => NamedKey::F6,
+            "18" => NamedKey::F7,
+            "19" => NamedKey::F8,
+            "20" => NamedKey::F9,
+            "21" => NamedKey::F10,
+            "23" => NamedKey::F11,
+            "24" => NamedKey::F12,
+            "29" => NamedKey::ContextMenu,
+            _ => return None,
+        };
+        Key::Named(named)
+    } else {
+        // Letter forms carry no number, or `1` when modified.
+        let expected = if modifiers.is_empty() { "" } else { "1" };
+        if number != expected {
+            return None;
+        }
+        decode_ss3(final_byte)?
+    };
+    Some((key, modifiers))
+}
+
+/// Invert [`modifier_param`]: `"2"..="8"` back to shift / alt / ctrl.
+fn decode_modifier_param(param: &str) -> Option<ModifiersState> {
+    let bits = match param.as_bytes() {
+        [p @ b'2'..=b'8'] => p - b'1',
+        _ => return None,
+    };
+    let mut m = ModifiersState::empty();
+    m.set(ModifiersState::SHIFT, bits & 1 != 0);
+    m.set(ModifiersState::ALT, bits & 2 != 0);
+    m.set(ModifiersState::CONTROL, bits & 4 != 0);
+    Some(m)
+}
+
 /// Encode a mouse event in the legacy X10 form `CSI M Cb Cx Cy`, each value a
 /// single byte offset by 32. `button` is the raw button-bits value (0 = left,
-/// 1 = middle, 2 = right, 3 = release; 64 / 65 = wheel up / down). `col` / `row`
+/// 1 = middle, 2 = right, 3 = release; 64 / 65 = wheel up / down; 128 / 129 =
+/// back / forward). `col` / `row`
 /// are 1-based cells; values above 223 can't fit a single byte and are clamped
 /// (the SGR form has no such limit).
 pub fn encode_mouse_x10(button: u8, col: u16, row: u16) -> Vec<u8> {
@@ -168,6 +385,9 @@ pub enum MouseButton {
     Right,
     WheelUp,
     WheelDown,
+    /// Side buttons 8 / 9 — browser-style back / forward.
+    Back,
+    Forward,
     /// No button — the "no buttons pressed" code (3), used for bare any-event
     /// (1003) pointer motion.
     None,
@@ -182,6 +402,8 @@ impl MouseButton {
             MouseButton::Right => 2,
             MouseButton::WheelUp => 64,
             MouseButton::WheelDown => 65,
+            MouseButton::Back => 128,
+            MouseButton::Forward => 129,
             MouseButton::None => 3,
         }
     }
@@ -197,32 +419,63 @@ pub enum MouseEventKind {
     Motion,
 }
 
+/// Encode a mouse event in urxvt (1015) form `CSI Cb ; Cx ; Cy M` — decimal
+/// fields, `Cb` offset by 32 as in the legacy form, `col` / `row` 1-based with
+/// no coordinate limit. There is no release final byte; callers pass
+/// button-bits 3 for a release.
+pub fn encode_mouse_urxvt(button: u8, col: u16, row: u16) -> Vec<u8> {
+    format!("\x1b[{};{col};{row}M", 32 + button as u16).into_bytes()
+}
+
 /// Compose an xterm mouse report for `(button, kind)` at 1-based cell
-/// `(col, row)`. `sgr` selects the SGR (1006) form over the legacy default
-/// (`CSI M`) form. This is the one place the protocol byte shape is decided —
-/// the coordinator maps platform events to `(MouseButton, MouseEventKind)` and
-/// reads the encoding off the emulator's [`term_core::MouseProtocol`]. Modifier
-/// keys are intentionally not folded into `Cb` (matching Warp); the UTF-8 (1005)
-/// and urxvt (1015) encodings are intentionally unsupported (deprecated, and
-/// Warp omits them too).
+/// `(col, row)` in the given `encoding` — SGR (1006), urxvt (1015), or the
+/// legacy default (`CSI M`). This is the one place the protocol byte shape is
+/// decided — the coordinator maps platform events to `(MouseButton,
+/// MouseEventKind)` and reads the encoding off the emulator's
+/// [`term_core::MouseProtocol`]. Modifier keys are intentionally not folded
+/// into `Cb` (matching Warp); the UTF-8 (1005) encoding is intentionally
+/// unsupported (deprecated, and Warp omits it too).
 pub fn encode_mouse_report(
     button: MouseButton,
     kind: MouseEventKind,
     col: u16,
     row: u16,
-    sgr: bool,
+    encoding: MouseEncoding,
 ) -> Vec<u8> {
     let motion = matches!(kind, MouseEventKind::Motion);
     let cb = button.base() + if motion { 32 } else { 0 };
-    if sgr {
+    // Legacy and urxvt carry no button identity on release — it is reported
+    // as button-bits 3.
+    let release = matches!(kind, MouseEventKind::Release);
+    let raw = if release { 3 } else { cb };
+    match encoding {
         // SGR keeps the real button code; press / motion → 'M', release → 'm'.
-        encode_mouse_sgr(cb, col, row, !matches!(kind, MouseEventKind::Release))
-    } else {
-        // Legacy form carries no button identity on release — it is reported as
-        // button-bits 3.
-        let raw = if matches!(kind, MouseEventKind::Release) { 3 } else { cb };
-        encode_mouse_x10(raw, col, row)
+        MouseEncoding::Sgr => encode_mouse_sgr(cb, col, row, !release),
+        MouseEncoding::Urxvt => encode_mouse_urxvt(raw, col, row),
+        MouseEncoding::Default => encode_mouse_x10(raw, col, row),
+    }
+}
+
+/// Decide whether a button / wheel event belongs to the child app and, if so,
+/// encode it. Returns `None` — the UI keeps the event (selection, scroll) —
+/// when the child hasn't enabled tracking (`?1000h` and up), when it's motion
+/// under click-only tracking, or when `shift` is held (the local-action bypass,
+/// matching Warp). `col` / `row` are 1-based cells.
+pub fn forward_mouse_report(
+    proto: MouseProtocol,
+    shift: bool,
+    button: MouseButton,
+    kind: MouseEventKind,
+    col: u16,
+    row: u16,
+) -> Option<Vec<u8>> {
+    if shift || !proto.is_active() {
+        return None;
+    }
+    if matches!(kind, MouseEventKind::Motion) && !proto.reports_motion() {
+        return None;
     }
+    Some(encode_mouse_report(button, kind, col, row, proto.encoding))
 }
 
 /// Decide the motion (drag / move) report when the pointer moves to 0-based
@@ -257,6 +510,6 @@ pub fn encode_motion_report(
         MouseEventKind::Motion,
         cell.0 + 1,
         cell.1 + 1,
-        proto.is_sgr(),
+        proto.encoding,
     ))
 }
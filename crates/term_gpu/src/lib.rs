@@ -19,8 +19,9 @@ pub mod text;
 
 pub use atlas::{GlyphAtlas, GlyphFormat, PlacedGlyph, RasterizedGlyph, ShelfPacker};
 pub use input::{
-    encode_key, encode_motion_report, encode_mouse_report, encode_mouse_sgr, encode_mouse_x10,
-    MouseButton, MouseEventKind,
+    decode_key, encode_key, encode_keypad_key, encode_motion_report, encode_mouse_report, encode_mouse_sgr, encode_mouse_urxvt,
+    encode_mouse_x10, forward_mouse_report, try_decode_key, MouseButton, MouseEventKind,
+    UndecodedKey, UndecodedReason,
 };
 pub use instances::{GlyphInstance, RectInstance, RenderLayer, ShadowInstance, Uniforms};
 pub use label::{measure_label_width, push_label};
@@ -28,7 +29,7 @@ pub use panel_render::{
     build_cursor_rect, measure_cell_metrics, populate_panel, CellMetrics, PanelRect,
     CURSOR_COLOR, CURSOR_STROKE_PHYSICAL, DEFAULT_FG,
 };
-pub use paste::{encode_paste, shell_quote_path};
+pub use paste::{encode_paste, sanitize_paste, shell_quote_path};
 pub use renderer::GpuRenderer;
 pub use scroll::{
     decay_velocity, ScrollState, ScrollVelocity, GESTURE_END_TIMEOUT, MOMENTUM_FRAME_INTERVAL,
@@ -405,7 +405,7 @@ pub fn build_cursor_rect(
     // get the absolute row, then subtract the visible-anchor offset so
     // that `scroll_offset_y == 0` puts the cursor at its expected place
     // inside the panel.
-    let abs_row = visible_start + cursor.row as usize;
+    let abs_row = visible_start + cursor.row;
     let scroll_offset_y_phys = scroll_offset_y_logical * sf;
     let baseline_offset_phys = visible_start as f32 * metrics.height_physical;
     let cell_offset_y_phys = abs_row as f32 * metrics.height_physical - baseline_offset_phys
@@ -3,29 +3,55 @@
 //! `encode_paste` prepares clipboard text for write to a PTY:
 //! normalises line endings (the macOS pasteboard often carries CRLF
 //! from Windows-origin content) and wraps in the bracketed-paste
-//! markers `\e[200~` / `\e[201~` when the emulator has that mode on.
+//! markers `\e[200~` / `\e[201~` when the emulator has that mode on
+//! (the child sent `?2004h`; `?2004l` turns it back off).
+//!
+//! `sanitize_paste` optionally strips control characters from
+//! untrusted clipboard text before it reaches the child.
 //!
 //! `shell_quote_path` single-quotes a file path so a shell tokenises
 //! it as one argument. Used when pasting image-from-clipboard paths
 //! into a PTY — see `term_clipboard::save_image_to_temp` for the
 //! image side of the bridge.
 
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
 /// Prepare clipboard text for write to a PTY. CRLF / lone-CR are
 /// folded to plain `\n`; bracketed-paste markers wrap the payload
 /// when the emulator has that mode on.
+///
+/// Markers already inside the text are always removed: a child that
+/// never enabled bracketed paste must not see them, and one that did
+/// must not have its paste ended early by an embedded `\e[201~`.
+/// Removal repeats until none are left, so a marker nested inside
+/// another (`\e[20\e[201~1~`) can't reassemble.
 pub fn encode_paste(text: &str, bracketed: bool) -> Vec<u8> {
-    let normalized: String = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut normalized: String = text.replace("\r\n", "\n").replace('\r', "\n");
+    while normalized.contains(PASTE_START) || normalized.contains(PASTE_END) {
+        normalized = normalized.replace(PASTE_START, "").replace(PASTE_END, "");
+    }
     if bracketed {
         let mut out = Vec::with_capacity(normalized.len() + 8);
-        out.extend_from_slice(b"\x1b[200~");
+        out.extend_from_slice(PASTE_START.as_bytes());
         out.extend_from_slice(normalized.as_bytes());
-        out.extend_from_slice(b"\x1b[201~");
+        out.extend_from_slice(PASTE_END.as_bytes());
         out
     } else {
         normalized.into_bytes()
     }
 }
 
+/// Strip control characters from paste text: C0 (except tab, LF, CR),
+/// DEL and C1. Removing ESC defangs embedded sequences — including a
+/// smuggled `\e[201~` that would end bracketed paste early — while
+/// ordinary text and line structure survive.
+pub fn sanitize_paste(text: &str) -> String {
+    text.chars()
+        .filter(|&c| matches!(c, '\t' | '\n' | '\r') || !c.is_control())
+        .collect()
+}
+
 /// Single-quote-escape a file path for safe shell tokenization.
 /// `'` inside the path is escaped as `'\''` (POSIX-compatible —
 /// bash, zsh, sh, dash all accept it). Empty paths become empty
@@ -263,7 +263,7 @@ impl GpuRenderer {
         });
         if !layer.shadows.is_empty() {
             self.queue
-                .write_buffer(&shadow_buf, 0, ShadowInstance::as_bytes(&layer.shadows));
+                .write_buffer(&shadow_buf, 0, ShadowInstance::as_bytes(layer.shadows));
         }
         let rect_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some(&format!("term_gpu/{name}_rect_buffer")),
@@ -273,7 +273,7 @@ impl GpuRenderer {
         });
         if !layer.rects.is_empty() {
             self.queue
-                .write_buffer(&rect_buf, 0, RectInstance::as_bytes(&layer.rects));
+                .write_buffer(&rect_buf, 0, RectInstance::as_bytes(layer.rects));
         }
         let glyph_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some(&format!("term_gpu/{name}_glyph_buffer")),
@@ -283,7 +283,7 @@ impl GpuRenderer {
         });
         if !layer.glyphs.is_empty() {
             self.queue
-                .write_buffer(&glyph_buf, 0, GlyphInstance::as_bytes(&layer.glyphs));
+                .write_buffer(&glyph_buf, 0, GlyphInstance::as_bytes(layer.glyphs));
         }
         LayerBuffers {
             shadow: shadow_buf,
@@ -247,6 +247,7 @@ impl TextShapeCache {
     /// `weight` and `style` are forwarded to cosmic-text's `Attrs` and form
     /// part of the cache key — bold vs regular, italic vs upright cache
     /// separately and may resolve to different font faces.
+    #[allow(clippy::too_many_arguments)]
     pub fn shape(
         &mut self,
         font_system: &mut FontSystem,
@@ -1,8 +1,9 @@
 //! Keyboard encoder byte tests. The encoder produces exact xterm/VT key
 //! sequences; these pin them. `encode_key(key, key_unmod, modifiers, app_cursor)`.
+//! `decode_key` must parse each encoding back to the key that produced it.
 
-use term_gpu::encode_key;
-use winit::keyboard::{Key, ModifiersState, NamedKey};
+use term_gpu::{decode_key, encode_key, encode_keypad_key, try_decode_key, UndecodedReason};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey};
 
 fn ch(s: &str) -> Key {
     Key::Character(s.into())
@@ -124,3 +125,129 @@ fn printable_and_shifted() {
     // Shift is already folded into the logical char.
     assert_eq!(enc(&ch("A"), ModifiersState::SHIFT), Some(b"A".to_vec()));
 }
+
+#[test]
+fn keypad_app_mode_sends_ss3_codes() {
+    assert_eq!(encode_keypad_key(KeyCode::NumpadEnter, true), Some(b"\x1bOM".to_vec()));
+    assert_eq!(encode_keypad_key(KeyCode::Numpad0, true), Some(b"\x1bOp".to_vec()));
+    assert_eq!(encode_keypad_key(KeyCode::Numpad9, true), Some(b"\x1bOy".to_vec()));
+    assert_eq!(encode_keypad_key(KeyCode::NumpadAdd, true), Some(b"\x1bOk".to_vec()));
+    assert_eq!(encode_keypad_key(KeyCode::NumpadSubtract, true), Some(b"\x1bOm".to_vec()));
+}
+
+#[test]
+fn keypad_numeric_mode_and_non_keypad_keys_fall_through() {
+    // Numeric mode: the caller encodes the plain character via `encode_key`.
+    assert_eq!(encode_keypad_key(KeyCode::Numpad0, false), None);
+    // The main-row digit is never a keypad key.
+    assert_eq!(encode_keypad_key(KeyCode::Digit0, true), None);
+}
+
+#[test]
+fn menu_key_sends_xterm_do() {
+    assert_eq!(enc(&named(NamedKey::ContextMenu), ModifiersState::empty()), Some(b"\x1b[29~".to_vec()));
+    assert_eq!(enc(&named(NamedKey::ContextMenu), ModifiersState::SHIFT), Some(b"\x1b[29;2~".to_vec()));
+}
+
+/// Encode then decode, expecting the original key + modifiers back.
+fn assert_round_trip(key: Key, m: ModifiersState) {
+    let bytes = enc(&key, m).unwrap_or_else(|| panic!("{key:?} {m:?} encodes"));
+    assert_eq!(decode_key(&bytes), Some((key, m)), "bytes {bytes:?}");
+}
+
+#[test]
+fn arrows_and_nav_keys_round_trip() {
+    let mods = [
+        ModifiersState::empty(),
+        ModifiersState::SHIFT,
+        ModifiersState::ALT,
+        ModifiersState::CONTROL | ModifiersState::SHIFT,
+    ];
+    for key in [
+        NamedKey::ArrowUp,
+        NamedKey::ArrowDown,
+        NamedKey::ArrowLeft,
+        NamedKey::ArrowRight,
+        NamedKey::Home,
+        NamedKey::End,
+        NamedKey::F1,
+        NamedKey::F4,
+        NamedKey::F5,
+        NamedKey::F12,
+        NamedKey::ContextMenu,
+    ] {
+        for m in mods {
+            assert_round_trip(named(key), m);
+        }
+    }
+    for key in [NamedKey::Insert, NamedKey::Delete, NamedKey::PageUp, NamedKey::PageDown] {
+        assert_round_trip(named(key), ModifiersState::empty());
+    }
+    // The application-cursor form decodes to the same arrow.
+    assert_eq!(decode_key(b"\x1bOA"), Some((named(NamedKey::ArrowUp), ModifiersState::empty())));
+}
+
+#[test]
+fn editing_keys_round_trip() {
+    for key in [NamedKey::Enter, NamedKey::Tab, NamedKey::Backspace, NamedKey::Escape, NamedKey::Space] {
+        assert_round_trip(named(key), ModifiersState::empty());
+    }
+    assert_round_trip(named(NamedKey::Tab), ModifiersState::SHIFT);
+    assert_round_trip(named(NamedKey::Backspace), ModifiersState::ALT);
+}
+
+#[test]
+fn alt_and_control_combos_round_trip() {
+    assert_round_trip(ch("a"), ModifiersState::ALT);
+    assert_round_trip(ch("["), ModifiersState::ALT);
+    assert_round_trip(ch("c"), ModifiersState::CONTROL);
+    assert_round_trip(ch("z"), ModifiersState::CONTROL);
+    assert_round_trip(ch("c"), ModifiersState::CONTROL | ModifiersState::ALT);
+    assert_round_trip(ch(" "), ModifiersState::CONTROL);
+    assert_round_trip(ch("]"), ModifiersState::CONTROL);
+    assert_round_trip(ch("é"), ModifiersState::empty());
+}
+
+#[test]
+fn ambiguous_bytes_decode_canonically() {
+    // Ctrl+Backspace shares ^W with Ctrl+W; Ctrl+I is Tab.
+    assert_eq!(decode_key(&[0x17]), Some((ch("w"), ModifiersState::CONTROL)));
+    assert_eq!(decode_key(&[0x09]), Some((named(NamedKey::Tab), ModifiersState::empty())));
+}
+
+#[test]
+fn partial_or_multiple_keys_do_not_decode() {
+    assert_eq!(decode_key(b""), None);
+    assert_eq!(decode_key(b"\x1b[1;9A"), None);
+    assert_eq!(decode_key(b"\x1b[99~"), None);
+    assert_eq!(decode_key(b"a\r"), None);
+    assert_eq!(decode_key(b"\x1b\x1b[A"), None);
+}
+
+fn reason(bytes: &[u8]) -> UndecodedReason {
+    let err = try_decode_key(bytes).expect_err("should not decode");
+    assert_eq!(err.raw, bytes, "raw bytes kept");
+    err.reason
+}
+
+#[test]
+fn undecoded_bytes_carry_a_reason() {
+    // A CSI cut off before its final byte, as a timed-out read leaves it.
+    assert_eq!(reason(b"\x1b[1;5"), UndecodedReason::Incomplete);
+    assert_eq!(reason("é".as_bytes().split_at(1).0), UndecodedReason::Incomplete);
+    // Complete sequences no key encodes to.
+    assert_eq!(reason(b"\x1b[1;5X"), UndecodedReason::Unrecognized);
+    assert_eq!(reason(b"\x1b[99~"), UndecodedReason::Unrecognized);
+    assert_eq!(reason(b"\x1bOz"), UndecodedReason::Unrecognized);
+    assert_eq!(reason(&[0xff, 0xfe]), UndecodedReason::InvalidUtf8);
+    assert_eq!(reason(b"a\r"), UndecodedReason::NotOneKey);
+    assert_eq!(reason(b"\x1b[A\x1b[B"), UndecodedReason::NotOneKey);
+    assert_eq!(reason(b"\x1b\x1b[A"), UndecodedReason::NotOneKey);
+}
+
+#[test]
+fn try_decode_agrees_with_decode_key() {
+    for bytes in [&b"\r"[..], b"\x1b[A", b"\x1bOP", b"x", b"\x1bx"] {
+        assert_eq!(try_decode_key(bytes).ok(), decode_key(bytes), "bytes {bytes:?}");
+    }
+}
@@ -2,10 +2,10 @@
 //! (xterm), so these pin the exact output — the one reliable check, since the
 //! feature has no consumer to verify against live in a keyboard-driven app.
 
-use term_core::{MouseEncoding, MouseProtocol, MouseTracking};
+use term_core::{create_emulator, MouseEncoding, MouseProtocol, MouseTracking};
 use term_gpu::{
-    encode_motion_report, encode_mouse_report, encode_mouse_sgr, encode_mouse_x10, MouseButton,
-    MouseEventKind,
+    encode_motion_report, encode_mouse_report, encode_mouse_sgr, encode_mouse_urxvt,
+    encode_mouse_x10, forward_mouse_report, MouseButton, MouseEventKind,
 };
 
 fn proto(tracking: MouseTracking, encoding: MouseEncoding) -> MouseProtocol {
@@ -51,17 +51,50 @@ fn sgr_wheel_has_no_coordinate_limit() {
     assert_eq!(encode_mouse_sgr(64, 500, 999, true), b"\x1b[<64;500;999M".to_vec());
 }
 
-// --- encode_mouse_report (the semantic composer over the two formatters) ---
+#[test]
+fn urxvt_left_press_is_decimal_with_the_32_offset() {
+    assert_eq!(encode_mouse_urxvt(0, 1, 1), b"\x1b[32;1;1M".to_vec());
+}
+
+#[test]
+fn urxvt_has_no_coordinate_limit() {
+    assert_eq!(encode_mouse_urxvt(0, 500, 300), b"\x1b[32;500;300M".to_vec());
+}
+
+#[test]
+fn urxvt_report_release_is_button_three() {
+    assert_eq!(
+        encode_mouse_report(MouseButton::Left, MouseEventKind::Release, 3, 4, MouseEncoding::Urxvt),
+        b"\x1b[35;3;4M".to_vec()
+    );
+}
+
+#[test]
+fn urxvt_left_click_forwarded_after_1015() {
+    let mut emu = create_emulator(80, 24, 0);
+    emu.process(b"\x1b[?1000h\x1b[?1015h");
+    let fx = forward_mouse_report(
+        emu.mouse_protocol(),
+        false,
+        MouseButton::Left,
+        MouseEventKind::Press,
+        5,
+        7,
+    );
+    assert_eq!(fx, Some(b"\x1b[32;5;7M".to_vec()));
+}
+
+// --- encode_mouse_report (the semantic composer over the three formatters) ---
 
 #[test]
 fn report_sgr_middle_and_right_carry_button_one_and_two() {
     // Middle = button 1, right = button 2 (SGR keeps the real code).
     assert_eq!(
-        encode_mouse_report(MouseButton::Middle, MouseEventKind::Press, 3, 4, true),
+        encode_mouse_report(MouseButton::Middle, MouseEventKind::Press, 3, 4, MouseEncoding::Sgr),
         b"\x1b[<1;3;4M".to_vec()
     );
     assert_eq!(
-        encode_mouse_report(MouseButton::Right, MouseEventKind::Press, 3, 4, true),
+        encode_mouse_report(MouseButton::Right, MouseEventKind::Press, 3, 4, MouseEncoding::Sgr),
         b"\x1b[<2;3;4M".to_vec()
     );
 }
@@ -70,7 +103,7 @@ fn report_sgr_middle_and_right_carry_button_one_and_two() {
 fn report_sgr_release_keeps_button_and_uses_lowercase_m() {
     // A right release reports button 2 with the trailing 'm' (button kept).
     assert_eq!(
-        encode_mouse_report(MouseButton::Right, MouseEventKind::Release, 7, 8, true),
+        encode_mouse_report(MouseButton::Right, MouseEventKind::Release, 7, 8, MouseEncoding::Sgr),
         b"\x1b[<2;7;8m".to_vec()
     );
 }
@@ -79,12 +112,12 @@ fn report_sgr_release_keeps_button_and_uses_lowercase_m() {
 fn report_sgr_motion_sets_the_plus_32_bit() {
     // Left drag = button 0 + 32 motion bit = 32, press-form 'M'.
     assert_eq!(
-        encode_mouse_report(MouseButton::Left, MouseEventKind::Motion, 2, 2, true),
+        encode_mouse_report(MouseButton::Left, MouseEventKind::Motion, 2, 2, MouseEncoding::Sgr),
         b"\x1b[<32;2;2M".to_vec()
     );
     // Right drag = 2 + 32 = 34.
     assert_eq!(
-        encode_mouse_report(MouseButton::Right, MouseEventKind::Motion, 2, 2, true),
+        encode_mouse_report(MouseButton::Right, MouseEventKind::Motion, 2, 2, MouseEncoding::Sgr),
         b"\x1b[<34;2;2M".to_vec()
     );
 }
@@ -93,12 +126,12 @@ fn report_sgr_motion_sets_the_plus_32_bit() {
 fn report_legacy_press_and_release_button_bits() {
     // Legacy middle press = button 1 → 32+1 in the button byte.
     assert_eq!(
-        encode_mouse_report(MouseButton::Middle, MouseEventKind::Press, 1, 1, false),
+        encode_mouse_report(MouseButton::Middle, MouseEventKind::Press, 1, 1, MouseEncoding::Default),
         vec![0x1b, b'[', b'M', 33, 33, 33]
     );
     // Legacy release carries no button identity → button-bits 3.
     assert_eq!(
-        encode_mouse_report(MouseButton::Right, MouseEventKind::Release, 1, 1, false),
+        encode_mouse_report(MouseButton::Right, MouseEventKind::Release, 1, 1, MouseEncoding::Default),
         vec![0x1b, b'[', b'M', 35, 33, 33]
     );
 }
@@ -107,7 +140,7 @@ fn report_legacy_press_and_release_button_bits() {
 fn report_legacy_motion_sets_plus_32_in_the_button_byte() {
     // Left drag legacy = 0 + 32 motion = 32 → byte 32+32 = 64.
     assert_eq!(
-        encode_mouse_report(MouseButton::Left, MouseEventKind::Motion, 1, 1, false)[3],
+        encode_mouse_report(MouseButton::Left, MouseEventKind::Motion, 1, 1, MouseEncoding::Default)[3],
         64
     );
 }
@@ -116,12 +149,12 @@ fn report_legacy_motion_sets_plus_32_in_the_button_byte() {
 fn report_bare_motion_uses_no_button_code_three() {
     // Any-event (1003) motion with no button held = code 3 + 32 motion = 35.
     assert_eq!(
-        encode_mouse_report(MouseButton::None, MouseEventKind::Motion, 9, 9, true),
+        encode_mouse_report(MouseButton::None, MouseEventKind::Motion, 9, 9, MouseEncoding::Sgr),
         b"\x1b[<35;9;9M".to_vec()
     );
     // Legacy form: 35 → button byte 32+35 = 67.
     assert_eq!(
-        encode_mouse_report(MouseButton::None, MouseEventKind::Motion, 9, 9, false)[3],
+        encode_mouse_report(MouseButton::None, MouseEventKind::Motion, 9, 9, MouseEncoding::Default)[3],
         67
     );
 }
@@ -129,15 +162,31 @@ fn report_bare_motion_uses_no_button_code_three() {
 #[test]
 fn report_wheel_maps_to_64_and_65_in_both_encodings() {
     assert_eq!(
-        encode_mouse_report(MouseButton::WheelUp, MouseEventKind::Press, 1, 1, true),
+        encode_mouse_report(MouseButton::WheelUp, MouseEventKind::Press, 1, 1, MouseEncoding::Sgr),
         b"\x1b[<64;1;1M".to_vec()
     );
     assert_eq!(
-        encode_mouse_report(MouseButton::WheelDown, MouseEventKind::Press, 1, 1, false)[3],
+        encode_mouse_report(MouseButton::WheelDown, MouseEventKind::Press, 1, 1, MouseEncoding::Default)[3],
         32 + 65
     );
 }
 
+#[test]
+fn report_back_and_forward_map_to_128_and_129() {
+    assert_eq!(
+        encode_mouse_report(MouseButton::Back, MouseEventKind::Press, 4, 2, MouseEncoding::Sgr),
+        b"\x1b[<128;4;2M".to_vec()
+    );
+    assert_eq!(
+        encode_mouse_report(MouseButton::Back, MouseEventKind::Release, 4, 2, MouseEncoding::Sgr),
+        b"\x1b[<128;4;2m".to_vec()
+    );
+    assert_eq!(
+        encode_mouse_report(MouseButton::Forward, MouseEventKind::Press, 1, 1, MouseEncoding::Default)[3],
+        32 + 129
+    );
+}
+
 // --- encode_motion_report (the tracking-level / dedup gating) ---
 
 #[test]
@@ -189,3 +238,37 @@ fn motion_honours_legacy_encoding() {
     // Bare motion legacy = 35 → button byte 32+35 = 67.
     assert_eq!(encode_motion_report(p, false, None, (0, 0)).unwrap()[3], 67);
 }
+
+#[test]
+fn click_is_forwarded_once_the_child_enables_tracking() {
+    let mut emu = create_emulator(80, 24, 0);
+    emu.process(b"\x1b[?1000h\x1b[?1006h");
+    let fx = forward_mouse_report(
+        emu.mouse_protocol(),
+        false,
+        MouseButton::Left,
+        MouseEventKind::Press,
+        3,
+        4,
+    );
+    assert_eq!(fx, Some(b"\x1b[<0;3;4M".to_vec()));
+}
+
+#[test]
+fn click_stays_with_the_ui_without_tracking_or_with_shift() {
+    let mut emu = create_emulator(80, 24, 0);
+    let press = |p, shift| {
+        forward_mouse_report(p, shift, MouseButton::Left, MouseEventKind::Press, 1, 1)
+    };
+    assert_eq!(press(emu.mouse_protocol(), false), None, "tracking never enabled");
+    emu.process(b"\x1b[?1000h");
+    assert_eq!(press(emu.mouse_protocol(), true), None, "Shift bypass");
+    emu.process(b"\x1b[?1000l");
+    assert_eq!(press(emu.mouse_protocol(), false), None, "tracking turned back off");
+}
+
+#[test]
+fn motion_is_kept_under_click_only_tracking() {
+    let p = proto(MouseTracking::Normal, MouseEncoding::Sgr);
+    assert!(forward_mouse_report(p, false, MouseButton::Left, MouseEventKind::Motion, 1, 1).is_none());
+}
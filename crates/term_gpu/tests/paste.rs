@@ -0,0 +1,62 @@
+//! Paste-flow helpers: the bracketed wrap, gated on the child's DECSET 2004,
+//! and the optional control-character sanitizer applied to untrusted
+//! clipboard text.
+
+use term_core::{TerminalEmulator, VtEmulator};
+use term_gpu::{encode_paste, sanitize_paste};
+
+#[test]
+fn sanitizer_strips_esc_and_bel_but_keeps_text_and_newlines() {
+    let pasted = "echo hi\x1b[31m red\x07\nnext\tline\r\n";
+    assert_eq!(sanitize_paste(pasted), "echo hi[31m red\nnext\tline\r\n");
+}
+
+#[test]
+fn sanitizer_defangs_a_smuggled_bracket_end_marker() {
+    let bytes = encode_paste(&sanitize_paste("a\x1b[201~rm -rf ~\n"), true);
+    // Only the real end marker survives.
+    let end_markers = bytes.windows(6).filter(|w| w == b"\x1b[201~").count();
+    assert_eq!(end_markers, 1);
+    assert!(bytes.ends_with(b"\x1b[201~"));
+}
+
+#[test]
+fn sanitizer_drops_del_and_c1_but_keeps_unicode() {
+    assert_eq!(sanitize_paste("é\u{7f}🦀\u{9b}ok"), "é🦀ok");
+}
+
+#[test]
+fn encode_paste_folds_crlf_and_wraps_when_bracketed() {
+    assert_eq!(encode_paste("a\r\nb\rc", false), b"a\nb\nc".to_vec());
+    assert_eq!(encode_paste("x", true), b"\x1b[200~x\x1b[201~".to_vec());
+}
+
+#[test]
+fn encode_paste_drops_embedded_markers_in_both_modes() {
+    let text = "a\x1b[200~b\x1b[201~c";
+    assert_eq!(encode_paste(text, false), b"abc".to_vec());
+    assert_eq!(encode_paste(text, true), b"\x1b[200~abc\x1b[201~".to_vec());
+}
+
+#[test]
+fn encode_paste_drops_markers_nested_inside_markers() {
+    // One pass would leave `\e[201~` behind and end the paste early.
+    let text = "a\x1b[20\x1b[201~1~b\x1b[2\x1b[200~00~c";
+    assert_eq!(encode_paste(text, false), b"abc".to_vec());
+    assert_eq!(encode_paste(text, true), b"\x1b[200~abc\x1b[201~".to_vec());
+}
+
+#[test]
+fn paste_follows_the_childs_decset_2004() {
+    let mut emu = VtEmulator::new(20, 4, 0);
+    let paste = |emu: &VtEmulator| encode_paste("ls\n", emu.bracketed_paste());
+
+    // Off until the child asks for it: sent raw.
+    assert_eq!(paste(&emu), b"ls\n".to_vec());
+
+    emu.process(b"\x1b[?2004h");
+    assert_eq!(paste(&emu), b"\x1b[200~ls\n\x1b[201~".to_vec());
+
+    emu.process(b"\x1b[?2004l");
+    assert_eq!(paste(&emu), b"ls\n".to_vec());
+}
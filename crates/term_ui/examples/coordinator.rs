@@ -197,16 +197,18 @@ impl Coordinator {
         // ── Reconcile phase (R7): refresh the retained tree from (AppState, ──
         //    frame_now), only when dirty. First frame builds; later dirty
         //    frames reconcile incrementally against the prior view (R5).
-        if self.root.is_none() {
+        if let Some(root) = self.root {
+            if self.dirty {
+                let next = view(&self.state, frame_now);
+                let prev = self.prev_view.take().expect("prev_view present once built");
+                reconcile_root(&mut self.tree, root, &prev, &next);
+                self.prev_view = Some(next);
+            }
+        } else {
             let v = view(&self.state, frame_now);
             let root = build_root(&mut self.tree, &v);
             self.root = Some(root);
             self.prev_view = Some(v);
-        } else if self.dirty {
-            let next = view(&self.state, frame_now);
-            let prev = self.prev_view.take().expect("prev_view present once built");
-            reconcile_root(&mut self.tree, self.root.unwrap(), &prev, &next);
-            self.prev_view = Some(next);
         }
         self.dirty = false;
         let root = self.root.unwrap();
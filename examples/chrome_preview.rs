@@ -220,16 +220,18 @@ impl Coordinator {
         let frame_now = Instant::now(); // fixed for this frame (R4 determinism)
 
         // Reconcile only when dirty; first frame builds, later frames diff.
-        if self.root.is_none() {
+        if let Some(root) = self.root {
+            if self.dirty {
+                let next = view(&self.state, frame_now);
+                let prev = self.prev_view.take().expect("prev_view present once built");
+                reconcile_root(&mut self.tree, root, &prev, &next);
+                self.prev_view = Some(next);
+            }
+        } else {
             let v = view(&self.state, frame_now);
             let root = build_root(&mut self.tree, &v);
             self.root = Some(root);
             self.prev_view = Some(v);
-        } else if self.dirty {
-            let next = view(&self.state, frame_now);
-            let prev = self.prev_view.take().expect("prev_view present once built");
-            reconcile_root(&mut self.tree, self.root.unwrap(), &prev, &next);
-            self.prev_view = Some(next);
         }
         self.dirty = false;
         let root = self.root.unwrap();
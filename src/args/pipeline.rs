@@ -24,6 +24,20 @@ pub struct SpawnParams {
     pub warnings: Vec<String>,
 }
 
+impl SpawnParams {
+    /// Remove the proxy wiring (`ANTHROPIC_BASE_URL` and the session-token
+    /// header), so the child inherits whatever the user's own environment
+    /// points at. Used by `--no-proxy`.
+    pub fn without_proxy(mut self) -> Self {
+        self.env.retain(|(key, value)| match key.as_str() {
+            "ANTHROPIC_BASE_URL" => false,
+            "ANTHROPIC_CUSTOM_HEADERS" => !value.starts_with("x-session-token:"),
+            _ => true,
+        });
+        self
+    }
+}
+
 /// Build spawn parameters from raw user arguments.
 ///
 /// This is the main entry point for the argument pipeline.
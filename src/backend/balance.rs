@@ -0,0 +1,94 @@
+//! Weighted random choice among pooled backends.
+//!
+//! With `defaults.balance_tag` set, traffic that would use the active
+//! backend is spread over every backend carrying that tag, in proportion
+//! to their `weight`. Requests from the same conversation land on the
+//! same backend, so its thinking blocks and prompt cache stay valid;
+//! requests without a conversation key are drawn one by one. The
+//! generator is a small seedable PRNG so tests can pin the sequence; it
+//! only needs to be fair, not unpredictable.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+
+/// Seedable picker shared by every request of a [`super::BackendState`].
+pub struct BackendBalancer {
+    seed: u64,
+    state: Mutex<u64>,
+}
+
+impl BackendBalancer {
+    /// A balancer seeded from the clock.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::seeded(nanos)
+    }
+
+    /// A balancer producing the same choices for the same `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed,
+            state: Mutex::new(seed),
+        }
+    }
+
+    /// Pick one name from `(name, weight)` candidates with probability
+    /// proportional to its weight. Zero-weight candidates are never
+    /// picked; `None` when no candidate has any weight.
+    pub fn pick<'a>(&self, candidates: &[(&'a str, u32)]) -> Option<&'a str> {
+        pick_weighted(candidates, self.next_u64())
+    }
+
+    /// Like [`pick`](Self::pick), but the same `key` always gets the same
+    /// candidate for a given pool, and keys spread over the candidates by
+    /// weight. No state is kept per key.
+    pub fn pick_for<'a>(&self, key: &str, candidates: &[(&'a str, u32)]) -> Option<&'a str> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        pick_weighted(candidates, mix(self.seed ^ hasher.finish()))
+    }
+
+    /// splitmix64.
+    fn next_u64(&self) -> u64 {
+        let mut state = self.state.lock();
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(*state)
+    }
+}
+
+/// Map `random` onto one of `candidates`, each with a chance proportional
+/// to its weight.
+fn pick_weighted<'a>(candidates: &[(&'a str, u32)], random: u64) -> Option<&'a str> {
+    let total: u64 = candidates.iter().map(|&(_, w)| u64::from(w)).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = random % total;
+    for &(name, weight) in candidates {
+        let weight = u64::from(weight);
+        if roll < weight {
+            return Some(name);
+        }
+        roll -= weight;
+    }
+    None
+}
+
+/// The splitmix64 output function.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Default for BackendBalancer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,118 @@
+//! Automatic return to the default backend after inactivity.
+//!
+//! With `defaults.idle_revert_minutes` set, a session left on some other
+//! backend (typically an expensive one picked for a single task) goes
+//! back to `defaults.active` once no request has been seen for that long.
+//! Main-agent requests (not teammates, which run on their own backends)
+//! are observed as an [`ObservabilityPlugin`] when they start, as each
+//! streamed chunk arrives and when they finish, so a long stream keeps the
+//! backend busy throughout.
+//! The check itself runs periodically from the proxy server. Both take the
+//! current time as an argument so the timing can be driven directly.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::metrics::{
+    BackendOverride, ObservabilityPlugin, PostResponseContext, PreRequestContext, RequestOrigin,
+    RequestRecord,
+};
+use crate::proxy::router::TeammateMarker;
+
+use super::BackendState;
+
+/// How often the proxy server runs [`IdleRevert::check`].
+pub const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+pub struct IdleRevert {
+    state: BackendState,
+    timeout: Duration,
+    activity: Mutex<Activity>,
+}
+
+struct Activity {
+    /// Last main-agent request start, streamed chunk or finish, or the
+    /// last time the active backend changed.
+    last: Instant,
+    /// The active backend as of `last`.
+    backend: String,
+}
+
+impl IdleRevert {
+    pub fn new(state: BackendState, timeout: Duration, now: Instant) -> Self {
+        let backend = state.get_active_backend();
+        Self {
+            state,
+            timeout,
+            activity: Mutex::new(Activity { last: now, backend }),
+        }
+    }
+
+    /// A request was seen at `now`.
+    pub fn record_activity(&self, now: Instant) {
+        self.activity.lock().last = now;
+    }
+
+    /// Switch back to the default backend if the active one isn't it and
+    /// has been idle for the timeout. A manual switch since the last check
+    /// restarts the clock. Returns the backend switched away from.
+    pub fn check(&self, now: Instant) -> Option<String> {
+        let active = self.state.get_active_backend();
+        let mut activity = self.activity.lock();
+        if activity.backend != active {
+            activity.backend = active;
+            activity.last = now;
+            return None;
+        }
+
+        let default = self.default_backend();
+        if active == default || now.saturating_duration_since(activity.last) < self.timeout {
+            return None;
+        }
+        self.state.switch_backend(&default).ok()?;
+        crate::metrics::app_log(
+            "backend",
+            &format!(
+                "No requests on '{}' for {}s; switched back to default backend '{}'",
+                active,
+                self.timeout.as_secs(),
+                default
+            ),
+        );
+        activity.backend = default;
+        activity.last = now;
+        Some(active)
+    }
+
+    /// `defaults.active`, or the first backend when that is unset.
+    fn default_backend(&self) -> String {
+        let config = self.state.get_config();
+        if config.defaults.active.is_empty() {
+            config.backends.first().map(|b| b.name.clone()).unwrap_or_default()
+        } else {
+            config.defaults.active
+        }
+    }
+}
+
+impl ObservabilityPlugin for IdleRevert {
+    fn pre_request(&self, ctx: &mut PreRequestContext<'_>) -> Option<BackendOverride> {
+        if ctx.request.extensions().get::<TeammateMarker>().is_none() {
+            self.record_activity(Instant::now());
+        }
+        None
+    }
+
+    fn stream_progress(&self, record: &RequestRecord) {
+        if record.origin == RequestOrigin::Main {
+            self.record_activity(Instant::now());
+        }
+    }
+
+    fn post_response(&self, ctx: &mut PostResponseContext<'_>) {
+        if ctx.record.origin == RequestOrigin::Main {
+            self.record_activity(Instant::now());
+        }
+    }
+}
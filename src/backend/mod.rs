@@ -3,9 +3,15 @@
 //! Provides thread-safe backend state management with support for
 //! runtime switching without interrupting in-flight requests.
 
+mod balance;
+mod idle_revert;
 mod state;
 
-pub use state::{BackendError, BackendState, AgentBackendState, AgentRegistry, SwitchLogEntry};
+pub use balance::BackendBalancer;
+pub use idle_revert::{IdleRevert, IDLE_CHECK_INTERVAL};
+pub use state::{
+    BackendError, BackendState, AgentBackendState, AgentRegistry, SwitchContext, SwitchLogEntry,
+};
 
 /// Manager for backend operations (placeholder for future CRUD operations).
 ///
@@ -3,7 +3,7 @@
 //! Provides thread-safe backend state management with support for
 //! runtime switching without interrupting in-flight requests.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -11,6 +11,8 @@ use parking_lot::RwLock;
 
 use crate::config::{Backend, Config};
 
+use super::BackendBalancer;
+
 /// Errors that can occur during backend operations.
 #[derive(Debug, Clone)]
 pub enum BackendError {
@@ -49,6 +51,31 @@ pub struct SwitchLogEntry {
     pub old_backend: Option<String>,
     /// The new active backend.
     pub new_backend: String,
+    /// Where the user was when they switched.
+    pub context: SwitchContext,
+}
+
+/// UI context captured with a user-initiated switch, for the history popup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwitchContext {
+    /// The child process's working directory.
+    pub cwd: Option<String>,
+    /// The terminal title the child had set (OSC 0/2).
+    pub title: Option<String>,
+}
+
+/// One-line rendering used by the diagnostic dump: epoch seconds, then
+/// `old → new`, with `(initial)` standing in for the startup entry.
+impl std::fmt::Display for SwitchLogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let from = self.old_backend.as_deref().unwrap_or("(initial)");
+        write!(f, "{}  {} → {}", secs, from, self.new_backend)
+    }
 }
 
 /// Runtime state for agent backend routing (subagents and teammates).
@@ -145,6 +172,8 @@ impl AgentRegistry {
 #[derive(Clone)]
 pub struct BackendState {
     inner: Arc<RwLock<BackendStateInner>>,
+    /// Picks among `defaults.balance_tag` members; shared by clones.
+    balancer: Arc<BackendBalancer>,
 }
 
 struct BackendStateInner {
@@ -152,8 +181,24 @@ struct BackendStateInner {
     active_backend: String,
     /// Full configuration (needed to look up backend details).
     config: Config,
-    /// History of backend switches for debugging/auditing.
-    switch_log: Vec<SwitchLogEntry>,
+    /// History of backend switches for debugging/auditing, oldest first.
+    /// Bounded by `config.defaults.switch_log_capacity`.
+    switch_log: VecDeque<SwitchLogEntry>,
+}
+
+impl BackendStateInner {
+    /// Append a switch, dropping the oldest entries beyond capacity.
+    fn log_switch(&mut self, entry: SwitchLogEntry) {
+        self.switch_log.push_back(entry);
+        self.trim_switch_log();
+    }
+
+    fn trim_switch_log(&mut self) {
+        let capacity = self.config.defaults.switch_log_capacity.max(1);
+        while self.switch_log.len() > capacity {
+            self.switch_log.pop_front();
+        }
+    }
 }
 
 impl BackendState {
@@ -182,21 +227,62 @@ impl BackendState {
             default.clone()
         };
 
-        let inner = BackendStateInner {
+        for warning in config.duplicate_backend_warnings() {
+            crate::metrics::app_log("config", &format!("Warning: {warning}"));
+        }
+
+        let mut inner = BackendStateInner {
             active_backend: active_backend.clone(),
             config,
-            switch_log: vec![SwitchLogEntry {
-                timestamp: SystemTime::now(),
-                old_backend: None,
-                new_backend: active_backend,
-            }],
+            switch_log: VecDeque::new(),
         };
+        inner.log_switch(SwitchLogEntry {
+            timestamp: SystemTime::now(),
+            old_backend: None,
+            new_backend: active_backend,
+            context: SwitchContext::default(),
+        });
 
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
+            balancer: Arc::new(BackendBalancer::new()),
         })
     }
 
+    /// Replace the balancer, e.g. with a seeded one for reproducible
+    /// selection. Call before cloning the state.
+    pub fn with_balancer(mut self, balancer: BackendBalancer) -> Self {
+        self.balancer = Arc::new(balancer);
+        self
+    }
+
+    /// Choose a backend from the `defaults.balance_tag` pool by weight,
+    /// returning it with the tag. `None` when balancing is off, or the
+    /// active backend isn't in the pool (the user switched away from it).
+    ///
+    /// Every request with the same `conversation` key gets the same backend
+    /// while the pool is unchanged; without a key each request is drawn on
+    /// its own.
+    pub fn pick_balanced_backend(&self, conversation: Option<&str>) -> Option<(String, String)> {
+        let state = self.inner.read();
+        let tag = state.config.defaults.balance_tag.as_ref()?;
+        let pool: Vec<(&str, u32)> = state
+            .config
+            .backends
+            .iter()
+            .filter(|b| b.tags.contains(tag))
+            .map(|b| (b.name.as_str(), b.weight))
+            .collect();
+        if !pool.iter().any(|&(name, _)| name == state.active_backend) {
+            return None;
+        }
+        let picked = match conversation {
+            Some(key) => self.balancer.pick_for(key, &pool)?,
+            None => self.balancer.pick(&pool)?,
+        };
+        Some((picked.to_string(), tag.clone()))
+    }
+
     /// Get the currently active backend ID.
     ///
     /// This is fast and non-blocking for concurrent readers.
@@ -240,6 +326,24 @@ impl BackendState {
         self.inner.read().config.clone()
     }
 
+    /// Read the current configuration under the lock, without cloning it.
+    /// For per-request settings that must follow a config reload.
+    pub fn with_config<R>(&self, f: impl FnOnce(&Config) -> R) -> R {
+        f(&self.inner.read().config)
+    }
+
+    /// The backend configured for `agent_id`'s team in
+    /// `[agents.team_backends]`, if any.
+    pub fn team_backend_for(&self, agent_id: &str) -> Option<String> {
+        let state = self.inner.read();
+        state
+            .config
+            .agents
+            .as_ref()?
+            .team_backend_for(agent_id)
+            .map(str::to_string)
+    }
+
     /// Get config and active backend atomically under a single lock.
     pub fn get_config_and_active_backend(&self) -> (Config, String) {
         let state = self.inner.read();
@@ -257,6 +361,16 @@ impl BackendState {
     /// # Performance
     /// Switch is atomic and takes less than 1ms under normal conditions.
     pub fn switch_backend(&self, backend_id: &str) -> Result<(), BackendError> {
+        self.switch_backend_with(backend_id, SwitchContext::default())
+    }
+
+    /// [`switch_backend`](Self::switch_backend), recording `context` in the
+    /// switch log entry.
+    pub fn switch_backend_with(
+        &self,
+        backend_id: &str,
+        context: SwitchContext,
+    ) -> Result<(), BackendError> {
         let mut state = self.inner.write();
 
         // Validate the target backend exists
@@ -276,8 +390,9 @@ impl BackendState {
             timestamp: SystemTime::now(),
             old_backend: Some(state.active_backend.clone()),
             new_backend: backend_id.to_string(),
+            context,
         };
-        state.switch_log.push(entry);
+        state.log_switch(entry);
 
         // Perform the atomic switch
         let old_backend = state.active_backend.clone();
@@ -289,9 +404,12 @@ impl BackendState {
         Ok(())
     }
 
-    /// Get the switch log for debugging/auditing.
+    /// Get the switch log for debugging/auditing, oldest first.
+    ///
+    /// Holds at most `defaults.switch_log_capacity` entries; once full, the
+    /// oldest (eventually including the initial startup entry) are dropped.
     pub fn get_switch_log(&self) -> Vec<SwitchLogEntry> {
-        self.inner.read().switch_log.clone()
+        self.inner.read().switch_log.iter().cloned().collect()
     }
 
     /// Validate that a backend ID exists in the current configuration.
@@ -311,6 +429,19 @@ impl BackendState {
             .collect()
     }
 
+    /// Get the IDs of the backends tagged `tag`, in config order. Empty
+    /// when no backend carries the tag.
+    pub fn list_backends_by_tag(&self, tag: &str) -> Vec<String> {
+        let state = self.inner.read();
+        state
+            .config
+            .backends
+            .iter()
+            .filter(|b| b.tags.iter().any(|t| t == tag))
+            .map(|b| b.name.clone())
+            .collect()
+    }
+
     /// Update the configuration (used when config file is reloaded).
     ///
     /// If the current active backend no longer exists in the new config,
@@ -342,12 +473,14 @@ impl BackendState {
                 timestamp: SystemTime::now(),
                 old_backend: Some(state.active_backend.clone()),
                 new_backend: new_active.clone(),
+                context: SwitchContext::default(),
             };
-            state.switch_log.push(entry);
+            state.log_switch(entry);
             state.active_backend = new_active;
-        }
+            }
 
         state.config = new_config;
+        state.trim_switch_log();
         Ok(())
     }
 }
@@ -1,7 +1,9 @@
 //! Authentication header building for API requests.
 //!
 //! Builds the appropriate authentication headers based on
-//! backend configuration and resolved credentials.
+//! backend configuration and resolved credentials. Backends whose auth
+//! depends on the request itself (`auth_type = "signed"`) get their headers
+//! from a [`RequestSigner`] instead.
 
 use super::credentials::{AuthType, CredentialStatus};
 use super::types::Backend;
@@ -9,15 +11,13 @@ use super::types::Backend;
 /// Header name and value for authentication.
 pub type AuthHeader = (String, String);
 
-/// Build the authentication header for a backend.
+/// Build the authentication header for a backend from its resolved
+/// `credential` (see [`Backend::resolve_credential`]).
 ///
 /// Returns `Some((header_name, header_value))` if auth is configured,
 /// or `None` if no auth is needed or credentials are missing.
-pub fn build_auth_header(backend: &Backend) -> Option<AuthHeader> {
-    let cred = backend.resolve_credential();
-    let auth_type = backend.auth_type();
-
-    match (auth_type, cred) {
+pub fn build_auth_header(backend: &Backend, credential: &CredentialStatus) -> Option<AuthHeader> {
+    match (backend.auth_type(), credential) {
         (AuthType::ApiKey, CredentialStatus::Configured(key)) => {
             Some(("x-api-key".to_string(), key.expose().to_string()))
         }
@@ -25,8 +25,26 @@ pub fn build_auth_header(backend: &Backend) -> Option<AuthHeader> {
             "Authorization".to_string(),
             format!("Bearer {}", key.expose()),
         )),
-        (AuthType::Passthrough, _) => None,
+        (AuthType::Passthrough | AuthType::Signed, _) => None,
         (_, CredentialStatus::Unconfigured { .. }) => None,
         (_, CredentialStatus::NoAuth) => None,
     }
 }
+
+/// The upstream request a [`RequestSigner`] signs.
+pub struct SigningRequest<'a> {
+    pub method: &'a str,
+    /// Full upstream URL, backend base URL included.
+    pub url: &'a str,
+    /// Headers about to be sent, incoming auth already stripped.
+    pub headers: &'a [AuthHeader],
+    pub body: &'a [u8],
+}
+
+/// Computes auth headers for each upstream request of an
+/// `auth_type = "signed"` backend, e.g. AWS SigV4 for Bedrock-style
+/// endpoints. Called again for every retry attempt.
+pub trait RequestSigner: Send + Sync {
+    /// Headers to add, or why the request can't be signed.
+    fn sign(&self, request: &SigningRequest<'_>) -> Result<Vec<AuthHeader>, String>;
+}
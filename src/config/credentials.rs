@@ -3,10 +3,19 @@
 //! This module provides secure handling of API keys and credentials
 //! resolved from the config at runtime.
 
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
 use super::types::Backend;
 
 /// Authentication type for API requests.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AuthType {
     /// Anthropic-style `x-api-key` header.
     ApiKey,
@@ -14,6 +23,9 @@ pub enum AuthType {
     Bearer,
     /// Passthrough: forward original client headers unchanged (for OAuth).
     Passthrough,
+    /// Headers computed per request by a [`RequestSigner`](super::RequestSigner)
+    /// registered for the backend (e.g. AWS SigV4).
+    Signed,
 }
 
 impl AuthType {
@@ -23,6 +35,7 @@ impl AuthType {
         match s.to_lowercase().as_str() {
             "api_key" => AuthType::ApiKey,
             "bearer" => AuthType::Bearer,
+            "signed" => AuthType::Signed,
             _ => AuthType::Passthrough,
         }
     }
@@ -32,7 +45,7 @@ impl AuthType {
     /// When true, incoming auth headers should be stripped and replaced
     /// with the backend's configured credentials.
     pub fn uses_own_credentials(&self) -> bool {
-        matches!(self, AuthType::ApiKey | AuthType::Bearer)
+        matches!(self, AuthType::ApiKey | AuthType::Bearer | AuthType::Signed)
     }
 }
 
@@ -89,19 +102,30 @@ impl Backend {
         AuthType::parse(&self.auth_type_str)
     }
 
-    /// Resolve the API key from environment variable.
+    /// Resolve the API key: `api_key` if set, else the output of
+    /// `api_key_command`.
     ///
-    /// This is called on-demand and NOT cached, enabling hot-reload
-    /// of credentials when environment variables change.
+    /// A configured `api_key` is read on every call, so config reloads take
+    /// effect at once; a command's key is reused for
+    /// `api_key_command_ttl_seconds`. Running the command blocks for up to
+    /// [`KEY_COMMAND_TIMEOUT`]; see
+    /// [`credential_needs_command`](Self::credential_needs_command).
     pub fn resolve_credential(&self) -> CredentialStatus {
         match self.auth_type() {
-            AuthType::Passthrough => CredentialStatus::NoAuth,
+            AuthType::Passthrough | AuthType::Signed => CredentialStatus::NoAuth,
             AuthType::ApiKey | AuthType::Bearer => {
                 if let Some(ref key) = self.api_key {
                     if !key.is_empty() {
                         return CredentialStatus::Configured(SecureString::new(key.clone()));
                     }
                 }
+                if let Some(ref command) = self.api_key_command {
+                    let ttl = Duration::from_secs(self.api_key_command_ttl_seconds);
+                    return match command_credential(command, ttl) {
+                        Ok(key) => CredentialStatus::Configured(key),
+                        Err(reason) => CredentialStatus::Unconfigured { reason },
+                    };
+                }
                 CredentialStatus::Unconfigured {
                     reason: "api_key is not set".to_string(),
                 }
@@ -109,12 +133,36 @@ impl Backend {
         }
     }
 
+    /// Whether [`resolve_credential`](Self::resolve_credential) would have
+    /// to run `api_key_command`: no `api_key` is set and there is no fresh
+    /// cached key.
+    pub fn credential_needs_command(&self) -> bool {
+        if !matches!(self.auth_type(), AuthType::ApiKey | AuthType::Bearer)
+            || self.api_key.as_deref().is_some_and(|key| !key.is_empty())
+        {
+            return false;
+        }
+        self.api_key_command.as_deref().is_some_and(|command| {
+            cached_command_key(command, Duration::from_secs(self.api_key_command_ttl_seconds))
+                .is_none()
+        })
+    }
+
     /// Check if this backend is configured (has valid credentials or doesn't need them).
+    ///
+    /// Never runs `api_key_command`: a command-backed backend counts as
+    /// configured unless the command's last run failed.
     pub fn is_configured(&self) -> bool {
-        matches!(
-            self.resolve_credential(),
-            CredentialStatus::Configured(_) | CredentialStatus::NoAuth
-        )
+        match self.auth_type() {
+            AuthType::Passthrough | AuthType::Signed => true,
+            AuthType::ApiKey | AuthType::Bearer => {
+                self.api_key.as_deref().is_some_and(|key| !key.is_empty())
+                    || self.api_key_command.as_deref().is_some_and(|command| {
+                        let cache = command_cache().lock();
+                        !matches!(cache.get(command), Some(CommandKey { key: Err(_), .. }))
+                    })
+            }
+        }
     }
 
     /// Whether to convert adaptive thinking to standard "enabled" format.
@@ -142,3 +190,121 @@ impl Backend {
     }
 
 }
+
+/// Longest an `api_key_command` may run before it is killed and the
+/// backend reported unconfigured.
+pub const KEY_COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running `api_key_command` is checked for exit.
+const KEY_COMMAND_POLL: Duration = Duration::from_millis(10);
+
+/// The outcome of an `api_key_command`'s last run.
+struct CommandKey {
+    key: Result<SecureString, String>,
+    read_at: Instant,
+}
+
+/// Last outcome of each `api_key_command`, by command.
+fn command_cache() -> &'static Mutex<HashMap<String, CommandKey>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CommandKey>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The key `command` printed less than `ttl` ago, if any.
+fn cached_command_key(command: &str, ttl: Duration) -> Option<SecureString> {
+    match command_cache().lock().get(command) {
+        Some(CommandKey { key: Ok(key), read_at }) if read_at.elapsed() < ttl => Some(key.clone()),
+        _ => None,
+    }
+}
+
+/// The key `command` prints, reusing one read less than `ttl` ago. The
+/// cache is locked only to look up and store, never while the command
+/// runs. Failures are recorded for [`Backend::is_configured`] but not
+/// reused, so the next request tries again.
+fn command_credential(command: &str, ttl: Duration) -> Result<SecureString, String> {
+    if let Some(key) = cached_command_key(command, ttl) {
+        return Ok(key);
+    }
+    let key = run_key_command(command);
+    command_cache().lock().insert(
+        command.to_string(),
+        CommandKey {
+            key: key.clone(),
+            read_at: Instant::now(),
+        },
+    );
+    key
+}
+
+/// Run `command` under `sh -c`, killing it after [`KEY_COMMAND_TIMEOUT`].
+/// Its trimmed stdout is the key.
+///
+/// Both pipes are drained on their own threads while the command runs, so
+/// one that prints more than a pipe buffer holds (a chatty stderr, say)
+/// doesn't block on a full pipe until the timeout kills it.
+fn run_key_command(command: &str) -> Result<SecureString, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("api_key_command failed to start: {e}"))?;
+    let stdout = drain_pipe(child.stdout.take());
+    let stderr = drain_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + KEY_COMMAND_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(KEY_COMMAND_POLL);
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "api_key_command timed out after {}s",
+                    KEY_COMMAND_TIMEOUT.as_secs()
+                ));
+            }
+            Err(e) => return Err(format!("api_key_command failed: {e}")),
+        }
+    };
+
+    if !status.success() {
+        let stderr = collect_pipe(stderr, deadline);
+        return Err(format!("api_key_command exited with {}: {}", status, stderr.trim()));
+    }
+    let key = collect_pipe(stdout, deadline).trim().to_string();
+    if key.is_empty() {
+        return Err("api_key_command printed nothing".to_string());
+    }
+    Ok(SecureString::new(key))
+}
+
+/// Read `pipe` to the end on a thread of its own; the text arrives on the
+/// returned channel.
+fn drain_pipe(pipe: Option<impl Read + Send + 'static>) -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            let _ = tx.send(text);
+        });
+    }
+    rx
+}
+
+/// What a [`drain_pipe`] reader collected. A background process the
+/// command left holding the pipe open can keep the reader going, so the
+/// wait gives up with nothing at `deadline`.
+fn collect_pipe(reader: Receiver<String>, deadline: Instant) -> String {
+    let wait = deadline
+        .saturating_duration_since(Instant::now())
+        .max(KEY_COMMAND_POLL);
+    reader.recv_timeout(wait).unwrap_or_default()
+}
@@ -4,8 +4,11 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use crate::config::credentials::CredentialStatus;
-use crate::config::types::{Backend, Config};
+use crate::config::credentials::{AuthType, CredentialStatus};
+use crate::config::types::{Backend, Config, MAX_HEADER_COUNT_LIMIT};
+
+/// Written in place of secrets by [`Config::redacted`].
+pub const REDACTED: &str = "<redacted>";
 
 /// Errors that can occur when loading configuration.
 #[derive(Debug, Error)]
@@ -98,6 +101,7 @@ impl Config {
     /// - At least one backend is configured
     /// - The active backend exists in the backends list
     /// - The active backend has valid credentials (or doesn't require them)
+    /// - `proxy.max_header_count` is within hyper's header limit
     pub fn validate(&self) -> Result<(), ConfigError> {
         if self.backends.is_empty() {
             return Err(ConfigError::ValidationError {
@@ -129,6 +133,15 @@ impl Config {
             }
         }
 
+        if self.proxy.max_header_count > MAX_HEADER_COUNT_LIMIT {
+            return Err(ConfigError::ValidationError {
+                message: format!(
+                    "proxy.max_header_count {} exceeds {}, the most headers the HTTP server accepts",
+                    self.proxy.max_header_count, MAX_HEADER_COUNT_LIMIT
+                ),
+            });
+        }
+
         if let Some(ref at) = self.agents {
             if !self.backends.iter().any(|b| b.name == at.teammate_backend) {
                 return Err(ConfigError::ValidationError {
@@ -138,6 +151,16 @@ impl Config {
                     ),
                 });
             }
+            for (team, backend) in &at.team_backends {
+                if !self.backends.iter().any(|b| b.name == *backend) {
+                    return Err(ConfigError::ValidationError {
+                        message: format!(
+                            "agents.team_backends.{} '{}' not found in configured backends",
+                            team, backend
+                        ),
+                    });
+                }
+            }
             if let Some(ref sb) = at.subagent_backend {
                 if !self.backends.iter().any(|b| b.name == *sb) {
                     return Err(ConfigError::ValidationError {
@@ -153,6 +176,57 @@ impl Config {
         Ok(())
     }
 
+    /// Warnings for backends that point at the same endpoint with the same
+    /// credentials and model mapping, one per group, in config order. Such
+    /// backends are indistinguishable to the upstream, so switching or
+    /// routing between them changes nothing but the label — usually a
+    /// copy-paste mistake. Two accounts on one endpoint are told apart by
+    /// auth type, `api_key` and `api_key_command`. Base URLs are compared
+    /// without trailing slashes and reported with any userinfo redacted.
+    pub fn duplicate_backend_warnings(&self) -> Vec<String> {
+        type Key<'a> = (
+            &'a str,
+            (AuthType, Option<&'a str>, Option<&'a str>),
+            (Option<&'a str>, Option<&'a str>, Option<&'a str>),
+        );
+        let mut groups: Vec<(Key, Vec<&str>)> = Vec::new();
+        let mut index: HashMap<Key, usize> = HashMap::new();
+        for backend in &self.backends {
+            let key = (
+                backend.base_url.trim_end_matches('/'),
+                (
+                    backend.auth_type(),
+                    backend.api_key.as_deref(),
+                    backend.api_key_command.as_deref(),
+                ),
+                (
+                    backend.model_opus.as_deref(),
+                    backend.model_sonnet.as_deref(),
+                    backend.model_haiku.as_deref(),
+                ),
+            );
+            match index.get(&key) {
+                Some(&i) => groups[i].1.push(&backend.name),
+                None => {
+                    index.insert(key, groups.len());
+                    groups.push((key, vec![&backend.name]));
+                }
+            }
+        }
+        groups
+            .into_iter()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|((base_url, ..), names)| {
+                let names: Vec<String> = names.iter().map(|n| format!("'{n}'")).collect();
+                format!(
+                    "Backends {} share base_url {}, credentials and model mapping",
+                    names.join(", "),
+                    redact_userinfo(base_url)
+                )
+            })
+            .collect()
+    }
+
     /// Log the status of all backends at startup.
     ///
     /// Logs warnings for unconfigured backends and info for configured ones.
@@ -188,6 +262,54 @@ impl Config {
             .iter()
             .find(|b| b.name == self.defaults.active && b.is_configured())
     }
+
+    /// A copy that is safe to print or attach to a bug report: every set
+    /// `api_key` and `api_key_command` is replaced with [`REDACTED`], as is
+    /// any userinfo (`user:password@`) in a base URL.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        config.proxy.base_url = redact_userinfo(&config.proxy.base_url);
+        for backend in &mut config.backends {
+            if backend.api_key.is_some() {
+                backend.api_key = Some(REDACTED.to_string());
+            }
+            if backend.api_key_command.is_some() {
+                backend.api_key_command = Some(REDACTED.to_string());
+            }
+            backend.base_url = redact_userinfo(&backend.base_url);
+        }
+        config
+    }
+
+    /// The configuration in effect, defaults filled in and secrets
+    /// redacted, as TOML. Backs `--print-config`.
+    pub fn to_effective_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(&self.redacted()).map_err(|e| ConfigError::ValidationError {
+            message: format!("Failed to serialize config: {}", e),
+        })
+    }
+
+    /// Same as [`to_effective_toml`](Self::to_effective_toml), as JSON.
+    pub fn to_effective_json(&self) -> Result<String, ConfigError> {
+        serde_json::to_string_pretty(&self.redacted()).map_err(|e| ConfigError::ValidationError {
+            message: format!("Failed to serialize config: {}", e),
+        })
+    }
+}
+
+/// `url` with the userinfo of its authority, if any, replaced with
+/// [`REDACTED`]: `https://user:pw@host/v1` becomes `https://<redacted>@host/v1`.
+fn redact_userinfo(url: &str) -> String {
+    let Some(scheme_end) = url.find("://").map(|i| i + 3) else {
+        return url.to_string();
+    };
+    let authority_len = url[scheme_end..]
+        .find(['/', '?', '#'])
+        .unwrap_or(url.len() - scheme_end);
+    match url[scheme_end..scheme_end + authority_len].rfind('@') {
+        Some(at) => format!("{}{}{}", &url[..scheme_end], REDACTED, &url[scheme_end + at..]),
+        None => url.to_string(),
+    }
 }
 
 /// Save claude_settings section to the config file.
@@ -8,18 +8,20 @@ mod auth;
 pub mod claude_settings;
 mod credentials;
 mod loader;
+mod overrides;
 mod store;
 mod types;
 
-pub use auth::{build_auth_header, AuthHeader};
+pub use auth::{build_auth_header, AuthHeader, RequestSigner, SigningRequest};
 pub use claude_settings::{
     ClaudeSettingsManager, SettingDef, SettingId, SettingSection, SettingsFieldSnapshot,
 };
-pub use credentials::{AuthType, CredentialStatus, SecureString};
-pub use loader::{save_claude_settings, ConfigError};
+pub use credentials::{AuthType, CredentialStatus, SecureString, KEY_COMMAND_TIMEOUT};
+pub use loader::{save_claude_settings, ConfigError, REDACTED};
+pub use overrides::ConfigOverrides;
 pub use store::ConfigStore;
 pub use types::{
     AgentsConfig, Backend, BackendPricing, Config, DebugLogDestination, DebugLogFormat,
     DebugLogLevel, DebugLogRotation, DebugLogRotationMode, DebugLoggingConfig, Defaults,
-    ProxyConfig, TerminalConfig,
+    ProxyConfig, ResponseNormalization, TerminalConfig, UsageMapping, MAX_HEADER_COUNT_LIMIT,
 };
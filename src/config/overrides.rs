@@ -0,0 +1,37 @@
+//! Per-run settings from the command line, laid over the loaded config.
+//!
+//! Only the fields given on the command line change; everything else keeps
+//! its value from the config file (or the defaults). Nothing is written
+//! back to disk.
+
+use crate::config::types::{Config, DebugLogLevel};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigOverrides {
+    /// `--backend`: the backend active at startup.
+    pub backend: Option<String>,
+    /// `--bind`: the proxy's listen address.
+    pub bind_addr: Option<String>,
+    /// `--timeout`: overall request timeout in seconds.
+    pub timeout_seconds: Option<u32>,
+    /// `--debug-log`: debug logging level.
+    pub debug_log: Option<DebugLogLevel>,
+}
+
+impl ConfigOverrides {
+    /// Apply every override that was given to `config`.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(name) = &self.backend {
+            config.defaults.active = name.clone();
+        }
+        if let Some(addr) = &self.bind_addr {
+            config.proxy.bind_addr = addr.clone();
+        }
+        if let Some(seconds) = self.timeout_seconds {
+            config.defaults.timeout_seconds = seconds;
+        }
+        if let Some(level) = self.debug_log {
+            config.debug_logging.level = level;
+        }
+    }
+}
@@ -39,23 +39,103 @@ pub struct Defaults {
     /// Max idle connections per host (default: 8).
     #[serde(default = "default_pool_max_idle_per_host")]
     pub pool_max_idle_per_host: u32,
+    /// Open a connection to each backend at startup so the first request
+    /// doesn't pay for the handshake (default: false).
+    #[serde(default)]
+    pub warmup_connections: bool,
     /// Max retry attempts for connection errors (default: 3).
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
     /// Base backoff in milliseconds for retry (default: 100).
     #[serde(default = "default_retry_backoff_base_ms")]
     pub retry_backoff_base_ms: u64,
+    /// Also retry POST and other non-idempotent requests after an upstream
+    /// timeout, when the backend may already have processed them
+    /// (default: false). Connect failures are always retried.
+    #[serde(default)]
+    pub retry_non_idempotent: bool,
+    /// Max backend switch log entries kept — in memory, in the history
+    /// popup and in `history.jsonl`; oldest are dropped (default: 500).
+    #[serde(default = "default_switch_log_capacity")]
+    pub switch_log_capacity: usize,
+    /// Max routing decisions kept for inspection; oldest are dropped
+    /// (default: 200).
+    #[serde(default = "default_routing_log_capacity")]
+    pub routing_log_capacity: usize,
+    /// How long a request waits for a slot on a backend at its
+    /// `max_concurrent` limit before failing, in seconds (default: 30).
+    #[serde(default = "default_queue_timeout")]
+    pub queue_timeout_seconds: u32,
+    /// Max requests waiting per saturated backend; further requests fail
+    /// immediately (default: 64).
+    #[serde(default = "default_max_queued_requests")]
+    pub max_queued_requests: usize,
+    /// Send an SSE comment to the client after this many seconds without
+    /// upstream bytes on a stream; 0 disables (default: 0).
+    #[serde(default)]
+    pub sse_keepalive_seconds: u32,
+    /// Switch back to `active` after this many minutes without main-agent
+    /// requests on another backend; 0 disables (default: 0).
+    #[serde(default)]
+    pub idle_revert_minutes: u32,
+    /// Spread requests for the active backend over every backend with this
+    /// tag, by `weight`, while the active backend carries the tag. Requests
+    /// of one conversation (same `metadata.user_id`) stay on one backend.
+    #[serde(default)]
+    pub balance_tag: Option<String>,
 }
 
 /// Proxy configuration for local routing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyConfig {
-    /// Bind address for the local proxy server (host:port).
+    /// Bind address for the local proxy server (host:port), or
+    /// `unix:/path` for a Unix domain socket (direct clients only).
     #[serde(default = "default_proxy_bind_addr")]
     pub bind_addr: String,
     /// Base URL exposed to Claude Code (scheme + host + port).
     #[serde(default = "default_proxy_base_url")]
     pub base_url: String,
+    /// Relay requests and responses untouched: no thinking filtering or
+    /// compat conversion, no model mapping, and no response buffering,
+    /// normalization, reshaping or injected events. For isolating proxy
+    /// issues.
+    #[serde(default)]
+    pub passthrough: bool,
+    /// Compute thinking transforms and log what they would change, but
+    /// forward the request's thinking content unmodified.
+    #[serde(default)]
+    pub thinking_dry_run: bool,
+    /// Reject `/v1/messages` requests whose body isn't valid JSON with a
+    /// 400 naming the parse error, instead of forwarding them upstream.
+    #[serde(default)]
+    pub validate_json: bool,
+    /// Client header carrying a correlation id. When a request has one it
+    /// is reused as the request id; otherwise a UUID is generated. Either
+    /// way the id is echoed back in this header.
+    #[serde(default = "default_request_id_header")]
+    pub request_id_header: String,
+    /// Most headers an incoming request may carry; more get a 431. At most
+    /// [`MAX_HEADER_COUNT_LIMIT`]: hyper turns away requests with more
+    /// headers than that before the proxy sees them.
+    #[serde(default = "default_max_header_count")]
+    pub max_header_count: usize,
+    /// Largest total size of an incoming request's header names and
+    /// values, in bytes; larger header sets get a 431.
+    #[serde(default = "default_max_header_bytes")]
+    pub max_header_bytes: usize,
+    /// Client header naming a backend for that one request (e.g.
+    /// `x-anyclaude-backend`), leaving the active backend unchanged.
+    /// Unset disables per-request overrides.
+    #[serde(default)]
+    pub backend_header: Option<String>,
+    /// Serve an HTML metrics overview at `/dashboard`.
+    #[serde(default)]
+    pub dashboard: bool,
+    /// Path prefixes (e.g. `/v1/models`) relayed both ways without the
+    /// thinking, transform and response rewriting stages, as if in
+    /// passthrough mode.
+    #[serde(default)]
+    pub bypass_paths: Vec<String>,
 }
 
 /// Terminal display settings.
@@ -64,6 +144,43 @@ pub struct TerminalConfig {
     /// Number of lines to keep in scrollback buffer.
     #[serde(default = "default_scrollback_lines")]
     pub scrollback_lines: usize,
+    /// Strip control characters (ESC, BEL, …) from pasted text before it
+    /// reaches the child. Tabs and newlines are kept.
+    #[serde(default)]
+    pub sanitize_paste: bool,
+    /// How long the Claude child gets to exit after SIGTERM (on quit,
+    /// restart, or a signal to the wrapper) before it is killed, in
+    /// milliseconds (default: 2000).
+    #[serde(default = "default_shutdown_grace_ms")]
+    pub shutdown_grace_ms: u64,
+    /// Render the terminal without the child's colours (bold, underline
+    /// and the like still show). Also enabled by a non-empty `NO_COLOR`.
+    #[serde(default)]
+    pub no_color: bool,
+    /// Size assumed for the child's PTY, as columns × rows, when the real
+    /// size can't be read (default: 80 × 24).
+    #[serde(default = "default_terminal_cols")]
+    pub default_cols: u16,
+    #[serde(default = "default_terminal_rows")]
+    pub default_rows: u16,
+    /// Answer the child's cursor-position query (`CSI 6 n`) from the
+    /// terminal (default: true). A child waiting on an unanswered query
+    /// hangs, so only turn this off when something else replies.
+    #[serde(default = "default_true")]
+    pub answer_cursor_position: bool,
+    /// Answer the child's device-attributes query (`CSI c`) (default: true).
+    #[serde(default = "default_true")]
+    pub answer_device_attributes: bool,
+    /// Treat bytes 0x80–0x9F from the child as 8-bit C1 controls (`0x9B`
+    /// as CSI, …) (default: false). Only for children that send raw 8-bit
+    /// controls: in UTF-8 output those bytes are continuation bytes.
+    #[serde(default)]
+    pub c1_controls: bool,
+    /// Chord that copies the last assistant response, e.g. `cmd+shift+c`
+    /// (the default) or `ctrl+shift+y`. It must include `cmd` or `ctrl`;
+    /// an unrecognised chord falls back to the default with a warning.
+    #[serde(default = "default_copy_last_response_key")]
+    pub copy_last_response_key: String,
 }
 
 /// Debug logging configuration.
@@ -87,6 +204,12 @@ pub struct DebugLoggingConfig {
     /// Pretty-print JSON bodies for readability
     #[serde(default = "default_true")]
     pub pretty_print: bool,
+    /// Fraction of requests (0.0–1.0) whose headers and bodies are
+    /// captured at `full` level (default: 1.0). The choice is made per
+    /// request id, so the same id is always in or out. Counters and
+    /// timings cover every request regardless.
+    #[serde(default = "default_detail_sample_rate")]
+    pub detail_sample_rate: f64,
     #[serde(default)]
     pub rotation: DebugLogRotation,
 }
@@ -165,10 +288,46 @@ fn default_retry_backoff_base_ms() -> u64 {
     100
 }
 
+fn default_terminal_cols() -> u16 {
+    80
+}
+
+fn default_terminal_rows() -> u16 {
+    24
+}
+
+fn default_copy_last_response_key() -> String {
+    "cmd+shift+c".to_string()
+}
+
+fn default_detail_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_switch_log_capacity() -> usize {
+    500
+}
+
+fn default_routing_log_capacity() -> usize {
+    200
+}
+
+fn default_queue_timeout() -> u32 {
+    30
+}
+
+fn default_max_queued_requests() -> usize {
+    64
+}
+
 fn default_scrollback_lines() -> usize {
     10_000
 }
 
+fn default_shutdown_grace_ms() -> u64 {
+    2000
+}
+
 fn default_debug_log_file_path() -> String {
     "~/.config/anyclaude/logs/debug.log".to_string()
 }
@@ -181,6 +340,14 @@ fn default_debug_header_preview() -> bool {
     true
 }
 
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_api_key_command_ttl() -> u64 {
+    300
+}
+
 fn default_true() -> bool {
     true
 }
@@ -201,6 +368,22 @@ fn default_proxy_base_url() -> String {
     "http://127.0.0.1:8080".to_string()
 }
 
+fn default_request_id_header() -> String {
+    "x-request-id".to_string()
+}
+
+/// Upper bound for `proxy.max_header_count`: hyper's own header limit,
+/// past which it rejects a request while parsing it.
+pub const MAX_HEADER_COUNT_LIMIT: usize = 100;
+
+fn default_max_header_count() -> usize {
+    MAX_HEADER_COUNT_LIMIT
+}
+
+fn default_max_header_bytes() -> usize {
+    64 * 1024
+}
+
 /// Backend configuration for an API provider.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Backend {
@@ -216,6 +399,15 @@ pub struct Backend {
     /// Direct API key for this backend.
     #[serde(default)]
     pub api_key: Option<String>,
+    /// Shell command printing the API key on stdout, used when `api_key`
+    /// is unset (e.g. a CLI that refreshes an OAuth token). Killed after
+    /// [`KEY_COMMAND_TIMEOUT`](crate::config::KEY_COMMAND_TIMEOUT).
+    #[serde(default)]
+    pub api_key_command: Option<String>,
+    /// How long a key printed by `api_key_command` is reused before the
+    /// command runs again. 0 runs it for every request.
+    #[serde(default = "default_api_key_command_ttl")]
+    pub api_key_command_ttl_seconds: u64,
     /// Optional pricing per million tokens.
     #[serde(default)]
     pub pricing: Option<BackendPricing>,
@@ -224,10 +416,55 @@ pub struct Backend {
     /// true = always convert, false = never convert.
     #[serde(default)]
     pub thinking_compat: Option<bool>,
-    /// Budget tokens when converting adaptive → enabled thinking.
-    /// Default: 10000.
+    /// Budget tokens for extended thinking. Used when converting adaptive →
+    /// enabled thinking; when set, requests get thinking enabled with this
+    /// budget, capped at `max_tokens - 1`, unless upstream would reject the
+    /// result. Default: none (conversion falls back to 10000).
     #[serde(default)]
     pub thinking_budget_tokens: Option<u32>,
+    /// Whether this backend accepts a `thinking` request field at all.
+    /// When false the field, and thinking blocks in `messages`, are stripped
+    /// from outgoing requests.
+    #[serde(default = "default_true")]
+    pub supports_thinking: bool,
+    /// Track and filter thinking blocks for this backend. When false its
+    /// requests get no thinking session: blocks are neither registered
+    /// nor filtered, and go upstream as the client sent them.
+    #[serde(default = "default_true")]
+    pub thinking_registry_enabled: bool,
+    /// Whether this backend accepts Anthropic prompt-caching `cache_control`
+    /// markers. When false they are stripped from outgoing requests.
+    #[serde(default = "default_true")]
+    pub supports_cache_control: bool,
+    /// Fix-ups applied to this backend's responses before they reach the
+    /// client. None = responses pass through unchanged.
+    #[serde(default)]
+    pub response_normalization: Option<ResponseNormalization>,
+    /// Where this backend reports token usage in streamed responses.
+    /// None = Anthropic's `message_start` / `message_delta` usage.
+    #[serde(default)]
+    pub usage_mapping: Option<UsageMapping>,
+    /// Collect this backend's whole SSE response, then send it on as one
+    /// clean frame per event. For backends whose chunking splits events.
+    /// Capped at `stream_shape::FORCE_BUFFER_LIMIT` bytes.
+    #[serde(default)]
+    pub force_buffer: bool,
+    /// Answer in the shape the client asked for when this backend ignores
+    /// the request's `stream` flag: fold SSE into JSON, or replay JSON as SSE.
+    #[serde(default)]
+    pub match_client_stream: bool,
+    /// Max concurrent in-flight upstream requests; excess requests queue.
+    /// None = unlimited.
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+    /// Free-form labels (e.g. "cheap", "reasoning") grouping backends for
+    /// routing and fallback.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Share of `defaults.balance_tag` traffic relative to the other pool
+    /// members (default: 1; 0 keeps it out of rotation).
+    #[serde(default = "default_weight")]
+    pub weight: u32,
     /// Model name to use for opus-family requests on this backend.
     #[serde(default)]
     pub model_opus: Option<String>,
@@ -245,6 +482,39 @@ pub struct BackendPricing {
     pub output_per_million: f64,
 }
 
+impl BackendPricing {
+    /// Cost in USD of a request with the given token counts.
+    pub fn cost_usd(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_million + output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Per-backend response fix-ups for fields Claude Code is strict about.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseNormalization {
+    /// Backend stop reason → Anthropic stop reason
+    /// (e.g. `stop = "end_turn"`, `length = "max_tokens"`).
+    #[serde(default)]
+    pub stop_reasons: HashMap<String, String>,
+    /// Fill missing `usage.input_tokens` / `usage.output_tokens` with 0.
+    #[serde(default)]
+    pub fill_usage_defaults: bool,
+}
+
+/// Location of token counts in a backend's SSE payloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageMapping {
+    /// Only payloads whose `type` equals this are read. None = every
+    /// payload, for backends (OpenAI-style) whose chunks carry no `type`.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// JSON pointer to the input token count (e.g. `/usage/prompt_tokens`).
+    pub input_tokens: String,
+    /// JSON pointer to the output token count (e.g. `/usage/completion_tokens`).
+    pub output_tokens: String,
+}
+
 /// Agents routing configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentsConfig {
@@ -255,6 +525,21 @@ pub struct AgentsConfig {
     /// Does NOT affect teammates — CC does not propagate this env var.
     #[serde(default)]
     pub subagent_backend: Option<String>,
+    /// Teammate backends per agent team (team name → backend name).
+    /// Teams not listed here use `teammate_backend`.
+    #[serde(default)]
+    pub team_backends: HashMap<String, String>,
+}
+
+impl AgentsConfig {
+    /// The configured backend for the team a teammate belongs to.
+    ///
+    /// Agent teams name teammates `{name}@{team}`; ids without a team
+    /// suffix, or whose team isn't in `team_backends`, yield `None`.
+    pub fn team_backend_for(&self, agent_id: &str) -> Option<&str> {
+        let (_, team) = agent_id.rsplit_once('@')?;
+        self.team_backends.get(team).map(String::as_str)
+    }
 }
 
 impl Default for Backend {
@@ -265,9 +550,21 @@ impl Default for Backend {
             base_url: "https://api.anthropic.com".to_string(),
             auth_type_str: "passthrough".to_string(),
             api_key: None,
+            api_key_command: None,
+            api_key_command_ttl_seconds: default_api_key_command_ttl(),
             pricing: None,
             thinking_compat: None,
             thinking_budget_tokens: None,
+            supports_thinking: true,
+            thinking_registry_enabled: true,
+            supports_cache_control: true,
+            response_normalization: None,
+            usage_mapping: None,
+            force_buffer: false,
+            match_client_stream: false,
+            max_concurrent: None,
+            tags: Vec::new(),
+            weight: default_weight(),
             model_opus: None,
             model_sonnet: None,
             model_haiku: None,
@@ -284,8 +581,17 @@ impl Default for Defaults {
             idle_timeout_seconds: 60,
             pool_idle_timeout_seconds: 90,
             pool_max_idle_per_host: 8,
+            warmup_connections: false,
             max_retries: 3,
             retry_backoff_base_ms: 100,
+            retry_non_idempotent: false,
+            switch_log_capacity: default_switch_log_capacity(),
+            routing_log_capacity: default_routing_log_capacity(),
+            queue_timeout_seconds: default_queue_timeout(),
+            max_queued_requests: default_max_queued_requests(),
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         }
     }
 }
@@ -309,14 +615,40 @@ impl Default for ProxyConfig {
         Self {
             bind_addr: default_proxy_bind_addr(),
             base_url: default_proxy_base_url(),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: default_request_id_header(),
+            max_header_count: default_max_header_count(),
+            max_header_bytes: default_max_header_bytes(),
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         }
     }
 }
 
+impl TerminalConfig {
+    /// Whether colour is off: `no_color`, or `NO_COLOR` set to anything
+    /// non-empty (<https://no-color.org>).
+    pub fn color_disabled(&self) -> bool {
+        self.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+    }
+}
+
 impl Default for TerminalConfig {
     fn default() -> Self {
         Self {
             scrollback_lines: default_scrollback_lines(),
+            sanitize_paste: false,
+            shutdown_grace_ms: default_shutdown_grace_ms(),
+            no_color: false,
+            default_cols: default_terminal_cols(),
+            default_rows: default_terminal_rows(),
+            answer_cursor_position: true,
+            answer_device_attributes: true,
+            c1_controls: false,
+            copy_last_response_key: default_copy_last_response_key(),
         }
     }
 }
@@ -332,6 +664,7 @@ impl Default for DebugLoggingConfig {
             header_preview: default_debug_header_preview(),
             full_body: false,
             pretty_print: true,
+            detail_sample_rate: default_detail_sample_rate(),
             rotation: DebugLogRotation::default(),
         }
     }
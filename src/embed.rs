@@ -0,0 +1,157 @@
+//! Running the proxy inside another application, without the terminal UI.
+//!
+//! [`ProxyBuilder`] takes a [`Config`] and produces either an axum
+//! [`Router`] for the host to mount in its own server, or a
+//! [`RunningProxy`] bound to `proxy.bind_addr` and serving on the
+//! current tokio runtime until it is shut down.
+//!
+//! ```no_run
+//! # async fn embed(config: anyclaude::config::Config) -> Result<(), Box<dyn std::error::Error>> {
+//! use anyclaude::embed::ProxyBuilder;
+//!
+//! let proxy = ProxyBuilder::new(config).start().await?;
+//! println!("proxy listening on {:?}", proxy.addr());
+//! // ... point clients at the proxy ...
+//! proxy.shutdown().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::Router;
+use tokio::task::JoinHandle;
+
+use crate::backend::{BackendError, BackendState};
+use crate::config::{Config, ConfigStore, RequestSigner};
+use crate::metrics::{DebugLogger, ObservabilityHub};
+use crate::proxy::{ProxyAddr, ProxyHandle, ProxyServer};
+
+/// Builds an embedded proxy from a [`Config`].
+pub struct ProxyBuilder {
+    config: Config,
+    config_path: PathBuf,
+    debug_logger: Option<Arc<DebugLogger>>,
+    session_token: Option<String>,
+    signers: Vec<(String, Arc<dyn RequestSigner>)>,
+}
+
+impl ProxyBuilder {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            config_path: PathBuf::new(),
+            debug_logger: None,
+            session_token: None,
+            signers: Vec::new(),
+        }
+    }
+
+    /// File the config was read from. Only used for reloads; without one
+    /// the embedded proxy keeps the config it was built with.
+    pub fn config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = path.into();
+        self
+    }
+
+    /// Logger for request/response debug output. Defaults to one built
+    /// from the config's `debug_logging` section.
+    pub fn debug_logger(mut self, logger: Arc<DebugLogger>) -> Self {
+        self.debug_logger = Some(logger);
+        self
+    }
+
+    /// Token required in `x-session-token` by the proxy routes outside
+    /// `/teammate/` and by `/api/log-tail`; `/dashboard` also accepts it as
+    /// `?token=`. The hook endpoints don't check it. See the TUI's
+    /// per-session token.
+    pub fn session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    /// Sign requests to the `auth_type = "signed"` backend named `backend`.
+    pub fn signer(mut self, backend: impl Into<String>, signer: Arc<dyn RequestSigner>) -> Self {
+        self.signers.push((backend.into(), signer));
+        self
+    }
+
+    fn build(self) -> Result<(ProxyServer, ConfigStore), BackendError> {
+        let debug_logger = self.debug_logger.unwrap_or_else(|| {
+            Arc::new(DebugLogger::new(self.config.debug_logging.clone()))
+        });
+        let store = ConfigStore::new(self.config, self.config_path);
+        let mut server = ProxyServer::new(store.clone(), debug_logger, self.session_token)?;
+        for (backend, signer) in self.signers {
+            server.set_signer(backend, signer);
+        }
+        Ok((server, store))
+    }
+
+    /// The proxy's routes, for serving from the host's own listener.
+    pub fn router(self) -> Result<Router, BackendError> {
+        let (server, _) = self.build()?;
+        Ok(server.router())
+    }
+
+    /// Bind `proxy.bind_addr` and serve on the current tokio runtime.
+    pub async fn start(self) -> Result<RunningProxy, Box<dyn Error>> {
+        let (mut server, store) = self.build()?;
+        let addr = server.bind(&store).await?;
+        let handle = server.handle();
+        let backend_state = server.backend_state();
+        let observability = server.observability();
+        let task = tokio::spawn(async move { server.run().await.map_err(|e| e.to_string()) });
+        Ok(RunningProxy {
+            addr,
+            handle,
+            backend_state,
+            observability,
+            task,
+        })
+    }
+}
+
+/// A proxy started by [`ProxyBuilder::start`].
+pub struct RunningProxy {
+    addr: ProxyAddr,
+    handle: ProxyHandle,
+    backend_state: BackendState,
+    observability: ObservabilityHub,
+    task: JoinHandle<Result<(), String>>,
+}
+
+impl RunningProxy {
+    /// Where the proxy listens; a TCP address reflects the port actually
+    /// bound after fallback.
+    pub fn addr(&self) -> &ProxyAddr {
+        &self.addr
+    }
+
+    /// Shutdown trigger that can be cloned and moved to another task.
+    pub fn handle(&self) -> ProxyHandle {
+        self.handle.clone()
+    }
+
+    pub fn backend_state(&self) -> BackendState {
+        self.backend_state.clone()
+    }
+
+    pub fn observability(&self) -> ObservabilityHub {
+        self.observability.clone()
+    }
+
+    /// Signal shutdown and wait for in-flight requests to drain.
+    pub async fn shutdown(self) -> Result<(), Box<dyn Error>> {
+        self.handle.shutdown();
+        self.wait().await
+    }
+
+    /// Wait until the server stops, e.g. after a [`ProxyHandle::shutdown`].
+    pub async fn wait(self) -> Result<(), Box<dyn Error>> {
+        self.task.await??;
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 pub mod args;
 pub mod backend;
 pub mod config;
+pub mod embed;
 pub mod metrics;
 pub mod proxy;
 pub mod shim;
@@ -1,7 +1,8 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::io;
+use std::path::PathBuf;
 
-use anyclaude::config::Config;
+use anyclaude::config::{Config, ConfigOverrides, DebugLogLevel};
 
 #[derive(Parser)]
 #[command(name = "anyclaude", version)]
@@ -11,11 +12,50 @@ struct Cli {
     #[arg(long, value_name = "NAME")]
     backend: Option<String>,
 
+    /// Proxy listen address for this run, e.g. 127.0.0.1:9000
+    #[arg(long, value_name = "ADDR")]
+    bind: Option<String>,
+
+    /// Request timeout in seconds for this run
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u32>,
+
+    /// Debug log level for this run (off, basic, verbose, full)
+    #[arg(long, value_name = "LEVEL", value_parser = parse_debug_level)]
+    debug_log: Option<DebugLogLevel>,
+
+    /// Run claude directly, without starting the proxy (no backend switching)
+    #[arg(long, conflicts_with = "backend")]
+    no_proxy: bool,
+
+    /// Print the effective configuration (secrets redacted) and exit
+    #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "toml")]
+    print_config: Option<ConfigFormat>,
+
+    /// Record everything typed into the session to FILE (JSON lines) on exit
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Replay input recorded with --record into the session, at its original pace
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
     /// Arguments passed to claude
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+fn parse_debug_level(value: &str) -> Result<DebugLogLevel, String> {
+    DebugLogLevel::parse(value)
+        .ok_or_else(|| format!("unknown level '{value}' (expected off, basic, verbose or full)"))
+}
+
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
 
@@ -42,5 +82,29 @@ fn main() -> io::Result<()> {
         }
     }
 
-    anyclaude::ui::gpu::run(cli.backend, cli.args)
+    let overrides = ConfigOverrides {
+        backend: cli.backend,
+        bind_addr: cli.bind,
+        timeout_seconds: cli.timeout,
+        debug_log: cli.debug_log,
+    };
+
+    if let Some(format) = cli.print_config {
+        let mut config = config;
+        overrides.apply(&mut config);
+        let printed = match format {
+            ConfigFormat::Toml => config.to_effective_toml(),
+            ConfigFormat::Json => config.to_effective_json(),
+        };
+        match printed {
+            Ok(text) => print!("{}", text),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    anyclaude::ui::gpu::run(overrides, cli.args, cli.no_proxy, cli.record, cli.replay)
 }
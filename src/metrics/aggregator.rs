@@ -9,10 +9,13 @@ pub struct BackendAccumulator {
     pub(crate) client_error_4xx: u64,
     pub(crate) server_error_5xx: u64,
     pub(crate) timeouts: u64,
+    pub(crate) cancelled: u64,
     latency_total_ms: u64,
     latency_samples: u64,
     ttfb_total_ms: u64,
     ttfb_samples: u64,
+    connect_total_ms: u64,
+    connect_samples: u64,
 }
 
 impl BackendAccumulator {
@@ -32,6 +35,9 @@ impl BackendAccumulator {
         if record.timed_out || record.status == Some(504) {
             self.timeouts += 1;
         }
+        if record.cancelled {
+            self.cancelled += 1;
+        }
 
         if let Some(latency_ms) = record.latency_ms {
             self.latency_total_ms = self.latency_total_ms.saturating_add(latency_ms);
@@ -42,6 +48,11 @@ impl BackendAccumulator {
             self.ttfb_total_ms = self.ttfb_total_ms.saturating_add(ttfb_ms);
             self.ttfb_samples += 1;
         }
+
+        if let Some(connect_ms) = record.connect_ms {
+            self.connect_total_ms = self.connect_total_ms.saturating_add(connect_ms);
+            self.connect_samples += 1;
+        }
     }
 
     pub fn avg_latency_ms(&self) -> f64 {
@@ -57,6 +68,13 @@ impl BackendAccumulator {
         }
         self.ttfb_total_ms as f64 / self.ttfb_samples as f64
     }
+
+    pub fn avg_connect_ms(&self) -> f64 {
+        if self.connect_samples == 0 {
+            return 0.0;
+        }
+        self.connect_total_ms as f64 / self.connect_samples as f64
+    }
 }
 
 pub fn apply_percentiles(
@@ -1,18 +1,20 @@
 use std::cmp::min;
+use std::collections::VecDeque;
 use std::fs::{File, OpenOptions};
 use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
 use std::sync::{Arc, OnceLock};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde_json::json;
 
 use crate::config::{
     DebugLogDestination, DebugLogFormat, DebugLogLevel, DebugLogRotationMode, DebugLoggingConfig,
 };
-use crate::metrics::types::{RequestMeta, ResponseMeta};
+use crate::metrics::types::{RequestMeta, ResponseMeta, TimingBreakdown};
+use crate::sse::SseEventHistogram;
 use crate::metrics::{
     ObservabilityPlugin, PostResponseContext, RequestAnalysis, RequestRecord, ResponseAnalysis,
     RoutingDecision,
@@ -20,6 +22,13 @@ use crate::metrics::{
 
 const LOG_CHANNEL_SIZE: usize = 512;
 
+/// How often [`DebugLogger::flush`] retries queueing its marker while the
+/// channel is full.
+const FLUSH_RETRY_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Most recent written lines kept in memory for [`DebugLogger::tail`].
+pub const LOG_TAIL_CAPACITY: usize = 1000;
+
 /// Log event types for the debug logger channel.
 #[derive(Debug, Clone)]
 pub enum LogEvent {
@@ -27,6 +36,8 @@ pub enum LogEvent {
     Request(Box<DebugLogEvent>),
     /// Auxiliary event (internal operations).
     Auxiliary(AuxiliaryLogEvent),
+    /// Reply once every event queued before this one has been written.
+    Flush(SyncSender<()>),
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +49,7 @@ pub struct DebugLogEvent {
     pub status: Option<u16>,
     pub latency_ms: Option<u64>,
     pub ttfb_ms: Option<u64>,
+    pub timing: TimingBreakdown,
     pub request_bytes: u64,
     pub response_bytes: u64,
     pub request_analysis: Option<RequestAnalysis>,
@@ -45,6 +57,7 @@ pub struct DebugLogEvent {
     pub routing_decision: Option<RoutingDecision>,
     pub request_meta: Option<RequestMeta>,
     pub response_meta: Option<ResponseMeta>,
+    pub sse_events: Option<SseEventHistogram>,
 }
 
 /// Auxiliary log event for internal operations.
@@ -65,19 +78,32 @@ pub struct AuxiliaryLogEvent {
 pub struct DebugLogger {
     config: Arc<RwLock<DebugLoggingConfig>>,
     sender: SyncSender<LogEvent>,
+    tail: Arc<Mutex<VecDeque<String>>>,
 }
 
 impl DebugLogger {
     pub fn new(config: DebugLoggingConfig) -> Self {
         let config = Arc::new(RwLock::new(config));
+        let tail = Arc::new(Mutex::new(VecDeque::with_capacity(LOG_TAIL_CAPACITY)));
         let (sender, receiver) = sync_channel(LOG_CHANNEL_SIZE);
         let config_clone = config.clone();
+        let tail_clone = tail.clone();
         std::thread::Builder::new()
             .name("debug-logger".to_string())
-            .spawn(move || writer_loop(receiver, config_clone))
+            .spawn(move || writer_loop(receiver, config_clone, tail_clone))
             .ok();
 
-        Self { config, sender }
+        Self { config, sender, tail }
+    }
+
+    /// The last `lines` lines written (at most [`LOG_TAIL_CAPACITY`]),
+    /// oldest first, uncoloured whatever the destination. Lines still queued
+    /// for the writer are not included; [`flush`](Self::flush) first to see
+    /// them.
+    pub fn tail(&self, lines: usize) -> Vec<String> {
+        let tail = self.tail.lock();
+        let skip = tail.len().saturating_sub(lines);
+        tail.iter().skip(skip).cloned().collect()
     }
 
     pub fn level(&self) -> DebugLogLevel {
@@ -117,6 +143,30 @@ impl DebugLogger {
         };
         let _ = self.sender.try_send(LogEvent::Auxiliary(event));
     }
+
+    /// Block until every event logged so far has been written, or `timeout`
+    /// passes. Returns whether the writer caught up.
+    ///
+    /// The flush marker itself waits for room in the queue within the same
+    /// `timeout`, so a full queue can't hold shutdown up.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let (done_tx, done_rx) = sync_channel(1);
+        let mut marker = LogEvent::Flush(done_tx);
+        loop {
+            match self.sender.try_send(marker) {
+                Ok(()) => break,
+                Err(TrySendError::Full(event)) if Instant::now() < deadline => {
+                    marker = event;
+                    std::thread::sleep(FLUSH_RETRY_INTERVAL);
+                }
+                Err(_) => return false,
+            }
+        }
+        done_rx
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .is_ok()
+    }
 }
 
 impl ObservabilityPlugin for DebugLogger {
@@ -165,6 +215,7 @@ impl DebugLogEvent {
             status: record.status,
             latency_ms: record.latency_ms,
             ttfb_ms: record.ttfb_ms,
+            timing: record.timing(),
             request_bytes: record.request_bytes,
             response_bytes: record.response_bytes,
             request_analysis: record.request_analysis.clone(),
@@ -172,16 +223,26 @@ impl DebugLogEvent {
             routing_decision: record.routing_decision.clone(),
             request_meta: record.request_meta.clone(),
             response_meta: record.response_meta.clone(),
+            sse_events: record.sse_events.clone(),
         }
     }
 }
 
-fn writer_loop(receiver: Receiver<LogEvent>, config: Arc<RwLock<DebugLoggingConfig>>) {
+fn writer_loop(
+    receiver: Receiver<LogEvent>,
+    config: Arc<RwLock<DebugLoggingConfig>>,
+    tail: Arc<Mutex<VecDeque<String>>>,
+) {
     let mut stderr = io::stderr();
     let mut file_writer: Option<RotatingFile> = None;
     let mut last_file_path: Option<String> = None;
 
     while let Ok(log_event) = receiver.recv() {
+        if let LogEvent::Flush(done) = log_event {
+            let _ = stderr.flush();
+            let _ = done.send(());
+            continue;
+        }
         let config_snapshot = config.read().clone();
         if config_snapshot.level == DebugLogLevel::Off {
             continue;
@@ -213,8 +274,17 @@ fn writer_loop(receiver: Receiver<LogEvent>, config: Arc<RwLock<DebugLoggingConf
                     (line.clone(), line)
                 }
             },
+            LogEvent::Flush(_) => continue,
         };
 
+        {
+            let mut tail = tail.lock();
+            if tail.len() == LOG_TAIL_CAPACITY {
+                tail.pop_front();
+            }
+            tail.push_back(line_file.clone());
+        }
+
         match config_snapshot.destination {
             DebugLogDestination::Stderr => {
                 let _ = writeln!(stderr, "{}", line_console);
@@ -302,6 +372,10 @@ fn format_console(event: &DebugLogEvent, level: DebugLogLevel, use_color: bool)
             routing,
             cost_usd
         ));
+        line.push_str(&format!("\ntiming: {}", event.timing));
+        if let Some(histogram) = &event.sse_events {
+            line.push_str(&format!("\nsse_events: {}", histogram));
+        }
     }
 
     if level >= DebugLogLevel::Full {
@@ -447,6 +521,7 @@ fn format_json(event: &DebugLogEvent, level: DebugLogLevel) -> String {
         "status": event.status,
         "latency_ms": event.latency_ms,
         "ttfb_ms": event.ttfb_ms,
+        "connect_ms": event.timing.connect_ms,
         "request_bytes": event.request_bytes,
         "response_bytes": event.response_bytes,
         "model": event.request_analysis.as_ref().and_then(|analysis| analysis.model.clone()),
@@ -459,6 +534,7 @@ fn format_json(event: &DebugLogEvent, level: DebugLogLevel) -> String {
             "reason": decision.reason,
         })),
         "cost_usd": cost_usd,
+        "sse_events": event.sse_events.clone(),
         "request": event.request_meta.clone(),
         "response": event.response_meta.clone(),
     });
@@ -9,10 +9,11 @@ use axum::http::Request;
 
 use super::aggregator::{apply_percentiles, BackendAccumulator};
 use super::plugin::ObservabilityPlugin;
-use super::ring::RequestRingBuffer;
+use super::ring::{RequestRingBuffer, RoutingLog};
 use super::span::{finalize_record, RequestSpan, RequestStart};
 use super::types::{
-    BackendMetrics, MetricsSnapshot, PostResponseContext, PreRequestContext, RequestRecord,
+    BackendMetrics, ErrorSummary, MetricsSnapshot, PostResponseContext, PreRequestContext, RequestOrigin,
+    RequestRecord, RoutingLogEntry, ThinkingFilterCounters, UsageTotals,
 };
 
 #[derive(Clone)]
@@ -22,8 +23,13 @@ pub struct ObservabilityHub {
 
 struct ObservabilityInner {
     ring: RequestRingBuffer,
+    routing: RoutingLog,
     aggregates: RwLock<HashMap<String, BackendAccumulator>>,
+    thinking_filter: RwLock<HashMap<String, ThinkingFilterCounters>>,
+    usage: RwLock<HashMap<RequestOrigin, UsageTotals>>,
     plugins: Vec<Arc<dyn ObservabilityPlugin>>,
+    /// Assistant text of the most recently completed successful response.
+    last_response: RwLock<Option<String>>,
 }
 
 impl ObservabilityHub {
@@ -31,8 +37,12 @@ impl ObservabilityHub {
         Self {
             inner: Arc::new(ObservabilityInner {
                 ring: RequestRingBuffer::new(capacity),
+                routing: RoutingLog::new(capacity),
                 aggregates: RwLock::new(HashMap::new()),
+                thinking_filter: RwLock::new(HashMap::new()),
+                usage: RwLock::new(HashMap::new()),
                 plugins: Vec::new(),
+                last_response: RwLock::new(None),
             }),
         }
     }
@@ -44,6 +54,14 @@ impl ObservabilityHub {
         self
     }
 
+    /// Size the routing log (defaults to the request ring's capacity).
+    pub fn with_routing_log_capacity(mut self, capacity: usize) -> Self {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.routing = RoutingLog::new(capacity);
+        }
+        self
+    }
+
     pub fn start_request(
         &self,
         request_id: String,
@@ -58,9 +76,12 @@ impl ObservabilityHub {
             completed_at: None,
             latency_ms: None,
             ttfb_ms: None,
+            connect_ms: None,
             backend: active_backend.to_string(),
+            origin: RequestOrigin::Main,
             status: None,
             timed_out: false,
+            cancelled: false,
             request_bytes: 0,
             response_bytes: 0,
             request_analysis: None,
@@ -68,6 +89,7 @@ impl ObservabilityHub {
             routing_decision: None,
             request_meta: None,
             response_meta: None,
+            sse_events: None,
         };
 
         let mut backend_override = None;
@@ -107,11 +129,45 @@ impl ObservabilityHub {
         self.inner.ring.push(span.record);
     }
 
+    /// Tell plugins that `span`'s streamed response delivered a chunk.
+    pub fn stream_progress(&self, span: &RequestSpan) {
+        for plugin in &self.inner.plugins {
+            plugin.stream_progress(&span.record);
+        }
+    }
+
     pub fn finish_error(&self, mut span: RequestSpan, status: Option<u16>) {
         span.record.status = status.or(span.record.status);
         self.finish_request(span);
     }
 
+    /// Failed requests in the recent-request ring that finished at or
+    /// after `since`, by category. Reads the ring in place, so the header
+    /// can call it every frame.
+    pub fn error_summary(&self, since: SystemTime) -> ErrorSummary {
+        self.inner.ring.with_records(|records| {
+            let mut summary = ErrorSummary::default();
+            let recent = records
+                .iter()
+                .filter(|r| r.completed_at.is_some_and(|at| at >= since));
+            for category in recent.filter_map(RequestRecord::error_category) {
+                summary.add(category);
+            }
+            summary
+        })
+    }
+
+    /// Remember `text` as the latest assistant response.
+    pub fn record_response_text(&self, text: String) {
+        *self.inner.last_response.write() = Some(text);
+    }
+
+    /// Assistant text of the most recent successful response that had
+    /// any; `None` until one completes.
+    pub fn last_response_text(&self) -> Option<String> {
+        self.inner.last_response.read().clone()
+    }
+
     pub fn snapshot(&self) -> MetricsSnapshot {
         let recent = self.inner.ring.snapshot();
         let mut per_backend = HashMap::new();
@@ -125,22 +181,49 @@ impl ObservabilityHub {
                 client_error_4xx: acc.client_error_4xx,
                 server_error_5xx: acc.server_error_5xx,
                 timeouts: acc.timeouts,
+                cancelled: acc.cancelled,
                 avg_latency_ms: acc.avg_latency_ms(),
                 avg_ttfb_ms: acc.avg_ttfb_ms(),
+                avg_connect_ms: acc.avg_connect_ms(),
                 ..Default::default()
             };
             per_backend.insert(backend, metrics);
         }
 
+        for (backend, counters) in self.inner.thinking_filter.read().iter() {
+            per_backend.entry(backend.clone()).or_default().thinking_filter = *counters;
+        }
+
         apply_percentiles(&mut per_backend, &recent);
 
         MetricsSnapshot {
             generated_at: SystemTime::now(),
             per_backend,
+            per_origin: self.inner.usage.read().clone(),
             recent,
         }
     }
 
+    /// Log where a request was routed and why.
+    pub fn record_routing(&self, entry: RoutingLogEntry) {
+        self.inner.routing.push(entry);
+    }
+
+    /// Recent routing decisions, newest first.
+    pub fn routing_decisions(&self) -> Vec<RoutingLogEntry> {
+        self.inner.routing.recent()
+    }
+
+    /// Add one request's thinking-filter decisions to `backend`'s counters.
+    pub fn record_thinking_filter(&self, backend: &str, counters: ThinkingFilterCounters) {
+        self.inner
+            .thinking_filter
+            .write()
+            .entry(backend.to_string())
+            .or_default()
+            .add(&counters);
+    }
+
     fn update_aggregates(&self, record: &RequestRecord) {
         let mut aggregates = self.inner.aggregates.write();
 
@@ -148,5 +231,13 @@ impl ObservabilityHub {
             .entry(record.backend.clone())
             .or_default();
         entry.update(record);
+        drop(aggregates);
+
+        self.inner
+            .usage
+            .write()
+            .entry(record.origin)
+            .or_default()
+            .add(record);
     }
 }
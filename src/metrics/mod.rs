@@ -12,16 +12,20 @@ pub mod types;
 
 pub use debug_logger::{
     app_log, app_log_error, init_global_logger, AuxiliaryLogEvent, DebugLogEvent, DebugLogger,
-    LogEvent,
+    LogEvent, LOG_TAIL_CAPACITY,
 };
 pub use hub::ObservabilityHub;
 pub use plugin::ObservabilityPlugin;
-pub use redaction::{redact_body, redact_body_preview, redact_headers};
+pub use redaction::{redact_body, redact_body_preview, redact_headers, redact_text};
 pub use request_parser::{RequestAnalysis, RequestParser};
-pub use response_parser::ResponseParser;
-pub use span::{RequestSpan, RequestStart};
-pub use stream::{ChunkRewriter, ObservedStream, ResponseCompleteCallback, ResponsePreview, StreamError};
+pub use response_parser::{ResponseParser, UsageTracker};
+pub use span::{sampled_for_detail, RequestSpan, RequestStart};
+pub use stream::{
+    ChunkRewriter, EventObserver, ObservedStream, ResponseCompleteCallback, ResponsePreview, StreamError,
+    SSE_KEEPALIVE,
+};
 pub use types::{
-    BackendMetrics, BackendOverride, MetricsSnapshot, PostResponseContext, PreRequestContext,
-    RequestMeta, RequestRecord, ResponseAnalysis, ResponseMeta, RoutingDecision,
+    BackendMetrics, BackendOverride, ErrorCategory, ErrorSeverity, ErrorSummary, MetricsSnapshot, PostResponseContext, PreRequestContext,
+    RequestMeta, RequestOrigin, RequestRecord, ResponseAnalysis, ResponseMeta, RoutingDecision,
+    RoutingLogEntry, ThinkingFilterCounters, TimingBreakdown, UsageTotals,
 };
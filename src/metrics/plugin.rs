@@ -1,9 +1,12 @@
-use super::types::{BackendOverride, PostResponseContext, PreRequestContext};
+use super::types::{BackendOverride, PostResponseContext, PreRequestContext, RequestRecord};
 
 pub trait ObservabilityPlugin: Send + Sync {
     fn pre_request(&self, _ctx: &mut PreRequestContext<'_>) -> Option<BackendOverride> {
         None
     }
 
+    /// A streamed response delivered another chunk.
+    fn stream_progress(&self, _record: &RequestRecord) {}
+
     fn post_response(&self, _ctx: &mut PostResponseContext<'_>) {}
 }
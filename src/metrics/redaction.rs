@@ -174,6 +174,12 @@ fn is_sensitive_key(key: &str) -> bool {
     )
 }
 
+/// Mask bearer tokens and `key=value` credentials in free text, by the
+/// same rules as non-JSON debug log bodies.
+pub fn redact_text(input: &str) -> String {
+    mask_tokens(input)
+}
+
 fn mask_tokens(input: &str) -> String {
     let mut output = input.to_string();
 
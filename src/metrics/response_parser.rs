@@ -1,6 +1,8 @@
 use serde_json::Value;
 
 use super::types::ResponseAnalysis;
+use crate::config::{BackendPricing, UsageMapping};
+use crate::sse::parse_sse_payloads;
 
 pub struct ResponseParser;
 
@@ -51,4 +53,93 @@ impl ResponseParser {
             cost_usd: None,
         }
     }
+
+    /// Token accounting for a complete SSE body; see [`UsageTracker`].
+    pub fn parse_sse(&self, body: &[u8], mapping: Option<&UsageMapping>) -> ResponseAnalysis {
+        let mut tracker = UsageTracker::new(mapping.cloned(), None);
+        for payload in parse_sse_payloads(body) {
+            tracker.observe(&payload);
+        }
+        tracker.finish()
+    }
+}
+
+/// Incremental token accounting for a streamed response.
+///
+/// Without a [`UsageMapping`] it reads Anthropic's shape: input tokens
+/// from `message_start`, output tokens (and a revised input count) from
+/// `message_delta`. With one, each matching payload is probed at the
+/// mapped JSON pointers. Later values win, so a final usage chunk
+/// overrides any running counts before it. It is fed payloads already
+/// decoded, e.g. by [`SseEventDecoder::push_payloads`](crate::sse::SseEventDecoder::push_payloads).
+pub struct UsageTracker {
+    mapping: Option<UsageMapping>,
+    pricing: Option<BackendPricing>,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    stop_reason: Option<String>,
+}
+
+impl UsageTracker {
+    pub fn new(mapping: Option<UsageMapping>, pricing: Option<BackendPricing>) -> Self {
+        Self {
+            mapping,
+            pricing,
+            input_tokens: None,
+            output_tokens: None,
+            stop_reason: None,
+        }
+    }
+
+    /// What was counted so far.
+    pub fn finish(&self) -> ResponseAnalysis {
+        let cost_usd = self.pricing.as_ref().map(|pricing| {
+            pricing.cost_usd(self.input_tokens.unwrap_or(0), self.output_tokens.unwrap_or(0))
+        });
+        ResponseAnalysis {
+            summary: String::new(),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            stop_reason: self.stop_reason.clone(),
+            cost_usd,
+        }
+    }
+
+    /// Count one payload of the stream.
+    pub fn observe(&mut self, payload: &Value) {
+        let event_type = payload.get("type").and_then(|v| v.as_str());
+        let (input, output) = match &self.mapping {
+            Some(mapping) => {
+                if mapping.event.as_deref().is_some_and(|event| event_type != Some(event)) {
+                    return;
+                }
+                (
+                    payload.pointer(&mapping.input_tokens),
+                    payload.pointer(&mapping.output_tokens),
+                )
+            }
+            None => match event_type {
+                Some("message_start") => (
+                    payload.pointer("/message/usage/input_tokens"),
+                    payload.pointer("/message/usage/output_tokens"),
+                ),
+                Some("message_delta") => {
+                    if let Some(reason) = payload.pointer("/delta/stop_reason").and_then(|v| v.as_str()) {
+                        self.stop_reason = Some(reason.to_string());
+                    }
+                    (
+                        payload.pointer("/usage/input_tokens"),
+                        payload.pointer("/usage/output_tokens"),
+                    )
+                }
+                _ => return,
+            },
+        };
+        if let Some(n) = input.and_then(|v| v.as_u64()) {
+            self.input_tokens = Some(n);
+        }
+        if let Some(n) = output.and_then(|v| v.as_u64()) {
+            self.output_tokens = Some(n);
+        }
+    }
 }
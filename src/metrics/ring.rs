@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 
-use super::types::RequestRecord;
+use super::types::{RequestRecord, RoutingLogEntry};
 
 #[derive(Clone)]
 pub struct RequestRingBuffer {
@@ -31,4 +31,39 @@ impl RequestRingBuffer {
         let records = self.records.read();
         records.iter().cloned().collect()
     }
+
+    /// Read the records, oldest first, under the lock without cloning them.
+    pub fn with_records<R>(&self, f: impl FnOnce(&VecDeque<RequestRecord>) -> R) -> R {
+        f(&self.records.read())
+    }
+}
+
+/// Bounded log of recent routing decisions, independent of the request
+/// ring so it can be sized separately (`defaults.routing_log_capacity`).
+pub struct RoutingLog {
+    capacity: usize,
+    entries: RwLock<VecDeque<RoutingLogEntry>>,
+}
+
+impl RoutingLog {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            entries: RwLock::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, entry: RoutingLogEntry) {
+        let mut entries = self.entries.write();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recorded decisions, newest first.
+    pub fn recent(&self) -> Vec<RoutingLogEntry> {
+        self.entries.read().iter().rev().cloned().collect()
+    }
 }
@@ -1,6 +1,6 @@
 use std::time::{Duration, Instant, SystemTime};
 
-use super::types::{BackendOverride, RequestRecord};
+use super::types::{BackendOverride, RequestOrigin, RequestRecord};
 
 pub struct RequestStart {
     pub span: RequestSpan,
@@ -11,6 +11,27 @@ pub struct RequestStart {
 pub struct RequestSpan {
     pub(crate) record: RequestRecord,
     pub(crate) timing: RequestTiming,
+    /// Whether headers and bodies are captured for this request; see
+    /// [`sampled_for_detail`].
+    detailed: bool,
+}
+
+/// Whether `request_id` falls within a detail sample of `rate` (0.0–1.0).
+/// The id is hashed (FNV-1a), so the answer is the same on every call and
+/// every run.
+pub fn sampled_for_detail(request_id: &str, rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 || rate.is_nan() {
+        return false;
+    }
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in request_id.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    ((hash % 10_000) as f64) < rate * 10_000.0
 }
 
 impl RequestSpan {
@@ -18,13 +39,27 @@ impl RequestSpan {
         Self {
             record,
             timing: RequestTiming::new(),
+            detailed: true,
         }
     }
 
+    /// Capture headers and bodies only when `detailed`.
+    pub fn set_detailed(&mut self, detailed: bool) {
+        self.detailed = detailed;
+    }
+
+    pub fn is_detailed(&self) -> bool {
+        self.detailed
+    }
+
     pub fn set_backend(&mut self, backend: String) {
         self.record.backend = backend;
     }
 
+    pub fn set_origin(&mut self, origin: RequestOrigin) {
+        self.record.origin = origin;
+    }
+
     pub fn set_status(&mut self, status: u16) {
         self.record.status = Some(status);
     }
@@ -41,6 +76,37 @@ impl RequestSpan {
         self.timing.mark_first_byte();
     }
 
+    /// Time spent opening the upstream connection; the last attempt's
+    /// when a request was retried.
+    pub fn set_connect_time(&mut self, elapsed: Duration) {
+        self.record.connect_ms = Some(elapsed.as_millis() as u64);
+    }
+
+    /// Time the upstream took to answer with response headers, from
+    /// sending the request; the last attempt's when a request was retried.
+    pub fn set_upstream_time(&mut self, elapsed: Duration) {
+        self.timing.upstream = Some(elapsed);
+    }
+
+    /// `Server-Timing` header value splitting the time so far between the
+    /// proxy and the upstream: `proxy;dur=1.4, upstream;dur=812.0`, plus
+    /// `connect` when a new upstream connection was opened. Durations are
+    /// milliseconds, per the header's convention.
+    pub fn server_timing(&self) -> String {
+        let elapsed = self.timing.started_instant.elapsed();
+        let upstream = self.timing.upstream.unwrap_or_default();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let mut value = format!(
+            "proxy;dur={:.1}, upstream;dur={:.1}",
+            ms(elapsed.saturating_sub(upstream)),
+            ms(upstream)
+        );
+        if let Some(connect_ms) = self.record.connect_ms {
+            value.push_str(&format!(", connect;dur={connect_ms}"));
+        }
+        value
+    }
+
     pub fn mark_completed(&mut self) {
         self.timing.mark_completed();
     }
@@ -49,6 +115,10 @@ impl RequestSpan {
         self.record.timed_out = true;
     }
 
+    pub fn mark_cancelled(&mut self) {
+        self.record.cancelled = true;
+    }
+
     pub fn record_mut(&mut self) -> &mut RequestRecord {
         &mut self.record
     }
@@ -62,6 +132,7 @@ pub struct RequestTiming {
     pub(crate) started_instant: Instant,
     pub(crate) first_byte_instant: Option<Instant>,
     pub(crate) completed_instant: Option<Instant>,
+    pub(crate) upstream: Option<Duration>,
 }
 
 impl Clone for RequestTiming {
@@ -70,6 +141,7 @@ impl Clone for RequestTiming {
             started_instant: self.started_instant,
             first_byte_instant: self.first_byte_instant,
             completed_instant: self.completed_instant,
+            upstream: self.upstream,
         }
     }
 }
@@ -86,6 +158,7 @@ impl RequestTiming {
             started_instant: Instant::now(),
             first_byte_instant: None,
             completed_instant: None,
+            upstream: None,
         }
     }
 
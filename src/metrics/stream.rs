@@ -1,5 +1,6 @@
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
@@ -7,21 +8,47 @@ use axum::body::Bytes;
 use futures_core::Stream;
 use tokio::time::{Instant, Sleep};
 
+use super::debug_logger::DebugLogger;
 use super::hub::ObservabilityHub;
 use super::redaction::redact_body;
+use super::response_parser::UsageTracker;
 use super::span::RequestSpan;
 use super::types::ResponseMeta;
+use crate::sse::{
+    analyze_thinking_stream, ResponseTextCollector, SseEvent, SseEventDecoder, SseEventHistogram,
+};
 
 /// Callback type for response completion notification.
 pub type ResponseCompleteCallback = Box<dyn Fn(&[u8]) + Send + Sync>;
 
 /// Callback that rewrites a chunk, returning modified bytes (or original if unchanged).
+///
+/// A rewriter may hold back the end of a chunk (an event split across
+/// chunks) by returning less than it was given. When the upstream ends it is
+/// called once more with an empty chunk and returns whatever it still holds.
 pub type ChunkRewriter = Box<dyn FnMut(Bytes) -> Bytes + Send>;
 
+/// Observer that sees the upstream's SSE events as they are decoded, before
+/// any rewriting.
+///
+/// Unlike [`ResponseCompleteCallback`] nothing is buffered: the stream
+/// decodes each chunk once and hands every consumer the same events.
+/// `on_finish` runs exactly once, after the last events, when the stream
+/// ends, errors, times out, or is dropped by a disconnecting client.
+pub trait EventObserver: Send {
+    fn on_events(&mut self, events: &[SseEvent]);
+    fn on_finish(&mut self);
+}
+
+/// SSE comment sent to the client while the upstream is silent. Clients
+/// ignore comment lines, so the event stream itself is unchanged.
+pub const SSE_KEEPALIVE: &[u8] = b": keepalive\n\n";
+
 /// Stream wrapper that adds observability and idle timeout to SSE streams.
 ///
 /// If no data is received within `idle_timeout`, the stream returns an error
-/// to prevent indefinite hangs during API stalls.
+/// to prevent indefinite hangs during API stalls. Dropped before the end
+/// (the client disconnected), it records the request as cancelled.
 pub struct ObservedStream<S> {
     inner: S,
     span: Option<RequestSpan>,
@@ -35,6 +62,123 @@ pub struct ObservedStream<S> {
     response_buffer: Vec<u8>,
     /// Optional chunk rewriter applied to each chunk before forwarding to client.
     chunk_rewriter: Option<ChunkRewriter>,
+    /// Optional observer fed the upstream's events as they are decoded.
+    event_observer: Option<Box<dyn EventObserver>>,
+    /// Opaque value held until the stream finishes (e.g. a concurrency permit).
+    guard: Option<Box<dyn Send>>,
+    /// Optional keep-alive injected while the upstream is silent.
+    keepalive: Option<Keepalive>,
+    /// Decodes the upstream once for every event consumer below.
+    decoder: SseEventDecoder,
+    /// Optional per-type event counts, stored on the record at finish.
+    sse_histogram: Option<SseEventHistogram>,
+    /// Optional tracking of `message_start`/`message_stop`, used to close a
+    /// truncated stream with an error event.
+    truncation: Option<TruncationGuard>,
+    /// Optional token accounting, stored on the record at finish.
+    usage: Option<UsageTracker>,
+    /// Optional capture of the assistant's text, handed to the hub at finish.
+    response_text: Option<ResponseTextCollector>,
+    /// Optional thinking-event analysis, written to the debug log at finish.
+    thinking_stats: Option<ThinkingStatsLog>,
+    /// A synthetic error event was sent; the stream is over.
+    closed: bool,
+    /// Upstream `Content-Length`, when known. The server stops polling
+    /// once that many bytes went out, so a drop at that point is a
+    /// completed stream rather than a disconnect.
+    content_length: Option<u64>,
+    /// Bytes received from upstream, before any rewriting.
+    upstream_bytes: u64,
+}
+
+/// Watches the message lifecycle so a stream that stops between
+/// `message_start` and `message_stop` can be ended with an `error` event
+/// instead of leaving the client waiting for a stop that never comes.
+struct TruncationGuard {
+    started: bool,
+    stopped: bool,
+    /// The last forwarded bytes ended an event (blank line).
+    at_boundary: bool,
+}
+
+impl TruncationGuard {
+    fn observe(&mut self, events: &[SseEvent]) {
+        for event in events {
+            match event.event_type.as_str() {
+                "message_start" => self.started = true,
+                "message_stop" => self.stopped = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Collects the events [`analyze_thinking_stream`] looks at, so the
+/// per-backend thinking format can be logged once the stream ends.
+struct ThinkingStatsLog {
+    events: Vec<SseEvent>,
+    logger: Arc<DebugLogger>,
+    backend: String,
+}
+
+impl ThinkingStatsLog {
+    fn observe(&mut self, events: &[SseEvent]) {
+        // Stops are kept for every block: only the analysis knows which
+        // indices were thinking blocks.
+        self.events.extend(
+            events
+                .iter()
+                .filter(|e| e.is_thinking_event() || e.event_type == "content_block_stop")
+                .cloned(),
+        );
+    }
+
+    fn finish(self, request_id: &str) {
+        let stats = analyze_thinking_stream(&self.events);
+        self.logger.log_auxiliary(
+            "thinking_stats",
+            None,
+            None,
+            Some(&format!(
+                "Thinking stream: backend='{}', request_id={}, {}",
+                self.backend, request_id, stats,
+            )),
+            None,
+        );
+    }
+}
+
+/// Keep-alive timer plus enough framing state to only inject a comment
+/// between events, never inside a partially forwarded one.
+struct Keepalive {
+    interval: Duration,
+    timer: Pin<Box<Sleep>>,
+    /// The last forwarded byte ended a line.
+    line_start: bool,
+    /// The last forwarded bytes ended an event (blank line).
+    at_boundary: bool,
+}
+
+impl Keepalive {
+    fn track(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            match b {
+                b'\n' => {
+                    self.at_boundary = self.line_start;
+                    self.line_start = true;
+                }
+                b'\r' => {}
+                _ => {
+                    self.line_start = false;
+                    self.at_boundary = false;
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.timer.as_mut().reset(Instant::now() + self.interval);
+    }
 }
 
 pub struct ResponsePreview {
@@ -105,22 +249,202 @@ impl<S> ObservedStream<S> {
             on_complete: None,
             response_buffer: Vec::new(),
             chunk_rewriter: None,
+            event_observer: None,
+            guard: None,
+            keepalive: None,
+            decoder: SseEventDecoder::new(),
+            sse_histogram: None,
+            truncation: None,
+            usage: None,
+            response_text: None,
+            thinking_stats: None,
+            closed: false,
+            content_length: None,
+            upstream_bytes: 0,
         }
     }
 
+    /// The upstream body's declared length, so a stream dropped after
+    /// delivering all of it isn't mistaken for a cancellation.
+    pub fn with_content_length(mut self, length: Option<u64>) -> Self {
+        self.content_length = length;
+        self
+    }
+
     /// Set a callback to be called with full response bytes when stream completes.
     pub fn with_on_complete(mut self, callback: ResponseCompleteCallback) -> Self {
         self.on_complete = Some(callback);
         self
     }
 
-    /// Set a chunk rewriter that transforms each chunk before forwarding to client.
-    pub fn with_chunk_rewriter(mut self, rewriter: ChunkRewriter) -> Self {
-        self.chunk_rewriter = Some(rewriter);
+    /// Add a chunk rewriter that transforms each chunk before forwarding to
+    /// client. Rewriters run in the order they were added.
+    pub fn with_chunk_rewriter(mut self, mut rewriter: ChunkRewriter) -> Self {
+        self.chunk_rewriter = Some(match self.chunk_rewriter.take() {
+            Some(mut first) => Box::new(move |bytes: Bytes| {
+                let flushing = bytes.is_empty();
+                let released = first(bytes);
+                if !flushing || released.is_empty() {
+                    return rewriter(released);
+                }
+                // The end-of-stream flush released bytes into `rewriter`;
+                // it still needs its own flush after them.
+                let mut out = rewriter(released).to_vec();
+                out.extend_from_slice(&rewriter(Bytes::new()));
+                Bytes::from(out)
+            }),
+            None => rewriter,
+        });
+        self
+    }
+
+    /// Set an observer that is fed the upstream's events as they are decoded.
+    pub fn with_event_observer(mut self, observer: Box<dyn EventObserver>) -> Self {
+        self.event_observer = Some(observer);
+        self
+    }
+
+    /// Hold `guard` for the lifetime of the stream, dropping it on finish.
+    pub fn with_guard(mut self, guard: Box<dyn Send>) -> Self {
+        self.guard = Some(guard);
         self
     }
 
+    /// Send [`SSE_KEEPALIVE`] after every `interval` of upstream silence,
+    /// as long as the stream is between events.
+    pub fn with_keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(Keepalive {
+            interval,
+            timer: Box::pin(tokio::time::sleep(interval)),
+            line_start: true,
+            at_boundary: true,
+        });
+        self
+    }
+
+    /// Count upstream SSE events by type into the request record.
+    pub fn with_sse_histogram(mut self) -> Self {
+        self.sse_histogram = Some(SseEventHistogram::new());
+        self
+    }
+
+    /// Count token usage from upstream SSE events into the record's
+    /// response analysis.
+    pub fn with_usage_tracker(mut self, tracker: UsageTracker) -> Self {
+        self.usage = Some(tracker);
+        self
+    }
+
+    /// Keep the assistant's text so the hub can offer it as the last
+    /// response once the stream completes successfully.
+    pub fn with_response_text(mut self) -> Self {
+        self.response_text = Some(ResponseTextCollector::new());
+        self
+    }
+
+    /// Analyze the stream's thinking events and log the resulting
+    /// [`ThinkingStreamStats`](crate::sse::ThinkingStreamStats), tagged with
+    /// `backend` and the request id, when the stream ends.
+    pub fn with_thinking_stats(mut self, logger: Arc<DebugLogger>, backend: String) -> Self {
+        self.thinking_stats = Some(ThinkingStatsLog {
+            events: Vec::new(),
+            logger,
+            backend,
+        });
+        self
+    }
+
+    /// End a stream cut short after `message_start` with a synthetic
+    /// `error` event, so the client fails the turn instead of hanging.
+    pub fn with_truncation_error(mut self) -> Self {
+        self.truncation = Some(TruncationGuard {
+            started: false,
+            stopped: false,
+            at_boundary: true,
+        });
+        self
+    }
+
+    /// The error event to send if the stream is ending mid-message, or
+    /// `None` if it completed (or never started) normally.
+    fn truncation_event(&mut self, reason: &str) -> Option<Bytes> {
+        self.flush_events();
+        let guard = self.truncation.take()?;
+        if !guard.started || guard.stopped {
+            return None;
+        }
+        crate::metrics::app_log(
+            "stream",
+            &format!("SSE stream ended before message_stop ({}); sending error event", reason),
+        );
+        let data = serde_json::json!({
+            "type": "error",
+            "error": {"type": "api_error", "message": format!("Upstream stream {}", reason)},
+        });
+        let separator = if guard.at_boundary { "" } else { "\n\n" };
+        self.closed = true;
+        Some(Bytes::from(format!("{}event: error\ndata: {}\n\n", separator, data)))
+    }
+
+    /// Whether any consumer needs the upstream decoded.
+    fn decodes_events(&self) -> bool {
+        self.event_observer.is_some()
+            || self.sse_histogram.is_some()
+            || self.truncation.is_some()
+            || self.usage.is_some()
+            || self.response_text.is_some()
+            || self.thinking_stats.is_some()
+    }
+
+    /// Hand the payloads decoded from one chunk to every consumer.
+    fn dispatch_payloads(&mut self, payloads: Vec<serde_json::Value>) {
+        if payloads.is_empty() {
+            return;
+        }
+        // Usage mappings may read payloads that carry no `type`.
+        if let Some(tracker) = &mut self.usage {
+            for payload in &payloads {
+                tracker.observe(payload);
+            }
+        }
+        let events: Vec<SseEvent> = payloads.into_iter().filter_map(SseEvent::from_payload).collect();
+        if let Some(histogram) = &mut self.sse_histogram {
+            for event in &events {
+                histogram.record(event);
+            }
+        }
+        if let Some(collector) = &mut self.response_text {
+            for event in &events {
+                collector.record(event);
+            }
+        }
+        if let Some(log) = &mut self.thinking_stats {
+            log.observe(&events);
+        }
+        if let Some(guard) = &mut self.truncation {
+            guard.observe(&events);
+        }
+        if let Some(observer) = &mut self.event_observer {
+            observer.on_events(&events);
+        }
+    }
+
+    /// Dispatch the trailing event of an upstream that ended without its
+    /// blank line. Safe to call more than once.
+    fn flush_events(&mut self) {
+        if self.decodes_events() {
+            let payloads = self.decoder.finish_payloads();
+            self.dispatch_payloads(payloads);
+        }
+    }
+
     fn finish(&mut self) {
+        self.guard = None;
+        self.flush_events();
+        if let Some(mut observer) = self.event_observer.take() {
+            observer.on_finish();
+        }
+
         // Call the completion callback with accumulated response bytes
         if let Some(callback) = self.on_complete.take() {
             if !self.response_buffer.is_empty() {
@@ -129,6 +453,37 @@ impl<S> ObservedStream<S> {
         }
 
         if let Some(mut span) = self.span.take() {
+            if let Some(histogram) = self.sse_histogram.take() {
+                let discarded = self.decoder.discarded_lines();
+                if discarded > 0 {
+                    let backend = span.record_mut().backend.clone();
+                    crate::metrics::app_log(
+                        "stream",
+                        &format!(
+                            "Skipped {} non-SSE line(s) in the event stream from '{}' for {}",
+                            discarded,
+                            backend,
+                            span.request_id()
+                        ),
+                    );
+                }
+                span.record_mut().sse_events = Some(histogram);
+            }
+            if let Some(tracker) = self.usage.take() {
+                span.record_mut().response_analysis = Some(tracker.finish());
+            }
+            if let Some(collector) = self.response_text.take() {
+                let record = span.record_mut();
+                let succeeded = record.status.is_some_and(|s| s < 400)
+                    && !record.timed_out
+                    && !record.cancelled;
+                if succeeded && !collector.text().is_empty() {
+                    self.hub.record_response_text(collector.into_text());
+                }
+            }
+            if let Some(log) = self.thinking_stats.take() {
+                log.finish(span.request_id());
+            }
             if let Some(preview) = self.response_preview.take() {
                 let preview_value = redact_body(
                     &preview.buffer,
@@ -149,6 +504,25 @@ impl<S> ObservedStream<S> {
         }
     }
 
+    /// Account for `bytes` about to go to the client.
+    fn record_sent(&mut self, bytes: &Bytes) {
+        if let Some(span) = &mut self.span {
+            span.add_response_bytes(bytes.len());
+        }
+        if let Some(preview) = &mut self.response_preview {
+            preview.push(bytes);
+        }
+        if let Some(guard) = &mut self.truncation {
+            if !bytes.is_empty() {
+                guard.at_boundary = bytes.ends_with(b"\n\n") || bytes.ends_with(b"\r\n\r\n");
+            }
+        }
+        // Accumulate bytes for completion callback
+        if self.on_complete.is_some() {
+            self.response_buffer.extend_from_slice(bytes);
+        }
+    }
+
     fn reset_deadline(&mut self) {
         self.deadline
             .as_mut()
@@ -163,6 +537,10 @@ where
     type Item = Result<Bytes, StreamError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+
         // Check if idle timeout has expired
         if self.deadline.as_mut().poll(cx).is_ready() {
             let duration = self.idle_timeout.as_secs();
@@ -170,42 +548,81 @@ where
             if let Some(span) = &mut self.span {
                 span.mark_timed_out();
             }
+            let event = self.truncation_event(&format!("idle for {}s", duration));
             self.finish();
-            return Poll::Ready(Some(Err(StreamError::IdleTimeout { duration })));
+            return Poll::Ready(Some(match event {
+                Some(bytes) => Ok(bytes),
+                None => Err(StreamError::IdleTimeout { duration }),
+            }));
         }
 
         match Pin::new(&mut self.inner).poll_next(cx) {
             Poll::Ready(Some(Ok(bytes))) => {
                 // Reset deadline on successful data receipt
                 self.reset_deadline();
+                self.upstream_bytes += bytes.len() as u64;
+                if let Some(keepalive) = &mut self.keepalive {
+                    keepalive.track(&bytes);
+                    keepalive.reset();
+                }
+                if self.decodes_events() {
+                    let payloads = self.decoder.push_payloads(&bytes);
+                    self.dispatch_payloads(payloads);
+                }
                 // Apply chunk rewriter if present (e.g. reverse model mapping)
                 let bytes = if let Some(ref mut rewriter) = self.chunk_rewriter {
                     (rewriter)(bytes)
                 } else {
                     bytes
                 };
-                if let Some(span) = &mut self.span {
+                let this = &mut *self;
+                if let Some(span) = &mut this.span {
                     span.mark_first_byte();
-                    span.add_response_bytes(bytes.len());
-                }
-                if let Some(preview) = &mut self.response_preview {
-                    preview.push(&bytes);
-                }
-                // Accumulate bytes for completion callback
-                if self.on_complete.is_some() {
-                    self.response_buffer.extend_from_slice(&bytes);
+                    this.hub.stream_progress(span);
                 }
+                self.record_sent(&bytes);
                 Poll::Ready(Some(Ok(bytes)))
             }
             Poll::Ready(Some(Err(err))) => {
+                let event = self.truncation_event(&format!("failed: {}", err));
                 self.finish();
-                Poll::Ready(Some(Err(StreamError::Upstream(err))))
+                Poll::Ready(Some(match event {
+                    Some(bytes) => Ok(bytes),
+                    None => Err(StreamError::Upstream(err)),
+                }))
             }
             Poll::Ready(None) => {
+                // Let the rewriters release anything they held back
+                let held = match self.chunk_rewriter.as_mut() {
+                    Some(rewriter) => rewriter(Bytes::new()),
+                    None => Bytes::new(),
+                };
+                self.record_sent(&held);
+                let event = self.truncation_event("closed before message_stop");
                 self.finish();
-                Poll::Ready(None)
+                match (held.is_empty(), event) {
+                    (true, event) => Poll::Ready(event.map(Ok)),
+                    (false, None) => Poll::Ready(Some(Ok(held))),
+                    (false, Some(event)) => {
+                        let mut out = held.to_vec();
+                        out.extend_from_slice(&event);
+                        Poll::Ready(Some(Ok(Bytes::from(out))))
+                    }
+                }
+            }
+            Poll::Pending => {
+                if let Some(keepalive) = &mut self.keepalive {
+                    if keepalive.timer.as_mut().poll(cx).is_ready() {
+                        keepalive.reset();
+                        if keepalive.at_boundary {
+                            return Poll::Ready(Some(Ok(Bytes::from_static(SSE_KEEPALIVE))));
+                        }
+                        // Mid-event: skip this beat, but arm the next one.
+                        let _ = keepalive.timer.as_mut().poll(cx);
+                    }
+                }
+                Poll::Pending
             }
-            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -240,7 +657,19 @@ impl std::error::Error for StreamError {
 }
 
 impl<S> Drop for ObservedStream<S> {
+    /// A stream dropped before it finished means the client went away.
+    /// The record is marked cancelled, and dropping `inner` along with
+    /// the stream closes the upstream response so the backend stops
+    /// generating tokens nobody will read.
     fn drop(&mut self) {
+        let delivered = self.content_length.is_some_and(|len| self.upstream_bytes >= len);
+        if let Some(span) = self.span.as_mut().filter(|_| !delivered) {
+            span.mark_cancelled();
+            crate::metrics::app_log(
+                "stream",
+                &format!("Client disconnected mid-stream; cancelling upstream for {}", span.request_id()),
+            );
+        }
         self.finish();
     }
 }
@@ -14,9 +14,16 @@ pub struct RequestRecord {
     pub completed_at: Option<SystemTime>,
     pub latency_ms: Option<u64>,
     pub ttfb_ms: Option<u64>,
+    /// Time spent opening a new upstream connection; `None` when a
+    /// pooled connection was reused or no request went upstream.
+    pub connect_ms: Option<u64>,
     pub backend: String,
+    /// Whether the main agent or a teammate sent the request.
+    pub origin: RequestOrigin,
     pub status: Option<u16>,
     pub timed_out: bool,
+    /// The client disconnected before the streamed response finished.
+    pub cancelled: bool,
     pub request_bytes: u64,
     pub response_bytes: u64,
     pub request_analysis: Option<super::RequestAnalysis>,
@@ -24,6 +31,142 @@ pub struct RequestRecord {
     pub routing_decision: Option<RoutingDecision>,
     pub request_meta: Option<RequestMeta>,
     pub response_meta: Option<ResponseMeta>,
+    /// Event counts by type, for streamed (SSE) responses.
+    pub sse_events: Option<crate::sse::SseEventHistogram>,
+}
+
+/// Where a request's time went, in milliseconds from its start.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimingBreakdown {
+    /// Opening the upstream connection (`None`: a pooled one was reused).
+    pub connect_ms: Option<u64>,
+    /// Until the first response byte reached the client.
+    pub ttfb_ms: Option<u64>,
+    /// Until the response completed.
+    pub total_ms: Option<u64>,
+}
+
+impl RequestRecord {
+    pub fn timing(&self) -> TimingBreakdown {
+        TimingBreakdown {
+            connect_ms: self.connect_ms,
+            ttfb_ms: self.ttfb_ms,
+            total_ms: self.latency_ms,
+        }
+    }
+
+    /// What kind of failure this request was, if it failed. Cancelled
+    /// requests are the client's choice, not an error.
+    pub fn error_category(&self) -> Option<ErrorCategory> {
+        if self.timed_out {
+            return Some(ErrorCategory::Timeout);
+        }
+        match self.status? {
+            429 => Some(ErrorCategory::RateLimit),
+            400..=499 => Some(ErrorCategory::Client),
+            500..=599 => Some(ErrorCategory::Upstream),
+            _ => None,
+        }
+    }
+}
+
+/// A class of failed request, for [`ErrorSummary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// 5xx, including the proxy's own 502/504 for unreachable backends.
+    Upstream,
+    Timeout,
+    RateLimit,
+    /// Other 4xx.
+    Client,
+}
+
+impl ErrorCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorCategory::Upstream => "upstream",
+            ErrorCategory::Timeout => "timeout",
+            ErrorCategory::RateLimit => "rate limit",
+            ErrorCategory::Client => "client",
+        }
+    }
+
+    pub fn severity(self) -> ErrorSeverity {
+        match self {
+            ErrorCategory::Upstream | ErrorCategory::Timeout => ErrorSeverity::Error,
+            ErrorCategory::RateLimit | ErrorCategory::Client => ErrorSeverity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorSeverity {
+    Warning,
+    Error,
+}
+
+/// Failed requests, counted by category. Displays as e.g.
+/// "3 upstream, 1 timeout", most severe categories first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorSummary {
+    pub counts: std::collections::BTreeMap<ErrorCategory, u64>,
+}
+
+impl ErrorSummary {
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// The worst severity among the counted errors.
+    pub fn max_severity(&self) -> Option<ErrorSeverity> {
+        self.counts.keys().map(|c| c.severity()).max()
+    }
+
+    pub(crate) fn add(&mut self, category: ErrorCategory) {
+        *self.counts.entry(category).or_default() += 1;
+    }
+}
+
+impl std::fmt::Display for ErrorSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (category, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{count} {}", category.label())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for TimingBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+        write!(
+            f,
+            "connect_ms={} ttfb_ms={} total_ms={}",
+            ms(self.connect_ms),
+            ms(self.ttfb_ms),
+            ms(self.total_ms)
+        )
+    }
+}
+
+/// Which agent a request came from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestOrigin {
+    /// The main client, including its subagents.
+    #[default]
+    Main,
+    /// A teammate routed through `/teammate/*`.
+    Teammate,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -41,6 +184,33 @@ pub struct RoutingDecision {
     pub reason: String,
 }
 
+/// One entry in the hub's routing log: where a request went and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingLogEntry {
+    pub request_id: String,
+    pub backend: String,
+    pub reason: String,
+    pub path: String,
+    pub timestamp: SystemTime,
+}
+
+/// One-line rendering used by the diagnostic dump: epoch seconds, request
+/// id, path, then `→ backend (reason)`.
+impl std::fmt::Display for RoutingLogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        write!(
+            f,
+            "{}  {} {} → {} ({})",
+            secs, self.request_id, self.path, self.backend, self.reason
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct RequestMeta {
     pub method: String,
@@ -63,17 +233,77 @@ pub struct BackendMetrics {
     pub client_error_4xx: u64,
     pub server_error_5xx: u64,
     pub timeouts: u64,
+    /// Streams the client abandoned mid-response.
+    pub cancelled: u64,
     pub avg_latency_ms: f64,
     pub avg_ttfb_ms: f64,
+    /// Average over requests that opened a new connection.
+    pub avg_connect_ms: f64,
     pub p50_latency_ms: Option<u64>,
     pub p95_latency_ms: Option<u64>,
     pub p99_latency_ms: Option<u64>,
+    pub thinking_filter: ThinkingFilterCounters,
+}
+
+/// Running totals of thinking-block filter decisions for one backend.
+///
+/// Monotonic counters: each filtered request adds its per-pass counts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ThinkingFilterCounters {
+    /// Requests that went through the thinking filter.
+    pub requests: u64,
+    /// Cached blocks confirmed by appearing in a request.
+    pub confirmed: u64,
+    /// Cached blocks evicted because they belonged to an older session.
+    pub old_session_removed: u64,
+    /// Confirmed blocks evicted once requests stopped carrying them.
+    pub unused_removed: u64,
+    /// Unconfirmed blocks evicted after the orphan grace period.
+    pub orphaned_removed: u64,
+    /// Blocks stripped from request bodies.
+    pub filtered: u64,
+}
+
+impl ThinkingFilterCounters {
+    pub fn add(&mut self, other: &ThinkingFilterCounters) {
+        self.requests += other.requests;
+        self.confirmed += other.confirmed;
+        self.old_session_removed += other.old_session_removed;
+        self.unused_removed += other.unused_removed;
+        self.orphaned_removed += other.orphaned_removed;
+        self.filtered += other.filtered;
+    }
+}
+
+/// Running request, token and cost totals for one [`RequestOrigin`].
+///
+/// Tokens and cost come from response analysis, which runs at the
+/// `verbose` debug level; `requests` counts every finished request.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, record: &RequestRecord) {
+        self.requests += 1;
+        if let Some(analysis) = &record.response_analysis {
+            self.input_tokens += analysis.input_tokens.unwrap_or(0);
+            self.output_tokens += analysis.output_tokens.unwrap_or(0);
+            self.cost_usd += analysis.cost_usd.unwrap_or(0.0);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct MetricsSnapshot {
     pub generated_at: SystemTime,
     pub per_backend: HashMap<String, BackendMetrics>,
+    /// Usage split between the main agent and teammates.
+    pub per_origin: HashMap<RequestOrigin, UsageTotals>,
     pub recent: Vec<RequestRecord>,
 }
 
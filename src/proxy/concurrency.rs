@@ -0,0 +1,143 @@
+//! Per-backend concurrency limits for upstream requests.
+//!
+//! Backends with `max_concurrent` set get a semaphore keyed by backend name.
+//! Requests beyond the limit wait in a bounded queue; a request that finds
+//! the queue full, or does not get a slot within the queue timeout, fails
+//! with [`ProxyError::BackendBusy`].
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::{Backend, Defaults};
+use crate::proxy::error::ProxyError;
+
+/// Shared limiter consulted before every upstream request.
+pub struct ConcurrencyLimiter {
+    slots: Mutex<HashMap<String, BackendSlots>>,
+    queue: Mutex<QueueLimits>,
+}
+
+#[derive(Clone, Copy)]
+struct QueueLimits {
+    timeout: Duration,
+    max_queued: usize,
+}
+
+struct BackendSlots {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+}
+
+/// An in-flight slot for one backend. Released on drop.
+pub struct BackendPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Decrements the queue length when a waiter leaves, including when the
+/// waiting request is cancelled by a client disconnect.
+struct QueuedGuard(Arc<AtomicUsize>);
+
+impl Drop for QueuedGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(queue_timeout: Duration, max_queued: usize) -> Self {
+        Self {
+            slots: Mutex::new(HashMap::new()),
+            queue: Mutex::new(QueueLimits {
+                timeout: queue_timeout,
+                max_queued,
+            }),
+        }
+    }
+
+    /// Take `queue_timeout_seconds` / `max_queued_requests` from (possibly
+    /// reloaded) defaults. Requests already waiting keep their timeout.
+    pub fn set_queue_limits(&self, defaults: &Defaults) {
+        *self.queue.lock() = QueueLimits {
+            timeout: Duration::from_secs(defaults.queue_timeout_seconds.into()),
+            max_queued: defaults.max_queued_requests,
+        };
+    }
+
+    /// Wait for an in-flight slot on `backend`.
+    ///
+    /// Returns `Ok(None)` when the backend has no limit configured.
+    pub async fn acquire(&self, backend: &Backend) -> Result<Option<BackendPermit>, ProxyError> {
+        let Some(limit) = backend.max_concurrent.filter(|&n| n > 0) else {
+            return Ok(None);
+        };
+        let (semaphore, queued) = self.slots_for(&backend.name, limit);
+        let queue = *self.queue.lock();
+
+        if let Ok(permit) = Arc::clone(&semaphore).try_acquire_owned() {
+            return Ok(Some(BackendPermit { _permit: permit }));
+        }
+
+        let busy = |reason: String| ProxyError::BackendBusy {
+            backend: backend.name.clone(),
+            limit,
+            reason,
+        };
+
+        if queued.fetch_add(1, Ordering::SeqCst) >= queue.max_queued {
+            queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(busy(format!("queue full ({} waiting)", queue.max_queued)));
+        }
+        let _queued = QueuedGuard(queued);
+
+        crate::metrics::app_log(
+            "upstream",
+            &format!("Backend '{}' at concurrency limit ({}), queueing request", backend.name, limit),
+        );
+
+        match tokio::time::timeout(queue.timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(Some(BackendPermit { _permit: permit })),
+            Ok(Err(_)) => Err(busy("limiter closed".to_string())),
+            Err(_) => Err(busy(format!(
+                "no slot freed within {}s",
+                queue.timeout.as_secs()
+            ))),
+        }
+    }
+
+    /// Semaphore + queue counter for a backend, rebuilt if its limit changed
+    /// (config reload). Requests holding old permits drain the old semaphore.
+    fn slots_for(&self, name: &str, limit: usize) -> (Arc<Semaphore>, Arc<AtomicUsize>) {
+        let mut slots = self.slots.lock();
+        let entry = slots.entry(name.to_string()).or_insert_with(|| BackendSlots {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+            queued: Arc::new(AtomicUsize::new(0)),
+        });
+        if entry.limit != limit {
+            entry.limit = limit;
+            entry.semaphore = Arc::new(Semaphore::new(limit));
+        }
+        (Arc::clone(&entry.semaphore), Arc::clone(&entry.queued))
+    }
+}
+
+impl Default for ConcurrencyLimiter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), 64)
+    }
+}
+
+impl From<&Defaults> for ConcurrencyLimiter {
+    fn from(defaults: &Defaults) -> Self {
+        Self::new(
+            Duration::from_secs(defaults.queue_timeout_seconds.into()),
+            defaults.max_queued_requests,
+        )
+    }
+}
@@ -0,0 +1,76 @@
+//! Upstream connect-time measurement.
+//!
+//! reqwest doesn't report how long a connection took to open, so the
+//! upstream client gets a connector layer that times each connect and
+//! reports it to whichever request is waiting in [`timed`]. A request
+//! served from a pooled connection sees no connect at all.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tower::{Layer, Service};
+
+type Slot = Arc<Mutex<Option<Duration>>>;
+
+tokio::task_local! {
+    static CONNECT_TIME: Slot;
+}
+
+/// Run `send` and return its result along with the time spent opening a
+/// new connection for it, if one was opened.
+pub async fn timed<F: Future>(send: F) -> (F::Output, Option<Duration>) {
+    let slot = Slot::default();
+    let output = CONNECT_TIME.scope(slot.clone(), send).await;
+    let elapsed = *slot.lock();
+    (output, elapsed)
+}
+
+/// Connector layer that reports connect durations to [`timed`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConnectTimingLayer;
+
+impl<S> Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTiming<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTiming { inner }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConnectTiming<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for ConnectTiming<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        // The connect starts inside the request's own poll, so the slot is
+        // taken here; the connect may finish after the request moved on.
+        let slot = CONNECT_TIME.try_with(Arc::clone).ok();
+        let started = Instant::now();
+        let connect = self.inner.call(req);
+        Box::pin(async move {
+            let result = connect.await;
+            if let (Ok(_), Some(slot)) = (&result, slot) {
+                *slot.lock() = Some(started.elapsed());
+            }
+            result
+        })
+    }
+}
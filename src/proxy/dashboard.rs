@@ -0,0 +1,122 @@
+//! `/dashboard`: the metrics snapshot as a self-contained HTML page.
+//!
+//! Enabled with `proxy.dashboard`, behind the session token (which a
+//! browser passes as `?token=`). The page has inline styles only and
+//! refreshes itself, so it can be left open in a browser tab.
+
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+
+use crate::config::Backend;
+use crate::metrics::{BackendMetrics, MetricsSnapshot, RequestOrigin};
+
+/// Seconds between the page's automatic reloads.
+const REFRESH_SECONDS: u32 = 5;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{padding:4px 12px;border-bottom:1px solid #ddd;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+tr.active td:first-child{font-weight:bold}\
+.err{color:#b00}";
+
+/// Render the dashboard: one row per configured backend (plus any that
+/// only appear in the metrics), then token usage by origin.
+pub fn render(snapshot: &MetricsSnapshot, backends: &[Backend], active: &str) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_SECONDS}\">\
+         <title>AnyClaude</title><style>{STYLE}</style></head><body>\
+         <h1>AnyClaude</h1><p>Active backend: <strong>{}</strong> \
+         &middot; updated {}</p>",
+        escape(active),
+        snapshot
+            .generated_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    );
+
+    html.push_str(
+        "<h2>Backends</h2><table><tr><th>Backend</th><th>Requests</th><th>2xx</th>\
+         <th>4xx</th><th>5xx</th><th>Timeouts</th><th>Cancelled</th><th>Avg ms</th>\
+         <th>p50</th><th>p95</th><th>p99</th><th>Avg TTFB ms</th><th>Avg connect ms</th></tr>",
+    );
+    let empty = BackendMetrics::default();
+    let mut names: Vec<&str> = backends.iter().map(|b| b.name.as_str()).collect();
+    let mut unconfigured: Vec<&str> = snapshot
+        .per_backend
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !names.contains(name))
+        .collect();
+    unconfigured.sort_unstable();
+    names.extend(unconfigured);
+    for name in names {
+        let metrics = snapshot.per_backend.get(name).unwrap_or(&empty);
+        backend_row(&mut html, name, metrics, name == active);
+    }
+    html.push_str("</table>");
+
+    html.push_str(
+        "<h2>Usage</h2><table><tr><th>Origin</th><th>Requests</th>\
+         <th>Input tokens</th><th>Output tokens</th><th>Cost (USD)</th></tr>",
+    );
+    for (origin, label) in [(RequestOrigin::Main, "main"), (RequestOrigin::Teammate, "teammates")] {
+        let totals = snapshot.per_origin.get(&origin).copied().unwrap_or_default();
+        let _ = write!(
+            html,
+            "<tr><td>{label}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.4}</td></tr>",
+            totals.requests, totals.input_tokens, totals.output_tokens, totals.cost_usd,
+        );
+    }
+    html.push_str("</table></body></html>");
+    html
+}
+
+fn backend_row(html: &mut String, name: &str, m: &BackendMetrics, active: bool) {
+    let errors = |count: u64| {
+        if count > 0 {
+            format!("<td class=\"err\">{count}</td>")
+        } else {
+            "<td>0</td>".to_string()
+        }
+    };
+    let percentile = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    let _ = write!(
+        html,
+        "<tr{}><td>{}</td><td>{}</td><td>{}</td>{}{}{}<td>{}</td>\
+         <td>{:.0}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td></tr>",
+        if active { " class=\"active\"" } else { "" },
+        escape(name),
+        m.total,
+        m.success_2xx,
+        errors(m.client_error_4xx),
+        errors(m.server_error_5xx),
+        errors(m.timeouts),
+        m.cancelled,
+        m.avg_latency_ms,
+        percentile(m.p50_latency_ms),
+        percentile(m.p95_latency_ms),
+        percentile(m.p99_latency_ms),
+        m.avg_ttfb_ms,
+        m.avg_connect_ms,
+    );
+}
+
+/// Escape text for an HTML element body.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -45,10 +45,22 @@ pub enum ProxyError {
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    /// Incoming request headers exceed `proxy.max_header_count` / `max_header_bytes`
+    #[error("Request headers too large: {0}")]
+    HeadersTooLarge(String),
+
     /// Upstream returned an error response
     #[error("Upstream error: {status} - {message}")]
     UpstreamError { status: u16, message: String },
 
+    /// Backend is at its `max_concurrent` limit and the request could not queue
+    #[error("Backend '{backend}' is at its concurrency limit ({limit}): {reason}")]
+    BackendBusy {
+        backend: String,
+        limit: usize,
+        reason: String,
+    },
+
     /// AC marker references a subagent ID not found in the registry
     #[error("Subagent '{id}' not registered in affinity registry")]
     SubagentNotRegistered { id: String },
@@ -79,9 +91,11 @@ impl ProxyError {
             ProxyError::RequestTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
             ProxyError::IdleTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
             ProxyError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ProxyError::HeadersTooLarge(_) => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
             ProxyError::UpstreamError { status, .. } => {
                 StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY)
             }
+            ProxyError::BackendBusy { .. } => StatusCode::SERVICE_UNAVAILABLE,
             ProxyError::SubagentNotRegistered { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ProxyError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ProxyError::Http(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -98,7 +112,9 @@ impl ProxyError {
             ProxyError::RequestTimeout { .. } => "request_timeout",
             ProxyError::IdleTimeout { .. } => "idle_timeout",
             ProxyError::InvalidRequest(_) => "invalid_request",
+            ProxyError::HeadersTooLarge(_) => "headers_too_large",
             ProxyError::UpstreamError { .. } => "upstream_error",
+            ProxyError::BackendBusy { .. } => "backend_busy",
             ProxyError::SubagentNotRegistered { .. } => "subagent_not_registered",
             ProxyError::Internal(_) => "internal_error",
             ProxyError::Http(_) => "http_error",
@@ -117,12 +117,13 @@ pub struct TeammateStartResponse {
 ///
 /// Called by the tmux shim when it detects a teammate spawn.
 /// Registers the teammate's agent_id in the shared registry,
-/// mapping it to the current teammate backend.
+/// mapping it to its team's backend, else the current teammate backend.
 pub async fn handle_teammate_start(
     State(state): State<HookState>,
     Json(input): Json<TeammateStartInput>,
 ) -> Json<TeammateStartResponse> {
-    let backend = state.teammate_backend.get()
+    let backend = state.backend_state.team_backend_for(&input.agent_id)
+        .or_else(|| state.teammate_backend.get())
         .unwrap_or_else(|| state.backend_state.get_active_backend());
 
     state.registry.register(&input.agent_id, &backend);
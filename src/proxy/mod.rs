@@ -1,14 +1,20 @@
+pub mod concurrency;
+pub mod connect_timing;
 pub mod connection;
+pub mod dashboard;
 pub mod error;
 pub mod health;
 pub mod hooks;
 pub mod model_rewrite;
+pub mod models;
 pub mod pool;
 pub mod router;
 pub mod server;
 pub mod shutdown;
+pub mod stream_shape;
 pub mod thinking;
 pub mod timeout;
+pub mod warmup;
 pub mod pipeline;
 
-pub use server::{ProxyHandle, ProxyServer};
+pub use server::{ProxyAddr, ProxyHandle, ProxyServer};
@@ -8,8 +8,15 @@
 //! - **SSE streaming**: a stateful `ChunkRewriter` closure transforms the
 //!   `message_start` event's `message.model` field in the first chunk
 //! - **Non-streaming JSON**: the top-level `$.model` field is rewritten
+//!
+//! The same line-level rewriting backs per-backend response normalization
+//! (`Backend::response_normalization`): mapping non-standard stop reasons
+//! and filling usage fields some backends leave out.
 
 use axum::body::Bytes;
+use serde_json::Value;
+
+use crate::config::{Backend, ResponseNormalization};
 use crate::metrics::ChunkRewriter;
 
 /// Forward and reverse model mapping pair.
@@ -21,6 +28,18 @@ pub struct ModelMapping {
     pub original: String,
 }
 
+impl ModelMapping {
+    /// The mapping `backend` applies to a request for `original`, or `None`
+    /// when the model passes through unchanged.
+    pub fn resolve(backend: &Backend, original: &str) -> Option<Self> {
+        let mapped = backend.resolve_model(original)?;
+        Some(Self {
+            backend: mapped.to_string(),
+            original: original.to_string(),
+        })
+    }
+}
+
 /// Create a stateful chunk rewriter that replaces `message.model` in the
 /// `message_start` SSE event back to the original model name.
 ///
@@ -34,12 +53,30 @@ pub struct ModelMapping {
 ///
 /// After the first chunk containing `message_start` is processed, the rewriter
 /// becomes a zero-cost no-op for all subsequent chunks.
+///
+/// Upstreams may split an event anywhere, so while waiting, a trailing
+/// `data:` line without its newline is held back and prepended to the next
+/// chunk. Nothing else is ever held; the empty end-of-stream chunk returns
+/// whatever is still pending.
 pub fn make_reverse_model_rewriter(mapping: ModelMapping) -> ChunkRewriter {
     let mut done = false;
+    let mut pending: Vec<u8> = Vec::new();
     Box::new(move |bytes: Bytes| {
         if done {
             return bytes;
         }
+        if bytes.is_empty() {
+            return Bytes::from(std::mem::take(&mut pending));
+        }
+
+        let mut bytes = if pending.is_empty() {
+            bytes
+        } else {
+            pending.extend_from_slice(&bytes);
+            Bytes::from(std::mem::take(&mut pending))
+        };
+        let held = bytes.split_off(complete_len(&bytes));
+        pending.extend_from_slice(&held);
 
         // Fast path: skip chunks that don't contain message_start.
         // Uses byte-level check instead of full parse_sse_events() to avoid
@@ -49,82 +86,78 @@ pub fn make_reverse_model_rewriter(mapping: ModelMapping) -> ChunkRewriter {
             return bytes;
         }
 
-        // Mark done — message_start appears once per response.
+        // Mark done — message_start appears once per response — and release
+        // anything held back along with it.
         done = true;
-
-        // Single-pass: parse SSE lines and rewrite the message_start data line.
-        //
-        // NOTE: This intentionally re-implements SSE line parsing rather than
-        // reusing `sse::parse_sse_events()`. That function discards non-data
-        // lines (event:, empty) and line structure, making it impossible to
-        // reconstruct the original SSE text with modifications. Here we need
-        // in-place transformation with full line reconstruction.
-        let text = String::from_utf8_lossy(&bytes);
-        let mut result = String::with_capacity(text.len());
-        let mut rewritten = false;
-
-        for line in text.split('\n') {
-            if !result.is_empty() {
-                result.push('\n');
+        let tail = std::mem::take(&mut pending);
+        let with_tail = |head: Bytes| {
+            if tail.is_empty() {
+                return head;
             }
+            let mut out = head.to_vec();
+            out.extend_from_slice(&tail);
+            Bytes::from(out)
+        };
 
-            let trimmed = line.trim();
-            let data_payload = trimmed
-                .strip_prefix("data:")
-                .map(|rest| rest.trim_start());
-
-            if let Some(payload) = data_payload {
-                if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(payload) {
-                    let is_msg_start = json
-                        .get("type")
-                        .and_then(|t| t.as_str())
-                        == Some("message_start");
-                    if is_msg_start {
-                        // Rewrite message.model
-                        if let Some(msg) = json.get_mut("message") {
-                            if let Some(model) = msg.get("model").and_then(|m| m.as_str()) {
-                                if model == mapping.backend {
-                                    msg["model"] = serde_json::json!(&mapping.original);
-                                    rewritten = true;
-                                } else {
-                                    crate::metrics::app_log(
-                                        "model_map",
-                                        &format!(
-                                            "Reverse mapping skipped: expected '{}' but found '{}'",
-                                            mapping.backend, model
-                                        ),
-                                    );
-                                }
-                            }
-                        }
-                        result.push_str("data: ");
-                        result.push_str(
-                            &serde_json::to_string(&json)
-                                .unwrap_or_else(|_| payload.to_string()),
-                        );
-                        continue;
-                    }
+        let rewritten = rewrite_sse_data_lines(&bytes, |json| {
+            if json.get("type").and_then(|t| t.as_str()) != Some("message_start") {
+                return false;
+            }
+            let Some(msg) = json.get_mut("message") else {
+                return false;
+            };
+            match msg.get("model").and_then(|m| m.as_str()) {
+                Some(model) if model == mapping.backend => {
+                    msg["model"] = serde_json::json!(&mapping.original);
+                    true
                 }
+                Some(model) => {
+                    crate::metrics::app_log(
+                        "model_map",
+                        &format!(
+                            "Reverse mapping skipped: expected '{}' but found '{}'",
+                            mapping.backend, model
+                        ),
+                    );
+                    false
+                }
+                None => false,
             }
-            // Non-data lines (event:, empty, ping, etc.) pass through unchanged.
-            result.push_str(line);
-        }
+        });
 
-        if rewritten {
-            crate::metrics::app_log(
-                "model_map",
-                &format!(
-                    "Reverse mapped model in message_start: '{}' → '{}'",
-                    mapping.backend, mapping.original
-                ),
-            );
-            Bytes::from(result.into_bytes())
-        } else {
-            bytes
+        match rewritten {
+            Some(result) => {
+                crate::metrics::app_log(
+                    "model_map",
+                    &format!(
+                        "Reverse mapped model in message_start: '{}' → '{}'",
+                        mapping.backend, mapping.original
+                    ),
+                );
+                with_tail(result)
+            }
+            None => with_tail(bytes),
         }
     })
 }
 
+/// How much of `bytes` can be passed on: everything, unless it ends in a
+/// `data:` line (or a prefix of one) still waiting for its newline.
+fn complete_len(bytes: &[u8]) -> usize {
+    let line_start = bytes
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let tail = &bytes[line_start..];
+    let partial_data = !tail.is_empty()
+        && (tail.starts_with(b"data:") || b"data:".starts_with(tail));
+    if partial_data {
+        line_start
+    } else {
+        bytes.len()
+    }
+}
+
 /// Rewrite `$.model` in a non-streaming JSON response body.
 pub fn reverse_model_in_response(
     body_bytes: &Bytes,
@@ -163,6 +196,153 @@ pub fn reverse_model_in_response(
     }
 }
 
+/// Create a chunk rewriter that applies `rules` to the `message_start` and
+/// `message_delta` events of an SSE stream. Other events pass through.
+///
+/// Like [`make_reverse_model_rewriter`], a trailing `data:` line still
+/// waiting for its newline is held back and prepended to the next chunk,
+/// so an event split across chunks is normalized whole. The empty
+/// end-of-stream chunk releases whatever is still pending.
+pub fn make_response_normalizer(rules: ResponseNormalization) -> ChunkRewriter {
+    let mut pending: Vec<u8> = Vec::new();
+    Box::new(move |bytes: Bytes| {
+        let flushing = bytes.is_empty();
+        let mut bytes = if pending.is_empty() {
+            bytes
+        } else {
+            pending.extend_from_slice(&bytes);
+            Bytes::from(std::mem::take(&mut pending))
+        };
+        if !flushing {
+            let held = bytes.split_off(complete_len(&bytes));
+            pending.extend_from_slice(&held);
+        }
+
+        let haystack = bytes.as_ref();
+        if !contains_bytes(haystack, b"\"message_start\"")
+            && !contains_bytes(haystack, b"\"message_delta\"")
+        {
+            return bytes;
+        }
+        rewrite_sse_data_lines(&bytes, |json| normalize_event(json, &rules)).unwrap_or(bytes)
+    })
+}
+
+/// Apply `rules` to a non-streaming JSON response body.
+pub fn normalize_response(body_bytes: &Bytes, rules: &ResponseNormalization) -> Bytes {
+    let Ok(mut json) = serde_json::from_slice::<Value>(body_bytes) else {
+        return body_bytes.clone();
+    };
+    if json.get("type").and_then(|t| t.as_str()) != Some("message")
+        || !normalize_message(&mut json, rules)
+    {
+        return body_bytes.clone();
+    }
+    serde_json::to_vec(&json)
+        .map(Bytes::from)
+        .unwrap_or_else(|_| body_bytes.clone())
+}
+
+/// Normalize one SSE event payload. Returns true if anything changed.
+fn normalize_event(json: &mut Value, rules: &ResponseNormalization) -> bool {
+    match json.get("type").and_then(|t| t.as_str()) {
+        Some("message_start") => json
+            .get_mut("message")
+            .is_some_and(|msg| normalize_message(msg, rules)),
+        Some("message_delta") => {
+            let mut changed = json
+                .get_mut("delta")
+                .is_some_and(|delta| map_stop_reason(delta, rules));
+            if rules.fill_usage_defaults {
+                changed |= fill_usage(json, &["output_tokens"]);
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+/// Normalize a full message object (non-streaming body or `message_start`).
+fn normalize_message(msg: &mut Value, rules: &ResponseNormalization) -> bool {
+    let mut changed = map_stop_reason(msg, rules);
+    if rules.fill_usage_defaults {
+        changed |= fill_usage(msg, &["input_tokens", "output_tokens"]);
+    }
+    changed
+}
+
+fn map_stop_reason(obj: &mut Value, rules: &ResponseNormalization) -> bool {
+    let Some(mapped) = obj
+        .get("stop_reason")
+        .and_then(|r| r.as_str())
+        .and_then(|r| rules.stop_reasons.get(r))
+    else {
+        return false;
+    };
+    obj["stop_reason"] = Value::String(mapped.clone());
+    true
+}
+
+/// Insert 0 for each of `fields` missing from `obj.usage`, creating the
+/// usage object if the backend omitted it.
+fn fill_usage(obj: &mut Value, fields: &[&str]) -> bool {
+    let Some(obj) = obj.as_object_mut() else {
+        return false;
+    };
+    let usage = obj
+        .entry("usage")
+        .or_insert_with(|| Value::Object(Default::default()));
+    let Some(usage) = usage.as_object_mut() else {
+        return false;
+    };
+    let mut changed = false;
+    for field in fields {
+        if !usage.get(*field).is_some_and(Value::is_u64) {
+            usage.insert(field.to_string(), Value::from(0));
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Rewrite the JSON `data:` lines of an SSE chunk with `f`, keeping every
+/// other line as-is. Returns `None` if `f` changed nothing.
+///
+/// NOTE: This intentionally re-implements SSE line parsing rather than
+/// reusing `sse::parse_sse_events()`. That function discards non-data
+/// lines (event:, empty) and line structure, making it impossible to
+/// reconstruct the original SSE text with modifications. Here we need
+/// in-place transformation with full line reconstruction.
+fn rewrite_sse_data_lines(bytes: &Bytes, mut f: impl FnMut(&mut Value) -> bool) -> Option<Bytes> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut result = String::with_capacity(text.len());
+    let mut rewritten = false;
+
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+
+        let payload = line.trim().strip_prefix("data:").map(|rest| rest.trim_start());
+        if let Some(payload) = payload {
+            if let Ok(mut json) = serde_json::from_str::<Value>(payload) {
+                if f(&mut json) {
+                    if let Ok(serialized) = serde_json::to_string(&json) {
+                        result.push_str("data: ");
+                        result.push_str(&serialized);
+                        rewritten = true;
+                        continue;
+                    }
+                }
+            }
+        }
+        // Non-data lines (event:, empty, ping, etc.) pass through unchanged.
+        result.push_str(line);
+    }
+
+    rewritten.then(|| Bytes::from(result.into_bytes()))
+}
+
 /// Check if `haystack` contains `needle` as a contiguous subsequence.
 fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
     haystack.windows(needle.len()).any(|w| w == needle)
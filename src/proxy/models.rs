@@ -0,0 +1,63 @@
+//! Synthetic `/v1/models` listing.
+//!
+//! Backends that map Claude model families onto their own models can't
+//! answer `/v1/models` with names the client understands, so the proxy
+//! lists the client-facing ids it would rewrite for the active backend.
+
+use serde::Serialize;
+
+use crate::config::Backend;
+
+/// Client-facing model id and display name advertised for each family.
+const CLIENT_MODELS: [(&str, &str); 3] = [
+    ("claude-opus-4-6", "Claude Opus 4.6"),
+    ("claude-sonnet-4-5", "Claude Sonnet 4.5"),
+    ("claude-haiku-4-5", "Claude Haiku 4.5"),
+];
+
+/// The listing has no real release dates; this keeps `created_at` valid.
+const CREATED_AT: &str = "1970-01-01T00:00:00Z";
+
+#[derive(Debug, Serialize)]
+pub struct ModelInfo {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+    pub display_name: String,
+    pub created_at: &'static str,
+}
+
+/// Anthropic `GET /v1/models` response body.
+#[derive(Debug, Serialize)]
+pub struct ModelList {
+    pub data: Vec<ModelInfo>,
+    pub has_more: bool,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
+}
+
+/// Client-facing models `backend` maps, or `None` when it maps none and
+/// the request should go upstream unchanged.
+pub fn model_list(backend: &Backend) -> Option<ModelList> {
+    let data: Vec<ModelInfo> = CLIENT_MODELS
+        .iter()
+        .filter_map(|&(id, name)| {
+            let mapped = backend.resolve_model(id)?;
+            Some(ModelInfo {
+                kind: "model",
+                id: id.to_string(),
+                display_name: format!("{} ({})", name, mapped),
+                created_at: CREATED_AT,
+            })
+        })
+        .collect();
+    if data.is_empty() {
+        return None;
+    }
+    Some(ModelList {
+        first_id: data.first().map(|m| m.id.clone()),
+        last_id: data.last().map(|m| m.id.clone()),
+        data,
+        has_more: false,
+    })
+}
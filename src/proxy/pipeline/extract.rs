@@ -29,6 +29,25 @@ pub struct ExtractedRequest {
     pub content_type: String,
 }
 
+/// Check that a `/v1/messages` request body is well-formed JSON.
+///
+/// Only messages paths are checked: other endpoints may legitimately carry
+/// non-JSON bodies. Returns [`ProxyError::InvalidRequest`] carrying the
+/// parse error, including its line and column, so the client sees what is wrong rather than a
+/// confusing 400 from the backend.
+pub fn validate_json_body(extracted: &ExtractedRequest) -> Result<(), ProxyError> {
+    if extracted.parsed_body.is_some() || !extracted.uri.path().starts_with("/v1/messages") {
+        return Ok(());
+    }
+    match serde_json::from_slice::<serde::de::IgnoredAny>(&extracted.body_bytes) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(ProxyError::InvalidRequest(format!(
+            "Malformed JSON request body: {}",
+            e
+        ))),
+    }
+}
+
 /// Stage 1: Extract request body and metadata.
 ///
 /// Collects the body bytes and optionally parses as JSON for downstream stages.
@@ -68,7 +87,8 @@ pub async fn extract_request(
 
     // Debug logging for headers
     let debug_config = ctx.debug_logger.config();
-    if debug_config.level >= DebugLogLevel::Full && debug_config.header_preview {
+    let detailed = ctx.span.is_detailed();
+    if detailed && debug_config.level >= DebugLogLevel::Full && debug_config.header_preview {
         let record = ctx.span.record_mut();
         let meta = record.request_meta.get_or_insert_with(|| {
             let query = uri.query().map(|v| v.to_string());
@@ -84,7 +104,7 @@ pub async fn extract_request(
     }
 
     // Debug logging for body preview
-    if debug_config.level >= DebugLogLevel::Full {
+    if detailed && debug_config.level >= DebugLogLevel::Full {
         let record = ctx.span.record_mut();
         let meta = record.request_meta.get_or_insert_with(|| {
             let query = uri.query().map(|v| v.to_string());
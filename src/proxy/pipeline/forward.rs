@@ -1,16 +1,50 @@
 //! Stage 6: Forward request with retry.
 //!
 //! Sends the request to the upstream backend with retry logic for
-//! connection errors and timeouts.
+//! connection errors and timeouts. A connect failure means nothing was
+//! sent, so it is always retried; a timeout may hit a request the
+//! backend already processed, so it is retried only when resending is
+//! safe (see [`is_idempotent`]). Time spent opening a new upstream
+//! connection, and waiting for the upstream's response headers, is
+//! recorded on the span.
+
+use std::time::Instant;
 
 use axum::http::{Method, Uri};
 use reqwest::Client;
 use tokio::time::sleep;
 
-use crate::config::Backend;
+use crate::config::{AuthType, Backend, SigningRequest};
+use crate::proxy::connect_timing::timed;
 use crate::proxy::error::ProxyError;
 use crate::proxy::pipeline::{PipelineConfig, PipelineContext};
 
+/// Header a client sets to mark a request as safe to resend.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Whether a request may be resent after the upstream might have seen
+/// it: safe methods, requests carrying an `Idempotency-Key`, or any
+/// request when `retry_non_idempotent` is configured.
+fn is_idempotent(method: &Method, headers: &[(String, String)], config: &PipelineConfig) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(IDEMPOTENCY_KEY_HEADER))
+        || config.pool_config.retry_non_idempotent
+}
+
+/// Finish the span for a backend that can't authenticate and return the
+/// error for it.
+fn not_configured(backend: &Backend, reason: String, ctx: &mut PipelineContext) -> ProxyError {
+    let err = ProxyError::BackendNotConfigured {
+        backend: backend.name.clone(),
+        reason,
+    };
+    ctx.observability.finish_error(ctx.span.clone(), Some(err.status_code().as_u16()));
+    ctx.span_finalized = true;
+    err
+}
+
 /// Stage 6: Forward request to upstream with retry logic.
 ///
 /// Returns the raw upstream response for Stage 7 to handle.
@@ -26,25 +60,27 @@ pub async fn forward_with_retry(
     config: &PipelineConfig,
     ctx: &mut PipelineContext,
 ) -> Result<reqwest::Response, ProxyError> {
-    // Validate backend is configured
-    if !backend.is_configured() {
-        let err = ProxyError::BackendNotConfigured {
-            backend: backend.name.clone(),
-            reason: "api_key is not set".to_string(),
-        };
-        ctx.observability.finish_error(ctx.span.clone(), Some(err.status_code().as_u16()));
-        ctx.span_finalized = true;
-        return Err(err);
-    }
-
     let path_and_query = uri
         .path_and_query()
         .map(|pq| pq.as_str())
         .unwrap_or("/");
     let upstream_uri = format!("{}{}", backend.base_url, path_and_query);
 
+    let idempotent = is_idempotent(&method, &headers, config);
     let mut attempt = 0u32;
 
+    // A signed backend needs its signer; a missing one is a setup error
+    let signer = if backend.auth_type() == AuthType::Signed {
+        match config.signers.get(&backend.name) {
+            Some(signer) => Some(signer.clone()),
+            None => {
+                return Err(not_configured(backend, "no request signer registered".to_string(), ctx));
+            }
+        }
+    } else {
+        None
+    };
+
     let upstream_resp = loop {
         let mut builder = client.request(method.clone(), &upstream_uri);
 
@@ -53,6 +89,24 @@ pub async fn forward_with_retry(
             builder = builder.header(name, value);
         }
 
+        // Signatures usually cover a timestamp, so sign each attempt afresh
+        if let Some(signer) = &signer {
+            let request = SigningRequest {
+                method: method.as_str(),
+                url: &upstream_uri,
+                headers: &headers,
+                body: &body_bytes,
+            };
+            match signer.sign(&request) {
+                Ok(signed) => {
+                    for (name, value) in signed {
+                        builder = builder.header(name, value);
+                    }
+                }
+                Err(reason) => return Err(not_configured(backend, reason, ctx)),
+            }
+        }
+
         // For streaming requests: skip reqwest timeout entirely.
         // connect_timeout is set on Client, idle_timeout on ObservedStream.
         // For non-streaming: apply request timeout to the full response.
@@ -60,10 +114,17 @@ pub async fn forward_with_retry(
             builder = builder.timeout(config.timeout_config.request);
         }
 
-        let send_result = builder.body(body_bytes.clone()).send().await;
+        let sent = Instant::now();
+        let (send_result, connect_time) = timed(builder.body(body_bytes.clone()).send()).await;
+        if let Some(connect_time) = connect_time {
+            ctx.span.set_connect_time(connect_time);
+        }
 
         match send_result {
-            Ok(response) => break response,
+            Ok(response) => {
+                ctx.span.set_upstream_time(sent.elapsed());
+                break response;
+            }
             Err(err) => {
                 crate::metrics::app_log_error(
                     "upstream",
@@ -78,7 +139,7 @@ pub async fn forward_with_retry(
                     &format!("{:?}", err),
                 );
 
-                let should_retry = err.is_connect() || err.is_timeout();
+                let should_retry = err.is_connect() || (err.is_timeout() && idempotent);
                 if should_retry && attempt < config.pool_config.max_retries {
                     let backoff = config
                         .pool_config
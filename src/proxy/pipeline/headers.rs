@@ -5,21 +5,39 @@
 //! - Strips auth headers when backend uses own credentials
 //! - Patches anthropic-beta header for non-Anthropic backends
 //! - Adds backend's own auth header if configured
+//!
+//! The backend's credential is resolved once per request, beforehand, by
+//! [`resolve_credential`].
 
 use axum::http::header::{AUTHORIZATION, CONTENT_LENGTH, HOST};
 use axum::http::HeaderMap;
 
-use crate::config::Backend;
+use crate::config::{Backend, CredentialStatus};
 use crate::config::build_auth_header;
 use crate::proxy::error::ProxyError;
 use crate::proxy::pipeline::PipelineContext;
 
+/// Resolve `backend`'s credential for one request. An `api_key_command`
+/// that has to run does so on the blocking pool, off the async workers.
+pub async fn resolve_credential(backend: &Backend) -> CredentialStatus {
+    if !backend.credential_needs_command() {
+        return backend.resolve_credential();
+    }
+    let backend = backend.clone();
+    tokio::task::spawn_blocking(move || backend.resolve_credential())
+        .await
+        .unwrap_or_else(|e| CredentialStatus::Unconfigured {
+            reason: format!("api_key_command task failed: {e}"),
+        })
+}
+
 /// Stage 5: Build headers for upstream request.
 ///
 /// Returns a Vec of (name, value) pairs to preserve multiple values for the same header name.
 pub fn build_headers(
     incoming_headers: &HeaderMap,
     backend: &Backend,
+    credential: &CredentialStatus,
     ctx: &mut PipelineContext,
 ) -> Result<Vec<(String, String)>, ProxyError> {
     let mut headers: Vec<(String, String)> = Vec::new();
@@ -77,7 +95,7 @@ pub fn build_headers(
     }
 
     // Add backend's own auth header (for bearer/api_key modes)
-    if let Some((name, value)) = build_auth_header(backend) {
+    if let Some((name, value)) = build_auth_header(backend, credential) {
         headers.push((name, value));
     }
 
@@ -4,11 +4,13 @@
 //! extract → routing → thinking → transform → headers → forward → response.
 
 use axum::body::Body;
-use axum::http::{Request, Response};
+use axum::http::{HeaderName, Request, Response};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::backend::{BackendState, AgentRegistry};
-use crate::metrics::{BackendOverride, DebugLogger, ObservabilityHub, RequestSpan};
+use crate::config::{CredentialStatus, RequestSigner};
+use crate::metrics::{BackendOverride, DebugLogger, ObservabilityHub, RequestSpan, RoutingLogEntry};
 use crate::proxy::thinking::TransformerRegistry;
 
 mod extract;
@@ -19,13 +21,13 @@ mod routing;
 mod thinking;
 mod transform;
 
-pub use extract::extract_request;
+pub use extract::{extract_request, validate_json_body};
 pub use forward::forward_with_retry;
-pub use headers::build_headers;
+pub use headers::{build_headers, resolve_credential};
 pub use response::handle_response;
 pub use routing::{extract_ac_marker, resolve_backend};
 pub use thinking::create_thinking;
-pub use transform::transform_body;
+pub use transform::{passthrough_body, transform_body};
 
 /// Context shared across pipeline stages.
 ///
@@ -57,6 +59,10 @@ impl PipelineContext {
 }
 
 /// Configuration for pipeline execution.
+///
+/// Passthrough, `bypass_paths` and the queue limits are read from
+/// `backend_state` per request, so they follow a config reload. The header
+/// and validation settings below are fixed when the pipeline is built.
 #[derive(Clone)]
 pub struct PipelineConfig {
     /// Backend state for resolving backends
@@ -71,6 +77,20 @@ pub struct PipelineConfig {
     pub pool_config: crate::proxy::pool::PoolConfig,
     /// HTTP client for upstream requests
     pub http_client: reqwest::Client,
+    /// Per-backend `max_concurrent` enforcement, shared across requests
+    pub limiter: Arc<crate::proxy::concurrency::ConcurrencyLimiter>,
+    /// Reject malformed `/v1/messages` bodies (`proxy.validate_json`)
+    pub validate_json: bool,
+    /// Correlation id header reused and echoed per request (`proxy.request_id_header`)
+    pub request_id_header: HeaderName,
+    /// Incoming header count limit (`proxy.max_header_count`)
+    pub max_header_count: usize,
+    /// Incoming header size limit in bytes (`proxy.max_header_bytes`)
+    pub max_header_bytes: usize,
+    /// Per-request backend override header (`proxy.backend_header`)
+    pub backend_header: Option<HeaderName>,
+    /// Signers for `auth_type = "signed"` backends, by backend name
+    pub signers: HashMap<String, Arc<dyn RequestSigner>>,
 }
 
 impl PipelineConfig {
@@ -85,8 +105,17 @@ impl PipelineConfig {
             .connect_timeout(timeout_config.connect)
             .pool_idle_timeout(Some(pool_config.pool_idle_timeout))
             .pool_max_idle_per_host(pool_config.pool_max_idle_per_host)
+            .connector_layer(crate::proxy::connect_timing::ConnectTimingLayer)
             .build()
             .expect("Failed to build upstream client");
+        let config = backend_state.get_config();
+        let limiter = Arc::new((&config.defaults).into());
+        if config.proxy.passthrough {
+            crate::metrics::app_log(
+                "pipeline",
+                "Passthrough mode: requests and responses are relayed without transformation",
+            );
+        }
 
         Self {
             backend_state,
@@ -95,8 +124,50 @@ impl PipelineConfig {
             timeout_config,
             pool_config,
             http_client,
+            limiter,
+            validate_json: config.proxy.validate_json,
+            request_id_header: request_id_header(&config.proxy.request_id_header),
+            max_header_count: config.proxy.max_header_count,
+            max_header_bytes: config.proxy.max_header_bytes,
+            backend_header: config.proxy.backend_header.as_deref().and_then(backend_header),
+            signers: HashMap::new(),
         }
     }
+
+    /// Whether requests to `path` skip the thinking and transform stages,
+    /// and their responses every rewrite, either globally (passthrough) or
+    /// by a `bypass_paths` prefix.
+    pub fn bypasses_transforms(&self, path: &str) -> bool {
+        self.backend_state.with_config(|config| {
+            config.proxy.passthrough
+                || config.proxy.bypass_paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+        })
+    }
+}
+
+/// Parse the configured request-id header name, falling back to
+/// `x-request-id` when it isn't a valid header name.
+fn request_id_header(name: &str) -> HeaderName {
+    HeaderName::try_from(name).unwrap_or_else(|_| {
+        crate::metrics::app_log(
+            "pipeline",
+            &format!("Invalid proxy.request_id_header '{}', using x-request-id", name),
+        );
+        HeaderName::from_static("x-request-id")
+    })
+}
+
+/// Parse the configured backend override header name. An invalid name
+/// disables the override rather than guessing at one.
+fn backend_header(name: &str) -> Option<HeaderName> {
+    HeaderName::try_from(name)
+        .inspect_err(|_| {
+            crate::metrics::app_log(
+                "pipeline",
+                &format!("Invalid proxy.backend_header '{}', per-request overrides disabled", name),
+            );
+        })
+        .ok()
 }
 
 /// Execute the 7-stage pipeline for a single request.
@@ -141,6 +212,15 @@ async fn execute_pipeline_inner(
 ) -> Result<Response<Body>, crate::proxy::error::ProxyError> {
     // Stage 1: Extract request
     let extracted = extract::extract_request(req, ctx).await?;
+    if config.validate_json {
+        extract::validate_json_body(&extracted)?;
+    }
+
+    // Only the main agent's answers are offered as the last response:
+    // teammates and hook-registered subagents answer someone else
+    let main_agent = !is_teammate
+        && (config.agent_registry.is_empty()
+            || extracted.parsed_body.as_ref().and_then(extract_ac_marker).is_none());
 
     // Stage 2: Resolve backend
     let backend = routing::resolve_backend(
@@ -151,10 +231,21 @@ async fn execute_pipeline_inner(
         &config.agent_registry,
         ctx,
     )?;
+    if let Some(decision) = ctx.span.record_mut().routing_decision.clone() {
+        ctx.observability.record_routing(RoutingLogEntry {
+            request_id: ctx.span.request_id().to_string(),
+            backend: decision.backend,
+            reason: decision.reason,
+            path: extracted.uri.path().to_string(),
+            timestamp: std::time::SystemTime::now(),
+        });
+    }
 
     // Stage 3: Create thinking session (after routing, before transform)
-    // Teammate requests (those with backend_override) skip thinking.
-    let thinking_session = if is_teammate {
+    // Teammate requests (those with backend_override) skip thinking, as
+    // does everything in passthrough mode or on a bypassed path.
+    let bypass = config.bypasses_transforms(extracted.uri.path());
+    let thinking_session = if is_teammate || bypass {
         None
     } else {
         thinking::create_thinking(
@@ -164,26 +255,42 @@ async fn execute_pipeline_inner(
         )
     };
 
-    // Stage 4: Transform body
-    let (transformed_body, is_streaming, model_mapping) = transform::transform_body(
-        extracted.body_bytes,
-        extracted.parsed_body,
-        &backend,
-        thinking_session.as_ref(),
-        ctx,
-    )?;
+    // Stage 4: Transform body (relayed as-is when bypassed)
+    let (transformed_body, is_streaming, model_mapping) = if bypass {
+        transform::passthrough_body(extracted.body_bytes, extracted.parsed_body.as_ref())
+    } else {
+        transform::transform_body(
+            extracted.body_bytes,
+            extracted.parsed_body,
+            &backend,
+            thinking_session.as_ref(),
+            ctx,
+        )?
+    };
 
     // Update span with request bytes after transformation
     ctx.span.set_request_bytes(transformed_body.len());
 
-    // Stage 5: Build headers
+    // Stage 5: Build headers, with the credential resolved once up front
+    let credential = headers::resolve_credential(&backend).await;
+    if let CredentialStatus::Unconfigured { reason } = &credential {
+        return Err(crate::proxy::error::ProxyError::BackendNotConfigured {
+            backend: backend.name.clone(),
+            reason: reason.clone(),
+        });
+    }
     let headers = headers::build_headers(
         &extracted.headers,
         &backend,
+        &credential,
         ctx,
     )?;
 
-    // Stage 6: Forward with retry
+    // Stage 6: Forward with retry, once the backend has a free slot
+    config
+        .backend_state
+        .with_config(|current| config.limiter.set_queue_limits(&current.defaults));
+    let permit = config.limiter.acquire(&backend).await?;
     let upstream_resp = forward::forward_with_retry(
         &config.http_client,
         extracted.method,
@@ -202,6 +309,10 @@ async fn execute_pipeline_inner(
         backend,
         thinking_session,
         model_mapping,
+        is_streaming,
+        main_agent,
+        bypass,
+        permit,
         config,
         ctx,
     ).await?;
@@ -2,32 +2,65 @@
 //!
 //! Processes the upstream response and converts it to an Axum response:
 //! - Detects streaming vs non-streaming
-//! - For streaming: creates ObservedStream with callbacks
+//! - For streaming: creates ObservedStream with callbacks (after buffering
+//!   and re-framing the whole response for `force_buffer` backends)
 //! - For non-streaming: reads full body, applies thinking registration
 //! - Applies reverse model mapping if needed
+//! - Applies the backend's response normalization, if configured
+//! - Reshapes SSE/JSON to match the request's `stream` flag, for
+//!   `match_client_stream` backends
+//! - Counts token usage (streamed per the backend's usage mapping) for
+//!   the per-origin totals, at every debug level
+//! - Logs per-backend thinking stream stats at verbose level
 //! - Handles debug logging and observability
+//! - Keeps the main agent's answer text for "copy last response"
+//! - Adds a `Server-Timing` header splitting latency between proxy and
+//!   upstream
+//!
+//! Passthrough mode and `proxy.bypass_paths` relay the body untouched: none
+//! of the buffering, rewriting, reshaping or injected events apply.
 
 use axum::body::Body;
-use axum::http::header::CONTENT_LENGTH;
+use futures::StreamExt;
+use axum::http::header::{CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING};
 use axum::http::Response;
 
 use crate::config::Backend;
 use crate::config::DebugLogLevel;
-use crate::metrics::{ObservedStream, redact_body, redact_headers, ResponseMeta, ResponsePreview};
+use crate::metrics::{
+    ObservedStream, redact_body, redact_headers, ResponseMeta, ResponseParser, ResponsePreview,
+    UsageTracker,
+};
 use crate::proxy::error::ProxyError;
-use crate::proxy::model_rewrite::{make_reverse_model_rewriter, ModelMapping, reverse_model_in_response};
+use crate::proxy::model_rewrite::{
+    make_response_normalizer, make_reverse_model_rewriter, normalize_response, ModelMapping,
+    reverse_model_in_response,
+};
+use crate::proxy::concurrency::BackendPermit;
+use crate::proxy::stream_shape::{buffer_sse, json_to_sse, sse_to_json, FORCE_BUFFER_LIMIT};
 use crate::proxy::thinking::ThinkingSession;
 use crate::proxy::pipeline::{PipelineConfig, PipelineContext};
+use crate::sse::ResponseTextCollector;
+
+const SERVER_TIMING: &str = "server-timing";
 
 /// Stage 7: Handle upstream response.
 ///
 /// Converts the upstream response into an Axum response, handling both
-/// streaming and non-streaming cases.
+/// streaming and non-streaming cases. `client_streaming` is the request's
+/// `stream` flag; a successful `main_agent` response's text becomes the
+/// hub's last response. A `bypass` response is observed but relayed
+/// byte for byte.
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_response(
     upstream_resp: reqwest::Response,
     backend: Backend,
     thinking: Option<ThinkingSession>,
     model_mapping: Option<ModelMapping>,
+    client_streaming: bool,
+    main_agent: bool,
+    bypass: bool,
+    permit: Option<BackendPermit>,
     config: &PipelineConfig,
     ctx: &mut PipelineContext,
 ) -> Result<Response<Body>, ProxyError> {
@@ -48,7 +81,8 @@ pub async fn handle_response(
 
     // Debug logging for response headers
     let debug_config = ctx.debug_logger.config();
-    if debug_config.level >= DebugLogLevel::Full && debug_config.header_preview {
+    let detailed = ctx.span.is_detailed();
+    if detailed && debug_config.level >= DebugLogLevel::Full && debug_config.header_preview {
         let meta = ctx
             .span
             .record_mut()
@@ -75,19 +109,58 @@ pub async fn handle_response(
 
     let mut response_builder = Response::builder().status(status);
 
-    // Copy response headers, stripping Content-Length if model mapping is active
+    // The backend ignored the request's `stream` flag: answer in the shape
+    // the client asked for
+    let reshape = !bypass && backend.match_client_stream && status.is_success();
+    let to_json = reshape && is_streaming && !client_streaming;
+    let to_sse = reshape && !is_streaming && client_streaming;
+    let reshaping = to_json || to_sse;
+
+    // Copy response headers, stripping Content-Length if the body may be
+    // rewritten and Content-Type if it may be reshaped. The upstream's
+    // framing (Transfer-Encoding, Connection) is hop-by-hop: the body is
+    // re-emitted here, so hyper frames it afresh for the client
+    let force_buffer = !bypass && backend.force_buffer;
+    let normalization = backend.response_normalization.clone().filter(|_| !bypass);
+    let rewrites_body = model_mapping.is_some()
+        || normalization.is_some()
+        || force_buffer
+        || reshaping;
     for (name, value) in response_headers.iter() {
-        if model_mapping.is_some() && name == CONTENT_LENGTH {
+        if name == TRANSFER_ENCODING
+            || name == CONNECTION
+            || (rewrites_body && name == CONTENT_LENGTH)
+            || (reshaping && name == CONTENT_TYPE)
+        {
             continue;
         }
         response_builder = response_builder.header(name, value);
     }
+    response_builder = response_builder.header(SERVER_TIMING, ctx.span.server_timing());
 
-    if is_streaming {
-        // Streaming response path
-        let stream = upstream_resp.bytes_stream();
+    if is_streaming && !to_json {
+        // Streaming response path. A `force_buffer` backend is read to the
+        // end first and replayed one event per chunk.
+        let upstream_length = upstream_resp.content_length();
+        let stream = if force_buffer {
+            // Progress beats follow the keep-alive interval, or half the
+            // idle timeout so a slow but live upstream never trips it.
+            let beat = config
+                .timeout_config
+                .keepalive
+                .unwrap_or(config.timeout_config.idle / 2);
+            buffer_sse(
+                upstream_resp.bytes_stream(),
+                backend.name.clone(),
+                FORCE_BUFFER_LIMIT,
+                beat,
+            )
+            .boxed()
+        } else {
+            upstream_resp.bytes_stream().boxed()
+        };
 
-        let response_preview = if debug_config.level >= DebugLogLevel::Full {
+        let response_preview = if detailed && debug_config.level >= DebugLogLevel::Full {
             let ct = content_type.clone().unwrap_or_default();
             if debug_config.full_body {
                 Some(ResponsePreview::full(ct, debug_config.pretty_print))
@@ -98,24 +171,50 @@ pub async fn handle_response(
             None
         };
 
-        // Register thinking blocks from SSE stream (main agent only)
-        let on_complete = thinking.map(|session| {
-            Box::new(move |bytes: &[u8]| {
-                let events = crate::sse::parse_sse_events(bytes);
-                session.register_from_sse(&events);
-            }) as crate::metrics::ResponseCompleteCallback
-        });
-
         let mut observed = ObservedStream::new(
             stream,
             ctx.span.clone(),
             ctx.observability.clone(),
             config.timeout_config.idle,
             response_preview,
-        );
+        )
+        .with_content_length(upstream_length)
+        .with_sse_histogram();
+
+        // Close a cut-off stream with an error event the client can see
+        if !bypass {
+            observed = observed.with_truncation_error();
+        }
+
+        // Offer the text for "copy last response" (main agent only)
+        if main_agent {
+            observed = observed.with_response_text();
+        }
+
+        // Token accounting, for the per-origin usage totals
+        observed = observed.with_usage_tracker(UsageTracker::new(
+            backend.usage_mapping.clone(),
+            backend.pricing.clone(),
+        ));
+
+        // Thinking-format stats for verbose logging
+        if debug_config.level >= DebugLogLevel::Verbose {
+            observed = observed.with_thinking_stats(ctx.debug_logger.clone(), backend.name.clone());
+        }
+
+        // Register thinking blocks as they complete in the SSE stream (main agent only)
+        if let Some(session) = thinking {
+            observed = observed.with_event_observer(Box::new(session.sse_registration()));
+        }
 
-        if let Some(cb) = on_complete {
-            observed = observed.with_on_complete(cb);
+        // Keep the backend's concurrency slot until the stream is done
+        if let Some(permit) = permit {
+            observed = observed.with_guard(Box::new(permit));
+        }
+
+        // Keep the client connection warm while the upstream is silent
+        if let Some(interval) = config.timeout_config.keepalive.filter(|_| !bypass) {
+            observed = observed.with_keepalive(interval);
         }
 
         // Reverse model mapping: rewrite model in message_start back to original
@@ -123,6 +222,11 @@ pub async fn handle_response(
             observed = observed.with_chunk_rewriter(make_reverse_model_rewriter(mapping));
         }
 
+        // Backend-specific stop_reason / usage fix-ups
+        if let Some(rules) = normalization {
+            observed = observed.with_chunk_rewriter(make_response_normalizer(rules));
+        }
+
         Ok(response_builder.body(Body::from_stream(observed))?)
     } else {
         // Non-streaming response path
@@ -137,34 +241,48 @@ pub async fn handle_response(
             }
         };
 
+        // Fold a stream the client didn't ask for into one message
+        let mut content_type = content_type;
+        let body_bytes = match to_json.then(|| sse_to_json(&body_bytes)).flatten() {
+            Some(json) => {
+                content_type = Some("application/json".to_string());
+                json
+            }
+            None => body_bytes,
+        };
+
         // Register thinking blocks from non-streaming response (main agent only)
         if let Some(ref session) = thinking {
             session.register_from_response(&body_bytes);
         }
 
-        // Response analysis for verbose logging
-        if debug_config.level >= DebugLogLevel::Verbose {
-            use crate::metrics::ResponseParser;
-            let parser = ResponseParser::new();
-            let mut analysis = parser.parse_response(&body_bytes);
-            if analysis.cost_usd.is_none() {
-                analysis.cost_usd = compute_cost_usd(
-                    &backend,
-                    analysis.input_tokens.or_else(|| {
-                        ctx.span
-                            .record_mut()
-                            .request_analysis
-                            .as_ref()
-                            .and_then(|a| a.estimated_input_tokens)
-                    }),
-                    analysis.output_tokens,
-                );
+        // Offer the text for "copy last response" (main agent only)
+        if main_agent && status.is_success() {
+            let text = ResponseTextCollector::from_message(&body_bytes).into_text();
+            if !text.is_empty() {
+                ctx.observability.record_response_text(text);
             }
-            ctx.span.record_mut().response_analysis = Some(analysis);
         }
 
+        // Response analysis, for the per-origin usage totals and verbose logging
+        let mut analysis = ResponseParser::new().parse_response(&body_bytes);
+        if analysis.cost_usd.is_none() {
+            analysis.cost_usd = compute_cost_usd(
+                &backend,
+                analysis.input_tokens.or_else(|| {
+                    ctx.span
+                        .record_mut()
+                        .request_analysis
+                        .as_ref()
+                        .and_then(|a| a.estimated_input_tokens)
+                }),
+                analysis.output_tokens,
+            );
+        }
+        ctx.span.record_mut().response_analysis = Some(analysis);
+
         // Response body preview for full logging
-        if debug_config.level >= DebugLogLevel::Full {
+        if detailed && debug_config.level >= DebugLogLevel::Full {
             let meta = ctx
                 .span
                 .record_mut()
@@ -210,6 +328,24 @@ pub async fn handle_response(
             body_bytes
         };
 
+        // Backend-specific stop_reason / usage fix-ups
+        let body_bytes = match normalization {
+            Some(ref rules) if status.is_success() => normalize_response(&body_bytes, rules),
+            _ => body_bytes,
+        };
+
+        // Replay as SSE for a streaming client
+        let body_bytes = match to_sse.then(|| json_to_sse(&body_bytes)).flatten() {
+            Some(sse) => {
+                content_type = Some("text/event-stream".to_string());
+                sse
+            }
+            None => body_bytes,
+        };
+        if let Some(ct) = content_type.filter(|_| reshaping) {
+            response_builder = response_builder.header(CONTENT_TYPE, ct);
+        }
+
         ctx.span.add_response_bytes(body_bytes.len());
         ctx.observability.finish_request(ctx.span.clone());
         ctx.span_finalized = true;
@@ -224,9 +360,5 @@ fn compute_cost_usd(
     output_tokens: Option<u64>,
 ) -> Option<f64> {
     let pricing = backend.pricing.as_ref()?;
-    let input_tokens = input_tokens.unwrap_or(0) as f64;
-    let output_tokens = output_tokens.unwrap_or(0) as f64;
-    let cost = (input_tokens * pricing.input_per_million + output_tokens * pricing.output_per_million)
-        / 1_000_000.0;
-    Some(cost)
+    Some(pricing.cost_usd(input_tokens.unwrap_or(0), output_tokens.unwrap_or(0)))
 }
@@ -2,9 +2,10 @@
 //!
 //! Resolves the target backend based on:
 //! - Backend override from extensions (teammate pipeline)
-//! - Plugin routing decisions
+//! - Plugin routing decisions and the per-request backend header
 //! - AC marker in request body (session affinity from hook)
 //! - Marker model prefixes (marker-*, anyclaude-*)
+//! - Weighted pick from the `defaults.balance_tag` pool
 //! - Active backend from backend_state
 
 use serde_json::Value;
@@ -18,11 +19,14 @@ use crate::proxy::pipeline::PipelineContext;
 /// Stage 2: Resolve the target backend.
 ///
 /// Priority:
-/// 1. Plugin backend override (from observability.start_request)
+/// 1. Header or plugin backend override (`proxy.backend_header`, then
+///    observability.start_request)
 /// 2. Explicit backend_override parameter (teammate routes)
 /// 3. AC marker in request body (session affinity from hook)
 /// 4. Marker model detection (marker-*, anyclaude-* prefixes, direct backend name)
-/// 5. Active backend from backend_state
+/// 5. Weighted pick from the balance pool, while the active backend is in it;
+///    requests sharing `metadata.user_id` get the same pick
+/// 6. Active backend from backend_state
 pub fn resolve_backend(
     backend_state: &BackendState,
     backend_override: Option<String>,
@@ -51,6 +55,10 @@ pub fn resolve_backend(
         .and_then(|model| detect_marker_model(model, backend_state))
     {
         (mb, "marker model".into())
+    } else if let Some((picked, tag)) =
+        backend_state.pick_balanced_backend(parsed_body.and_then(conversation_key))
+    {
+        (picked, format!("weighted pool '{tag}'"))
     } else {
         (backend_state.get_active_backend(), "active backend".into())
     };
@@ -70,6 +78,13 @@ pub fn resolve_backend(
     Ok(backend)
 }
 
+/// The request's `metadata.user_id`, which Claude Code sets to a value
+/// that includes the session id. Keys the balanced pick so a conversation
+/// stays on one backend.
+fn conversation_key(body: &Value) -> Option<&str> {
+    body.get("metadata")?.get("user_id")?.as_str()
+}
+
 /// CC wrapper prefix that always precedes our marker in `additionalContext`.
 /// Without this prefix the marker is ignored — prevents false positives
 /// from user text that happens to contain `⟨AC:...⟩`.
@@ -13,7 +13,8 @@ use crate::proxy::pipeline::PipelineContext;
 ///
 /// Creates a ThinkingSession for main agent requests. The caller is
 /// responsible for skipping this stage for teammate requests (those
-/// with a backend_override).
+/// with a backend_override). Backends with `thinking_registry_enabled =
+/// false` get no session, so their thinking blocks pass through.
 ///
 /// The session is created AFTER backend resolution to ensure the
 /// correct backend is captured (fixes the old thinking_middleware
@@ -23,6 +24,20 @@ pub fn create_thinking(
     backend: &Backend,
     ctx: &mut PipelineContext,
 ) -> Option<ThinkingSession> {
+    if !backend.thinking_registry_enabled {
+        ctx.debug_logger.log_auxiliary(
+            "thinking",
+            None,
+            None,
+            Some(&format!(
+                "Thinking registry disabled for backend '{}'; no thinking session",
+                backend.name
+            )),
+            None,
+        );
+        return None;
+    }
+
     let session = transformer_registry.begin_request(
         &backend.name,
         ctx.debug_logger.clone(),
@@ -3,14 +3,18 @@
 //! Applies transformations to the request body:
 //! - Model rewriting (family-based mapping)
 //! - Thinking compatibility conversion (adaptive -> enabled)
+//! - Per-backend thinking budget (override or strip the `thinking` field)
 //! - Thinking block filtering (via ThinkingSession)
+//! - Signature dropping for backends seen returning unsigned thinking
 
 use serde_json::Value;
 
 use crate::config::Backend;
 use crate::proxy::error::ProxyError;
 use crate::proxy::model_rewrite::ModelMapping;
-use crate::proxy::thinking::ThinkingSession;
+use crate::proxy::thinking::{
+    normalize_thinking_blocks, strip_thinking_signatures, ThinkingSession,
+};
 use crate::proxy::pipeline::PipelineContext;
 
 /// Result of body transformation.
@@ -33,11 +37,7 @@ pub fn transform_body(
         return Ok((body_bytes, false, None));
     };
 
-    // Detect streaming from body
-    let is_streaming_request = json_body
-        .get("stream")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
+    let is_streaming_request = is_streaming(&json_body);
 
     // Track if any transformation occurred
     let mut model_rewritten = false;
@@ -46,24 +46,45 @@ pub fn transform_body(
 
     // 1. Rewrite model field via family-based mapping
     if let Some(model_val) = json_body.get("model").and_then(|m| m.as_str()) {
-        if let Some(new_model) = backend.resolve_model(model_val) {
+        if let Some(mapping) = ModelMapping::resolve(backend, model_val) {
             ctx.debug_logger.log_auxiliary(
                 "model_map",
                 None,
                 None,
-                Some(&format!("Rewrote model '{}' -> '{}'", model_val, new_model)),
+                Some(&format!("Rewrote model '{}' -> '{}'", mapping.original, mapping.backend)),
                 None,
             );
-            model_mapping = Some(ModelMapping {
-                backend: new_model.to_string(),
-                original: model_val.to_string(),
-            });
-            json_body["model"] = serde_json::json!(new_model);
+            json_body["model"] = serde_json::json!(&mapping.backend);
+            model_mapping = Some(mapping);
             model_rewritten = true;
         }
     }
 
-    // 2. Convert adaptive thinking to standard format for non-Anthropic backends
+    // 2. Strip prompt-caching markers the backend can't accept
+    let cache_control_stripped = if backend.supports_cache_control {
+        0
+    } else {
+        strip_cache_control(&mut json_body)
+    };
+    if cache_control_stripped > 0 {
+        ctx.debug_logger.log_auxiliary(
+            "cache_control",
+            None,
+            None,
+            Some(&format!(
+                "Stripped {} cache_control marker(s) for backend '{}'",
+                cache_control_stripped, backend.name
+            )),
+            None,
+        );
+    }
+
+    // Dry run: steps 3-6 run on the body as usual, then it is restored
+    let dry_run_original = thinking
+        .filter(|session| session.is_dry_run())
+        .map(|_| json_body.clone());
+
+    // 3. Convert adaptive thinking to standard format for non-Anthropic backends
     if needs_thinking_compat {
         if let Some(changed) = convert_adaptive_thinking(&mut json_body, backend.thinking_budget_tokens) {
             if changed {
@@ -87,13 +108,92 @@ pub fn transform_body(
         }
     }
 
-    // 3. Filter thinking blocks (main agent only - ThinkingSession present)
+    // 4. Apply the backend's thinking budget, or strip thinking it can't accept
+    if let Some(change) = apply_thinking_budget(&mut json_body, backend) {
+        thinking_converted = true;
+        ctx.debug_logger.log_auxiliary(
+            "thinking_compat",
+            None,
+            None,
+            Some(&format!("{} for backend '{}'", change, backend.name)),
+            None,
+        );
+    }
+
+    // 5. Drop empty signatures and duplicate thinking blocks left over from
+    //    earlier provider switches, and every signature for a backend that
+    //    returns unsigned thinking
+    let mut normalized = normalize_thinking_blocks(&mut json_body);
+    if thinking.is_some_and(|session| session.drops_signatures()) {
+        let stripped = strip_thinking_signatures(&mut json_body);
+        if stripped > 0 {
+            normalized += stripped;
+            ctx.debug_logger.log_auxiliary(
+                "thinking_compat",
+                None,
+                None,
+                Some(&format!(
+                    "Backend '{}' returns unsigned thinking; dropped {} signature(s) \
+                     regardless of its thinking settings",
+                    backend.name, stripped
+                )),
+                None,
+            );
+        }
+    }
+    if normalized > 0 {
+        ctx.debug_logger.log_auxiliary(
+            "thinking_compat",
+            None,
+            None,
+            Some(&format!(
+                "Normalized {} thinking block field(s) for backend '{}'",
+                normalized, backend.name
+            )),
+            None,
+        );
+    }
+
+    // 6. Filter thinking blocks (main agent only - ThinkingSession present)
     if let Some(session) = thinking {
-        filtered_count = session.filter(&mut json_body);
+        let outcome = session.filter_with_outcome(&mut json_body);
+        ctx.observability
+            .record_thinking_filter(&backend.name, outcome.into());
+        filtered_count = outcome.filtered;
+
+        if let Some(original) = dry_run_original {
+            ctx.debug_logger.log_auxiliary(
+                "thinking_dry_run",
+                None,
+                None,
+                Some(&format!(
+                    "Dry run for backend '{}': would convert thinking config={} normalize={} \
+                     filter={} (confirmed={} old_session={} unused={} orphaned={}); request left unchanged",
+                    backend.name,
+                    thinking_converted,
+                    normalized,
+                    outcome.filtered,
+                    outcome.confirmed,
+                    outcome.old_session_removed,
+                    outcome.unused_removed,
+                    outcome.orphaned_removed,
+                )),
+                None,
+            );
+            json_body = original;
+            thinking_converted = false;
+            normalized = 0;
+            filtered_count = 0;
+        }
     }
 
     // Re-serialize body if any transformation occurred
-    if model_rewritten || thinking_converted || filtered_count > 0 {
+    if model_rewritten
+        || cache_control_stripped > 0
+        || thinking_converted
+        || normalized > 0
+        || filtered_count > 0
+    {
         if thinking_converted {
             let thinking_json = json_body
                 .get("thinking")
@@ -124,9 +224,61 @@ pub fn transform_body(
     }
 }
 
+/// Passthrough counterpart of [`transform_body`]: the body goes upstream
+/// byte-for-byte and only the streaming flag is read from it.
+pub fn passthrough_body(
+    body_bytes: Vec<u8>,
+    parsed_body: Option<&Value>,
+) -> (Vec<u8>, bool, Option<ModelMapping>) {
+    let is_streaming_request = parsed_body.is_some_and(is_streaming);
+    (body_bytes, is_streaming_request, None)
+}
+
+/// Detect streaming from the request body's `stream` flag.
+fn is_streaming(body: &Value) -> bool {
+    body.get("stream").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// Remove every `cache_control` marker from the places Claude Code puts
+/// them: system blocks, tool definitions, message content blocks, and
+/// blocks nested in `tool_result` content. Tool input schemas are not
+/// searched, so a schema property named `cache_control` survives.
+///
+/// Returns the number of markers removed.
+fn strip_cache_control(body: &mut Value) -> u32 {
+    fn strip_blocks(blocks: Option<&mut Value>) -> u32 {
+        let Some(blocks) = blocks.and_then(|b| b.as_array_mut()) else {
+            return 0;
+        };
+        let mut removed = 0;
+        for block in blocks {
+            let Some(obj) = block.as_object_mut() else {
+                continue;
+            };
+            if obj.remove("cache_control").is_some() {
+                removed += 1;
+            }
+            if obj.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
+                removed += strip_blocks(obj.get_mut("content"));
+            }
+        }
+        removed
+    }
+
+    let mut removed = strip_blocks(body.get_mut("system"));
+    removed += strip_blocks(body.get_mut("tools"));
+    if let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) {
+        for message in messages {
+            removed += strip_blocks(message.get_mut("content"));
+        }
+    }
+    removed
+}
+
 /// Convert `"thinking": {"type": "adaptive"}` to `"thinking": {"type": "enabled", "budget_tokens": N}`.
 ///
 /// Budget priority: explicit config (`thinking_budget_tokens`) > `max_tokens - 1` from request > default 10000.
+/// A configured budget is capped at `max_tokens - 1`.
 ///
 /// Returns `Some(true)` if converted, `Some(false)` if thinking exists but not adaptive,
 /// `None` if no thinking field present.
@@ -141,12 +293,12 @@ fn convert_adaptive_thinking(body: &mut Value, configured_budget: Option<u32>) -
         return body.get("thinking").map(|_| false);
     }
 
-    let budget = configured_budget.unwrap_or_else(|| {
-        body.get("max_tokens")
-            .and_then(|v| v.as_u64())
-            .map(|mt| mt.saturating_sub(1) as u32)
-            .unwrap_or(10_000)
-    });
+    let max_tokens = body.get("max_tokens").and_then(|v| v.as_u64());
+    let budget = match (configured_budget, max_tokens) {
+        (Some(configured), Some(mt)) => u64::from(configured).min(mt.saturating_sub(1)) as u32,
+        (Some(configured), None) => configured,
+        (None, mt) => mt.map(|mt| mt.saturating_sub(1) as u32).unwrap_or(10_000),
+    };
 
     body.as_object_mut()?.insert(
         "thinking".to_string(),
@@ -157,3 +309,150 @@ fn convert_adaptive_thinking(body: &mut Value, configured_budget: Option<u32>) -
     );
     Some(true)
 }
+
+/// Smallest `budget_tokens` upstream accepts for enabled thinking.
+const MIN_THINKING_BUDGET: u64 = 1024;
+
+/// Enforce the backend's thinking configuration on an outgoing request.
+///
+/// - `supports_thinking = false`: the `thinking` field is removed, along
+///   with `thinking` and `redacted_thinking` blocks in `messages`.
+/// - `thinking_budget_tokens = N`: a request without thinking gets
+///   `{"type": "enabled", "budget_tokens": N}`, and one with `"enabled"`
+///   thinking has `budget_tokens` overridden to N. Requests with
+///   `"disabled"`, or still `"adaptive"` (native backends), are left alone.
+///   N is capped at `max_tokens - 1`, since upstream rejects a budget that
+///   doesn't fit under `max_tokens`.
+///
+/// Upstream rejects budgets under 1024, so when the capped budget is too
+/// small, `"enabled"` thinking is removed and no thinking is added. Thinking
+/// is not added to a request upstream would reject with it: one that forces
+/// a tool, sets `temperature`, `top_k` or `top_p`, or has an earlier
+/// assistant `tool_use` turn that doesn't start with a thinking block. An
+/// `"enabled"` budget is not overridden on a request that forces a tool.
+///
+/// Returns a description of the change, or `None` if the body was untouched.
+fn apply_thinking_budget(body: &mut Value, backend: &Backend) -> Option<String> {
+    if !backend.supports_thinking {
+        let removed_config = body.as_object_mut()?.remove("thinking").is_some();
+        let removed_blocks = strip_thinking_blocks(body);
+        return match (removed_config, removed_blocks) {
+            (false, 0) => None,
+            (true, 0) => Some("Stripped thinking config".to_string()),
+            (false, n) => Some(format!("Stripped {} thinking block(s)", n)),
+            (true, n) => Some(format!("Stripped thinking config and {} thinking block(s)", n)),
+        };
+    }
+
+    let configured = u64::from(backend.thinking_budget_tokens?);
+    let budget = match body.get("max_tokens").and_then(|v| v.as_u64()) {
+        Some(max_tokens) => configured.min(max_tokens.saturating_sub(1)),
+        None => configured,
+    };
+    let thinking_type = body
+        .get("thinking")
+        .and_then(|t| t.get("type"))
+        .and_then(|t| t.as_str());
+
+    if budget < MIN_THINKING_BUDGET {
+        if thinking_type != Some("enabled") {
+            return None;
+        }
+        body.as_object_mut()?.remove("thinking");
+        return Some(format!(
+            "Dropped thinking: budget_tokens={} is under the {} minimum",
+            budget, MIN_THINKING_BUDGET
+        ));
+    }
+    if forces_tool_use(body) {
+        return None;
+    }
+
+    if body.get("thinking").is_none() {
+        if sets_sampling_params(body) || has_unthought_tool_use(body) {
+            return None;
+        }
+        body.as_object_mut()?.insert(
+            "thinking".to_string(),
+            serde_json::json!({
+                "type": "enabled",
+                "budget_tokens": budget
+            }),
+        );
+        return Some(format!("Enabled thinking with budget_tokens={}", budget));
+    }
+
+    let thinking = body.get_mut("thinking")?.as_object_mut()?;
+    if thinking.get("type").and_then(|t| t.as_str()) != Some("enabled") {
+        return None;
+    }
+    if thinking.get("budget_tokens").and_then(|b| b.as_u64()) == Some(budget) {
+        return None;
+    }
+    thinking.insert("budget_tokens".to_string(), serde_json::json!(budget));
+    Some(format!("Set thinking budget_tokens={}", budget))
+}
+
+/// Whether a content block is a `thinking` or `redacted_thinking` block.
+fn is_thinking_block(block: &Value) -> bool {
+    matches!(
+        block.get("type").and_then(|t| t.as_str()),
+        Some("thinking" | "redacted_thinking")
+    )
+}
+
+/// Remove `thinking` and `redacted_thinking` blocks from message content.
+///
+/// Returns the number of blocks removed.
+fn strip_thinking_blocks(body: &mut Value) -> u32 {
+    let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for message in messages {
+        let Some(blocks) = message.get_mut("content").and_then(|c| c.as_array_mut()) else {
+            continue;
+        };
+        let before = blocks.len();
+        blocks.retain(|block| !is_thinking_block(block));
+        removed += (before - blocks.len()) as u32;
+    }
+    removed
+}
+
+/// Whether the request sets sampling parameters upstream doesn't allow
+/// together with thinking.
+fn sets_sampling_params(body: &Value) -> bool {
+    ["temperature", "top_k", "top_p"]
+        .iter()
+        .any(|key| body.get(key).is_some())
+}
+
+/// Whether an assistant turn calls a tool without starting with a thinking
+/// block. Upstream rejects such a history once thinking is enabled.
+fn has_unthought_tool_use(body: &Value) -> bool {
+    let Some(messages) = body.get("messages").and_then(|m| m.as_array()) else {
+        return false;
+    };
+    messages
+        .iter()
+        .filter(|message| message.get("role").and_then(|r| r.as_str()) == Some("assistant"))
+        .filter_map(|message| message.get("content").and_then(|c| c.as_array()))
+        .any(|blocks| {
+            let uses_tool = blocks
+                .iter()
+                .any(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"));
+            uses_tool && !blocks.first().is_some_and(is_thinking_block)
+        })
+}
+
+/// Whether the request forces tool use (`tool_choice` of `any` or `tool`),
+/// which upstream doesn't allow together with thinking.
+fn forces_tool_use(body: &Value) -> bool {
+    matches!(
+        body.get("tool_choice")
+            .and_then(|c| c.get("type"))
+            .and_then(|t| t.as_str()),
+        Some("any" | "tool")
+    )
+}
@@ -15,6 +15,8 @@ pub struct PoolConfig {
     pub max_retries: u32,
     /// Base backoff duration for retries.
     pub retry_backoff_base: Duration,
+    /// Retry timed-out requests that aren't known to be idempotent.
+    pub retry_non_idempotent: bool,
 }
 
 impl PoolConfig {
@@ -30,6 +32,7 @@ impl PoolConfig {
             pool_max_idle_per_host,
             max_retries,
             retry_backoff_base: Duration::from_millis(retry_backoff_base_ms),
+            retry_non_idempotent: false,
         }
     }
 }
@@ -41,6 +44,7 @@ impl Default for PoolConfig {
             pool_max_idle_per_host: 8,
             max_retries: 3,
             retry_backoff_base: Duration::from_millis(100),
+            retry_non_idempotent: false,
         }
     }
 }
@@ -52,6 +56,7 @@ impl From<&Defaults> for PoolConfig {
             pool_max_idle_per_host: defaults.pool_max_idle_per_host as usize,
             max_retries: defaults.max_retries,
             retry_backoff_base: Duration::from_millis(defaults.retry_backoff_base_ms),
+            retry_non_idempotent: defaults.retry_non_idempotent,
         }
     }
 }
@@ -1,20 +1,22 @@
 use axum::body::Body;
-use axum::extract::{RawQuery, State};
-use axum::Extension;
-use axum::http::Request;
+use axum::extract::{Path, RawQuery, State};
+use axum::{Extension, Json};
+use axum::http::{HeaderName, HeaderValue, Request};
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::Router;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::backend::{BackendState, AgentBackendState, AgentRegistry};
-use crate::config::DebugLogLevel;
-use crate::proxy::error::ErrorResponse;
+use crate::config::{DebugLogLevel, RequestSigner};
+use crate::proxy::error::{ErrorResponse, ProxyError};
 use crate::proxy::hooks::HookState;
-use crate::metrics::{DebugLogger, ObservabilityHub, RequestMeta};
+use crate::metrics::{sampled_for_detail, DebugLogger, ObservabilityHub, RequestMeta, RequestOrigin};
+use crate::metrics::BackendOverride as RoutingOverride;
 use crate::proxy::health::HealthHandler;
+use crate::proxy::models::model_list;
 use crate::proxy::pipeline::{PipelineConfig, PipelineContext};
 use crate::proxy::pool::PoolConfig;
 use crate::proxy::thinking::TransformerRegistry;
@@ -81,6 +83,28 @@ impl RouterEngine {
             session_token,
         }
     }
+
+    /// Sign requests to `backend` (an `auth_type = "signed"` backend) with
+    /// `signer`.
+    pub(crate) fn set_signer(&mut self, backend: String, signer: Arc<dyn RequestSigner>) {
+        self.pipeline_config.signers.insert(backend, signer);
+    }
+
+    /// Whether `token` passes the session-token check: always, when no
+    /// token is configured.
+    fn accepts_session_token(&self, token: Option<&str>) -> bool {
+        match self.session_token {
+            Some(ref expected) => token == Some(expected.as_str()),
+            None => true,
+        }
+    }
+
+    /// Prime the upstream pool for every configured backend; see
+    /// [`crate::proxy::warmup`].
+    pub(crate) async fn warm_up(&self) -> usize {
+        let backends = self.backend_state.get_config().backends;
+        crate::proxy::warmup::warm_up(&self.pipeline_config.http_client, &backends).await
+    }
 }
 
 /// Auth middleware — validates session token for proxy requests.
@@ -91,19 +115,87 @@ async fn auth_middleware(
     req: Request<Body>,
     next: Next,
 ) -> Response {
-    if let Some(ref expected_token) = state.session_token {
-        let session_header = req.headers()
-            .get("x-session-token")
-            .and_then(|v| v.to_str().ok());
-
-        let valid = session_header.is_some_and(|t| t == expected_token);
-
-        if !valid {
-            return Response::builder()
-                .status(401)
-                .body(Body::from("Unauthorized: invalid session token"))
-                .unwrap();
+    let session_header = req.headers()
+        .get("x-session-token")
+        .and_then(|v| v.to_str().ok());
+    if !state.accepts_session_token(session_header) {
+        return unauthorized();
+    }
+    next.run(req).await
+}
+
+/// Dashboard auth middleware — like [`auth_middleware`], but a browser
+/// can't set headers, so the token may also come as `?token=...`.
+async fn dashboard_auth_middleware(
+    State(state): State<RouterEngine>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let token = req
+        .headers()
+        .get("x-session-token")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            req.uri()
+                .query()
+                .unwrap_or("")
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        });
+    if !state.accepts_session_token(token) {
+        return unauthorized();
+    }
+    next.run(req).await
+}
+
+fn unauthorized() -> Response {
+    Response::builder()
+        .status(401)
+        .body(Body::from("Unauthorized: invalid session token"))
+        .unwrap()
+}
+
+/// Header limit middleware — rejects requests whose headers exceed
+/// `proxy.max_header_count` or `proxy.max_header_bytes` with a 431. Like
+/// every proxy response, the rejection carries the request id header,
+/// reusing the client's id when it sent one.
+///
+/// hyper already caps the raw header buffer and rejects requests with more
+/// than [`MAX_HEADER_COUNT_LIMIT`](crate::config::MAX_HEADER_COUNT_LIMIT)
+/// headers itself; this enforces the configured limits, which config
+/// validation keeps at or under that bound, before any handler runs.
+async fn header_limit_middleware(
+    State(state): State<RouterEngine>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let config = &state.pipeline_config;
+    let headers = req.headers();
+    let count = headers.len();
+    let bytes: usize = headers
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len())
+        .sum();
+
+    let reason = if count > config.max_header_count {
+        Some(format!("{} headers, limit {}", count, config.max_header_count))
+    } else if bytes > config.max_header_bytes {
+        Some(format!("{} header bytes, limit {}", bytes, config.max_header_bytes))
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        let request_id = client_request_id(&req, &config.request_id_header)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        crate::metrics::app_log(
+            "router",
+            &format!("Rejected {} {}: {} request_id={}", req.method(), req.uri().path(), reason, request_id),
+        );
+        let mut response = ErrorResponse::from_error(&ProxyError::HeadersTooLarge(reason), &request_id);
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(config.request_id_header.clone(), value);
         }
+        return response;
     }
     next.run(req).await
 }
@@ -113,6 +205,8 @@ pub fn build_router(
 ) -> Router {
     // Main pipeline: auth middleware only (thinking is handled inside the pipeline)
     let main = Router::new()
+        .route("/v1/models", get(models_handler))
+        .route("/api/log-tail", get(log_tail_handler))
         .fallback(proxy_handler)
         .layer(axum::middleware::from_fn_with_state(
             engine.clone(),
@@ -133,16 +227,26 @@ pub fn build_router(
         .route("/api/teammate-start", post(crate::proxy::hooks::handle_teammate_start))
         .with_state(hook_state);
 
-    let mut router = Router::new()
-        .route("/health", get(health_handler))
-        .with_state(engine.clone())
-        .merge(hook_routes);
+    let mut router = Router::new().route("/health", get(health_handler));
+    // Behind the session token like /api/log-tail; the page's own refresh
+    // keeps the `?token=` it was opened with.
+    if engine.backend_state.get_config().proxy.dashboard {
+        router = router.route(
+            "/dashboard",
+            get(dashboard_handler).layer(axum::middleware::from_fn_with_state(
+                engine.clone(),
+                dashboard_auth_middleware,
+            )),
+        );
+    }
+    let mut router = router.with_state(engine.clone()).merge(hook_routes);
 
     // Teammate pipeline: dynamic per-teammate backend via agent_id in URL path.
     // URL: /teammate/{agent_id}/v1/messages → agent_id extracted, path stripped.
     // Always enabled — without [agents] config, falls back to active main backend.
     {
         let teammate = Router::new()
+            .route("/{agent_id}/v1/models", get(teammate_models_handler))
             .fallback(proxy_handler)
             .layer(Extension(TeammateMarker))
             .with_state(engine.clone());
@@ -155,7 +259,9 @@ pub fn build_router(
         router = router.nest("/teammate", teammate);
     }
 
-    router.merge(main)
+    router
+        .merge(main)
+        .layer(axum::middleware::from_fn_with_state(engine, header_limit_middleware))
 }
 
 async fn health_handler(
@@ -165,6 +271,95 @@ async fn health_handler(
     state.health.handle().await
 }
 
+/// GET /dashboard — the metrics snapshot as HTML (`proxy.dashboard`).
+async fn dashboard_handler(State(state): State<RouterEngine>) -> Html<String> {
+    let config = state.backend_state.get_config();
+    Html(crate::proxy::dashboard::render(
+        &state.observability.snapshot(),
+        &config.backends,
+        &state.backend_state.get_active_backend(),
+    ))
+}
+
+/// Lines returned by /api/log-tail without a `lines` parameter.
+const DEFAULT_LOG_TAIL_LINES: usize = 100;
+
+/// GET /api/log-tail?lines=N — the debug log's most recent lines as
+/// `{"lines": [...]}`, oldest first, for tools without access to the log
+/// file. Behind the session token like the proxy routes.
+async fn log_tail_handler(
+    State(state): State<RouterEngine>,
+    RawQuery(query): RawQuery,
+) -> Json<serde_json::Value> {
+    let lines = query
+        .as_deref()
+        .unwrap_or("")
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("lines="))
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_LOG_TAIL_LINES);
+    Json(serde_json::json!({ "lines": state.debug_logger.tail(lines) }))
+}
+
+/// GET /v1/models — answered locally when the active backend maps model
+/// families, otherwise forwarded like any other request.
+async fn models_handler(
+    State(state): State<RouterEngine>,
+    query: RawQuery,
+    req: Request<Body>,
+) -> Response {
+    let listing = state
+        .backend_state
+        .get_active_backend_config()
+        .ok()
+        .and_then(|backend| model_list(&backend));
+    match listing {
+        Some(list) => Json(list).into_response(),
+        None => proxy_handler(State(state), query, req).await,
+    }
+}
+
+/// GET /teammate/{agent_id}/v1/models — [`models_handler`] for the
+/// backend that teammate's requests go to.
+async fn teammate_models_handler(
+    State(state): State<RouterEngine>,
+    Path(agent_id): Path<String>,
+    query: RawQuery,
+    req: Request<Body>,
+) -> Response {
+    let backend = teammate_backend_for(&state, Some(&agent_id))
+        .unwrap_or_else(|| state.backend_state.get_active_backend());
+    let listing = state
+        .backend_state
+        .get_backend_config(&backend)
+        .ok()
+        .and_then(|backend| model_list(&backend));
+    match listing {
+        Some(list) => Json(list).into_response(),
+        None => proxy_handler(State(state), query, req).await,
+    }
+}
+
+/// The backend for teammate `agent_id`: its registry entry, then its
+/// team's backend (`{name}@{team}`), then the current teammate backend.
+/// `None` means the active backend.
+fn teammate_backend_for(state: &RouterEngine, agent_id: Option<&str>) -> Option<String> {
+    let resolved = agent_id.and_then(|id| state.pipeline_config.agent_registry.lookup(id));
+
+    if let Some(backend) = resolved {
+        Some(backend)
+    } else if let Some(backend) = agent_id.and_then(|id| state.backend_state.team_backend_for(id)) {
+        Some(backend)
+    } else {
+        if let Some(id) = agent_id {
+            crate::metrics::app_log("router", &format!(
+                "Teammate '{}' not in registry, using current teammate backend", id
+            ));
+        }
+        state.teammate_backend.get()
+    }
+}
+
 async fn proxy_handler(
     State(state): State<RouterEngine>,
     RawQuery(query): RawQuery,
@@ -172,10 +367,21 @@ async fn proxy_handler(
 ) -> Response {
     use crate::proxy::pipeline::execute_pipeline;
 
-    let request_id = Uuid::new_v4().to_string();
+    let request_header = state.pipeline_config.request_id_header.clone();
+    let request_id = client_request_id(&req, &request_header)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
     let query_str = query.as_deref().unwrap_or("");
     crate::metrics::app_log("router", &format!("Incoming request: {} {} request_id={}", req.method(), req.uri().path(), request_id));
 
+    // Per-request backend override (`proxy.backend_header`). The header is
+    // removed so it never reaches the upstream.
+    let header_backend = state
+        .pipeline_config
+        .backend_header
+        .as_ref()
+        .and_then(|name| req.headers_mut().remove(name))
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).trim().to_string());
+
     // Determine if this is a teammate request and resolve backend override.
     // TeammateMarker: extract agent_id from first path segment → registry lookup.
     // The shim sets ANTHROPIC_BASE_URL=.../teammate/{agent_id}, so after axum
@@ -209,20 +415,7 @@ async fn proxy_handler(
             }
         }
 
-        // Registry lookup determines backend; fallback to teammate backend.
-        let resolved = candidate.as_ref()
-            .and_then(|id| state.pipeline_config.agent_registry.lookup(id));
-
-        if let Some(backend) = resolved {
-            Some(backend)
-        } else {
-            if let Some(id) = &candidate {
-                crate::metrics::app_log("router", &format!(
-                    "Teammate '{}' not in registry, using current teammate backend", id
-                ));
-            }
-            state.teammate_backend.get()
-        }
+        teammate_backend_for(&state, candidate.as_deref())
     } else {
         req.extensions()
             .get::<BackendOverride>()
@@ -236,8 +429,13 @@ async fn proxy_handler(
     let mut start = state
         .observability
         .start_request(request_id.clone(), &req, &active_backend);
+    if is_teammate {
+        start.span.set_origin(RequestOrigin::Teammate);
+    }
+    let sample_rate = state.debug_logger.config().detail_sample_rate;
+    start.span.set_detailed(sampled_for_detail(&request_id, sample_rate));
 
-    if state.debug_logger.level() != DebugLogLevel::Off {
+    if state.debug_logger.level() != DebugLogLevel::Off && start.span.is_detailed() {
         start.span.record_mut().request_meta = Some(RequestMeta {
             method: req.method().to_string(),
             path: req.uri().path().to_string(),
@@ -253,6 +451,29 @@ async fn proxy_handler(
 
     let backend_override = teammate_backend;
 
+    let plugin_override = match header_backend {
+        Some(backend) if !state.backend_state.validate_backend(&backend) => {
+            let header = state.pipeline_config.backend_header.as_ref().map(HeaderName::as_str);
+            let err = ProxyError::InvalidRequest(format!(
+                "Unknown backend '{}' in {} header",
+                backend,
+                header.unwrap_or_default()
+            ));
+            crate::metrics::app_log_error("router", &format!("Request rejected: request_id={}", request_id), &err.to_string());
+            state.observability.finish_error(start.span, Some(err.status_code().as_u16()));
+            let mut response = ErrorResponse::from_error(&err, &request_id);
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                response.headers_mut().insert(request_header, value);
+            }
+            return response;
+        }
+        Some(backend) => Some(RoutingOverride {
+            backend,
+            reason: "header override".into(),
+        }),
+        None => start.backend_override,
+    };
+
     let pipeline_config = state.pipeline_config.clone();
 
     let mut pipeline_ctx = PipelineContext::new(
@@ -261,11 +482,29 @@ async fn proxy_handler(
         state.debug_logger.clone(),
     );
 
-    match execute_pipeline(req, &pipeline_config, &mut pipeline_ctx, backend_override, start.backend_override).await {
+    let mut response = match execute_pipeline(req, &pipeline_config, &mut pipeline_ctx, backend_override, plugin_override).await {
         Ok(resp) => resp,
         Err(e) => {
             crate::metrics::app_log_error("router", &format!("Request failed: request_id={}", request_id), &format!("{} ({})", e, e.error_type()));
             ErrorResponse::from_error(&e, &request_id)
         }
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(request_header, value);
     }
+    response
+}
+
+/// Longest client-supplied correlation id reused as the request id.
+const MAX_CLIENT_REQUEST_ID_LEN: usize = 128;
+
+/// The client's correlation id from `header`, if it is present and sane:
+/// non-empty, at most [`MAX_CLIENT_REQUEST_ID_LEN`] chars, visible ASCII
+/// only (it goes into log lines and back into a response header).
+fn client_request_id(req: &Request<Body>, header: &HeaderName) -> Option<String> {
+    let id = req.headers().get(header)?.to_str().ok()?.trim();
+    let valid = !id.is_empty()
+        && id.len() <= MAX_CLIENT_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| id.to_string())
 }
@@ -1,13 +1,17 @@
 use std::future::IntoFuture;
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
 
-use crate::backend::{BackendState, AgentBackendState, AgentRegistry};
-use crate::config::ConfigStore;
-use crate::metrics::{DebugLogger, ObservabilityHub};
+use crate::backend::{BackendState, AgentBackendState, AgentRegistry, IdleRevert, IDLE_CHECK_INTERVAL};
+use crate::config::{ConfigStore, RequestSigner};
+use crate::metrics::{DebugLogger, ObservabilityHub, ObservabilityPlugin};
 use crate::proxy::connection::ConnectionCounter;
 use crate::proxy::pool::PoolConfig;
 use crate::proxy::router::{build_router, RouterEngine};
@@ -15,11 +19,31 @@ use crate::proxy::shutdown::ShutdownManager;
 use crate::proxy::thinking::TransformerRegistry;
 use crate::proxy::timeout::TimeoutConfig;
 
+/// How long shutdown waits for the debug logger to drain its queue.
+const LOG_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `bind_addr` prefix selecting a Unix domain socket (`unix:/path/to/sock`).
+pub const UNIX_BIND_PREFIX: &str = "unix:";
+
+/// Where a bound proxy listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+enum BoundListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
 pub struct ProxyServer {
     pub addr: SocketAddr,
     /// The bound listener, kept alive to prevent port race conditions.
-    /// Populated by try_bind(), consumed by run().
-    listener: Option<TcpListener>,
+    /// Populated by try_bind() or bind(), consumed by run().
+    listener: Option<BoundListener>,
     router: RouterEngine,
     shutdown: Arc<ShutdownManager>,
     backend_state: BackendState,
@@ -28,6 +52,7 @@ pub struct ProxyServer {
     observability: ObservabilityHub,
     debug_logger: Arc<DebugLogger>,
     transformer_registry: Arc<TransformerRegistry>,
+    idle_revert: Option<Arc<IdleRevert>>,
 }
 
 impl ProxyServer {
@@ -52,9 +77,19 @@ impl ProxyServer {
         let teammate_backend = AgentBackendState::new(teammate_initial);
         let agent_registry = AgentRegistry::new();
 
+        let idle_revert = (cfg.defaults.idle_revert_minutes > 0).then(|| {
+            let timeout = Duration::from_secs(u64::from(cfg.defaults.idle_revert_minutes) * 60);
+            Arc::new(IdleRevert::new(backend_state.clone(), timeout, Instant::now()))
+        });
+        let mut plugins: Vec<Arc<dyn ObservabilityPlugin>> = vec![debug_logger.clone()];
+        if let Some(idle_revert) = &idle_revert {
+            plugins.push(idle_revert.clone());
+        }
         let observability = ObservabilityHub::new(1000)
-            .with_plugins(vec![debug_logger.clone()]);
-        let transformer_registry = Arc::new(TransformerRegistry::new());
+            .with_routing_log_capacity(cfg.defaults.routing_log_capacity)
+            .with_plugins(plugins);
+        let transformer_registry =
+            Arc::new(TransformerRegistry::new().with_dry_run(cfg.proxy.thinking_dry_run));
         let router = RouterEngine::new(
             timeout_config,
             pool_config,
@@ -67,17 +102,25 @@ impl ProxyServer {
             transformer_registry.clone(),
             session_token,
         );
+        // Write out queued log lines before the process exits
+        let shutdown = Arc::new(ShutdownManager::new());
+        let logger = debug_logger.clone();
+        shutdown.on_shutdown(move || {
+            logger.flush(LOG_FLUSH_TIMEOUT);
+        });
+
         Ok(Self {
             addr: SocketAddr::from(([127, 0, 0, 1], 0)),
             listener: None,
             router,
-            shutdown: Arc::new(ShutdownManager::new()),
+            shutdown,
             backend_state,
             subagent_backend,
             teammate_backend,
             observability,
             debug_logger,
             transformer_registry,
+            idle_revert,
         })
     }
 
@@ -86,10 +129,21 @@ impl ProxyServer {
     ///
     /// The listener is kept alive to prevent port race conditions - another process
     /// cannot claim the port between try_bind() and run().
+    ///
+    /// TCP only: Claude Code reaches the proxy through an `http://` base URL,
+    /// so a `unix:` bind address is rejected here; see [`bind`](Self::bind).
     pub async fn try_bind(&mut self, config: &ConfigStore) -> Result<(SocketAddr, String), Box<dyn std::error::Error>> {
         let bind_addr_str = config.get().proxy.bind_addr.clone();
         let base_url_template = config.get().proxy.base_url.clone();
 
+        if bind_addr_str.starts_with(UNIX_BIND_PREFIX) {
+            return Err(format!(
+                "bind_addr '{}' is a Unix socket, but Claude Code can only reach the proxy over TCP",
+                bind_addr_str
+            )
+            .into());
+        }
+
         // Parse the configured bind address to get the starting port
         let bind_addr: SocketAddr = bind_addr_str.parse()
             .map_err(|e| format!("Invalid bind address '{}': {}", bind_addr_str, e))?;
@@ -114,7 +168,7 @@ impl ProxyServer {
 
                     self.addr = actual_addr;
                     // Keep listener alive to prevent race conditions
-                    self.listener = Some(listener);
+                    self.listener = Some(BoundListener::Tcp(listener));
                     crate::metrics::app_log("proxy", &format!("Proxy bound to {} (base_url: {})", actual_addr, actual_base_url));
                     return Ok((actual_addr, actual_base_url));
                 }
@@ -128,6 +182,45 @@ impl ProxyServer {
         Err(format!("Could not find available port in range {}-{}", start_port, start_port + 100).into())
     }
 
+    /// Bind to the configured `bind_addr`: a TCP address (with the same
+    /// port fallback as [`try_bind`](Self::try_bind)), or on Unix a
+    /// `unix:/path` socket, for local clients that talk to the proxy
+    /// directly. A stale socket file at the path is replaced, and the file
+    /// is removed again on shutdown.
+    pub async fn bind(&mut self, config: &ConfigStore) -> Result<ProxyAddr, Box<dyn std::error::Error>> {
+        let bind_addr = config.get().proxy.bind_addr.clone();
+        match bind_addr.strip_prefix(UNIX_BIND_PREFIX) {
+            #[cfg(unix)]
+            Some(path) => {
+                let path = PathBuf::from(path);
+                self.bind_unix(&path)?;
+                Ok(ProxyAddr::Unix(path))
+            }
+            #[cfg(not(unix))]
+            Some(_) => Err(format!("bind_addr '{}': Unix sockets are not supported on this platform", bind_addr).into()),
+            None => Ok(ProxyAddr::Tcp(self.try_bind(config).await?.0)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(&mut self, path: &Path) -> std::io::Result<()> {
+        use std::os::unix::fs::FileTypeExt;
+
+        // Only clear out a leftover socket, never a regular file.
+        if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        crate::metrics::app_log("proxy", &format!("Proxy bound to unix socket {}", path.display()));
+
+        let socket = path.to_path_buf();
+        self.shutdown.on_shutdown(move || {
+            let _ = std::fs::remove_file(socket);
+        });
+        self.listener = Some(BoundListener::Unix(listener));
+        Ok(())
+    }
+
     pub fn backend_state(&self) -> BackendState {
         self.backend_state.clone()
     }
@@ -156,6 +249,17 @@ impl ProxyServer {
         self.transformer_registry.clone()
     }
 
+    /// Compute auth headers for every request to `backend` with `signer`;
+    /// the backend needs `auth_type = "signed"`.
+    pub fn set_signer(&mut self, backend: impl Into<String>, signer: Arc<dyn RequestSigner>) {
+        self.router.set_signer(backend.into(), signer);
+    }
+
+    /// The proxy's routes, for serving from a listener owned elsewhere.
+    pub fn router(&self) -> axum::Router {
+        build_router(self.router.clone())
+    }
+
     pub fn handle(&self) -> ProxyHandle {
         ProxyHandle {
             shutdown: self.shutdown.clone(),
@@ -165,25 +269,57 @@ impl ProxyServer {
     /// Run the proxy server.
     ///
     /// Consumes self to take ownership of the pre-bound listener.
-    /// Call try_bind() before run() to bind to an available port.
+    /// Call try_bind() or bind() before run().
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         let listener = self.listener
             .ok_or("try_bind() must be called before run()")?;
 
-        crate::metrics::app_log("proxy", &format!("Starting proxy server on {}", self.addr));
-
         let app = build_router(self.router.clone());
         let make_service = app.into_make_service();
         let make_service = ConnectionCounter::new(make_service, self.shutdown.clone());
 
-        let shutdown = self.shutdown.clone();
-        axum::serve(listener, make_service)
-            .with_graceful_shutdown(async move {
-                let _ = shutdown.wait_for_shutdown().await;
+        if self.backend_state.get_config().defaults.warmup_connections {
+            let engine = self.router.clone();
+            tokio::spawn(async move {
+                engine.warm_up().await;
+            });
+        }
+
+        let idle_check = self.idle_revert.clone().map(|idle_revert| {
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    idle_revert.check(Instant::now());
+                }
             })
-            .into_future()
-            .await?;
+        });
 
+        let shutdown = self.shutdown.clone();
+        let signal = async move {
+            let _ = shutdown.wait_for_shutdown().await;
+        };
+        match listener {
+            BoundListener::Tcp(listener) => {
+                crate::metrics::app_log("proxy", &format!("Starting proxy server on {}", self.addr));
+                axum::serve(listener, make_service)
+                    .with_graceful_shutdown(signal)
+                    .into_future()
+                    .await?;
+            }
+            #[cfg(unix)]
+            BoundListener::Unix(listener) => {
+                crate::metrics::app_log("proxy", "Starting proxy server on unix socket");
+                axum::serve(listener, make_service)
+                    .with_graceful_shutdown(signal)
+                    .into_future()
+                    .await?;
+            }
+        }
+
+        if let Some(idle_check) = idle_check {
+            idle_check.abort();
+        }
         self.shutdown.wait_for_connections(Duration::from_secs(10)).await;
         crate::metrics::app_log("proxy", "Shutting down gracefully");
 
@@ -2,14 +2,19 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
+use parking_lot::Mutex;
 use tokio::sync::Notify;
 
 use crate::metrics::app_log;
 
+/// Work to run once the server has stopped, e.g. flushing logs.
+pub type ShutdownCallback = Box<dyn FnOnce() + Send>;
+
 pub struct ShutdownManager {
     shutdown: Arc<AtomicBool>,
     active_connections: Arc<AtomicUsize>,
     notify: Arc<Notify>,
+    callbacks: Mutex<Vec<ShutdownCallback>>,
 }
 
 impl ShutdownManager {
@@ -18,9 +23,17 @@ impl ShutdownManager {
             shutdown: Arc::new(AtomicBool::new(false)),
             active_connections: Arc::new(AtomicUsize::new(0)),
             notify: Arc::new(Notify::new()),
+            callbacks: Mutex::new(Vec::new()),
         }
     }
 
+    /// Register `callback` to run after connections have drained (or the
+    /// drain timed out), in registration order. Each runs exactly once, on
+    /// the blocking pool, so it may block (e.g. on file I/O).
+    pub fn on_shutdown(&self, callback: impl FnOnce() + Send + 'static) {
+        self.callbacks.lock().push(Box::new(callback));
+    }
+
     pub async fn wait_for_shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
         if self.is_shutting_down() {
             return Ok(());
@@ -72,17 +85,35 @@ impl ShutdownManager {
 
         let start = tokio::time::Instant::now();
 
-        while start.elapsed() < timeout {
+        loop {
             let active = self.active_connections.load(Ordering::SeqCst);
             if active == 0 {
                 app_log("proxy-shutdown", "Server stopped");
-                return;
+                break;
+            }
+            if start.elapsed() >= timeout {
+                app_log("proxy-shutdown", &format!("Forced shutdown after timeout ({} connections remain)", active));
+                break;
             }
             tokio::time::sleep(Duration::from_millis(100)).await;
         }
 
-        let active = self.active_connections.load(Ordering::SeqCst);
-        app_log("proxy-shutdown", &format!("Forced shutdown after timeout ({} connections remain)", active));
+        self.run_callbacks().await;
+    }
+
+    async fn run_callbacks(&self) {
+        let callbacks = std::mem::take(&mut *self.callbacks.lock());
+        if callbacks.is_empty() {
+            return;
+        }
+        let run = tokio::task::spawn_blocking(move || {
+            for callback in callbacks {
+                callback();
+            }
+        });
+        if run.await.is_err() {
+            app_log("proxy-shutdown", "A shutdown callback panicked");
+        }
     }
 }
 
@@ -0,0 +1,268 @@
+//! Convert responses between Anthropic's streaming (SSE) and non-streaming
+//! (JSON) shapes, for backends that ignore the request's `stream` flag.
+//!
+//! Enabled per backend with `match_client_stream`: a streamed response to a
+//! non-streaming request is folded into one `message` body, and a JSON
+//! response to a streaming request is replayed as a minimal event sequence.
+//!
+//! [`buffer_sse`] re-frames a whole stream for `force_buffer` backends.
+
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+
+use crate::metrics::SSE_KEEPALIVE;
+use crate::sse::{parse_sse_payloads, split_sse_frames};
+
+/// Most bytes a `force_buffer` response is held in memory for. Past this
+/// the response is abandoned with an SSE `error` event.
+pub const FORCE_BUFFER_LIMIT: usize = 32 * 1024 * 1024;
+
+enum Buffering<S> {
+    Reading { upstream: S, body: Vec<u8>, last_beat: Instant },
+    Replaying(std::vec::IntoIter<Vec<u8>>),
+    Done,
+}
+
+/// Read `upstream` to the end, then replay it one SSE event per chunk.
+///
+/// While reading, [`SSE_KEEPALIVE`] goes out at most once per `beat` for
+/// as long as upstream data keeps arriving, so the client and the idle
+/// timeout both see progress; outright silence is left to the caller's
+/// keep-alive and idle handling. A response that grows past `limit` bytes
+/// is dropped and answered with an `error` event instead. Upstream errors
+/// pass through and end the stream.
+pub fn buffer_sse<S, E>(
+    upstream: S,
+    backend: String,
+    limit: usize,
+    beat: Duration,
+) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    let start = Buffering::Reading {
+        upstream,
+        body: Vec::new(),
+        last_beat: Instant::now(),
+    };
+    futures::stream::unfold(start, move |state| {
+        let backend = backend.clone();
+        async move {
+            match state {
+                Buffering::Reading {
+                    mut upstream,
+                    mut body,
+                    mut last_beat,
+                } => loop {
+                    match upstream.next().await {
+                        Some(Ok(chunk)) => {
+                            if body.len() + chunk.len() > limit {
+                                crate::metrics::app_log(
+                                    "upstream",
+                                    &format!(
+                                        "Buffered SSE response from backend '{}' exceeded {} bytes; sending error event",
+                                        backend, limit
+                                    ),
+                                );
+                                return Some((Ok(overflow_event(limit)), Buffering::Done));
+                            }
+                            body.extend_from_slice(&chunk);
+                            if last_beat.elapsed() >= beat {
+                                last_beat = Instant::now();
+                                let beat_chunk = Bytes::from_static(SSE_KEEPALIVE);
+                                return Some((Ok(beat_chunk), Buffering::Reading { upstream, body, last_beat }));
+                            }
+                        }
+                        Some(Err(err)) => return Some((Err(err), Buffering::Done)),
+                        None => {
+                            let frames = split_sse_frames(&body);
+                            crate::metrics::app_log(
+                                "upstream",
+                                &format!(
+                                    "Buffered SSE response from backend '{}': {} bytes, {} events",
+                                    backend,
+                                    body.len(),
+                                    frames.len()
+                                ),
+                            );
+                            return next_frame(frames.into_iter());
+                        }
+                    }
+                },
+                Buffering::Replaying(frames) => next_frame(frames),
+                Buffering::Done => None,
+            }
+        }
+    })
+}
+
+fn next_frame<S, E>(
+    mut frames: std::vec::IntoIter<Vec<u8>>,
+) -> Option<(Result<Bytes, E>, Buffering<S>)> {
+    let frame = frames.next()?;
+    Some((Ok(Bytes::from(frame)), Buffering::Replaying(frames)))
+}
+
+fn overflow_event(limit: usize) -> Bytes {
+    let data = json!({
+        "type": "error",
+        "error": {
+            "type": "api_error",
+            "message": format!("Buffered upstream response exceeded {} bytes", limit),
+        },
+    });
+    Bytes::from(format!("event: error\ndata: {}\n\n", data))
+}
+
+/// Fold an Anthropic SSE stream into the equivalent `message` body.
+///
+/// Returns `None` when the stream has no `message_start`, i.e. it isn't an
+/// Anthropic message stream, so the caller can pass it through unchanged.
+pub fn sse_to_json(body: &[u8]) -> Option<Bytes> {
+    let mut message: Option<Value> = None;
+    let mut blocks: Vec<Value> = Vec::new();
+    let mut partial_json: Vec<String> = Vec::new();
+
+    for payload in parse_sse_payloads(body) {
+        let index = payload["index"].as_u64().map(|i| i as usize);
+        match payload["type"].as_str() {
+            Some("message_start") => message = Some(payload["message"].clone()),
+            Some("content_block_start") => {
+                let index = index.unwrap_or(blocks.len());
+                if blocks.len() <= index {
+                    blocks.resize(index + 1, Value::Null);
+                    partial_json.resize(index + 1, String::new());
+                }
+                blocks[index] = payload["content_block"].clone();
+            }
+            Some("content_block_delta") => {
+                let Some(index) = index.filter(|&i| i < blocks.len()) else {
+                    continue;
+                };
+                let delta = &payload["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => append_str(&mut blocks[index], "text", &delta["text"]),
+                    Some("thinking_delta") => {
+                        append_str(&mut blocks[index], "thinking", &delta["thinking"])
+                    }
+                    Some("signature_delta") => {
+                        append_str(&mut blocks[index], "signature", &delta["signature"])
+                    }
+                    Some("input_json_delta") => {
+                        partial_json[index].push_str(delta["partial_json"].as_str().unwrap_or(""))
+                    }
+                    _ => {}
+                }
+            }
+            Some("message_delta") => {
+                let Some(message) = message.as_mut() else {
+                    continue;
+                };
+                if let Some(delta) = payload["delta"].as_object() {
+                    for (key, value) in delta {
+                        message[key] = value.clone();
+                    }
+                }
+                if let Some(usage) = payload["usage"].as_object() {
+                    for (key, value) in usage {
+                        message["usage"][key] = value.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut message = message?;
+    for (block, input) in blocks.iter_mut().zip(&partial_json) {
+        if let Ok(parsed) = serde_json::from_str::<Value>(input) {
+            block["input"] = parsed;
+        }
+    }
+    blocks.retain(|block| !block.is_null());
+    message["content"] = Value::Array(blocks);
+    serde_json::to_vec(&message).ok().map(Bytes::from)
+}
+
+/// Replay a non-streaming `message` body as the minimal Anthropic event
+/// sequence: one start/delta/stop per content block.
+///
+/// Returns `None` when the body isn't a `message` object (e.g. an error).
+pub fn json_to_sse(body: &[u8]) -> Option<Bytes> {
+    let message: Value = serde_json::from_slice(body).ok()?;
+    if message["type"] != "message" {
+        return None;
+    }
+
+    let mut out = String::new();
+    let mut start = message.clone();
+    start["content"] = json!([]);
+    start["stop_reason"] = Value::Null;
+    start["stop_sequence"] = Value::Null;
+    push_event(&mut out, "message_start", json!({"type": "message_start", "message": start}));
+
+    let content = message["content"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for (index, block) in content.iter().enumerate() {
+        let (empty, deltas) = match block["type"].as_str() {
+            Some("text") => (
+                json!({"type": "text", "text": ""}),
+                vec![json!({"type": "text_delta", "text": block["text"]})],
+            ),
+            Some("thinking") => (
+                json!({"type": "thinking", "thinking": ""}),
+                vec![
+                    json!({"type": "thinking_delta", "thinking": block["thinking"]}),
+                    json!({"type": "signature_delta", "signature": block["signature"]}),
+                ],
+            ),
+            Some("tool_use") => (
+                json!({"type": "tool_use", "id": block["id"], "name": block["name"], "input": {}}),
+                vec![json!({"type": "input_json_delta", "partial_json": block["input"].to_string()})],
+            ),
+            // Anything else (e.g. redacted_thinking) arrives whole in its start event.
+            _ => (block.clone(), Vec::new()),
+        };
+        push_event(
+            &mut out,
+            "content_block_start",
+            json!({"type": "content_block_start", "index": index, "content_block": empty}),
+        );
+        for delta in deltas {
+            push_event(
+                &mut out,
+                "content_block_delta",
+                json!({"type": "content_block_delta", "index": index, "delta": delta}),
+            );
+        }
+        push_event(
+            &mut out,
+            "content_block_stop",
+            json!({"type": "content_block_stop", "index": index}),
+        );
+    }
+
+    push_event(
+        &mut out,
+        "message_delta",
+        json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": message["stop_reason"], "stop_sequence": message["stop_sequence"]},
+            "usage": {"output_tokens": message["usage"]["output_tokens"]},
+        }),
+    );
+    push_event(&mut out, "message_stop", json!({"type": "message_stop"}));
+    Some(Bytes::from(out))
+}
+
+fn append_str(block: &mut Value, field: &str, value: &Value) {
+    let mut text = block[field].as_str().unwrap_or("").to_string();
+    text.push_str(value.as_str().unwrap_or(""));
+    block[field] = Value::String(text);
+}
+
+fn push_event(out: &mut String, event: &str, data: Value) {
+    out.push_str(&format!("event: {}\ndata: {}\n\n", event, data));
+}
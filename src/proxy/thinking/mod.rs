@@ -11,13 +11,19 @@
 //! - **ThinkingSession**: Per-request handle for the thinking lifecycle
 
 mod registry;
-pub use registry::{fast_hash, safe_suffix, safe_truncate, BlockInfo, CacheStats, ThinkingRegistry};
+pub use registry::{
+    block_hash, fast_hash, normalize_thinking_blocks, safe_suffix, safe_truncate,
+    strip_thinking_signatures, BlockInfo, BlockKind, CacheStats, FilterOutcome,
+    SseBlockAccumulator, ThinkingRegistry,
+};
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use parking_lot::Mutex;
 
-use crate::metrics::DebugLogger;
+use crate::metrics::{DebugLogger, EventObserver, ThinkingFilterCounters};
+use crate::sse::{analyze_thinking_stream, SseEvent};
 
 /// Thread-safe wrapper around ThinkingRegistry.
 ///
@@ -26,6 +32,11 @@ use crate::metrics::DebugLogger;
 pub struct TransformerRegistry {
     /// Registry for tracking thinking blocks by session
     thinking_registry: Mutex<ThinkingRegistry>,
+    /// Sessions report thinking transforms without applying them
+    dry_run: bool,
+    /// Backends seen returning thinking blocks without signatures since the
+    /// last backend switch
+    unsigned_backends: Mutex<HashSet<String>>,
 }
 
 impl TransformerRegistry {
@@ -34,9 +45,21 @@ impl TransformerRegistry {
         crate::metrics::app_log("thinking", "Creating TransformerRegistry");
         Self {
             thinking_registry: Mutex::new(ThinkingRegistry::new()),
+            dry_run: false,
+            unsigned_backends: Mutex::new(HashSet::new()),
         }
     }
 
+    /// Make every session dry-run: thinking transforms are computed and
+    /// logged, but the request keeps its original thinking content.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        if dry_run {
+            crate::metrics::app_log("thinking", "Thinking transforms in dry-run mode");
+        }
+        self.dry_run = dry_run;
+        self
+    }
+
     /// Begin a new request's thinking lifecycle.
     ///
     /// Atomically notifies the registry about the current backend
@@ -53,22 +76,58 @@ impl TransformerRegistry {
         debug_logger: Arc<DebugLogger>,
     ) -> ThinkingSession {
         let mut reg = self.thinking_registry.lock();
-        reg.on_backend_switch(backend);
-        let session_id = reg.current_session();
+        let session_id = self.switch_backend(&mut reg, backend);
         ThinkingSession {
             registry: Arc::clone(self),
             session_id,
+            backend: Arc::from(backend),
             debug_logger,
+            dry_run: self.dry_run,
         }
     }
 
+    /// Record that `backend` returned a completed thinking block without a
+    /// signature. Until the next backend switch its requests have thinking
+    /// signatures stripped, whatever the backend's thinking settings say.
+    pub fn mark_unsigned_thinking(&self, backend: &str) {
+        if self.unsigned_backends.lock().insert(backend.to_string()) {
+            crate::metrics::app_log(
+                "thinking",
+                &format!(
+                    "Backend '{}' returns thinking without signatures; \
+                     dropping thinking signatures from its requests",
+                    backend
+                ),
+            );
+        }
+    }
+
+    /// Whether `backend` was seen returning unsigned thinking.
+    pub fn returns_unsigned_thinking(&self, backend: &str) -> bool {
+        self.unsigned_backends.lock().contains(backend)
+    }
+
     /// Notify about a backend switch (e.g. from IPC command).
     ///
     /// Increments the thinking session if the backend changed,
     /// invalidating blocks from the previous backend.
     pub fn notify_backend_switch(&self, backend: &str) {
         let mut reg = self.thinking_registry.lock();
+        self.switch_backend(&mut reg, backend);
+    }
+
+    /// Move `reg` to `backend` and return the current session. A switch
+    /// also forgets which backends returned unsigned thinking: the backend
+    /// switched to may have been reconfigured since, so it is observed
+    /// afresh.
+    fn switch_backend(&self, reg: &mut ThinkingRegistry, backend: &str) -> u64 {
+        let before = reg.current_session();
         reg.on_backend_switch(backend);
+        let session_id = reg.current_session();
+        if session_id != before {
+            self.unsigned_backends.lock().clear();
+        }
+        session_id
     }
 
     /// Get cache statistics for monitoring.
@@ -104,7 +163,96 @@ impl std::fmt::Debug for TransformerRegistry {
 pub struct ThinkingSession {
     registry: Arc<TransformerRegistry>,
     session_id: u64,
+    backend: Arc<str>,
     debug_logger: Arc<DebugLogger>,
+    dry_run: bool,
+}
+
+/// Incremental SSE registration for one streaming response.
+///
+/// Created by [`ThinkingSession::sse_registration`]. Fed the stream's SSE
+/// events as they are decoded, it accumulates thinking deltas per block
+/// index and registers every completed block under the session captured at
+/// request time.
+pub struct SseRegistration {
+    session: ThinkingSession,
+    blocks: SseBlockAccumulator,
+    events: usize,
+    registered: usize,
+    /// Thinking blocks started, and whether any carried a signature.
+    thinking_blocks: usize,
+    signed: bool,
+}
+
+impl SseRegistration {
+    /// Register blocks cut off before their stop, once the stream is over.
+    pub fn finish(&mut self) {
+        // Only completed blocks count: a signature arrives just before the
+        // stop, so a block cut off early proves nothing.
+        if self.registered > 0 && self.thinking_blocks > 0 && !self.signed {
+            self.session.registry.mark_unsigned_thinking(&self.session.backend);
+        }
+        let leftovers = self.blocks.finish();
+        self.register(leftovers);
+
+        self.session.debug_logger.log_auxiliary(
+            "sse_callback",
+            None,
+            None,
+            Some(&format!(
+                "SSE stream: {} events, registered {} thinking blocks",
+                self.events, self.registered,
+            )),
+            None,
+        );
+    }
+
+    /// Feed the next decoded events of the stream.
+    pub fn feed(&mut self, events: &[SseEvent]) {
+        self.events += events.len();
+        // Block and signature counts need no cross-chunk state, so each
+        // batch can be analyzed on its own.
+        let stats = analyze_thinking_stream(events);
+        self.thinking_blocks += stats.thinking_blocks;
+        self.signed |= stats.has_signatures;
+        let completed: Vec<_> = events.iter().filter_map(|e| self.blocks.feed(e)).collect();
+        self.register(completed);
+    }
+
+    fn register(&mut self, blocks: Vec<(BlockKind, String)>) {
+        if blocks.is_empty() {
+            return;
+        }
+        let mut reg = self.session.registry.thinking_registry.lock();
+        for (kind, content) in &blocks {
+            reg.register_block(*kind, content, self.session.session_id);
+        }
+        self.registered += blocks.len();
+    }
+}
+
+impl EventObserver for SseRegistration {
+    fn on_events(&mut self, events: &[SseEvent]) {
+        self.feed(events);
+    }
+
+    fn on_finish(&mut self) {
+        self.finish();
+    }
+}
+
+impl From<FilterOutcome> for ThinkingFilterCounters {
+    /// One filter pass, counted as one request.
+    fn from(outcome: FilterOutcome) -> Self {
+        Self {
+            requests: 1,
+            confirmed: outcome.confirmed.into(),
+            old_session_removed: outcome.old_session_removed.into(),
+            unused_removed: outcome.unused_removed.into(),
+            orphaned_removed: outcome.orphaned_removed.into(),
+            filtered: outcome.filtered.into(),
+        }
+    }
 }
 
 impl std::fmt::Debug for ThinkingSession {
@@ -116,13 +264,34 @@ impl std::fmt::Debug for ThinkingSession {
 }
 
 impl ThinkingSession {
+    /// Whether thinking transforms for this request are report-only.
+    ///
+    /// The registry still confirms and evicts blocks as usual, so the
+    /// would-be counts match what a real run reports.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Whether this request's backend returns unsigned thinking, so its
+    /// thinking signatures should be dropped.
+    pub fn drops_signatures(&self) -> bool {
+        self.registry.returns_unsigned_thinking(&self.backend)
+    }
+
     /// Filter invalid thinking blocks from a request body.
     ///
     /// Returns the number of blocks removed.
     pub fn filter(&self, body: &mut serde_json::Value) -> u32 {
+        self.filter_with_outcome(body).filtered
+    }
+
+    /// Filter invalid thinking blocks, reporting every decision made.
+    pub fn filter_with_outcome(&self, body: &mut serde_json::Value) -> FilterOutcome {
         let mut reg = self.registry.thinking_registry.lock();
         let cache_size = reg.cache_stats().total;
-        let filtered = reg.filter_request(body);
+        let outcome = reg.filter_request_outcome(body);
+        drop(reg);
+        let filtered = outcome.filtered;
         if filtered > 0 || cache_size > 0 {
             self.debug_logger.log_auxiliary(
                 "thinking_filter",
@@ -135,12 +304,15 @@ impl ThinkingSession {
                 None,
             );
         }
-        filtered
+        outcome
     }
 
     /// Register thinking blocks from a completed SSE stream.
     pub fn register_from_sse(&self, events: &[crate::sse::SseEvent]) {
-        let thinking_stats = crate::sse::analyze_thinking_stream(events);
+        let thinking_stats = analyze_thinking_stream(events);
+        if thinking_stats.thinking_stops > 0 && !thinking_stats.has_signatures {
+            self.registry.mark_unsigned_thinking(&self.backend);
+        }
         self.debug_logger.log_auxiliary(
             "sse_callback",
             None,
@@ -171,6 +343,22 @@ impl ThinkingSession {
         );
     }
 
+    /// Start incremental registration for a streaming response.
+    ///
+    /// The returned [`SseRegistration`] is fed events as they arrive and
+    /// registers each block as soon as it completes, so registration neither
+    /// waits for the end of a long stream nor gets lost on client disconnect.
+    pub fn sse_registration(&self) -> SseRegistration {
+        SseRegistration {
+            session: self.clone(),
+            blocks: SseBlockAccumulator::new(),
+            events: 0,
+            registered: 0,
+            thinking_blocks: 0,
+            signed: false,
+        }
+    }
+
     /// Register thinking blocks from a non-streaming response body.
     pub fn register_from_response(&self, response_body: &[u8]) {
         if response_body.is_empty() {
@@ -36,9 +36,29 @@ pub struct BlockInfo {
     registered_at: Instant,
 }
 
+/// The kind of a thinking block — part of its registry identity, so a
+/// `thinking` text and a `redacted_thinking` payload with the same string
+/// never share a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockKind {
+    Thinking,
+    Redacted,
+}
+
+impl BlockKind {
+    /// Namespace prefix fed to the hasher ahead of the content.
+    fn tag(self) -> &'static str {
+        match self {
+            BlockKind::Thinking => "t:",
+            BlockKind::Redacted => "r:",
+        }
+    }
+}
+
 /// Registry for tracking thinking blocks across backend switches.
 ///
-/// Each thinking block is identified by a hash of its content (prefix + length).
+/// Each thinking block is identified by a hash of its kind and content
+/// (see [`block_hash`]).
 /// When a backend switch occurs, the session ID increments, invalidating
 /// all previous thinking blocks.
 #[derive(Debug)]
@@ -112,8 +132,8 @@ impl ThinkingRegistry {
 
         if let Some(content) = json.get("content").and_then(|c| c.as_array()) {
             for item in content {
-                if let Some(thinking) = extract_thinking_content(item) {
-                    self.register_block(&thinking, session_id);
+                if let Some((kind, thinking)) = extract_thinking_content(item) {
+                    self.register_block(kind, &thinking, session_id);
                 }
             }
         }
@@ -134,78 +154,22 @@ impl ThinkingRegistry {
     /// The session_id should be captured at request time to avoid races with
     /// concurrent backend switches.
     pub fn register_from_sse_stream(&mut self, events: &[crate::sse::SseEvent], session_id: u64) {
-        let mut accumulators: HashMap<u64, String> = HashMap::new();
-
+        let mut accumulator = SseBlockAccumulator::new();
         for event in events {
-            match event.event_type.as_str() {
-                "content_block_start" => {
-                    let Some(block) = event.data.get("content_block") else {
-                        continue;
-                    };
-                    let block_type = block.get("type").and_then(|t| t.as_str());
-                    match block_type {
-                        Some("thinking") => {
-                            if let Some(index) = event.data.get("index").and_then(|i| i.as_u64()) {
-                                let initial = block
-                                    .get("thinking")
-                                    .and_then(|t| t.as_str())
-                                    .unwrap_or("");
-                                accumulators.insert(index, initial.to_string());
-                            }
-                        }
-                        Some("redacted_thinking") => {
-                            if let Some(data) = block.get("data").and_then(|d| d.as_str()) {
-                                self.register_block(data, session_id);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                "content_block_delta" => {
-                    if let Some(delta) = event.data.get("delta") {
-                        if delta.get("type").and_then(|t| t.as_str()) == Some("thinking_delta") {
-                            if let Some(index) = event.data.get("index").and_then(|i| i.as_u64()) {
-                                if let Some(thinking) =
-                                    delta.get("thinking").and_then(|t| t.as_str())
-                                {
-                                    if let Some(acc) = accumulators.get_mut(&index) {
-                                        acc.push_str(thinking);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                "content_block_stop" => {
-                    if let Some(index) = event.data.get("index").and_then(|i| i.as_u64()) {
-                        if let Some(accumulated) = accumulators.remove(&index) {
-                            if !accumulated.is_empty() {
-                                crate::metrics::app_log("thinking-registry", &format!(
-                                    "SSE: registering complete thinking block index={} len={}", index, accumulated.len()
-                                ));
-                                self.register_block(&accumulated, session_id);
-                            }
-                        }
-                    }
-                }
-                _ => {}
+            if let Some((kind, content)) = accumulator.feed(event) {
+                self.register_block(kind, &content, session_id);
             }
         }
 
         // Register any remaining accumulators (stream may have been truncated)
-        for (index, accumulated) in accumulators {
-            if !accumulated.is_empty() {
-                crate::metrics::app_log("thinking-registry", &format!(
-                    "SSE: registering thinking block without content_block_stop index={} len={}", index, accumulated.len()
-                ));
-                self.register_block(&accumulated, session_id);
-            }
+        for (kind, content) in accumulator.finish() {
+            self.register_block(kind, &content, session_id);
         }
     }
 
     /// Register a single thinking block under the given session ID.
-    fn register_block(&mut self, content: &str, session_id: u64) {
-        let hash = fast_hash(content);
+    pub fn register_block(&mut self, kind: BlockKind, content: &str, session_id: u64) {
+        let hash = block_hash(kind, content);
         let now = Instant::now();
 
         // Check if already registered
@@ -242,6 +206,13 @@ impl ThinkingRegistry {
     ///
     /// Returns the number of blocks removed from the request.
     pub fn filter_request(&mut self, body: &mut Value) -> u32 {
+        self.filter_request_outcome(body).filtered
+    }
+
+    /// Same as [`filter_request`](Self::filter_request), but reports every
+    /// decision the pass made: confirmations, each cleanup rule, and the
+    /// blocks stripped from the body.
+    pub fn filter_request_outcome(&mut self, body: &mut Value) -> FilterOutcome {
         let now = Instant::now();
 
         // Step 1: Extract all thinking block hashes from request
@@ -285,7 +256,13 @@ impl ThinkingRegistry {
             ));
         }
 
-        filtered_count
+        FilterOutcome {
+            confirmed: confirmed_count,
+            old_session_removed: cleanup_stats.old_session,
+            unused_removed: cleanup_stats.confirmed_unused,
+            orphaned_removed: cleanup_stats.orphaned,
+            filtered: filtered_count,
+        }
     }
 
     /// Extract all thinking block hashes from a request body.
@@ -302,8 +279,8 @@ impl ThinkingRegistry {
             };
 
             for item in content {
-                if let Some(thinking) = extract_thinking_content(item) {
-                    hashes.insert(fast_hash(&thinking));
+                if let Some((kind, thinking)) = extract_thinking_content(item) {
+                    hashes.insert(block_hash(kind, &thinking));
                 }
             }
         }
@@ -430,12 +407,12 @@ impl ThinkingRegistry {
                 }
 
                 // Extract content and compute hash
-                let Some(thinking) = extract_thinking_content(item) else {
+                let Some((kind, thinking)) = extract_thinking_content(item) else {
                     crate::metrics::app_log("thinking-registry", "Removing thinking block: failed to extract content");
                     return false;
                 };
 
-                let hash = fast_hash(&thinking);
+                let hash = block_hash(kind, &thinking);
 
                 // Check if block is in cache (implies valid session)
                 if self.blocks.contains_key(&hash) {
@@ -513,6 +490,88 @@ impl ThinkingRegistry {
     }
 }
 
+/// Per-stream accumulator that turns SSE events into complete thinking blocks.
+///
+/// Holds the partial `thinking_delta` text for every open block index, so a
+/// stream can be fed one event at a time as it arrives. Used both by the batch
+/// [`ThinkingRegistry::register_from_sse_stream`] path and by incremental
+/// registration while a response is still streaming.
+#[derive(Debug, Default)]
+pub struct SseBlockAccumulator {
+    accumulators: HashMap<u64, String>,
+}
+
+impl SseBlockAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one event. Returns a block once it is complete: redacted blocks on
+    /// `content_block_start`, thinking blocks on their `content_block_stop`.
+    pub fn feed(&mut self, event: &crate::sse::SseEvent) -> Option<(BlockKind, String)> {
+        let index = event.data.get("index").and_then(|i| i.as_u64());
+        match event.event_type.as_str() {
+            "content_block_start" => {
+                let block = event.data.get("content_block")?;
+                match block.get("type").and_then(|t| t.as_str()) {
+                    Some("thinking") => {
+                        let initial = block
+                            .get("thinking")
+                            .and_then(|t| t.as_str())
+                            .unwrap_or("");
+                        self.accumulators.insert(index?, initial.to_string());
+                        None
+                    }
+                    Some("redacted_thinking") => {
+                        let data = block.get("data").and_then(|d| d.as_str())?;
+                        Some((BlockKind::Redacted, data.to_string()))
+                    }
+                    _ => None,
+                }
+            }
+            "content_block_delta" => {
+                let delta = event.data.get("delta")?;
+                if delta.get("type").and_then(|t| t.as_str()) == Some("thinking_delta") {
+                    if let Some(thinking) = delta.get("thinking").and_then(|t| t.as_str()) {
+                        if let Some(acc) = self.accumulators.get_mut(&index?) {
+                            acc.push_str(thinking);
+                        }
+                    }
+                }
+                None
+            }
+            "content_block_stop" => {
+                let index = index?;
+                let accumulated = self.accumulators.remove(&index)?;
+                if accumulated.is_empty() {
+                    return None;
+                }
+                crate::metrics::app_log("thinking-registry", &format!(
+                    "SSE: registering complete thinking block index={} len={}", index, accumulated.len()
+                ));
+                Some((BlockKind::Thinking, accumulated))
+            }
+            _ => None,
+        }
+    }
+
+    /// Drain blocks that never saw a `content_block_stop` (truncated stream).
+    pub fn finish(&mut self) -> Vec<(BlockKind, String)> {
+        let mut remaining: Vec<_> = self.accumulators.drain().collect();
+        remaining.sort_by_key(|(index, _)| *index);
+        remaining
+            .into_iter()
+            .filter(|(_, accumulated)| !accumulated.is_empty())
+            .map(|(index, accumulated)| {
+                crate::metrics::app_log("thinking-registry", &format!(
+                    "SSE: registering thinking block without content_block_stop index={} len={}", index, accumulated.len()
+                ));
+                (BlockKind::Thinking, accumulated)
+            })
+            .collect()
+    }
+}
+
 /// Statistics from cache cleanup.
 #[derive(Debug, Default)]
 struct CleanupStats {
@@ -527,6 +586,21 @@ impl CleanupStats {
     }
 }
 
+/// Decisions made by one [`ThinkingRegistry::filter_request_outcome`] pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FilterOutcome {
+    /// Cached blocks newly confirmed by appearing in the request.
+    pub confirmed: u32,
+    /// Cached blocks evicted because they belong to an older session.
+    pub old_session_removed: u32,
+    /// Confirmed blocks evicted because the request no longer carries them.
+    pub unused_removed: u32,
+    /// Unconfirmed blocks evicted after the orphan grace period.
+    pub orphaned_removed: u32,
+    /// Blocks stripped from the request body.
+    pub filtered: u32,
+}
+
 /// Cache statistics for monitoring.
 #[derive(Debug, Clone)]
 pub struct CacheStats {
@@ -537,23 +611,117 @@ pub struct CacheStats {
     pub old_session: usize,
 }
 
-/// Extract thinking content from a JSON value.
-fn extract_thinking_content(item: &Value) -> Option<String> {
+/// Normalize thinking blocks in a request body after provider switches.
+///
+/// Removes empty `signature` fields (some backends reject `"signature": ""`
+/// but accept a block without one) and collapses identical thinking blocks
+/// within a single message to their first occurrence.
+///
+/// Returns the number of changes made (signatures removed + duplicates dropped).
+pub fn normalize_thinking_blocks(body: &mut Value) -> u32 {
+    let Some(messages) = body.get_mut("messages").and_then(|v| v.as_array_mut()) else {
+        return 0;
+    };
+
+    let mut changes = 0u32;
+    for message in messages.iter_mut() {
+        let Some(content) = message.get_mut("content").and_then(|v| v.as_array_mut()) else {
+            continue;
+        };
+
+        for item in content.iter_mut() {
+            let is_thinking = matches!(
+                item.get("type").and_then(|t| t.as_str()),
+                Some("thinking") | Some("redacted_thinking")
+            );
+            if !is_thinking {
+                continue;
+            }
+            let empty_signature = item
+                .get("signature")
+                .is_some_and(|sig| sig.is_null() || sig.as_str() == Some(""));
+            if empty_signature {
+                if let Some(obj) = item.as_object_mut() {
+                    obj.remove("signature");
+                    changes += 1;
+                }
+            }
+        }
+
+        let mut seen: Vec<Value> = Vec::new();
+        let before_len = content.len();
+        content.retain(|item| {
+            if extract_thinking_content(item).is_none() {
+                return true;
+            }
+            if seen.contains(item) {
+                crate::metrics::app_log("thinking-registry", "Dropping duplicate thinking block within message");
+                return false;
+            }
+            seen.push(item.clone());
+            true
+        });
+        changes += (before_len - content.len()) as u32;
+    }
+
+    changes
+}
+
+/// Remove the `signature` field from every `thinking` block in a request
+/// body, for backends known to return unsigned thinking.
+///
+/// Returns the number of signatures removed.
+pub fn strip_thinking_signatures(body: &mut Value) -> u32 {
+    let Some(messages) = body.get_mut("messages").and_then(|v| v.as_array_mut()) else {
+        return 0;
+    };
+
+    let mut stripped = 0u32;
+    for message in messages.iter_mut() {
+        let Some(content) = message.get_mut("content").and_then(|v| v.as_array_mut()) else {
+            continue;
+        };
+        for item in content.iter_mut() {
+            if item.get("type").and_then(|t| t.as_str()) != Some("thinking") {
+                continue;
+            }
+            if let Some(obj) = item.as_object_mut() {
+                if obj.remove("signature").is_some() {
+                    stripped += 1;
+                }
+            }
+        }
+    }
+    stripped
+}
+
+/// Extract the kind and content of a thinking block from a JSON value.
+fn extract_thinking_content(item: &Value) -> Option<(BlockKind, String)> {
     let item_type = item.get("type").and_then(|t| t.as_str())?;
 
     match item_type {
         "thinking" => item
             .get("thinking")
             .and_then(|t| t.as_str())
-            .map(|s| s.to_string()),
+            .map(|s| (BlockKind::Thinking, s.to_string())),
         "redacted_thinking" => item
             .get("data")
             .and_then(|d| d.as_str())
-            .map(|s| s.to_string()),
+            .map(|s| (BlockKind::Redacted, s.to_string())),
         _ => None,
     }
 }
 
+/// Registry key for a block: [`fast_hash`] of the content, namespaced by
+/// the block kind so identical `thinking` text and `redacted_thinking`
+/// data hash differently.
+pub fn block_hash(kind: BlockKind, content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.tag().hash(&mut hasher);
+    hash_content(content, &mut hasher);
+    hasher.finish()
+}
+
 /// Fast hash using prefix + suffix + length for reliability.
 ///
 /// Hashes:
@@ -565,19 +733,21 @@ fn extract_thinking_content(item: &Value) -> Option<String> {
 /// Two blocks with same prefix but different endings will have different hashes.
 pub fn fast_hash(content: &str) -> u64 {
     let mut hasher = DefaultHasher::new();
+    hash_content(content, &mut hasher);
+    hasher.finish()
+}
 
+fn hash_content(content: &str, hasher: &mut DefaultHasher) {
     // Hash prefix (first ~256 bytes, adjusted to char boundary)
     let prefix = safe_truncate(content, 256);
-    prefix.hash(&mut hasher);
+    prefix.hash(hasher);
 
     // Hash suffix (last ~256 bytes, adjusted to char boundary)
     let suffix = safe_suffix(content, 256);
-    suffix.hash(&mut hasher);
+    suffix.hash(hasher);
 
     // Hash the total length
-    content.len().hash(&mut hasher);
-
-    hasher.finish()
+    content.len().hash(hasher);
 }
 
 /// Safely truncate a string from the start at a char boundary.
@@ -15,6 +15,8 @@ pub struct TimeoutConfig {
     pub request: Duration,
     /// Max time between bytes for streaming responses
     pub idle: Duration,
+    /// Upstream silence after which a stream gets an SSE keep-alive comment
+    pub keepalive: Option<Duration>,
 }
 
 impl TimeoutConfig {
@@ -24,6 +26,7 @@ impl TimeoutConfig {
             connect: Duration::from_secs(connect_secs),
             request: Duration::from_secs(request_secs),
             idle: Duration::from_secs(idle_secs),
+            keepalive: None,
         }
     }
 }
@@ -34,6 +37,7 @@ impl Default for TimeoutConfig {
             connect: Duration::from_secs(5),
             request: Duration::from_secs(30),
             idle: Duration::from_secs(60),
+            keepalive: None,
         }
     }
 }
@@ -44,6 +48,8 @@ impl From<&Defaults> for TimeoutConfig {
             connect: Duration::from_secs(defaults.connect_timeout_seconds.into()),
             request: Duration::from_secs(defaults.timeout_seconds.into()),
             idle: Duration::from_secs(defaults.idle_timeout_seconds.into()),
+            keepalive: (defaults.sse_keepalive_seconds > 0)
+                .then(|| Duration::from_secs(defaults.sse_keepalive_seconds.into())),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+//! Startup connection warmup.
+//!
+//! With `defaults.warmup_connections` on, the server sends one `HEAD` to
+//! each configured backend's `base_url` as it starts, through the same
+//! client the pipeline forwards with. The response itself is ignored: the
+//! point is that the pool then holds an established (TCP + TLS)
+//! connection, so the first real request skips the handshake. Failures
+//! are logged and otherwise harmless.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::future::join_all;
+use reqwest::Client;
+
+use crate::config::Backend;
+
+/// Upper bound on a single warmup request.
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Open a pooled connection to every distinct backend `base_url`.
+/// Returns how many backends answered.
+pub async fn warm_up(client: &Client, backends: &[Backend]) -> usize {
+    let mut seen = HashSet::new();
+    let targets: Vec<&Backend> = backends
+        .iter()
+        .filter(|b| !b.base_url.is_empty() && seen.insert(b.base_url.as_str()))
+        .collect();
+
+    let results = join_all(targets.iter().map(|backend| async move {
+        let result = client
+            .head(&backend.base_url)
+            .timeout(WARMUP_TIMEOUT)
+            .send()
+            .await;
+        match result {
+            Ok(resp) => {
+                crate::metrics::app_log(
+                    "warmup",
+                    &format!("Warmed connection to backend '{}' ({})", backend.name, resp.status()),
+                );
+                true
+            }
+            Err(e) => {
+                crate::metrics::app_log_error(
+                    "warmup",
+                    &format!("Warmup for backend '{}' failed; first request will connect cold", backend.name),
+                    &e.to_string(),
+                );
+                false
+            }
+        }
+    }))
+    .await;
+    results.into_iter().filter(|ok| *ok).count()
+}
@@ -43,12 +43,15 @@ impl TeammateShim {
     /// Returns a `("PATH", "shim_dir:$PATH")` tuple for injection into
     /// the spawned process environment via `build_spawn_params()` or
     /// `build_restart_params()`.
+    ///
+    /// Joined with the platform separator (`:` on Unix, `;` on Windows).
     pub fn path_env(&self) -> (String, String) {
-        let current = std::env::var("PATH").unwrap_or_default();
-        (
-            "PATH".to_string(),
-            format!("{}:{}", self.dir_path.display(), current),
-        )
+        let current = std::env::var_os("PATH").unwrap_or_default();
+        let dirs = std::iter::once(self.dir_path.clone()).chain(std::env::split_paths(&current));
+        let joined = std::env::join_paths(dirs)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| self.dir_path.display().to_string());
+        ("PATH".to_string(), joined)
     }
 
     /// Path to the tmux shim log file (may not exist yet).
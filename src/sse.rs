@@ -3,10 +3,17 @@
 //! Provides a single, robust parser for SSE streams used across the codebase.
 //! Handles format variations (e.g. `data:{...}` vs `data: {...}`).
 
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+
+/// Longest SSE line the parsers will decode. Longer lines are skipped with
+/// a warning, and the incremental decoder stops buffering one as soon as it
+/// passes this size, so a runaway or malicious line can't grow memory.
+pub const MAX_SSE_LINE_BYTES: usize = 4 * 1024 * 1024;
 
 /// A parsed SSE event.
+#[derive(Debug, Clone)]
 pub struct SseEvent {
     /// Event type from the `type` field in JSON data.
     pub event_type: String,
@@ -15,6 +22,13 @@ pub struct SseEvent {
 }
 
 impl SseEvent {
+    /// The event for a decoded payload, or `None` when it has no `type`
+    /// (e.g. an OpenAI-style `chat.completion.chunk`).
+    pub fn from_payload(data: Value) -> Option<Self> {
+        let event_type = data.get("type")?.as_str()?.to_string();
+        Some(Self { event_type, data })
+    }
+
     /// Returns true if this event is a thinking-related SSE event.
     ///
     /// Matches:
@@ -183,38 +197,400 @@ pub fn analyze_thinking_stream(events: &[SseEvent]) -> ThinkingStreamStats {
     stats
 }
 
+/// The assistant's visible text in one SSE stream: every `text` block's
+/// initial text plus its `text_delta`s, in order, with blocks separated by
+/// a blank line. Thinking and tool-use blocks are left out.
+#[derive(Debug, Default, Clone)]
+pub struct ResponseTextCollector {
+    text: String,
+    /// Index of the text block the last appended text belonged to.
+    current_block: Option<u64>,
+}
+
+impl ResponseTextCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: &SseEvent) {
+        let index = event.data.get("index").and_then(|i| i.as_u64());
+        let fragment = match event.event_type.as_str() {
+            "content_block_start" => event
+                .data
+                .get("content_block")
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .map(|b| b.get("text").and_then(|t| t.as_str()).unwrap_or("")),
+            "content_block_delta" => event
+                .data
+                .get("delta")
+                .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("text_delta"))
+                .and_then(|d| d.get("text"))
+                .and_then(|t| t.as_str()),
+            _ => None,
+        };
+        let Some(fragment) = fragment else { return };
+        if self.current_block != index {
+            if !self.text.is_empty() {
+                self.text.push_str("\n\n");
+            }
+            self.current_block = index;
+        }
+        self.text.push_str(fragment);
+    }
+
+    /// The same text from a non-streaming response: the `text` blocks of a
+    /// Messages API JSON body, joined the same way.
+    pub fn from_message(body: &[u8]) -> Self {
+        let mut collector = Self::new();
+        let Ok(message) = serde_json::from_slice::<Value>(body) else {
+            return collector;
+        };
+        let blocks = message.get("content").and_then(|c| c.as_array());
+        for (index, block) in blocks.into_iter().flatten().enumerate() {
+            if block.get("type").and_then(|t| t.as_str()) != Some("text") {
+                continue;
+            }
+            collector.record(&SseEvent {
+                event_type: "content_block_start".to_string(),
+                data: serde_json::json!({ "index": index, "content_block": block }),
+            });
+        }
+        collector
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+/// Per-type event counts for one SSE stream.
+///
+/// Counts every event by its `type`. `content_block_delta` events are also
+/// counted under their delta type (`text_delta`, `thinking_delta`, ...), so
+/// those keys break the `content_block_delta` count down rather than add to
+/// the number of events.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct SseEventHistogram {
+    counts: BTreeMap<String, u64>,
+}
+
+impl SseEventHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a histogram from already-parsed events.
+    pub fn from_events(events: &[SseEvent]) -> Self {
+        let mut histogram = Self::new();
+        for event in events {
+            histogram.record(event);
+        }
+        histogram
+    }
+
+    pub fn record(&mut self, event: &SseEvent) {
+        *self.counts.entry(event.event_type.clone()).or_default() += 1;
+        if event.event_type == "content_block_delta" {
+            if let Some(delta_type) = event
+                .data
+                .get("delta")
+                .and_then(|d| d.get("type"))
+                .and_then(|t| t.as_str())
+            {
+                *self.counts.entry(delta_type.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Count for an event or delta type; 0 if it never appeared.
+    pub fn count(&self, key: &str) -> u64 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// `(type, count)` pairs in type order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.counts.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+}
+
+impl std::fmt::Display for SseEventHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for (i, (kind, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{kind}={count}")?;
+        }
+        Ok(())
+    }
+}
+
 /// Parse SSE stream bytes into structured events.
 ///
 /// Handles:
 /// - `data: {...}` (standard, with space)
 /// - `data:{...}` (compact, no space — used by some providers)
+/// - Multi-line events: consecutive `data:` lines up to a blank line are
+///   joined with `\n`, per the SSE spec
 /// - `[DONE]` markers and non-JSON lines are skipped
-/// - Non-data lines (comments, event:, id:, empty) are skipped
+/// - Non-data fields (comments, `event:`, `id:`, `retry:`) are skipped
+/// - Lines over [`MAX_SSE_LINE_BYTES`] are skipped
 pub fn parse_sse_events(bytes: &[u8]) -> Vec<SseEvent> {
-    let text = String::from_utf8_lossy(bytes);
-    text.lines()
-        .filter_map(parse_sse_line)
-        .collect()
+    parse_sse_payloads(bytes).into_iter().filter_map(SseEvent::from_payload).collect()
+}
+
+/// Like [`parse_sse_events`], but keeps every JSON payload, including ones
+/// without a `type` field (e.g. OpenAI-style `chat.completion.chunk`s).
+pub fn parse_sse_payloads(bytes: &[u8]) -> Vec<Value> {
+    let mut fields = SseFieldParser::default();
+    let mut payloads = Vec::new();
+    for line in sse_lines(bytes) {
+        fields.line(&line, &mut payloads);
+    }
+    fields.dispatch(&mut payloads);
+    payloads
+}
+
+/// Each event's data as the SSE spec defines it: the event's `data:`
+/// lines joined with `\n`, dispatched on the blank line that ends it (or
+/// at end of input). Events without data are skipped.
+pub fn parse_sse_data(bytes: &[u8]) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut data: Vec<String> = Vec::new();
+    for line in sse_lines(bytes) {
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        if line.is_empty() {
+            if !data.is_empty() {
+                events.push(std::mem::take(&mut data).join("\n"));
+            }
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+    }
+    if !data.is_empty() {
+        events.push(data.join("\n"));
+    }
+    events
+}
+
+/// Lines of `bytes`, decoded one at a time rather than as a whole buffer,
+/// with over-long lines dropped.
+fn sse_lines(bytes: &[u8]) -> impl Iterator<Item = std::borrow::Cow<'_, str>> {
+    bytes.split(|&b| b == b'\n').filter_map(|line| {
+        if line.len() > MAX_SSE_LINE_BYTES {
+            warn_long_line(line.len());
+            return None;
+        }
+        Some(String::from_utf8_lossy(line))
+    })
+}
+
+fn warn_long_line(len: usize) {
+    crate::metrics::app_log(
+        "sse",
+        &format!("Skipping SSE line of {} bytes (limit {})", len, MAX_SSE_LINE_BYTES),
+    );
 }
 
-/// Extract a JSON event from a line of text.
+/// Per-line SSE field handling, shared by the batch parsers and
+/// [`SseEventDecoder`].
 ///
-/// Tries two strategies:
-/// 1. Parse the line as JSON directly (handles raw JSON, non-SSE responses)
-/// 2. Strip SSE `data:` prefix and parse the remainder
-fn parse_sse_line(line: &str) -> Option<SseEvent> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
-    }
-
-    let json: Value = serde_json::from_str(line)
-        .ok()
-        .or_else(|| {
-            let data = line.strip_prefix("data:")?.trim_start();
-            serde_json::from_str(data).ok()
-        })?;
-
-    let event_type = json.get("type")?.as_str()?.to_string();
-    Some(SseEvent { event_type, data: json })
+/// Data lines collect until the blank line that ends the event, as the
+/// spec requires, and are parsed joined; if the joined text is not JSON,
+/// each collected line is tried alone. A line that is no SSE field at all
+/// is tried as raw JSON (non-SSE bodies). Lines that end up in no payload
+/// are counted as discarded noise.
+#[derive(Debug, Default)]
+struct SseFieldParser {
+    data: Vec<String>,
+    discarded: usize,
+}
+
+impl SseFieldParser {
+    fn line(&mut self, line: &str, out: &mut Vec<Value>) {
+        let line = line.trim();
+        if line.is_empty() {
+            self.dispatch(out);
+            return;
+        }
+        if let Some(data) = line.strip_prefix("data:") {
+            self.data.push(data.trim_start().to_string());
+            return;
+        }
+        let is_field = line.starts_with(':')
+            || ["event:", "id:", "retry:"].iter().any(|field| line.starts_with(field));
+        if !is_field {
+            match serde_json::from_str::<Value>(line) {
+                Ok(value) => out.push(value),
+                Err(_) => self.discarded += 1,
+            }
+        }
+    }
+
+    /// End the current event (blank line or end of input).
+    fn dispatch(&mut self, out: &mut Vec<Value>) {
+        if self.data.is_empty() {
+            return;
+        }
+        let data = std::mem::take(&mut self.data);
+        match serde_json::from_str(&data.join("\n")) {
+            Ok(value) => out.push(value),
+            Err(_) => {
+                for line in &data {
+                    match serde_json::from_str(line) {
+                        Ok(value) => out.push(value),
+                        // The OpenAI-style end marker is expected, not noise.
+                        Err(_) if line == "[DONE]" => {}
+                        Err(_) => self.discarded += 1,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split a complete SSE body into one frame per event, each ending in a
+/// blank line. CRLF line endings become LF, runs of blank lines collapse,
+/// and a trailing event missing its blank line is terminated.
+pub fn split_sse_frames(body: &[u8]) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    let mut frame = Vec::new();
+    for line in body.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            if !frame.is_empty() {
+                frame.push(b'\n');
+                frames.push(std::mem::take(&mut frame));
+            }
+            continue;
+        }
+        frame.extend_from_slice(line);
+        frame.push(b'\n');
+    }
+    if !frame.is_empty() {
+        frame.push(b'\n');
+        frames.push(frame);
+    }
+    frames
+}
+
+/// Incremental SSE decoder for streams that arrive in arbitrary chunks.
+///
+/// Network chunks do not respect line boundaries, so a trailing partial line
+/// is held back until the next chunk completes it, and the data lines of an
+/// unfinished multi-line event are held until its blank line. Lines are
+/// parsed exactly like [`parse_sse_events`], so feeding a stream piecewise
+/// yields the same events as parsing it in one go.
+#[derive(Debug, Default)]
+pub struct SseEventDecoder {
+    partial: Vec<u8>,
+    /// Bytes of an over-long line dropped so far; the rest of it is
+    /// discarded up to the next newline.
+    skipping: Option<usize>,
+    fields: SseFieldParser,
+}
+
+impl SseEventDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk and return the events completed by it.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.push_payloads(chunk).into_iter().filter_map(SseEvent::from_payload).collect()
+    }
+
+    /// Flush the trailing line or event of a stream that ended without its
+    /// newline or blank line.
+    pub fn finish(&mut self) -> Vec<SseEvent> {
+        self.finish_payloads().into_iter().filter_map(SseEvent::from_payload).collect()
+    }
+
+    /// Like [`push`](Self::push), returning raw payloads as
+    /// [`parse_sse_payloads`] does.
+    pub fn push_payloads(&mut self, mut chunk: &[u8]) -> Vec<Value> {
+        if let Some(skipped) = self.skipping {
+            match chunk.iter().position(|&b| b == b'\n') {
+                Some(newline) => {
+                    warn_long_line(skipped + newline);
+                    self.skipping = None;
+                    chunk = &chunk[newline + 1..];
+                }
+                None => {
+                    self.skipping = Some(skipped + chunk.len());
+                    return Vec::new();
+                }
+            }
+        }
+
+        let Some(last_newline) = chunk.iter().rposition(|&b| b == b'\n') else {
+            self.hold(chunk);
+            return Vec::new();
+        };
+
+        // Drop the final newline: it ends the last line, it is not a blank one.
+        let (complete, rest) = (&chunk[..last_newline], &chunk[last_newline + 1..]);
+        let mut payloads = Vec::new();
+        if self.partial.is_empty() {
+            self.feed_lines(complete, &mut payloads);
+        } else {
+            let mut joined = std::mem::take(&mut self.partial);
+            joined.extend_from_slice(complete);
+            self.feed_lines(&joined, &mut payloads);
+        }
+        self.hold(rest);
+        payloads
+    }
+
+    /// Like [`finish`](Self::finish), returning raw payloads.
+    pub fn finish_payloads(&mut self) -> Vec<Value> {
+        if let Some(skipped) = self.skipping.take() {
+            warn_long_line(skipped);
+        }
+        let mut payloads = Vec::new();
+        let partial = std::mem::take(&mut self.partial);
+        self.feed_lines(&partial, &mut payloads);
+        self.fields.dispatch(&mut payloads);
+        payloads
+    }
+
+    /// Bytes held back waiting for the newline that completes them.
+    pub fn pending_len(&self) -> usize {
+        self.partial.len()
+    }
+
+    /// Lines decoded so far that were neither SSE fields nor JSON: noise a
+    /// backend mixed into its event stream, skipped without an event.
+    pub fn discarded_lines(&self) -> usize {
+        self.fields.discarded
+    }
+
+    fn feed_lines(&mut self, bytes: &[u8], out: &mut Vec<Value>) {
+        for line in sse_lines(bytes) {
+            self.fields.line(&line, out);
+        }
+    }
+
+    /// Buffer the start of an unfinished line, switching to skip mode once
+    /// it outgrows [`MAX_SSE_LINE_BYTES`].
+    fn hold(&mut self, bytes: &[u8]) {
+        if self.partial.len() + bytes.len() > MAX_SSE_LINE_BYTES {
+            self.skipping = Some(self.partial.len() + bytes.len());
+            self.partial = Vec::new();
+        } else {
+            self.partial.extend_from_slice(bytes);
+        }
+    }
 }
@@ -17,7 +17,7 @@ use std::time::Instant;
 use glam::Vec2;
 use term_core::RenderSnapshot;
 use term_gpu::{
-    decay_velocity, encode_key, expand_line, expand_word, CellPoint, ScrollState, ScrollVelocity,
+    decay_velocity, encode_key, encode_keypad_key, expand_line, expand_word, CellPoint, ScrollState, ScrollVelocity,
     Selection, MOMENTUM_MIN_VELOCITY, MOMENTUM_THRESHOLD,
 };
 use winit::event::TouchPhase;
@@ -25,7 +25,7 @@ use winit::keyboard::{Key, KeyCode, ModifiersState, PhysicalKey};
 
 use crate::ui::backend_switch::{BackendSwitchIntent, BackendSwitchState};
 use crate::ui::history::{HistoryDialogState, HistoryIntent};
-use crate::ui::input::{self, AppShortcut};
+use crate::ui::input::{self, AppShortcut, KeyChord};
 use crate::ui::settings::{SettingsDialogState, SettingsIntent};
 use crate::ui::term_geometry::LastClick;
 
@@ -40,6 +40,8 @@ pub struct AppState {
 
     // Input + selection.
     pub modifiers: ModifiersState,
+    /// The chord bound to "copy last response" (`terminal.copy_last_response_key`).
+    pub copy_last_response_key: KeyChord,
     /// Last mouse position in logical pixels (top-left origin).
     pub cursor_pos: Option<(f32, f32)>,
     pub dragging_selection: bool,
@@ -62,6 +64,10 @@ pub struct AppState {
     /// stored (R12).
     pub session_copied_until: Option<Instant>,
 
+    /// The Claude child's exit code once it has exited; cleared by a restart.
+    /// The final screen stays up until then.
+    pub child_exit: Option<u32>,
+
     // Popup overlays (each a plain `apply()` state machine).
     pub backend_switch: BackendSwitchState,
     pub history: HistoryDialogState,
@@ -109,6 +115,9 @@ pub enum Effect {
     SaveSettings,
     /// Copy the current selection to the clipboard.
     CopySelection,
+    /// Copy the last assistant response to the clipboard (Cmd+Shift+C, or
+    /// `terminal.copy_last_response_key`).
+    CopyLastResponse,
     /// Copy the session id to the clipboard + arm the header "copied!" flash.
     CopySessionId,
     /// Read the clipboard and paste into the PTY.
@@ -122,6 +131,11 @@ pub enum Effect {
     Quit,
     /// Drain pending PTY bytes into the emulator, redrawing if any arrived.
     Drain,
+    /// Print the "process exited" banner into the terminal below the child's
+    /// final output (no PTY write — the child is gone).
+    AnnounceChildExit { code: u32 },
+    /// Print the "output lost" banner after a PTY read error.
+    AnnouncePtyReadError { error: String },
 }
 
 /// An input event translated to a pure message. The coordinator does any
@@ -156,6 +170,8 @@ pub enum Msg {
         logical_unmod: Key,
         physical: PhysicalKey,
         app_cursor: bool,
+        /// The emulator's DECKPAM state — keypad keys send `SS3` codes.
+        keypad_app: bool,
     },
     /// The cursor moved to `(x, y)` logical px. `point` is the cell under it,
     /// pre-resolved by the coordinator (when a selection drag is in flight OR a
@@ -192,6 +208,10 @@ pub enum Msg {
     Close,
     /// The PTY signalled that new output is ready to drain.
     PtyBytes,
+    /// The Claude child exited with `code`.
+    ChildExited { code: u32 },
+    /// Reading the PTY failed; no further output will arrive.
+    PtyReadFailed { error: String },
 }
 
 /// Read-only context the coordinator supplies to [`AppState::apply`]: the frame
@@ -260,8 +280,8 @@ impl AppState {
                 fx.push(Effect::Redraw);
                 fx
             }
-            Msg::Key { logical, logical_unmod, physical, app_cursor } => {
-                self.on_key(logical, logical_unmod, physical, app_cursor)
+            Msg::Key { logical, logical_unmod, physical, app_cursor, keypad_app } => {
+                self.on_key(logical, logical_unmod, physical, app_cursor, keypad_app)
             }
             Msg::CursorMoved { x, y, point, motion_report } => {
                 self.set_cursor_pos(x, y);
@@ -322,6 +342,14 @@ impl AppState {
             Msg::Tick => vec![Effect::Redraw],
             Msg::Close => vec![Effect::Quit],
             Msg::PtyBytes => vec![Effect::Drain],
+            Msg::ChildExited { code } => {
+                self.child_exit = Some(code);
+                // Drain first so the banner lands after the child's last output.
+                vec![Effect::Drain, Effect::AnnounceChildExit { code }, Effect::Redraw]
+            }
+            Msg::PtyReadFailed { error } => {
+                vec![Effect::Drain, Effect::AnnouncePtyReadError { error }, Effect::Redraw]
+            }
         }
     }
 
@@ -347,14 +375,16 @@ impl AppState {
         logical_unmod: Key,
         physical: PhysicalKey,
         app_cursor: bool,
+        keypad_app: bool,
     ) -> Vec<Effect> {
         if self.any_popup_visible() {
             return self.on_popup_key(physical);
         }
         if let PhysicalKey::Code(code) = physical {
-            if let Some(shortcut) = input::app_shortcut(code, self.modifiers) {
+            if let Some(shortcut) = input::bound_app_shortcut(code, self.modifiers, self.copy_last_response_key) {
                 return vec![match shortcut {
                     AppShortcut::CopySelection => Effect::CopySelection,
+                    AppShortcut::CopyLastResponse => Effect::CopyLastResponse,
                     AppShortcut::Paste => Effect::Paste,
                     AppShortcut::ToggleBackendPopup => Effect::ToggleBackendPopup,
                     AppShortcut::ToggleHistoryPopup => Effect::ToggleHistoryPopup,
@@ -370,6 +400,11 @@ impl AppState {
         if self.modifiers.super_key() {
             return Vec::new();
         }
+        if let PhysicalKey::Code(code) = physical {
+            if let Some(bytes) = encode_keypad_key(code, keypad_app) {
+                return vec![Effect::WriteToPty(bytes)];
+            }
+        }
         match encode_key(&logical, &logical_unmod, self.modifiers, app_cursor) {
             Some(bytes) => vec![Effect::WriteToPty(bytes)],
             None => Vec::new(),
@@ -422,6 +457,7 @@ impl AppState {
             scroll: ScrollState::default(),
             scroll_velocity: None,
             modifiers: ModifiersState::empty(),
+            copy_last_response_key: KeyChord::COPY_LAST_RESPONSE,
             cursor_pos: None,
             dragging_selection: false,
             selection: None,
@@ -431,6 +467,7 @@ impl AppState {
             session_id,
             start_time,
             session_copied_until: None,
+            child_exit: None,
             backend_switch: BackendSwitchState::default(),
             history: HistoryDialogState::default(),
             settings: SettingsDialogState::default(),
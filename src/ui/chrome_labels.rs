@@ -11,9 +11,13 @@
 //! The immediate-mode `draw_header` / `draw_footer` are gone (Phase E.6), so the
 //! chrome words + palette live solely here.
 
+use std::time::Duration;
+
 use term_ui::{Block, BlockStyle, CrossAxis, Insets, Sizing, Stack, WidgetId};
 use uikit::{footer_bar, header_bar, Segment};
 
+use crate::metrics::{ErrorSeverity, ErrorSummary};
+
 /// Dim grey for chrome labels and the inter-segment separator.
 pub const CHROME_TEXT_COLOR: [f32; 4] = [0.55, 0.55, 0.55, 1.0];
 
@@ -25,9 +29,18 @@ pub const CHROME_BG: [f32; 4] = [0.04, 0.04, 0.06, 1.0];
 /// Green flash for the "Session ID copied!" confirmation.
 pub const CHROME_FLASH_COLOR: [f32; 4] = [0.4, 0.85, 0.4, 1.0];
 
+/// Amber for a header error summary that holds only warnings (4xx).
+pub const CHROME_WARNING_COLOR: [f32; 4] = [0.9, 0.7, 0.3, 1.0];
+
+/// Red for a header error summary with at least one 5xx or timeout.
+pub const CHROME_ERROR_COLOR: [f32; 4] = [0.9, 0.35, 0.35, 1.0];
+
 /// 1px fence between chrome and the terminal panel.
 pub const CHROME_SEPARATOR_COLOR: [f32; 4] = [0.25, 0.25, 0.27, 1.0];
 
+/// How far back the header's error summary looks.
+pub const ERROR_SUMMARY_WINDOW: Duration = Duration::from_secs(5 * 60);
+
 /// Separator drawn between header segments.
 pub const HEADER_SEPARATOR: &str = " │ ";
 
@@ -35,6 +48,18 @@ pub const HEADER_SEPARATOR: &str = " │ ";
 pub const FOOTER_HINTS: &str =
     " Cmd+B: Switch │ Cmd+H: History │ Cmd+E: Settings │ Cmd+R: Restart │ Cmd+Q: Quit";
 
+/// The line printed into the terminal when the Claude child exits: dim, on
+/// its own line below the final output, pointing at the restart hotkey.
+pub fn child_exit_banner(code: u32) -> String {
+    format!("\r\n\x1b[0;2m[process exited with code {code} — Cmd+R to restart]\x1b[0m\r\n")
+}
+
+/// The line printed into the terminal when reading the child's output
+/// fails: the child may still run, but nothing more will be shown.
+pub fn pty_read_error_banner(error: &str) -> String {
+    format!("\r\n\x1b[0;31m[terminal output lost: {error} — Cmd+R to restart]\x1b[0m\r\n")
+}
+
 /// Build the header segments in order: backend / sub / team / Reqs / Uptime /
 /// Session. `subagent` / `teammate` render as "—" when absent. The Session run
 /// flips to `CHROME_FLASH_COLOR` + "Session ID copied!" while `session_copied`
@@ -67,6 +92,16 @@ pub fn header_segments(
     segs
 }
 
+/// The "Errors: 3 upstream, 1 timeout" header run, coloured by the worst
+/// severity; `None` when there were no recent failures.
+pub fn error_segment(summary: &ErrorSummary) -> Option<Segment> {
+    let color = match summary.max_severity()? {
+        ErrorSeverity::Warning => CHROME_WARNING_COLOR,
+        ErrorSeverity::Error => CHROME_ERROR_COLOR,
+    };
+    Some(Segment::new(format!("Errors: {summary}"), color))
+}
+
 /// Stable widget id for the click-to-copy "Session: …" header run. The
 /// coordinator resolves this against the laid-out chrome tree each frame to
 /// recover the label's bounds. (The chrome view assigns no other WidgetIds, so
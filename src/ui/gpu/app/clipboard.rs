@@ -1,5 +1,6 @@
 //! Clipboard operations: copy the session id (with header flash), copy the
-//! current selection, and paste the system clipboard into the PTY.
+//! current selection or the last assistant response, and paste the system
+//! clipboard into the PTY.
 
 use std::time::Instant;
 
@@ -7,7 +8,7 @@ use term_clipboard::{
     get_image_filepaths_from_paths, pick_best_image, save_image_to_temp,
     should_insert_text_on_paste, ClipboardContent,
 };
-use term_gpu::{encode_paste, selection_to_text, shell_quote_path};
+use term_gpu::{encode_paste, sanitize_paste, selection_to_text, shell_quote_path};
 
 use crate::ui::gpu::chrome::SESSION_COPY_FLASH;
 
@@ -43,6 +44,17 @@ impl super::GpuApp {
         self.clipboard.write(ClipboardContent::plain_text(text));
     }
 
+    /// Copy the text of the most recent assistant response, as seen by the
+    /// proxy, to the clipboard. Before any response has completed there is
+    /// nothing to copy: the clipboard is left alone and the miss is logged.
+    pub(super) fn copy_last_response(&mut self) {
+        let Some(text) = self.backends.observability.last_response_text() else {
+            crate::metrics::app_log("clipboard", "No assistant response to copy yet");
+            return;
+        };
+        self.clipboard.write(ClipboardContent::plain_text(text));
+    }
+
     /// Read the system clipboard and paste into the PTY. Mirrors
     /// Warp's `process_paste_event` step-for-step
     /// (`app/src/terminal/input.rs:10573`):
@@ -60,7 +72,9 @@ impl super::GpuApp {
     ///      path to the payload.
     ///
     /// Paths are shell-quoted (single-quote escape) so spaces in
-    /// names don't break tokenisation in the shell. The final
+    /// names don't break tokenisation in the shell. With
+    /// `terminal.sanitize_paste` on, control characters are stripped
+    /// from the text part first (`sanitize_paste`). The final
     /// payload is normalised (CRLF → LF) and wrapped in
     /// `\x1b[200~` … `\x1b[201~` when the emulator has bracketed
     /// paste enabled.
@@ -69,7 +83,11 @@ impl super::GpuApp {
         let mut parts: Vec<String> = Vec::new();
 
         if should_insert_text_on_paste(&content) && !content.plain_text.is_empty() {
-            parts.push(content.plain_text.clone());
+            if self.backends.backend_state.get_config().terminal.sanitize_paste {
+                parts.push(sanitize_paste(&content.plain_text));
+            } else {
+                parts.push(content.plain_text.clone());
+            }
         }
 
         if let Some(paths) = content.paths.as_deref() {
@@ -96,10 +114,8 @@ impl super::GpuApp {
             .map(|e| e.bracketed_paste())
             .unwrap_or(false);
         let bytes = encode_paste(&payload, bracketed);
-        if let Some(pty) = self.session.pty.as_mut() {
-            if let Err(e) = pty.write(&bytes) {
-                eprintln!("anyclaude: paste write failed: {e}");
-            }
+        if let Err(e) = self.session.write_input(&bytes) {
+            eprintln!("anyclaude: paste write failed: {e}");
         }
     }
 }
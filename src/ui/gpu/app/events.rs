@@ -10,7 +10,6 @@
 use std::sync::Arc;
 use std::time::Instant;
 
-use term_core::create_emulator;
 use term_gpu::{
     GpuRenderer, MouseButton, MouseEventKind, GESTURE_END_TIMEOUT, MOMENTUM_FRAME_INTERVAL,
     NUM_PIXELS_PER_LINE,
@@ -21,11 +20,11 @@ use winit::event::{ElementState, MouseButton as WinitMouseButton, MouseScrollDel
 use winit::event_loop::ActiveEventLoop;
 use winit::window::{WindowAttributes, WindowId};
 
+use crate::ui::chrome_labels::{child_exit_banner, pty_read_error_banner};
 use crate::ui::app_state::{ApplyCtx, Effect, Msg};
 use crate::ui::gpu::diagnostic;
-use crate::ui::gpu::pty::ChildPty;
 
-use super::{UserEvent, INITIAL_H, INITIAL_W, MULTI_CLICK_THRESHOLD_MS, SCROLLBACK_LINES};
+use super::{UserEvent, INITIAL_H, INITIAL_W, MULTI_CLICK_THRESHOLD_MS};
 
 impl super::GpuApp {
     /// Translate a `Msg` to its state transition and perform the resulting
@@ -64,19 +63,10 @@ impl super::GpuApp {
                     self.timers.schedule_gesture_end(&self.proxy, GESTURE_END_TIMEOUT);
                 }
                 Effect::Redraw => self.request_redraw(),
-                Effect::ResizeEmulatorAndPty { cols, rows } => {
-                    if let Some(emu) = self.session.emulator.as_mut() {
-                        emu.resize(cols, rows);
-                    }
-                    if let Some(pty) = self.session.pty.as_ref() {
-                        pty.resize(cols as u16, rows as u16);
-                    }
-                }
+                Effect::ResizeEmulatorAndPty { cols, rows } => self.session.resize(cols, rows),
                 Effect::WriteToPty(bytes) => {
-                    if let Some(pty) = self.session.pty.as_mut() {
-                        if let Err(e) = pty.write(&bytes) {
-                            eprintln!("anyclaude: PTY write failed: {e}");
-                        }
+                    if let Err(e) = self.session.write_input(&bytes) {
+                        eprintln!("anyclaude: PTY write failed: {e}");
                     }
                 }
                 Effect::ToggleBackendPopup => self.toggle_backend_switch_popup(),
@@ -86,6 +76,7 @@ impl super::GpuApp {
                 Effect::ApplyBackendSelection => self.apply_backend_switch_selection(),
                 Effect::SaveSettings => self.apply_settings_and_save(),
                 Effect::CopySelection => self.copy_selection(),
+                Effect::CopyLastResponse => self.copy_last_response(),
                 Effect::CopySessionId => self.copy_session_id(),
                 Effect::Paste => self.paste_into_pty(),
                 Effect::RestartPty => self.restart_pty(),
@@ -96,19 +87,35 @@ impl super::GpuApp {
                         self.request_redraw();
                     }
                 }
+                Effect::AnnounceChildExit { code } => {
+                    if let Some(emu) = self.session.emulator.as_mut() {
+                        emu.process(child_exit_banner(code).as_bytes());
+                    }
+                }
+                Effect::AnnouncePtyReadError { error } => {
+                    if let Some(emu) = self.session.emulator.as_mut() {
+                        emu.process(pty_read_error_banner(&error).as_bytes());
+                    }
+                }
             }
         }
         exit
     }
 
-    /// Dump a diagnostic snapshot (grid + scroll + emulator) to stderr.
+    /// Dump a diagnostic snapshot (grid + scroll + switch log + routing
+    /// log + last stream + emulator) to stderr.
     fn dump_diagnostic(&self) {
         let snap = self.session.emulator.as_ref().map(|e| e.snapshot());
+        let recent = self.backends.observability.snapshot().recent;
+        let last_stream = recent.iter().rev().find(|r| r.sse_events.is_some());
         diagnostic::dump_snapshot(
             self.state.grid_size,
             self.state.scroll.offset_y,
             self.state.scroll.max_offset(),
             snap.as_ref(),
+            &self.backends.backend_state.get_switch_log(),
+            &self.backends.observability.routing_decisions(),
+            last_stream,
         );
     }
 }
@@ -133,21 +140,12 @@ impl ApplicationHandler<UserEvent> for super::GpuApp {
 
         let (cols, rows) = self.fit_grid();
         self.state.grid_size = (cols, rows);
-        self.session.emulator = Some(create_emulator(cols, rows, SCROLLBACK_LINES));
+        self.session.emulator = Some(self.new_emulator(cols, rows));
 
-        let proxy = self.proxy.clone();
-        match ChildPty::spawn(
-            cols as u16,
-            rows as u16,
-            self.session.spawn_command.clone(),
-            self.session.spawn_args.clone(),
-            self.session.spawn_env.clone(),
-            move || {
-                let _ = proxy.send_event(UserEvent::PtyBytesArrived);
-            },
-        ) {
+        match self.spawn_child(cols, rows) {
             Ok(pty) => {
                 self.session.pty = Some(pty);
+                self.start_replay();
             }
             Err(e) => {
                 eprintln!("anyclaude: failed to spawn shell: {e}");
@@ -161,10 +159,23 @@ impl ApplicationHandler<UserEvent> for super::GpuApp {
         window.request_redraw();
     }
 
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        self.save_recording();
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         match event {
             UserEvent::PtyBytesArrived => {
                 self.dispatch(Msg::PtyBytes);
+                if let Some(e) = self.session.pty.as_mut().and_then(|p| p.poll_read_error()) {
+                    self.dispatch(Msg::PtyReadFailed { error: e.to_string() });
+                }
+            }
+            UserEvent::ReplayInput => self.feed_replay(),
+            UserEvent::ChildExited => {
+                if let Some(code) = self.session.pty.as_mut().and_then(|p| p.poll_exit()) {
+                    self.dispatch(Msg::ChildExited { code });
+                }
             }
             UserEvent::GestureEnded => {
                 self.dispatch(Msg::GestureEnd);
@@ -176,15 +187,19 @@ impl ApplicationHandler<UserEvent> for super::GpuApp {
             UserEvent::TickRedraw => {
                 self.dispatch(Msg::Tick);
             }
+            UserEvent::ShutdownSignal(signal) => {
+                if let Some(pty) = self.session.pty.take() {
+                    pty.shutdown(signal);
+                }
+                event_loop.exit();
+            }
         }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         match event {
-            WindowEvent::CloseRequested => {
-                if self.dispatch(Msg::Close) {
-                    event_loop.exit();
-                }
+            WindowEvent::CloseRequested if self.dispatch(Msg::Close) => {
+                event_loop.exit();
             }
             WindowEvent::Resized(new_size) => {
                 if let Some(r) = self.renderer.as_mut() {
@@ -254,18 +269,23 @@ impl ApplicationHandler<UserEvent> for super::GpuApp {
                     self.dispatch(Msg::MouseRelease { mouse_report });
                 }
             },
-            // Middle / right buttons have no local action — they only matter to a
-            // mouse-reporting app, so forward the encoded report when one's active
-            // and otherwise drop the event (§6).
+            // Middle / right / back / forward buttons have no local action — they
+            // only matter to a mouse-reporting app, so forward the encoded report
+            // when one's active and otherwise drop the event (§6).
             WindowEvent::MouseInput {
                 state,
-                button: button @ (WinitMouseButton::Middle | WinitMouseButton::Right),
+                button:
+                    button @ (WinitMouseButton::Middle
+                    | WinitMouseButton::Right
+                    | WinitMouseButton::Back
+                    | WinitMouseButton::Forward),
                 ..
             } => {
-                let report_button = if matches!(button, WinitMouseButton::Right) {
-                    MouseButton::Right
-                } else {
-                    MouseButton::Middle
+                let report_button = match button {
+                    WinitMouseButton::Right => MouseButton::Right,
+                    WinitMouseButton::Back => MouseButton::Back,
+                    WinitMouseButton::Forward => MouseButton::Forward,
+                    _ => MouseButton::Middle,
                 };
                 let kind = match state {
                     ElementState::Pressed => MouseEventKind::Press,
@@ -283,19 +303,21 @@ impl ApplicationHandler<UserEvent> for super::GpuApp {
                 // lives in AppState::apply. Quit comes back as the exit signal,
                 // since the event loop is the coordinator's to drive. Resolve the
                 // resource-backed inputs the encoder needs here: the DECCKM state
-                // (SS3 vs CSI arrows) and the un-composed base key (Meta form).
-                let app_cursor = self
+                // (SS3 vs CSI arrows), the DECKPAM state (SS3 keypad) and the
+                // un-composed base key (Meta form).
+                let (app_cursor, keypad_app) = self
                     .session
                     .emulator
                     .as_ref()
-                    .map(|e| e.cursor_keys_app())
-                    .unwrap_or(false);
+                    .map(|e| (e.cursor_keys_app(), e.keypad_app()))
+                    .unwrap_or((false, false));
                 let logical_unmod = key_without_modifiers(&event);
                 if self.dispatch(Msg::Key {
                     logical: event.logical_key,
                     logical_unmod,
                     physical: event.physical_key,
                     app_cursor,
+                    keypad_app,
                 }) {
                     event_loop.exit();
                 }
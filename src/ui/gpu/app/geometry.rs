@@ -5,7 +5,7 @@
 use std::time::Instant;
 
 use term_gpu::{
-    encode_motion_report, encode_mouse_report, measure_cell_metrics, CellMetrics, CellPoint,
+    encode_motion_report, forward_mouse_report, measure_cell_metrics, CellMetrics, CellPoint,
     MouseButton, MouseEventKind, PanelRect,
 };
 
@@ -165,7 +165,8 @@ impl super::GpuApp {
     /// `col` / `row` are 1-based cells (the snapshot cell + 1 — viewport-correct
     /// on the alt screen, where mouse-mode apps live and there's no scrollback).
     /// The tracking level + encoding come from the emulator's split
-    /// `MouseProtocol`; the byte shape is decided in `encode_mouse_report`.
+    /// `MouseProtocol`; the forward-or-keep decision lives in the pure
+    /// `forward_mouse_report`.
     fn mouse_report(
         &self,
         button: MouseButton,
@@ -173,19 +174,8 @@ impl super::GpuApp {
         col: u16,
         row: u16,
     ) -> Option<Vec<u8>> {
-        // Shift is the local-action bypass: forward nothing so the gesture
-        // stays a selection / scroll even while an app has tracking on.
-        if self.state.modifiers.shift_key() {
-            return None;
-        }
         let proto = self.session.emulator.as_ref()?.mouse_protocol();
-        if !proto.is_active() {
-            return None;
-        }
-        if matches!(kind, MouseEventKind::Motion) && !proto.reports_motion() {
-            return None;
-        }
-        Some(encode_mouse_report(button, kind, col, row, proto.is_sgr()))
+        forward_mouse_report(proto, self.state.modifiers.shift_key(), button, kind, col, row)
     }
 
     /// The mouse report for the cell currently under the cursor (release /
@@ -14,9 +14,10 @@
 //!   - [`geometry`]  — cell metrics, panel/grid fit, scroll bounds, mouse hit-test
 //!   - [`popups`]    — the three popup toggles + their apply/save handlers
 //!   - [`clipboard`] — copy session id / copy selection / paste
-//!   - [`session_ops`] — drain the PTY / restart the session
+//!   - [`session_ops`] — drain the PTY / restart the session / `--replay` + `--record`
 
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
 use std::time::Instant;
 use term_clipboard::Clipboard;
 use term_gpu::GpuRenderer;
@@ -28,9 +29,12 @@ use crate::backend::{AgentBackendState, BackendState};
 use crate::config::ClaudeSettingsManager;
 use crate::metrics::ObservabilityHub;
 use crate::ui::app_state::AppState;
+use crate::ui::history::HistoryStore;
+use crate::ui::input::KeyChord;
 
 use super::backends::Backends;
 use super::overlay::OverlayRenderer;
+use super::replay::{InputRecorder, InputRecording};
 use super::session::Session;
 use super::text::TextResources;
 use super::timers::Timers;
@@ -67,11 +71,18 @@ const POPUP_FADE_SECS: f32 = 0.12;
 #[derive(Debug, Clone, Copy)]
 pub(super) enum UserEvent {
     PtyBytesArrived,
+    /// The Claude child exited; the code is read via `ChildPty::poll_exit`.
+    ChildExited,
+    /// The `--replay` thread queued input; drained via `GpuApp::feed_replay`.
+    ReplayInput,
     GestureEnded,
     MomentumTick,
     /// 1Hz heartbeat that keeps Uptime / Reqs / sub / team chrome
     /// fresh even when the PTY is silent.
     TickRedraw,
+    /// The wrapper got SIGINT/SIGTERM: forward it to the child, give it
+    /// the shutdown grace, then quit.
+    ShutdownSignal(i32),
 }
 
 pub(super) struct GpuApp {
@@ -113,9 +124,17 @@ pub(super) struct GpuApp {
     /// Proxy + config handles — backend state, subagent / teammate overrides,
     /// observability, settings manager. See [`Backends`].
     backends: Backends,
+
+    /// `--replay` input: the recording until the first child spawns, then
+    /// the queue its pacing thread fills.
+    replay: Option<InputRecording>,
+    replay_rx: Option<mpsc::Receiver<Vec<u8>>>,
+    /// `--record` destination, written when the event loop exits.
+    record_path: Option<PathBuf>,
 }
 
 impl GpuApp {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         proxy: EventLoopProxy<UserEvent>,
         spawn_command: String,
@@ -127,6 +146,23 @@ impl GpuApp {
         observability: ObservabilityHub,
         settings_manager: ClaudeSettingsManager,
     ) -> Self {
+        let history_store = HistoryStore::new(
+            HistoryStore::default_path(),
+            backend_state.get_config().defaults.switch_log_capacity,
+        );
+        let past_history = history_store.load();
+        let mut state = AppState::new(
+            Uuid::new_v4().to_string(),
+            Instant::now(),
+            (INITIAL_GRID_COLS, INITIAL_GRID_ROWS),
+        );
+        let copy_key = backend_state.get_config().terminal.copy_last_response_key;
+        match KeyChord::parse(&copy_key) {
+            Some(chord) => state.copy_last_response_key = chord,
+            None => eprintln!(
+                "anyclaude: terminal.copy_last_response_key '{copy_key}' is not a valid chord, using cmd+shift+c"
+            ),
+        }
         Self {
             proxy,
             window: None,
@@ -135,11 +171,7 @@ impl GpuApp {
             text: TextResources::new(),
             overlay: OverlayRenderer::new(),
             session: Session::new(spawn_command, spawn_args, spawn_env),
-            state: AppState::new(
-                Uuid::new_v4().to_string(),
-                Instant::now(),
-                (INITIAL_GRID_COLS, INITIAL_GRID_ROWS),
-            ),
+            state,
             timers: Timers::new(),
             session_click_zone: None,
             clipboard: make_clipboard(),
@@ -149,8 +181,28 @@ impl GpuApp {
                 teammate_backend,
                 observability,
                 settings_manager,
+                history_store,
+                past_history,
             },
+            replay: None,
+            replay_rx: None,
+            record_path: None,
+        }
+    }
+
+    /// Record the session's input to `record`, and/or replay `replay` into
+    /// the child once it spawns.
+    pub(super) fn with_input_script(
+        mut self,
+        record: Option<PathBuf>,
+        replay: Option<InputRecording>,
+    ) -> Self {
+        if record.is_some() {
+            self.session.recorder = Some(InputRecorder::new());
         }
+        self.record_path = record;
+        self.replay = replay;
+        self
     }
 
     fn request_redraw(&self) {
@@ -2,6 +2,7 @@
 //! open-close toggles and the apply/save handlers that commit a popup's edits
 //! back to the backend / settings managers.
 
+use crate::backend::SwitchContext;
 use crate::config::{save_claude_settings, Config, SettingsFieldSnapshot};
 use crate::ui::backend_switch::{
     override_selection_to_backend_id, BackendPopupSection, BackendSwitchIntent, BackendSwitchState,
@@ -19,6 +20,24 @@ impl super::GpuApp {
         }
     }
 
+    /// The child's cwd and title, recorded with a backend switch so the
+    /// history popup can show where it happened.
+    fn switch_context(&self) -> SwitchContext {
+        let title = self
+            .session
+            .emulator
+            .as_ref()
+            .map(|e| e.title().to_string())
+            .filter(|t| !t.is_empty());
+        let cwd = self
+            .session
+            .pty
+            .as_ref()
+            .and_then(|pty| pty.cwd())
+            .map(|dir| dir.display().to_string());
+        SwitchContext { cwd, title }
+    }
+
     /// Cmd+B handler — open or close the backend switch popup. Open
     /// dispatches the Open intent with the active backend pre-selected
     /// so pressing Enter is a no-op if the user is just inspecting.
@@ -50,6 +69,31 @@ impl super::GpuApp {
         }
     }
 
+    /// Earlier sessions' history followed by this session's switch log.
+    fn history_entries(&self) -> Vec<HistoryEntry> {
+        let mut entries = self.backends.past_history.clone();
+        entries.extend(
+            self.backends
+                .backend_state
+                .get_switch_log()
+                .into_iter()
+                .map(HistoryEntry::from),
+        );
+        entries
+    }
+
+    /// Write the full history to disk; a failure only costs persistence.
+    fn save_history(&self) {
+        let store = &self.backends.history_store;
+        if let Err(e) = store.save(&self.history_entries()) {
+            crate::metrics::app_log_error(
+                "history",
+                &format!("Failed to save history to {}", store.path().display()),
+                &e.to_string(),
+            );
+        }
+    }
+
     /// Cmd+H handler — open or close the history popup. The switch
     /// log is snapshotted into the popup at open time; subsequent
     /// switches only show up after the user reopens.
@@ -58,19 +102,10 @@ impl super::GpuApp {
             self.close_all_popups();
             return;
         }
-        let entries = self.backends.backend_state.get_switch_log();
-        let history_entries: Vec<HistoryEntry> = entries
-            .into_iter()
-            .map(|e| HistoryEntry {
-                timestamp: e.timestamp,
-                from_backend: e.old_backend,
-                to_backend: e.new_backend,
-            })
-            .collect();
+        let entries = self.history_entries();
+        let capacity = self.backends.backend_state.get_config().defaults.switch_log_capacity;
         self.close_all_popups();
-        self.state.history.apply(HistoryIntent::Load {
-            entries: history_entries,
-        });
+        self.state.history.apply(HistoryIntent::Load { entries, capacity });
         if let Some(w) = self.window.as_ref() {
             w.request_redraw();
         }
@@ -156,8 +191,10 @@ impl super::GpuApp {
             BackendPopupSection::ActiveBackend => {
                 if let Some(b) = cfg.backends.get(backend_sel) {
                     let id = b.name.clone();
-                    if let Err(e) = self.backends.backend_state.switch_backend(&id) {
-                        eprintln!("anyclaude: backend switch failed: {e}");
+                    let context = self.switch_context();
+                    match self.backends.backend_state.switch_backend_with(&id, context) {
+                        Ok(()) => self.save_history(),
+                        Err(e) => eprintln!("anyclaude: backend switch failed: {e}"),
                     }
                 }
             }
@@ -6,7 +6,7 @@
 //! them to the [`OverlayRenderer`] for the retained-tree pipeline. The overlay
 //! is drawn entirely after the terminal base, so the bars / popup sit on top.
 
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
 use glam::Vec2;
 use term_gpu::{
@@ -121,7 +121,7 @@ impl super::GpuApp {
         // Chrome (header + footer) is a term_ui view: build it from the current
         // AppState here (it needs the backend / observability data), then hand it
         // to the overlay renderer for the term_ui pipeline + the session hitbox.
-        let header = chrome_labels::header_segments(
+        let mut header = chrome_labels::header_segments(
             &active_backend,
             subagent_label.as_deref(),
             teammate_label.as_deref(),
@@ -130,6 +130,14 @@ impl super::GpuApp {
             &self.state.session_id,
             self.state.session_copied(now),
         );
+        // Failures from the last few minutes sit just before the session run.
+        let since = SystemTime::now()
+            .checked_sub(chrome_labels::ERROR_SUMMARY_WINDOW)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let errors = self.backends.observability.error_summary(since);
+        if let Some(segment) = chrome_labels::error_segment(&errors) {
+            header.insert(header.len() - 1, segment);
+        }
         let (footer_left, footer_right) = chrome_labels::footer_segments(env!("CARGO_PKG_VERSION"));
         let chrome = chrome_labels::chrome_view(
             &header,
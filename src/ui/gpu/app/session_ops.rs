@@ -1,7 +1,12 @@
 //! Terminal session operations: draining the PTY's pending bytes into the
-//! emulator, and tearing down + respawning the Claude session (Cmd+R).
+//! emulator, tearing down + respawning the Claude session (Cmd+R), and the
+//! `--replay` / `--record` input scripts.
 
-use term_core::create_emulator;
+use std::io;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use term_core::{QueryReplies, TerminalEmulator, VtEmulator};
 use term_gpu::ScrollState;
 
 use crate::ui::gpu::pty::ChildPty;
@@ -42,7 +47,16 @@ impl super::GpuApp {
             for chunk in chunks {
                 emu.process(&chunk);
             }
+            // Ship DSR / DA / DECRQM replies back to the child — a program
+            // blocking on `CSI 6 n` would otherwise hang waiting for them.
+            let responses = emu.take_responses();
+            if !responses.is_empty() {
+                if let Some(pty) = self.session.pty.as_mut() {
+                    let _ = pty.write(&responses);
+                }
+            }
         }
+        self.sync_window_title();
         self.refresh_scroll_geometry();
         if was_at_bottom {
             self.state.scroll.offset_y = 0.0;
@@ -64,17 +78,47 @@ impl super::GpuApp {
         true
     }
 
+    /// Mirror the child's title (OSC 0/2, restored by an XTWINOPS pop) onto
+    /// the window as `anyclaude — <title>`; plain `anyclaude` when unset.
+    fn sync_window_title(&mut self) {
+        let Some(title) = self.session.emulator.as_ref().map(|e| e.title()) else {
+            return;
+        };
+        if title == self.session.shown_title {
+            return;
+        }
+        self.session.shown_title = title.to_string();
+        if let Some(w) = self.window.as_ref() {
+            if self.session.shown_title.is_empty() {
+                w.set_title("anyclaude");
+            } else {
+                w.set_title(&format!("anyclaude — {}", self.session.shown_title));
+            }
+        }
+    }
+
     /// Tear down the running Claude session and start a fresh one with
     /// the same spawn params. Wired to Cmd+R. The terminal state
     /// (emulator, scroll, selection) is reset so the new session
     /// renders into a clean panel.
     ///
-    /// The old reader thread exits on its own as soon as its master
-    /// PTY is dropped — the spawn flow is fire-and-forget.
+    /// A live session is replaced in place by `ChildPty::respawn`, which
+    /// signals the old child and joins its reader thread; if the fresh
+    /// spawn fails the old child keeps running.
     pub(super) fn restart_pty(&mut self) {
-        self.session.pty = None;
         let (cols, rows) = self.state.grid_size;
-        self.session.emulator = Some(create_emulator(cols, rows, SCROLLBACK_LINES));
+        self.session.resize(cols, rows);
+        let result = if let Some(pty) = self.session.pty.as_mut() {
+            pty.respawn()
+        } else {
+            self.spawn_child(cols, rows).map(|pty| self.session.pty = Some(pty))
+        };
+        if let Err(e) = result {
+            eprintln!("anyclaude: failed to restart shell: {e}");
+            return;
+        }
+
+        self.session.emulator = Some(self.new_emulator(cols, rows));
         self.state.scroll = ScrollState::default();
         self.state.scroll_velocity = None;
         self.timers.cancel_momentum();
@@ -82,9 +126,33 @@ impl super::GpuApp {
         self.state.selection = None;
         self.state.dragging_selection = false;
         self.state.last_click = None;
+        self.state.child_exit = None;
+        self.request_redraw();
+    }
+
+    /// A fresh emulator at `cols × rows`, colourless when
+    /// `terminal.no_color` or `NO_COLOR` says so, answering the queries
+    /// `terminal.answer_*` enables and reading C1 controls when
+    /// `terminal.c1_controls` is set.
+    pub(super) fn new_emulator(&self, cols: usize, rows: usize) -> Box<dyn TerminalEmulator> {
+        let terminal = self.backends.backend_state.get_config().terminal;
+        let mut emulator = VtEmulator::new(cols, rows, SCROLLBACK_LINES);
+        emulator.set_no_color(terminal.color_disabled());
+        emulator.set_query_replies(QueryReplies {
+            cursor_position: terminal.answer_cursor_position,
+            device_attributes: terminal.answer_device_attributes,
+        });
+        emulator.set_c1_controls(terminal.c1_controls);
+        Box::new(emulator)
+    }
 
+    /// Spawn the Claude child at `cols × rows` from the session's spawn
+    /// params, wired to wake the event loop on output and on exit.
+    pub(super) fn spawn_child(&self, cols: usize, rows: usize) -> io::Result<ChildPty> {
         let proxy = self.proxy.clone();
-        match ChildPty::spawn(
+        let exit_proxy = self.proxy.clone();
+        let terminal = self.backends.backend_state.get_config().terminal;
+        let mut pty = ChildPty::spawn(
             cols as u16,
             rows as u16,
             self.session.spawn_command.clone(),
@@ -93,14 +161,53 @@ impl super::GpuApp {
             move || {
                 let _ = proxy.send_event(UserEvent::PtyBytesArrived);
             },
-        ) {
-            Ok(pty) => {
-                self.session.pty = Some(pty);
-            }
-            Err(e) => {
-                eprintln!("anyclaude: failed to restart shell: {e}");
+            move || {
+                let _ = exit_proxy.send_event(UserEvent::ChildExited);
+            },
+        )?;
+        pty.set_shutdown_grace(Duration::from_millis(terminal.shutdown_grace_ms));
+        pty.set_fallback_size(terminal.default_cols, terminal.default_rows);
+        Ok(pty)
+    }
+
+    /// Start pacing the `--replay` recording into the child on a thread;
+    /// each write wakes the loop with `ReplayInput`. Only the first child
+    /// gets the replay; a restart doesn't rerun it.
+    pub(super) fn start_replay(&mut self) {
+        let Some(recording) = self.replay.take() else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        let proxy = self.proxy.clone();
+        std::thread::spawn(move || {
+            recording.replay(|bytes| {
+                tx.send(bytes.to_vec()).is_ok() && proxy.send_event(UserEvent::ReplayInput).is_ok()
+            });
+            crate::metrics::app_log("replay", "Input replay finished");
+        });
+        self.replay_rx = Some(rx);
+    }
+
+    /// Write the replay input queued so far to the child.
+    pub(super) fn feed_replay(&mut self) {
+        let Some(rx) = self.replay_rx.as_ref() else {
+            return;
+        };
+        let pending: Vec<Vec<u8>> = rx.try_iter().collect();
+        for bytes in pending {
+            if let Err(e) = self.session.write_input(&bytes) {
+                eprintln!("anyclaude: replay write failed: {e}");
             }
         }
-        self.request_redraw();
+    }
+
+    /// Write the `--record` recording, if one was requested.
+    pub(super) fn save_recording(&self) {
+        let (Some(path), Some(recorder)) = (self.record_path.as_ref(), self.session.recorder.as_ref()) else {
+            return;
+        };
+        if let Err(e) = recorder.recording().save(path) {
+            eprintln!("anyclaude: failed to save input recording to {}: {e}", path.display());
+        }
     }
 }
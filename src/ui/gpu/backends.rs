@@ -8,6 +8,7 @@
 use crate::backend::{AgentBackendState, BackendState};
 use crate::config::ClaudeSettingsManager;
 use crate::metrics::ObservabilityHub;
+use crate::ui::history::{HistoryEntry, HistoryStore};
 
 pub(super) struct Backends {
     /// Live proxy backend state. The backend popup reads the list; Enter calls
@@ -21,4 +22,8 @@ pub(super) struct Backends {
     pub(super) observability: ObservabilityHub,
     /// Settings registry + current values, persisted on the Cmd+E popup's Enter.
     pub(super) settings_manager: ClaudeSettingsManager,
+    /// On-disk history, rewritten after every switch.
+    pub(super) history_store: HistoryStore,
+    /// Entries from earlier sessions, loaded from `history_store` at startup.
+    pub(super) past_history: Vec<HistoryEntry>,
 }
@@ -4,33 +4,48 @@
 //! server / teammate shim setup, prepares spawn params for the Claude
 //! Code child, builds the winit event loop with a [`UserEvent`] proxy,
 //! and hands off to [`GpuApp`]. The proxy + runtime stay alive for the
-//! duration of `event_loop.run_app` and drop cleanly once the user
-//! quits.
+//! duration of `event_loop.run_app`; once the user quits, the proxy is
+//! shut down gracefully (draining connections and flushing the debug
+//! log) before the runtime drops.
+//!
+//! `--record` / `--replay` name input script files (see
+//! [`super::replay`]); a replay file is read before anything starts.
+//!
+//! With `--no-proxy` the proxy server, teammate shim and subagent hooks
+//! are skipped entirely: nothing listens, and the child talks to the
+//! user's own `ANTHROPIC_BASE_URL`. The backend state the chrome reads is
+//! still built from config, but switching it has no effect.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use uuid::Uuid;
 use winit::event_loop::EventLoop;
 
-use crate::args::{build_spawn_params, ArgAssembler};
-use crate::config::{ClaudeSettingsManager, Config, ConfigStore, DebugLogLevel};
-use crate::metrics::{init_global_logger, DebugLogger};
-use crate::proxy::ProxyServer;
+use crate::args::{build_spawn_params, ArgAssembler, SpawnParams};
+use crate::backend::{AgentBackendState, BackendState};
+use crate::config::{ClaudeSettingsManager, Config, ConfigOverrides, ConfigStore, DebugLogLevel};
+use crate::metrics::{init_global_logger, DebugLogger, ObservabilityHub};
+use crate::proxy::{ProxyHandle, ProxyServer};
 use crate::shim::TeammateShim;
 
 use super::app::{GpuApp, UserEvent};
+use super::replay::InputRecording;
 
 /// Entry point for the GPU UI. Routed from `main.rs`.
 pub fn run(
-    backend_override: Option<String>,
+    overrides: ConfigOverrides,
     claude_args: Vec<String>,
+    no_proxy: bool,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
 ) -> std::io::Result<()> {
-    // --- Config + backend override ----------------------------------
+    let replay = replay.as_deref().map(InputRecording::load).transpose()?;
+
+    // --- Config + command-line overrides ----------------------------
     let mut config = Config::load()
         .map_err(|e| std::io::Error::other(format!("Failed to load config: {e}")))?;
-    if let Some(name) = backend_override {
-        config.defaults.active = name;
-    }
+    overrides.apply(&mut config);
     let config_path = Config::config_path();
     let config_store = ConfigStore::new(config, config_path);
     let base_proxy_url = config_store.get().proxy.base_url.clone();
@@ -77,15 +92,128 @@ pub fn run(
     let debug_logger = Arc::new(DebugLogger::new(debug_config));
     init_global_logger(debug_logger.clone());
 
+    let (handles, teammate_shim) = if no_proxy {
+        crate::metrics::app_log("gpu_runtime", "--no-proxy: proxy server not started");
+        spawn = spawn.without_proxy();
+        let backend_state = BackendState::from_config(config_store.get())
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let handles = ProxyHandles {
+            backend_state,
+            subagent_backend: AgentBackendState::new(None),
+            teammate_backend: AgentBackendState::new(None),
+            observability: ObservabilityHub::new(1),
+            server: None,
+        };
+        (handles, None)
+    } else {
+        start_proxy(&config_store, &debug_logger, &session_token, &session_id, &async_runtime, &mut spawn)?
+    };
+
+    // --- Hand off to the winit event loop ---------------------------
+    let _ = scrollback_lines; // Reserved for future grid configuration.
+    let event_loop = EventLoop::<UserEvent>::with_user_event()
+        .build()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let proxy = event_loop.create_proxy();
+    let server = handles.server;
+    #[cfg(unix)]
+    forward_shutdown_signals(&async_runtime, proxy.clone());
+    let mut app = GpuApp::new(
+        proxy,
+        spawn.command,
+        spawn.args,
+        spawn.env,
+        handles.backend_state,
+        handles.subagent_backend,
+        handles.teammate_backend,
+        handles.observability,
+        settings_manager,
+    )
+    .with_input_script(record, replay);
+    event_loop
+        .run_app(&mut app)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    // Closing the window drops the session; its child gets the rest of
+    // the shutdown grace here, off the UI, before the wrapper exits.
+    drop(app);
+    crate::ui::gpu::pty::wait_for_teardowns();
+
+    // Stop the proxy the graceful way so its shutdown callbacks (debug
+    // log flush, socket cleanup) run before the runtime goes away.
+    if let Some(server) = server {
+        server.handle.shutdown();
+        let _ = async_runtime.block_on(server.task);
+    }
+
+    // Tokio runtime + teammate shim drop here, cleaning up the shim's
+    // temp directory.
+    drop(teammate_shim);
+    drop(async_runtime);
+    Ok(())
+}
+
+/// Turn SIGINT/SIGTERM sent to the wrapper into a
+/// [`UserEvent::ShutdownSignal`], so the event loop passes the signal on
+/// to the Claude child and quits instead of dying with the child orphaned.
+#[cfg(unix)]
+fn forward_shutdown_signals(
+    async_runtime: &tokio::runtime::Runtime,
+    proxy: winit::event_loop::EventLoopProxy<UserEvent>,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    async_runtime.spawn(async move {
+        let (Ok(mut interrupt), Ok(mut terminate)) =
+            (signal(SignalKind::interrupt()), signal(SignalKind::terminate()))
+        else {
+            crate::metrics::app_log("gpu_runtime", "Signal handlers unavailable; not forwarding");
+            return;
+        };
+        let signo = tokio::select! {
+            _ = interrupt.recv() => libc::SIGINT,
+            _ = terminate.recv() => libc::SIGTERM,
+        };
+        crate::metrics::app_log("gpu_runtime", &format!("Forwarding signal {signo} to the child"));
+        let _ = proxy.send_event(UserEvent::ShutdownSignal(signo));
+    });
+}
+
+/// State the UI shares with the proxy: the backend selections and the
+/// observability hub, plus the running server (absent with `--no-proxy`).
+struct ProxyHandles {
+    backend_state: BackendState,
+    subagent_backend: AgentBackendState,
+    teammate_backend: AgentBackendState,
+    observability: ObservabilityHub,
+    server: Option<RunningProxy>,
+}
+
+/// The proxy task and the handle that asks it to stop.
+struct RunningProxy {
+    handle: ProxyHandle,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Bind the proxy, set up the teammate shim, point `spawn` at both, and
+/// run the proxy as a task on `async_runtime`.
+fn start_proxy(
+    config_store: &ConfigStore,
+    debug_logger: &Arc<DebugLogger>,
+    session_token: &str,
+    session_id: &str,
+    async_runtime: &tokio::runtime::Runtime,
+    spawn: &mut SpawnParams,
+) -> std::io::Result<(ProxyHandles, Option<TeammateShim>)> {
     // --- Proxy server + bind ----------------------------------------
     let mut proxy_server = ProxyServer::new(
         config_store.clone(),
         debug_logger.clone(),
-        Some(session_token.clone()),
+        Some(session_token.to_string()),
     )
     .map_err(|e| std::io::Error::other(e.to_string()))?;
     let (actual_addr, actual_base_url) = async_runtime
-        .block_on(async { proxy_server.try_bind(&config_store).await })
+        .block_on(async { proxy_server.try_bind(config_store).await })
         .map_err(|e| std::io::Error::other(e.to_string()))?;
     // Patch ANTHROPIC_BASE_URL with the actually-bound port.
     for (key, value) in &mut spawn.env {
@@ -97,7 +225,7 @@ pub fn run(
     // --- Teammate shim (optional — config-driven) -------------------
     let log_enabled = config_store.get().debug_logging.level != DebugLogLevel::Off;
     let teammate_shim =
-        match TeammateShim::create(actual_addr.port(), &session_token, &session_id, log_enabled) {
+        match TeammateShim::create(actual_addr.port(), session_token, session_id, log_enabled) {
             Ok(shim) => {
                 crate::metrics::app_log(
                     "gpu_runtime",
@@ -137,36 +265,20 @@ pub fn run(
     let subagent_backend = proxy_server.subagent_backend();
     let teammate_backend = proxy_server.teammate_backend();
     let observability = proxy_server.observability();
-    let _proxy_task = async_runtime.spawn(async move {
+    let handle = proxy_server.handle();
+    let task = async_runtime.spawn(async move {
         if let Err(e) = proxy_server.run().await {
             crate::metrics::app_log_error("gpu_runtime", "Proxy server exited", &e.to_string());
         }
     });
 
-    // --- Hand off to the winit event loop ---------------------------
-    let _ = scrollback_lines; // Reserved for future grid configuration.
-    let event_loop = EventLoop::<UserEvent>::with_user_event()
-        .build()
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
-    let proxy = event_loop.create_proxy();
-    let mut app = GpuApp::new(
-        proxy,
-        spawn.command,
-        spawn.args,
-        spawn.env,
+    let handles = ProxyHandles {
         backend_state,
         subagent_backend,
         teammate_backend,
         observability,
-        settings_manager,
-    );
-    event_loop
-        .run_app(&mut app)
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
+        server: Some(RunningProxy { handle, task }),
+    };
+    Ok((handles, teammate_shim))
 
-    // Tokio runtime + teammate shim drop here, shutting the proxy
-    // task down and cleaning up the shim's temp directory.
-    drop(teammate_shim);
-    drop(async_runtime);
-    Ok(())
 }
@@ -1,14 +1,25 @@
 //! Cmd+Shift+D snapshot dump for the GPU UI.
 //!
 //! Prints grid dimensions, scroll offset, cursor state, visible-row
-//! range, title, and the first four visible rows (chars + non-zero
-//! attribute flags) to stderr. Used to capture the emulator's
+//! range, title, the first four visible rows (chars + non-zero
+//! attribute flags), the most recent backend switches and routing
+//! decisions, and the SSE event histogram of the last streamed response
+//! to stderr. Used to capture the emulator's
 //! perspective at the moment a user-visible rendering bug surfaces.
 //! See `feedback_capture_pty_bytes_for_render_bugs` — this is the
 //! companion to the `ANYCLAUDE_DEBUG_PTY` byte tee in `pty.rs`.
 
 use term_core::RenderSnapshot;
 
+use crate::backend::SwitchLogEntry;
+use crate::metrics::{RequestRecord, RoutingLogEntry};
+
+/// How many backend switches the dump lists, newest first.
+const RECENT_SWITCHES: usize = 10;
+
+/// How many routing decisions the dump lists, newest first.
+const RECENT_ROUTES: usize = 10;
+
 /// Dump the diagnostic snapshot to stderr. Free function so the
 /// caller (`GpuApp::on_diagnostic_key`) can pass borrowed pieces of
 /// itself without holding `&self` across the whole call.
@@ -17,6 +28,9 @@ pub(super) fn dump_snapshot(
     scroll_offset_y: f32,
     scroll_max_offset: f32,
     snapshot: Option<&RenderSnapshot>,
+    switch_log: &[SwitchLogEntry],
+    routing: &[RoutingLogEntry],
+    last_stream: Option<&RequestRecord>,
 ) {
     eprintln!("=== anyclaude diagnostic snapshot ===");
     eprintln!("grid_size: {} cols x {} rows", grid_size.0, grid_size.1);
@@ -24,6 +38,24 @@ pub(super) fn dump_snapshot(
         "scroll: offset_y={:.2}, max={:.2}",
         scroll_offset_y, scroll_max_offset
     );
+    eprintln!(
+        "backend switches: {} total, newest first",
+        switch_log.len()
+    );
+    for entry in switch_log.iter().rev().take(RECENT_SWITCHES) {
+        eprintln!("    {entry}");
+    }
+    eprintln!("routing decisions: {} kept, newest first", routing.len());
+    for entry in routing.iter().take(RECENT_ROUTES) {
+        eprintln!("    {entry}");
+    }
+    match last_stream.and_then(|r| r.sse_events.as_ref().map(|h| (r, h))) {
+        Some((record, histogram)) => eprintln!(
+            "last stream: {} ({}) sse_events: {}",
+            record.id, record.backend, histogram
+        ),
+        None => eprintln!("last stream: -"),
+    }
     let Some(snap) = snapshot else {
         eprintln!("(no emulator)");
         eprintln!("=== end snapshot ===");
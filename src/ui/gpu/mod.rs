@@ -14,7 +14,8 @@ mod chrome;
 mod diagnostic;
 mod overlay;
 pub mod pty;
-mod session;
+pub mod replay;
+pub mod session;
 mod text;
 mod timers;
 
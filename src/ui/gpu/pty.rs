@@ -6,38 +6,158 @@
 //! wake up and drain. Resize and write are direct pass-throughs to
 //! the master PTY.
 //!
-//! Intentionally simpler than the legacy `pty::PtySession` — there's
-//! no `AppEvent::ProcessExit` signaling because the GPU UI doesn't
-//! own a restart state machine yet. Restart support lands together
-//! with the post-Phase-5 MVI App store integration.
+//! Resizes are driven by winit `Resized` events rather than SIGWINCH,
+//! so the same path covers Unix PTYs and Windows ConPTY (which
+//! `native_pty_system` selects on Windows) without a platform watcher.
+//!
+//! The reader stops at EOF (or the hangup a Linux PTY reports as `EIO`
+//! once every slave fd is closed). Any other read error is logged and
+//! queued for [`ChildPty::poll_read_error`], and `on_data` fires once more
+//! so the host notices instead of sitting on a frozen screen.
+//!
+//! A second thread blocks in `wait()` on the child. When it exits the
+//! code is queued for [`ChildPty::poll_exit`] and an `on_exit` callback
+//! wakes the host loop, which keeps the final screen and reports the
+//! status (restart stays a user action, Cmd+R → [`ChildPty::respawn`]).
+//!
+//! Teardown is graceful: the child's process group gets a signal (SIGTERM
+//! on drop) and is only force-killed once the shutdown grace runs out.
+//! Dropping a session, or [`ChildPty::shutdown`], does the waiting on a
+//! background thread so respawn and window close never stall the UI;
+//! [`wait_for_teardowns`] lets the wrapper finish them before it exits.
+//! The wrapper forwards its own SIGINT/SIGTERM through the same path.
 
 use std::fs::OpenOptions;
 use std::io::{self, Read, Write};
-use std::sync::mpsc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+
+/// How long teardown waits for the old reader thread to see EOF before
+/// detaching it. A grandchild that inherited the PTY can keep it open
+/// past the child's death; the UI must not hang on that.
+const READER_JOIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long [`ChildPty::terminate`] waits for the child to exit on its
+/// own before force-killing it, unless overridden per session.
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
 
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+/// Background teardowns started by dropped sessions, joined by
+/// [`wait_for_teardowns`].
+static TEARDOWNS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Block until every background teardown has signalled (and if need be
+/// killed) its child. Each is bounded by its shutdown grace plus
+/// [`READER_JOIN_TIMEOUT`]. Called once the UI is gone, so an exiting
+/// wrapper doesn't leave a child that ignored SIGTERM behind.
+pub fn wait_for_teardowns() {
+    let handles = std::mem::take(&mut *TEARDOWNS.lock().unwrap_or_else(|e| e.into_inner()));
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
+
+/// Size assumed when the PTY can't report one, unless overridden per
+/// session (`terminal.default_cols` / `default_rows`).
+pub const DEFAULT_PTY_SIZE: (u16, u16) = (80, 24);
+
+/// Size queries made before falling back, and the pause between them.
+/// A query can fail transiently while the PTY is still being set up.
+const SIZE_QUERY_ATTEMPTS: usize = 3;
+const SIZE_QUERY_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Ask `query` for a `(cols, rows)` size, retrying a failed or zero-sized
+/// answer a few times before settling on `fallback`.
+pub fn query_size<E>(
+    mut query: impl FnMut() -> Result<(u16, u16), E>,
+    fallback: (u16, u16),
+) -> (u16, u16) {
+    for attempt in 0..SIZE_QUERY_ATTEMPTS {
+        if attempt > 0 {
+            std::thread::sleep(SIZE_QUERY_RETRY_DELAY);
+        }
+        if let Ok((cols, rows)) = query() {
+            if cols > 0 && rows > 0 {
+                return (cols, rows);
+            }
+        }
+    }
+    fallback
+}
+
+/// Everything needed to (re)launch the child: the command line and the
+/// host callbacks. Shared so `respawn` can relaunch without the caller.
+struct SpawnSpec {
+    command: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    on_data: Arc<dyn Fn() + Send + Sync>,
+    on_exit: Arc<dyn Fn() + Send + Sync>,
+}
 
 pub struct ChildPty {
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
     bytes_rx: mpsc::Receiver<Vec<u8>>,
+    read_error_rx: mpsc::Receiver<io::Error>,
+    exit_rx: mpsc::Receiver<u32>,
+    /// Set by the waiter thread once the child has been reaped.
+    exited: Arc<AtomicBool>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    reader: Option<JoinHandle<()>>,
+    pid: Option<u32>,
+    shutdown_grace: Duration,
+    fallback_size: (u16, u16),
+    spec: Arc<SpawnSpec>,
 }
 
 impl ChildPty {
     /// Spawn `command` with `args` and the additional environment
     /// vars in `env`. `on_data` fires from the reader thread after
-    /// every successful read so the caller can request a redraw.
-    pub fn spawn<F>(
+    /// every successful read so the caller can request a redraw;
+    /// `on_exit` fires from the waiter thread when the child exits.
+    pub fn spawn<F, G>(
         cols: u16,
         rows: u16,
         command: String,
         args: Vec<String>,
         env: Vec<(String, String)>,
         on_data: F,
+        on_exit: G,
     ) -> io::Result<Self>
     where
-        F: Fn() + Send + 'static,
+        F: Fn() + Send + Sync + 'static,
+        G: Fn() + Send + Sync + 'static,
     {
+        let spec = Arc::new(SpawnSpec {
+            command,
+            args,
+            env,
+            on_data: Arc::new(on_data),
+            on_exit: Arc::new(on_exit),
+        });
+        Self::launch(cols, rows, spec)
+    }
+
+    /// Replace the child with a fresh one from the same command, args,
+    /// env, and callbacks, at the current PTY size. The old child is
+    /// signalled and torn down in the background. If the new child fails
+    /// to spawn, the old session is left untouched.
+    pub fn respawn(&mut self) -> io::Result<()> {
+        let (cols, rows) = self.size();
+        let mut fresh = Self::launch(cols, rows, Arc::clone(&self.spec))?;
+        fresh.shutdown_grace = self.shutdown_grace;
+        fresh.fallback_size = self.fallback_size;
+        // Dropping the old session runs its teardown.
+        drop(std::mem::replace(self, fresh));
+        Ok(())
+    }
+
+    fn launch(cols: u16, rows: u16, spec: Arc<SpawnSpec>) -> io::Result<Self> {
         let pty_system = native_pty_system();
         let pair = pty_system
             .openpty(PtySize {
@@ -48,18 +168,21 @@ impl ChildPty {
             })
             .map_err(|e| io::Error::other(e.to_string()))?;
 
-        let mut cmd = CommandBuilder::new(command);
-        cmd.args(args);
+        let mut cmd = CommandBuilder::new(&spec.command);
+        cmd.args(&spec.args);
         cmd.cwd(std::env::current_dir()?);
         cmd.env("TERM", "xterm-256color");
-        for (key, value) in env {
+        for (key, value) in &spec.env {
             cmd.env(key, value);
         }
-        pair.slave
+        let mut child = pair
+            .slave
             .spawn_command(cmd)
             .map_err(|e| io::Error::other(e.to_string()))?;
         // Drop slave so the PTY closes when the child exits.
         drop(pair.slave);
+        let killer = child.clone_killer();
+        let pid = child.process_id();
 
         let mut reader = pair
             .master
@@ -86,22 +209,38 @@ impl ChildPty {
                     .ok()
             });
 
+        let (read_error_tx, read_error_rx) = mpsc::channel::<io::Error>();
+        let on_data = Arc::clone(&spec.on_data);
+        let reader_thread = std::thread::spawn(move || {
+            let result = pump_output(&mut reader, |bytes| {
+                if let Some(f) = trace_file.as_mut() {
+                    let _ = f.write_all(bytes);
+                }
+                if tx.send(bytes.to_vec()).is_err() {
+                    return false;
+                }
+                on_data();
+                true
+            });
+            if let Err(e) = result {
+                crate::metrics::app_log_error("pty", "PTY read failed; no further output", &e.to_string());
+                if read_error_tx.send(e).is_ok() {
+                    on_data();
+                }
+            }
+        });
+
+        let (exit_tx, exit_rx) = mpsc::channel::<u32>();
+        let exited = Arc::new(AtomicBool::new(false));
+        let exited_flag = Arc::clone(&exited);
+        let on_exit = Arc::clone(&spec.on_exit);
         std::thread::spawn(move || {
-            let mut buf = [0u8; 4096];
-            loop {
-                match reader.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        let bytes = &buf[..n];
-                        if let Some(f) = trace_file.as_mut() {
-                            let _ = f.write_all(bytes);
-                        }
-                        if tx.send(bytes.to_vec()).is_err() {
-                            break;
-                        }
-                        on_data();
-                    }
-                    Err(_) => break,
+            if let Ok(status) = child.wait() {
+                exited_flag.store(true, Ordering::Release);
+                // The receiver is gone once this session was restarted;
+                // a stale exit must not wake the loop for the new child.
+                if exit_tx.send(status.exit_code()).is_ok() {
+                    on_exit();
                 }
             }
         });
@@ -110,9 +249,113 @@ impl ChildPty {
             master: pair.master,
             writer,
             bytes_rx: rx,
+            read_error_rx,
+            exit_rx,
+            exited,
+            killer,
+            reader: Some(reader_thread),
+            pid,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            fallback_size: DEFAULT_PTY_SIZE,
+            spec,
         })
     }
 
+    /// The child's OS process id, when the platform reports one.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// The child's current working directory. Read live from `/proc` on
+    /// Linux; elsewhere the directory it was spawned in.
+    pub fn cwd(&self) -> Option<PathBuf> {
+        #[cfg(target_os = "linux")]
+        if let Some(pid) = self.pid {
+            if let Ok(dir) = std::fs::read_link(format!("/proc/{pid}/cwd")) {
+                return Some(dir);
+            }
+        }
+        std::env::current_dir().ok()
+    }
+
+    /// How long [`terminate`](Self::terminate) (and teardown) waits for a
+    /// graceful exit before force-killing. Kept across [`respawn`](Self::respawn).
+    pub fn set_shutdown_grace(&mut self, grace: Duration) {
+        self.shutdown_grace = grace;
+    }
+
+    /// The `(cols, rows)` [`size`](Self::size) reports when the PTY can't
+    /// tell. Kept across [`respawn`](Self::respawn).
+    pub fn set_fallback_size(&mut self, cols: u16, rows: u16) {
+        self.fallback_size = (cols.max(1), rows.max(1));
+    }
+
+    /// Ask the child to exit: on Unix `signal` goes to its process group
+    /// (the PTY child leads its own session), then the child gets up to
+    /// the shutdown grace before it is force-killed. Elsewhere the child
+    /// is killed directly. Returns whether it exited on its own.
+    ///
+    /// Blocks the calling thread for up to the grace; the UI thread uses
+    /// [`shutdown`](Self::shutdown) instead.
+    pub fn terminate(&mut self, signal: i32) -> bool {
+        self.stopper().stop(signal)
+    }
+
+    /// End the session: send `signal` now and leave the grace, the kill
+    /// and the reader join to a background thread (see
+    /// [`wait_for_teardowns`]). Returns immediately.
+    pub fn shutdown(mut self, signal: i32) {
+        self.detach_teardown(signal);
+    }
+
+    fn stopper(&self) -> Stopper {
+        Stopper {
+            killer: self.killer.clone_killer(),
+            exited: Arc::clone(&self.exited),
+            pid: self.pid,
+            grace: self.shutdown_grace,
+        }
+    }
+
+    /// Hang up the exit channel, so the waiter's send fails and the
+    /// stopped child never fires `on_exit` for the host, then run the
+    /// teardown on its own thread.
+    fn detach_teardown(&mut self, signal: i32) {
+        let Some(reader) = self.reader.take() else {
+            return;
+        };
+        self.exit_rx = mpsc::channel().1;
+        let mut stopper = self.stopper();
+        stopper.send(signal);
+        let handle = std::thread::spawn(move || {
+            stopper.wait_or_kill();
+            let deadline = Instant::now() + READER_JOIN_TIMEOUT;
+            while !reader.is_finished() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            if reader.is_finished() {
+                let _ = reader.join();
+            } else {
+                crate::metrics::app_log("pty", "PTY reader still blocked after child teardown; detaching");
+            }
+        });
+        let mut teardowns = TEARDOWNS.lock().unwrap_or_else(|e| e.into_inner());
+        teardowns.retain(|handle| !handle.is_finished());
+        teardowns.push(handle);
+    }
+
+    /// The child's exit code, returned once — on the first poll after
+    /// the child has exited. `None` while it runs and on later polls.
+    pub fn poll_exit(&mut self) -> Option<u32> {
+        self.exit_rx.try_recv().ok()
+    }
+
+    /// The error that stopped the reader thread, returned once. `None`
+    /// while output flows and after a normal EOF.
+    pub fn poll_read_error(&mut self) -> Option<io::Error> {
+        self.read_error_rx.try_recv().ok()
+    }
+
     /// Drain every byte chunk currently queued by the reader thread.
     /// Returns empty when no PTY output is pending.
     pub fn drain(&mut self) -> Vec<Vec<u8>> {
@@ -123,15 +366,28 @@ impl ChildPty {
         chunks
     }
 
+    /// Resize the master PTY. Dimensions are clamped to at least 1×1:
+    /// ConPTY rejects a zero-sized console, which a minimized window
+    /// can otherwise produce.
     pub fn resize(&self, cols: u16, rows: u16) {
         let _ = self.master.resize(PtySize {
-            rows,
-            cols,
+            rows: rows.max(1),
+            cols: cols.max(1),
             pixel_width: 0,
             pixel_height: 0,
         });
     }
 
+    /// The master PTY's current size as `(cols, rows)`. The query is
+    /// retried briefly; if the platform still can't report it, the
+    /// fallback size (see [`set_fallback_size`](Self::set_fallback_size)).
+    pub fn size(&self) -> (u16, u16) {
+        query_size(
+            || self.master.get_size().map(|size| (size.cols, size.rows)),
+            self.fallback_size,
+        )
+    }
+
     /// Write `bytes` to the PTY's stdin. Returns an error when the
     /// child has closed (broken pipe) or the underlying write fails.
     pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
@@ -139,3 +395,102 @@ impl ChildPty {
         self.writer.flush()
     }
 }
+
+impl Drop for ChildPty {
+    /// Terminate the child (SIGTERM, then a kill after the grace) and
+    /// join the reader thread, which sees EOF once the child's end of the
+    /// PTY closes, all on a background thread. The reader join is bounded
+    /// by [`READER_JOIN_TIMEOUT`]; a reader still blocked after that is
+    /// detached.
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        self.detach_teardown(libc::SIGTERM);
+        #[cfg(not(unix))]
+        self.detach_teardown(0);
+    }
+}
+
+/// What teardown needs from a session, detached from it so the wait can
+/// run on another thread.
+struct Stopper {
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    exited: Arc<AtomicBool>,
+    pid: Option<u32>,
+    grace: Duration,
+}
+
+impl Stopper {
+    /// Signal, then wait out the grace and kill. Returns whether the
+    /// child exited on its own.
+    fn stop(&mut self, signal: i32) -> bool {
+        self.send(signal);
+        self.wait_or_kill()
+    }
+
+    /// On Unix, send `signal` to the child's process group. Elsewhere
+    /// there is no signal; [`wait_or_kill`](Self::wait_or_kill) kills.
+    fn send(&self, signal: i32) {
+        #[cfg(unix)]
+        if let Some(pid) = self.pid.filter(|_| !self.exited.load(Ordering::Acquire)) {
+            // SAFETY: killpg has no memory-safety preconditions; a stale
+            // group id only yields ESRCH.
+            unsafe {
+                libc::killpg(pid as libc::pid_t, signal);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = signal;
+    }
+
+    fn wait_or_kill(&mut self) -> bool {
+        if self.exited.load(Ordering::Acquire) {
+            return true;
+        }
+        #[cfg(unix)]
+        if self.pid.is_some() {
+            let deadline = Instant::now() + self.grace;
+            while !self.exited.load(Ordering::Acquire) && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            if self.exited.load(Ordering::Acquire) {
+                return true;
+            }
+        }
+        crate::metrics::app_log("pty", "Child did not exit within the shutdown grace; killing");
+        let _ = self.killer.kill();
+        false
+    }
+}
+
+/// Read `reader` to the end, handing each chunk to `deliver` until it
+/// returns `false`. EOF and a PTY hangup end with `Ok`; any other read
+/// error is returned. Interrupted reads are retried.
+pub fn pump_output<R: Read>(reader: &mut R, mut deliver: impl FnMut(&[u8]) -> bool) -> io::Result<()> {
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                if !deliver(&buf[..n]) {
+                    return Ok(());
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) if is_hangup(&e) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Linux reports a closed slave side as `EIO` rather than EOF.
+fn is_hangup(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        err.raw_os_error() == Some(libc::EIO)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = err;
+        false
+    }
+}
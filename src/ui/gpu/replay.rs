@@ -0,0 +1,148 @@
+//! Recorded PTY input, for scripted demos and regression runs.
+//!
+//! `--record <file>` logs every write the UI makes to the child — keys
+//! (as [`term_gpu::encode_key`] bytes), pastes, mouse reports — with the
+//! gap since the previous one. `--replay <file>` feeds such a recording
+//! back into a fresh child at the same pace.
+//!
+//! The file is JSON lines, one write per line: `{"delay_ms":120,"text":"hi"}`,
+//! or `"bytes":[...]` for a write that isn't valid UTF-8. Recordings can be
+//! written by hand; control keys are plain JSON escapes (`"\u001b[A"`).
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// One write to the child, `delay` after the previous one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedInput {
+    pub delay: Duration,
+    pub bytes: Vec<u8>,
+}
+
+/// A sequence of writes to replay into the child.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputRecording {
+    pub events: Vec<RecordedInput>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    delay_ms: u64,
+    #[serde(flatten)]
+    payload: Payload,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Payload {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl InputRecording {
+    /// Read a recording from `path`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {e}", path.display()))
+        })
+    }
+
+    /// Parse the JSON-lines form. Blank lines are skipped; an invalid line
+    /// fails the whole recording, naming its line number.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            if raw.trim().is_empty() {
+                continue;
+            }
+            let line: Line =
+                serde_json::from_str(raw).map_err(|e| format!("line {}: {e}", index + 1))?;
+            let bytes = match line.payload {
+                Payload::Text(text) => text.into_bytes(),
+                Payload::Bytes(bytes) => bytes,
+            };
+            events.push(RecordedInput {
+                delay: Duration::from_millis(line.delay_ms),
+                bytes,
+            });
+        }
+        Ok(Self { events })
+    }
+
+    /// The JSON-lines form read by [`parse`](Self::parse).
+    pub fn to_jsonl(&self) -> String {
+        let mut out = String::new();
+        for event in &self.events {
+            let payload = match String::from_utf8(event.bytes.clone()) {
+                Ok(text) => Payload::Text(text),
+                Err(e) => Payload::Bytes(e.into_bytes()),
+            };
+            let line = Line {
+                delay_ms: event.delay.as_millis() as u64,
+                payload,
+            };
+            if let Ok(json) = serde_json::to_string(&line) {
+                out.push_str(&json);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Write the recording to `path`, replacing any existing file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_jsonl())
+    }
+
+    /// Hand each write to `send` after its delay, blocking the calling
+    /// thread. Stops early when `send` returns `false` (the child is gone).
+    pub fn replay(&self, mut send: impl FnMut(&[u8]) -> bool) {
+        for event in &self.events {
+            std::thread::sleep(event.delay);
+            if !send(&event.bytes) {
+                return;
+            }
+        }
+    }
+}
+
+/// Collects writes to the child with the time between them.
+#[derive(Debug)]
+pub struct InputRecorder {
+    last: Instant,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    /// Start recording; the first write's delay counts from now.
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            recording: InputRecording::default(),
+        }
+    }
+
+    pub fn record(&mut self, bytes: &[u8]) {
+        let now = Instant::now();
+        self.recording.events.push(RecordedInput {
+            delay: now - self.last,
+            bytes: bytes.to_vec(),
+        });
+        self.last = now;
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
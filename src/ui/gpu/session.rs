@@ -4,27 +4,76 @@
 //! orchestrate this against `AppState` (follow-mode scroll, reset on restart)
 //! and the timers. `pty` / `emulator` are lazily populated in `resumed` (they
 //! need the window's pixel size first).
+//!
+//! [`Session::resize`] is the single resize path: the coordinator calls it
+//! for `Effect::ResizeEmulatorAndPty` (winit `Resized` → `Msg::GridResized`)
+//! and on restart, and tests drive it directly.
+//!
+//! [`Session::write_input`] is the path for input the user (or a replay)
+//! sends the child; under `--record` it is logged there.
+
+use std::io;
 
 use term_core::TerminalEmulator;
 
 use crate::ui::gpu::pty::ChildPty;
+use crate::ui::gpu::replay::InputRecorder;
 
-pub(super) struct Session {
-    pub(super) pty: Option<ChildPty>,
-    pub(super) emulator: Option<Box<dyn TerminalEmulator>>,
+pub struct Session {
+    pub pty: Option<ChildPty>,
+    pub emulator: Option<Box<dyn TerminalEmulator>>,
     /// Spawn params, prepared by `run()` before the event loop; reused by
     /// `resumed` + `restart_pty`.
     pub(super) spawn_command: String,
     pub(super) spawn_args: Vec<String>,
     pub(super) spawn_env: Vec<(String, String)>,
+    /// The child-set title last mirrored onto the window (see
+    /// `sync_window_title`), so the window is only retitled on change.
+    pub(super) shown_title: String,
+    /// Set under `--record`: every [`write_input`](Self::write_input).
+    pub recorder: Option<InputRecorder>,
 }
 
 impl Session {
-    pub(super) fn new(
+    pub fn new(
         spawn_command: String,
         spawn_args: Vec<String>,
         spawn_env: Vec<(String, String)>,
     ) -> Self {
-        Self { pty: None, emulator: None, spawn_command, spawn_args, spawn_env }
+        Self {
+            pty: None,
+            emulator: None,
+            spawn_command,
+            spawn_args,
+            spawn_env,
+            shown_title: String::new(),
+            recorder: None,
+        }
+    }
+
+    /// Send input to the child, recording it when a recorder is set. A
+    /// no-op before the child is spawned.
+    pub fn write_input(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(pty) = self.pty.as_mut() else {
+            return Ok(());
+        };
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(bytes);
+        }
+        pty.write(bytes)
+    }
+
+    /// Resize the emulator grid and the PTY master together, so the child
+    /// and the surface never disagree about the size. Clamped to at least
+    /// 1×1 (see [`ChildPty::resize`]).
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.clamp(1, u16::MAX as usize);
+        let rows = rows.clamp(1, u16::MAX as usize);
+        if let Some(emu) = self.emulator.as_mut() {
+            emu.resize(cols, rows);
+        }
+        if let Some(pty) = self.pty.as_ref() {
+            pty.resize(cols as u16, rows as u16);
+        }
     }
 }
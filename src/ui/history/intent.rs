@@ -4,8 +4,16 @@ use crate::ui::history::state::HistoryEntry;
 /// [`HistoryDialogState::apply`]. Plain enum (no MVI traits).
 #[derive(Debug, Clone)]
 pub enum HistoryIntent {
-    Load { entries: Vec<HistoryEntry> },
+    /// Open with `entries` (oldest first), keeping only the newest
+    /// `capacity` of them.
+    Load {
+        entries: Vec<HistoryEntry>,
+        capacity: usize,
+    },
     Close,
     ScrollUp,
     ScrollDown,
+    /// Scroll by a full window (`MAX_VISIBLE_ROWS`), clamped at the ends.
+    PageUp,
+    PageDown,
 }
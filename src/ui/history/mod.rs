@@ -1,5 +1,7 @@
 mod intent;
 mod state;
+mod store;
 
 pub use intent::HistoryIntent;
 pub use state::{HistoryDialogState, HistoryEntry, MAX_VISIBLE_ROWS};
+pub use store::HistoryStore;
@@ -1,16 +1,35 @@
 use std::time::SystemTime;
 
+use serde::{Deserialize, Serialize};
+
+use crate::backend::SwitchLogEntry;
 use crate::ui::history::intent::HistoryIntent;
 
 /// Max history rows visible at once — drives the initial scroll-to-end and the
 /// scroll-down clamp.
 pub const MAX_VISIBLE_ROWS: usize = 14;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub timestamp: SystemTime,
     pub from_backend: Option<String>,
     pub to_backend: String,
+    /// The child's working directory at switch time, when known.
+    pub cwd: Option<String>,
+    /// The child's terminal title at switch time, when set.
+    pub title: Option<String>,
+}
+
+impl From<SwitchLogEntry> for HistoryEntry {
+    fn from(e: SwitchLogEntry) -> Self {
+        Self {
+            timestamp: e.timestamp,
+            from_backend: e.old_backend,
+            to_backend: e.new_backend,
+            cwd: e.context.cwd,
+            title: e.context.title,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -32,8 +51,14 @@ impl HistoryDialogState {
     /// old MVI `Actor::handle_intent` — same semantics, mutated in place).
     pub fn apply(&mut self, intent: HistoryIntent) {
         match intent {
-            HistoryIntent::Load { entries } => {
-                // Open scrolled to the most-recent rows.
+            HistoryIntent::Load {
+                mut entries,
+                capacity,
+            } => {
+                // Keep the newest `capacity`, then open scrolled to the
+                // most-recent rows.
+                let evict = entries.len().saturating_sub(capacity.max(1));
+                entries.drain(..evict);
                 let scroll_offset = entries.len().saturating_sub(MAX_VISIBLE_ROWS);
                 *self = HistoryDialogState::Visible {
                     entries,
@@ -41,21 +66,27 @@ impl HistoryDialogState {
                 };
             }
             HistoryIntent::Close => *self = HistoryDialogState::Hidden,
-            HistoryIntent::ScrollUp => {
-                if let HistoryDialogState::Visible { scroll_offset, .. } = self {
-                    *scroll_offset = scroll_offset.saturating_sub(1);
-                }
-            }
-            HistoryIntent::ScrollDown => {
-                if let HistoryDialogState::Visible {
-                    entries,
-                    scroll_offset,
-                } = self
-                {
-                    let max_offset = entries.len().saturating_sub(MAX_VISIBLE_ROWS);
-                    *scroll_offset = (*scroll_offset + 1).min(max_offset);
-                }
-            }
+            HistoryIntent::ScrollUp => self.scroll_up(1),
+            HistoryIntent::ScrollDown => self.scroll_down(1),
+            HistoryIntent::PageUp => self.scroll_up(MAX_VISIBLE_ROWS),
+            HistoryIntent::PageDown => self.scroll_down(MAX_VISIBLE_ROWS),
+        }
+    }
+
+    fn scroll_up(&mut self, rows: usize) {
+        if let HistoryDialogState::Visible { scroll_offset, .. } = self {
+            *scroll_offset = scroll_offset.saturating_sub(rows);
+        }
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        if let HistoryDialogState::Visible {
+            entries,
+            scroll_offset,
+        } = self
+        {
+            let max_offset = entries.len().saturating_sub(MAX_VISIBLE_ROWS);
+            *scroll_offset = (*scroll_offset + rows).min(max_offset);
         }
     }
 }
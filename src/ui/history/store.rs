@@ -0,0 +1,72 @@
+//! History persisted across sessions as JSONL, one entry per line, oldest
+//! first. Every save rewrites the file with the newest `capacity` entries,
+//! so older ones rotate out and the file never grows past the cap.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::metrics::redact_text;
+use crate::ui::history::HistoryEntry;
+
+pub struct HistoryStore {
+    path: PathBuf,
+    capacity: usize,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf, capacity: usize) -> Self {
+        Self {
+            path,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// `~/.config/anyclaude/history.jsonl`, next to the config file.
+    pub fn default_path() -> PathBuf {
+        crate::config::Config::config_path().with_file_name("history.jsonl")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The newest `capacity` saved entries, oldest first. A missing or
+    /// unreadable file is an empty history; malformed lines are skipped.
+    pub fn load(&self) -> Vec<HistoryEntry> {
+        let Ok(contents) = std::fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        let entries: Vec<HistoryEntry> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let skip = entries.len().saturating_sub(self.capacity);
+        entries.into_iter().skip(skip).collect()
+    }
+
+    /// Replace the file with the newest `capacity` of `entries`. Titles and
+    /// paths are redacted like debug log text. Written to a sibling temp
+    /// file and renamed, so a crash never leaves a truncated history.
+    pub fn save(&self, entries: &[HistoryEntry]) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let skip = entries.len().saturating_sub(self.capacity);
+        let mut out = Vec::new();
+        for entry in &entries[skip..] {
+            let entry = HistoryEntry {
+                cwd: entry.cwd.as_deref().map(redact_text),
+                title: entry.title.as_deref().map(redact_text),
+                ..entry.clone()
+            };
+            serde_json::to_writer(&mut out, &entry)?;
+            out.push(b'\n');
+        }
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&out)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
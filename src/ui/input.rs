@@ -16,6 +16,7 @@ use crate::ui::settings::SettingsIntent;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppShortcut {
     CopySelection,
+    CopyLastResponse,
     Paste,
     ToggleBackendPopup,
     ToggleHistoryPopup,
@@ -25,7 +26,8 @@ pub enum AppShortcut {
     Quit,
 }
 
-/// Map a modifier combo to its app shortcut. Clipboard is **Cmd+C / Cmd+V**;
+/// Map a modifier combo to its app shortcut. Clipboard is **Cmd+C / Cmd+V**,
+/// with **Cmd+Shift+C** copying the last assistant response;
 /// app features are a single **Ctrl** chord. `Ctrl+B` (Claude Code) and `Ctrl+D`
 /// (EOF) are deliberately left for the terminal — backend takes `Ctrl+T`,
 /// diagnostic `Ctrl+G`. Diagnostic is a debug-build-only dev aid, so in a
@@ -35,6 +37,7 @@ pub fn app_shortcut(code: KeyCode, modifiers: ModifiersState) -> Option<AppShort
     // macOS clipboard — Cmd, not Ctrl (Ctrl+C/V are interrupt / literal-next).
     if modifiers.super_key() {
         return match code {
+            KeyCode::KeyC if modifiers.shift_key() => Some(AppShortcut::CopyLastResponse),
             KeyCode::KeyC => Some(AppShortcut::CopySelection),
             KeyCode::KeyV => Some(AppShortcut::Paste),
             _ => None,
@@ -56,6 +59,114 @@ pub fn app_shortcut(code: KeyCode, modifiers: ModifiersState) -> Option<AppShort
     None
 }
 
+/// A key plus the modifiers that must be held with it, matched on the
+/// physical key like every other shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: ModifiersState,
+}
+
+impl KeyChord {
+    /// The default "copy last response" chord, **Cmd+Shift+C**.
+    pub const COPY_LAST_RESPONSE: KeyChord = KeyChord {
+        code: KeyCode::KeyC,
+        modifiers: ModifiersState::SUPER.union(ModifiersState::SHIFT),
+    };
+
+    /// Parse a chord like `cmd+shift+c` or `ctrl+alt+y`: modifiers
+    /// (`cmd`/`super`, `ctrl`, `shift`, `alt`/`option`) joined by `+`,
+    /// then one letter or digit, case-insensitive. A chord must hold Cmd or
+    /// Ctrl so it can't swallow plain typing; `None` otherwise or when
+    /// anything is unrecognised.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts: Vec<String> = spec
+            .split('+')
+            .map(|p| p.trim().to_ascii_lowercase())
+            .collect();
+        let key = parts.pop()?;
+        let mut modifiers = ModifiersState::empty();
+        for part in &parts {
+            modifiers |= match part.as_str() {
+                "cmd" | "super" => ModifiersState::SUPER,
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "shift" => ModifiersState::SHIFT,
+                "alt" | "option" => ModifiersState::ALT,
+                _ => return None,
+            };
+        }
+        if !modifiers.intersects(ModifiersState::SUPER | ModifiersState::CONTROL) {
+            return None;
+        }
+        let code = match key.as_bytes() {
+            [c @ b'a'..=b'z'] => LETTERS[(c - b'a') as usize],
+            [d @ b'0'..=b'9'] => DIGITS[(d - b'0') as usize],
+            _ => return None,
+        };
+        Some(Self { code, modifiers })
+    }
+
+    fn matches(&self, code: KeyCode, modifiers: ModifiersState) -> bool {
+        self.code == code && self.modifiers == modifiers
+    }
+}
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+];
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// [`app_shortcut`] with "copy last response" on `copy_last_response`
+/// instead of Cmd+Shift+C. The chord wins over any default binding it
+/// shadows; once moved, Cmd+Shift+C is unbound.
+pub fn bound_app_shortcut(
+    code: KeyCode,
+    modifiers: ModifiersState,
+    copy_last_response: KeyChord,
+) -> Option<AppShortcut> {
+    if copy_last_response.matches(code, modifiers) {
+        return Some(AppShortcut::CopyLastResponse);
+    }
+    app_shortcut(code, modifiers).filter(|s| *s != AppShortcut::CopyLastResponse)
+}
+
 /// Backend-switch popup navigation. `Enter` is intentionally absent — it
 /// applies the selection and closes the popup (an effect the caller performs).
 pub fn backend_switch_nav(code: KeyCode) -> Option<BackendSwitchIntent> {
@@ -73,6 +184,8 @@ pub fn history_nav(code: KeyCode) -> Option<HistoryIntent> {
     match code {
         KeyCode::ArrowUp => Some(HistoryIntent::ScrollUp),
         KeyCode::ArrowDown => Some(HistoryIntent::ScrollDown),
+        KeyCode::PageUp => Some(HistoryIntent::PageUp),
+        KeyCode::PageDown => Some(HistoryIntent::PageDown),
         _ => None,
     }
 }
@@ -94,7 +94,8 @@ fn title_row(title: &str, color: [f32; 4]) -> Text {
     Text::new(title, POPUP_FONT_SIZE, color).weight(WEIGHT_BOLD)
 }
 
-/// History popup: a read-only, newest-first list of backend switches, windowed
+/// History popup: a read-only, newest-first list of backend switches (with
+/// the child's title and cwd at switch time, when captured), windowed
 /// to `MAX_VISIBLE_ROWS` rows driven by `scroll_offset` (R11 virtualization —
 /// the legacy immediate path drew every row and overflowed a tall history off
 /// the window). Row strings + colours match the old popup verbatim.
@@ -109,7 +110,12 @@ fn history_view(entries: &[HistoryEntry], scroll_offset: usize) -> Block {
                 .map(|d| d.as_secs())
                 .unwrap_or(0);
             let from = e.from_backend.as_deref().unwrap_or("(initial)");
-            format!("{secs}  ·  {from}  →  {}", e.to_backend)
+            let mut row = format!("{secs}  ·  {from}  →  {}", e.to_backend);
+            for context in [&e.title, &e.cwd].into_iter().flatten() {
+                row.push_str("  ·  ");
+                row.push_str(context);
+            }
+            row
         })
         .collect();
 
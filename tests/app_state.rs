@@ -36,7 +36,10 @@ fn any_popup_visible_tracks_each_popup() {
     assert!(s.any_popup_visible());
 
     let mut s = state();
-    s.history.apply(HistoryIntent::Load { entries: vec![] });
+    s.history.apply(HistoryIntent::Load {
+        entries: vec![],
+        capacity: 500,
+    });
     assert!(s.any_popup_visible());
 
     let mut s = state();
@@ -53,7 +56,10 @@ fn close_all_popups_hides_everything() {
         teammate_selection: 0,
         backends_count: 1,
     });
-    s.history.apply(HistoryIntent::Load { entries: vec![] });
+    s.history.apply(HistoryIntent::Load {
+        entries: vec![],
+        capacity: 500,
+    });
     s.settings.apply(SettingsIntent::Load { fields: vec![] });
     assert!(s.any_popup_visible());
 
@@ -263,6 +269,7 @@ fn key(physical: KeyCode) -> Msg {
         logical_unmod: Key::Named(NamedKey::Space),
         physical: PhysicalKey::Code(physical),
         app_cursor: false,
+        keypad_app: false,
     }
 }
 
@@ -276,12 +283,27 @@ fn terminal_key_emits_write_to_pty() {
             logical_unmod: Key::Named(NamedKey::Enter),
             physical: PhysicalKey::Code(KeyCode::Enter),
             app_cursor: false,
+            keypad_app: false,
         },
         &ctx(),
     );
     assert!(matches!(fx.as_slice(), [Effect::WriteToPty(_)]), "terminal key writes to PTY: {fx:?}");
 }
 
+#[test]
+fn keypad_key_in_app_mode_writes_ss3() {
+    let mut s = state();
+    let keypad_enter = |keypad_app| Msg::Key {
+        logical: Key::Named(NamedKey::Enter),
+        logical_unmod: Key::Named(NamedKey::Enter),
+        physical: PhysicalKey::Code(KeyCode::NumpadEnter),
+        app_cursor: false,
+        keypad_app,
+    };
+    assert_eq!(s.apply(keypad_enter(true), &ctx()), vec![Effect::WriteToPty(b"\x1bOM".to_vec())]);
+    assert_eq!(s.apply(keypad_enter(false), &ctx()), vec![Effect::WriteToPty(b"\r".to_vec())]);
+}
+
 #[test]
 fn ctrl_shortcut_maps_to_its_effect() {
     let mut s = state();
@@ -301,6 +323,9 @@ fn cmd_clipboard_maps_and_unbound_cmd_is_swallowed() {
     let mut s = state();
     s.modifiers = ModifiersState::SUPER;
     assert_eq!(s.apply(key(KeyCode::KeyC), &ctx()), vec![Effect::CopySelection]);
+    s.modifiers = ModifiersState::SUPER | ModifiersState::SHIFT;
+    assert_eq!(s.apply(key(KeyCode::KeyC), &ctx()), vec![Effect::CopyLastResponse]);
+    s.modifiers = ModifiersState::SUPER;
     // A Cmd combo with no shortcut is swallowed, never leaked to the PTY.
     assert!(s.apply(key(KeyCode::F13), &ctx()).is_empty());
 }
@@ -365,7 +390,10 @@ fn press(
 #[test]
 fn click_on_open_popup_dismisses_it() {
     let mut s = state();
-    s.history.apply(HistoryIntent::Load { entries: vec![] });
+    s.history.apply(HistoryIntent::Load {
+        entries: vec![],
+        capacity: 500,
+    });
     assert_eq!(s.apply(press(false, false, None, None), &ctx()), vec![Effect::Redraw]);
     assert!(!s.any_popup_visible(), "a click anywhere dismisses the open popup");
 }
@@ -407,6 +435,22 @@ fn grid_resized_updates_grid_and_asks_to_resize_resources() {
     );
 }
 
+#[test]
+fn simulated_size_change_resizes_the_emulator_grid() {
+    // Mirrors the coordinator's ResizeEmulatorAndPty handling: the window
+    // resize path is platform-neutral (winit), so this covers ConPTY too.
+    let mut s = state();
+    let mut emu = create_emulator(80, 24, 100);
+    for fx in s.apply(Msg::GridResized { cols: 120, rows: 30 }, &ctx()) {
+        if let Effect::ResizeEmulatorAndPty { cols, rows } = fx {
+            emu.resize(cols, rows);
+        }
+    }
+    let snap = emu.snapshot();
+    assert_eq!(snap.visible_rows, 30);
+    assert!(snap.visible_iter().all(|row| row.cells.len() == 120));
+}
+
 #[test]
 fn grid_resized_to_the_same_size_only_redraws() {
     let mut s = state(); // (80, 24)
@@ -421,6 +465,32 @@ fn tick_close_pty_map_to_their_effects() {
     assert_eq!(s.apply(Msg::PtyBytes, &ctx()), vec![Effect::Drain]);
 }
 
+#[test]
+fn child_exit_records_code_and_announces_after_draining() {
+    let mut s = state();
+    assert_eq!(s.child_exit, None);
+    assert_eq!(
+        s.apply(Msg::ChildExited { code: 3 }, &ctx()),
+        vec![Effect::Drain, Effect::AnnounceChildExit { code: 3 }, Effect::Redraw]
+    );
+    assert_eq!(s.child_exit, Some(3));
+}
+
+#[test]
+fn pty_read_failure_announces_after_draining() {
+    let mut s = state();
+    assert_eq!(
+        s.apply(Msg::PtyReadFailed { error: "Broken pipe".to_string() }, &ctx()),
+        vec![
+            Effect::Drain,
+            Effect::AnnouncePtyReadError { error: "Broken pipe".to_string() },
+            Effect::Redraw,
+        ]
+    );
+    // The child may still be running: no exit is recorded.
+    assert_eq!(s.child_exit, None);
+}
+
 #[test]
 fn modifiers_changed_updates_state_with_no_effect() {
     let mut s = state();
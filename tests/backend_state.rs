@@ -1,6 +1,6 @@
 mod common;
 
-use anyclaude::backend::{BackendError, BackendState};
+use anyclaude::backend::{BackendBalancer, BackendError, BackendState, SwitchContext};
 use anyclaude::config::{Backend, Config, Defaults, ProxyConfig, TerminalConfig, DebugLoggingConfig};
 use std::collections::HashMap;
 
@@ -13,8 +13,17 @@ fn create_test_config() -> Config {
             idle_timeout_seconds: 60,
             pool_idle_timeout_seconds: 90,
             pool_max_idle_per_host: 8,
+            warmup_connections: false,
             max_retries: 3,
             retry_backoff_base_ms: 100,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig::default(),
         terminal: TerminalConfig::default(),
@@ -27,9 +36,21 @@ fn create_test_config() -> Config {
                 base_url: "https://api1.example.com".to_string(),
                 auth_type_str: "api_key".to_string(),
                 api_key: None,
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: None,
                 thinking_budget_tokens: None,
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: None,
                 model_sonnet: None,
                 model_haiku: None,
@@ -40,9 +61,21 @@ fn create_test_config() -> Config {
                 base_url: "https://api2.example.com".to_string(),
                 auth_type_str: "bearer".to_string(),
                 api_key: None,
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: None,
                 thinking_budget_tokens: None,
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: None,
                 model_sonnet: None,
                 model_haiku: None,
@@ -139,6 +172,67 @@ fn test_switch_log() {
     assert_eq!(log[2].new_backend, "backend1".to_string());
 }
 
+#[test]
+fn test_switch_log_records_switch_context() {
+    let state = BackendState::from_config(create_test_config()).unwrap();
+    let context = SwitchContext {
+        cwd: Some("/work/project".to_string()),
+        title: Some("fix tests".to_string()),
+    };
+
+    state.switch_backend_with("backend2", context.clone()).unwrap();
+    state.switch_backend("backend1").unwrap();
+
+    let log = state.get_switch_log();
+    assert_eq!(log[0].context, SwitchContext::default());
+    assert_eq!(log[1].context, context);
+    assert_eq!(log[2].context, SwitchContext::default());
+}
+
+#[test]
+fn test_switch_log_is_capped_to_most_recent_entries() {
+    let mut config = create_test_config();
+    config.defaults.switch_log_capacity = 4;
+    let state = BackendState::from_config(config).unwrap();
+
+    // The initial entry counts towards capacity.
+    assert_eq!(state.get_switch_log().len(), 1);
+    state.switch_backend("backend2").unwrap();
+    state.switch_backend("backend1").unwrap();
+    state.switch_backend("backend2").unwrap();
+    let log = state.get_switch_log();
+    assert_eq!(log.len(), 4);
+    assert_eq!(log[0].old_backend, None);
+
+    // One more switch evicts the initial entry; order stays oldest first.
+    for _ in 0..3 {
+        state.switch_backend("backend1").unwrap();
+        state.switch_backend("backend2").unwrap();
+    }
+    let log = state.get_switch_log();
+    assert_eq!(log.len(), 4);
+    assert!(log.iter().all(|e| e.old_backend.is_some()));
+    assert_eq!(log[3].old_backend.as_deref(), Some("backend1"));
+    assert_eq!(log[3].new_backend, "backend2");
+    assert!(log.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+}
+
+#[test]
+fn test_switch_log_entry_display() {
+    let config = create_test_config();
+    let state = BackendState::from_config(config).unwrap();
+    state.switch_backend("backend2").unwrap();
+
+    let log = state.get_switch_log();
+    let initial = log[0].to_string();
+    assert!(initial.ends_with("  (initial) → backend1"), "{initial}");
+    let switch = log[1].to_string();
+    assert!(switch.ends_with("  backend1 → backend2"), "{switch}");
+
+    let secs: u64 = switch.split_whitespace().next().unwrap().parse().unwrap();
+    assert!(secs > 0, "timestamp rendered as epoch seconds");
+}
+
 #[test]
 fn test_validate_backend() {
     let config = create_test_config();
@@ -160,6 +254,125 @@ fn test_list_backends() {
     assert!(backends.contains(&"backend2".to_string()));
 }
 
+#[test]
+fn test_list_backends_by_tag() {
+    let mut config = create_test_config();
+    config.backends[0].tags = vec!["cheap".to_string(), "reasoning".to_string()];
+    config.backends[1].tags = vec!["cheap".to_string()];
+    let state = BackendState::from_config(config).unwrap();
+
+    assert_eq!(state.list_backends_by_tag("cheap"), vec!["backend1", "backend2"]);
+    assert_eq!(state.list_backends_by_tag("reasoning"), vec!["backend1"]);
+    assert!(state.list_backends_by_tag("unused").is_empty());
+    // Tags match exactly.
+    assert!(state.list_backends_by_tag("Cheap").is_empty());
+}
+
+/// Tag both test backends into a pool weighted 70/30.
+fn create_balanced_state(seed: u64) -> BackendState {
+    let mut config = create_test_config();
+    config.defaults.balance_tag = Some("pool".to_string());
+    for (backend, weight) in config.backends.iter_mut().zip([7, 3]) {
+        backend.tags = vec!["pool".to_string()];
+        backend.weight = weight;
+    }
+    BackendState::from_config(config)
+        .unwrap()
+        .with_balancer(BackendBalancer::seeded(seed))
+}
+
+#[test]
+fn test_balanced_selection_follows_weights() {
+    let requests = 10_000;
+    let state = create_balanced_state(42);
+    let first = (0..requests)
+        .filter(|_| state.pick_balanced_backend(None).unwrap().0 == "backend1")
+        .count();
+    let share = first as f64 / requests as f64;
+    assert!((share - 0.7).abs() < 0.02, "backend1 share {share}");
+
+    // The same seed gives the same sequence.
+    let (a, b) = (create_balanced_state(7), create_balanced_state(7));
+    for _ in 0..50 {
+        assert_eq!(a.pick_balanced_backend(None), b.pick_balanced_backend(None));
+    }
+}
+
+#[test]
+fn test_balanced_conversations_follow_weights() {
+    let conversations = 10_000;
+    let state = create_balanced_state(42);
+    let first = (0..conversations)
+        .filter(|n| {
+            let key = format!("user_abc_session_{n}");
+            state.pick_balanced_backend(Some(&key)).unwrap().0 == "backend1"
+        })
+        .count();
+    let share = first as f64 / conversations as f64;
+    assert!((share - 0.7).abs() < 0.02, "backend1 share {share}");
+}
+
+#[test]
+fn test_balanced_pick_sticks_to_the_conversation() {
+    let state = create_balanced_state(42);
+    let mut seen = std::collections::HashSet::new();
+    for n in 0..50 {
+        let key = format!("session-{n}");
+        let first = state.pick_balanced_backend(Some(&key)).unwrap();
+        for _ in 0..20 {
+            assert_eq!(state.pick_balanced_backend(Some(&key)).as_ref(), Some(&first));
+        }
+        seen.insert(first.0);
+    }
+    assert_eq!(seen.len(), 2, "conversations are spread over the pool");
+}
+
+#[test]
+fn test_balanced_pick_moves_off_a_zero_weight_backend() {
+    let state = create_balanced_state(42);
+    let (first, _) = state.pick_balanced_backend(Some("session")).unwrap();
+    let mut config = state.get_config();
+    for backend in &mut config.backends {
+        if backend.name == first {
+            backend.weight = 0;
+        }
+    }
+    state.update_config(config).unwrap();
+
+    let (picked, _) = state.pick_balanced_backend(Some("session")).unwrap();
+    assert_ne!(picked, first);
+}
+
+#[test]
+fn test_balanced_selection_skips_zero_weight_and_reports_tag() {
+    let state = create_balanced_state(1);
+    let mut config = state.get_config();
+    config.backends[1].weight = 0;
+    state.update_config(config).unwrap();
+
+    for _ in 0..100 {
+        assert_eq!(
+            state.pick_balanced_backend(None),
+            Some(("backend1".to_string(), "pool".to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_balancing_pauses_when_active_leaves_the_pool() {
+    let state = create_balanced_state(1);
+    let mut config = state.get_config();
+    config.backends[0].tags.clear();
+    state.update_config(config).unwrap();
+
+    // Active backend1 is no longer tagged: requests follow it, unbalanced.
+    assert_eq!(state.pick_balanced_backend(None), None);
+
+    // No balance tag at all: no pool.
+    let plain = BackendState::from_config(create_test_config()).unwrap();
+    assert_eq!(plain.pick_balanced_backend(None), None);
+}
+
 #[test]
 fn test_get_active_backend_config() {
     let config = create_test_config();
@@ -186,9 +399,21 @@ fn test_update_config() {
         base_url: "https://api3.example.com".to_string(),
         auth_type_str: "api_key".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
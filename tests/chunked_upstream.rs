@@ -0,0 +1,100 @@
+//! Non-streaming JSON answered with `Transfer-Encoding: chunked`: the proxy
+//! reassembles the body, rewrites and parses it, and re-frames it for the
+//! client instead of copying the upstream's framing headers.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{
+    Backend, Config, ConfigStore, DebugLogLevel, DebugLoggingConfig, Defaults, ProxyConfig,
+};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+#[tokio::test]
+async fn chunked_json_is_reverse_mapped_and_usage_parsed() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json_chunked(&[
+        r#"{"id":"msg_01","type":"message","role":"assistant","#,
+        r#""model":"glm-5","content":[{"type":"text","text":"Hello"}],"#,
+        r#""stop_reason":"end_turn","usage":{"input_tokens":21,"#,
+        r#""output_tokens":8}}"#,
+    ]))
+    .await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "glm".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "glm".to_string(),
+            display_name: "GLM".to_string(),
+            base_url: mock.base_url(),
+            auth_type_str: "passthrough".to_string(),
+            model_opus: Some("glm-5".to_string()),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test-chunked.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(DebugLoggingConfig {
+        level: DebugLogLevel::Verbose,
+        ..DebugLoggingConfig::default()
+    }));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let hub = server.observability();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(addr, Duration::from_secs(5)).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("http://{addr}/v1/messages"))
+        .header("content-type", "application/json")
+        .body(r#"{"model":"claude-opus-4-6","stream":false,"max_tokens":16,"messages":[]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(
+        resp.headers().get("transfer-encoding").is_none(),
+        "upstream framing was copied: {:?}",
+        resp.headers()
+    );
+    let content_length = resp.headers().get("content-length").cloned();
+    let body = resp.text().await.unwrap();
+    if let Some(length) = content_length {
+        assert_eq!(length.to_str().unwrap(), body.len().to_string());
+    }
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["model"], "claude-opus-4-6");
+    assert_eq!(json["content"][0]["text"], "Hello");
+
+    let mut snapshot = hub.snapshot();
+    for _ in 0..50 {
+        if !snapshot.recent.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        snapshot = hub.snapshot();
+    }
+    let analysis = snapshot.recent[0]
+        .response_analysis
+        .as_ref()
+        .expect("response analysed");
+    assert_eq!(analysis.input_tokens, Some(21));
+    assert_eq!(analysis.output_tokens, Some(8));
+    assert_eq!(analysis.stop_reason.as_deref(), Some("end_turn"));
+}
@@ -69,3 +69,53 @@ fn test_missing_backend_value_shows_error() {
         stderr
     );
 }
+
+#[test]
+fn test_help_shows_no_proxy_option() {
+    let output = anyclaude_cmd()
+        .arg("--help")
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--no-proxy"));
+    assert!(stdout.contains("without starting the proxy"));
+}
+
+#[test]
+fn test_no_proxy_conflicts_with_backend() {
+    let output = anyclaude_cmd()
+        .args(["--no-proxy", "--backend", "claude"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2), "clap usage error");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "{stderr}");
+}
+
+#[test]
+fn test_help_shows_override_flags() {
+    let output = anyclaude_cmd()
+        .arg("--help")
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--bind"));
+    assert!(stdout.contains("--timeout"));
+    assert!(stdout.contains("--debug-log"));
+}
+
+#[test]
+fn test_invalid_debug_log_level_exits_with_error() {
+    let output = anyclaude_cmd()
+        .arg("--debug-log")
+        .arg("loud")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown level 'loud'"));
+}
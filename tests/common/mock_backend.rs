@@ -9,6 +9,7 @@ use axum::routing::any;
 use axum::Router;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
@@ -29,6 +30,10 @@ pub struct MockResponse {
     pub headers: Vec<(String, String)>,
     pub body: Vec<u8>,
     pub delay_ms: u64,
+    /// When non-empty, sent instead of `body`, one piece at a time with
+    /// `chunk_pause_ms` between pieces (simulates a stalling upstream).
+    pub chunks: Vec<Vec<u8>>,
+    pub chunk_pause_ms: u64,
 }
 
 impl Default for MockResponse {
@@ -38,6 +43,8 @@ impl Default for MockResponse {
             headers: vec![("content-type".to_string(), "application/json".to_string())],
             body: br#"{"ok": true}"#.to_vec(),
             delay_ms: 0,
+            chunks: Vec::new(),
+            chunk_pause_ms: 0,
         }
     }
 }
@@ -49,6 +56,8 @@ impl MockResponse {
             headers: vec![("content-type".to_string(), "application/json".to_string())],
             body: body.as_bytes().to_vec(),
             delay_ms: 0,
+            chunks: Vec::new(),
+            chunk_pause_ms: 0,
         }
     }
 
@@ -58,6 +67,8 @@ impl MockResponse {
             headers: vec![("content-type".to_string(), "application/json".to_string())],
             body: format!(r#"{{"error": "{}"}}"#, message).into_bytes(),
             delay_ms: 0,
+            chunks: Vec::new(),
+            chunk_pause_ms: 0,
         }
     }
 
@@ -81,6 +92,8 @@ impl MockResponse {
             headers: vec![("content-type".to_string(), "text/event-stream".to_string())],
             body: body.into_bytes(),
             delay_ms: 0,
+            chunks: Vec::new(),
+            chunk_pause_ms: 0,
         }
     }
 
@@ -90,6 +103,29 @@ impl MockResponse {
         resp
     }
 
+    /// Raw SSE body sent as `pieces`, pausing `pause_ms` before each
+    /// piece after the first.
+    pub fn sse_chunked(pieces: &[&str], pause_ms: u64) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("content-type".to_string(), "text/event-stream".to_string())],
+            body: pieces.concat().into_bytes(),
+            delay_ms: 0,
+            chunks: pieces.iter().map(|p| p.as_bytes().to_vec()).collect(),
+            chunk_pause_ms: pause_ms,
+        }
+    }
+
+    /// JSON body sent as `pieces` with chunked transfer encoding and no
+    /// Content-Length.
+    pub fn json_chunked(pieces: &[&str]) -> Self {
+        Self {
+            body: pieces.concat().into_bytes(),
+            chunks: pieces.iter().map(|p| p.as_bytes().to_vec()).collect(),
+            ..Self::default()
+        }
+    }
+
     pub fn with_delay(mut self, ms: u64) -> Self {
         self.delay_ms = ms;
         self
@@ -100,6 +136,8 @@ impl MockResponse {
 struct MockState {
     requests: Arc<Mutex<Vec<CapturedRequest>>>,
     responses: Arc<Mutex<VecDeque<MockResponse>>>,
+    /// Pieces of chunked bodies actually pulled by the peer.
+    chunks_sent: Arc<AtomicUsize>,
 }
 
 /// Mock backend server for testing.
@@ -115,6 +153,7 @@ impl MockBackend {
         let state = MockState {
             requests: Arc::new(Mutex::new(Vec::new())),
             responses: Arc::new(Mutex::new(VecDeque::new())),
+            chunks_sent: Arc::new(AtomicUsize::new(0)),
         };
 
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
@@ -162,6 +201,11 @@ impl MockBackend {
         format!("http://{}", self.addr)
     }
 
+    /// How many pieces of `sse_chunked` bodies the peer has pulled so far.
+    pub fn chunks_sent(&self) -> usize {
+        self.state.chunks_sent.load(Ordering::SeqCst)
+    }
+
     /// Clear captured requests.
     pub async fn clear(&self) {
         self.state.requests.lock().await.clear();
@@ -220,5 +264,24 @@ async fn handle_request(
         builder = builder.header(name, value);
     }
 
-    builder.body(Body::from(mock_resp.body)).unwrap()
+    if mock_resp.chunks.is_empty() {
+        return builder.body(Body::from(mock_resp.body)).unwrap();
+    }
+    let pause = tokio::time::Duration::from_millis(mock_resp.chunk_pause_ms);
+    let sent = state.chunks_sent.clone();
+    let pieces = futures::stream::unfold(
+        (mock_resp.chunks.into_iter(), true),
+        move |(mut rest, first)| {
+            let sent = sent.clone();
+            async move {
+                let piece = rest.next()?;
+                if !first {
+                    tokio::time::sleep(pause).await;
+                }
+                sent.fetch_add(1, Ordering::SeqCst);
+                Some((Ok::<_, std::io::Error>(piece), (rest, false)))
+            }
+        },
+    );
+    builder.body(Body::from_stream(pieces)).unwrap()
 }
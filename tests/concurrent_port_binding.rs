@@ -27,12 +27,30 @@ fn test_config(backend: Backend, bind_addr: &str) -> Config {
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 1,
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
 
         terminal: TerminalConfig::default(),
@@ -50,9 +68,21 @@ fn create_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
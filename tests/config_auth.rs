@@ -1,6 +1,27 @@
 mod common;
 
-use anyclaude::config::{build_auth_header, Backend};
+use std::sync::Arc;
+
+use anyclaude::config::{
+    build_auth_header, AuthHeader, Backend, Config, CredentialStatus, Defaults, ProxyConfig,
+    RequestSigner, SigningRequest, KEY_COMMAND_TIMEOUT,
+};
+use anyclaude::embed::ProxyBuilder;
+use anyclaude::proxy::ProxyAddr;
+use common::mock_backend::{MockBackend, MockResponse};
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// The auth header for `backend`, resolving its credential as the
+/// pipeline does.
+fn auth_header(backend: &Backend) -> Option<AuthHeader> {
+    build_auth_header(backend, &backend.resolve_credential())
+}
 
 fn make_backend(auth_type: &str, api_key: Option<&str>) -> Backend {
     Backend {
@@ -9,9 +30,21 @@ fn make_backend(auth_type: &str, api_key: Option<&str>) -> Backend {
         base_url: "https://example.com".to_string(),
         auth_type_str: auth_type.to_string(),
         api_key: api_key.map(|value| value.to_string()),
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -21,13 +54,13 @@ fn make_backend(auth_type: &str, api_key: Option<&str>) -> Backend {
 #[test]
 fn test_passthrough_backend() {
     let backend = make_backend("passthrough", None);
-    assert!(build_auth_header(&backend).is_none());
+    assert!(auth_header(&backend).is_none());
 }
 
 #[test]
 fn test_api_key_header() {
     let backend = make_backend("api_key", Some("test-key-123"));
-    let header = build_auth_header(&backend);
+    let header = auth_header(&backend);
 
     assert!(header.is_some());
     let (name, value) = header.unwrap();
@@ -38,7 +71,7 @@ fn test_api_key_header() {
 #[test]
 fn test_bearer_header() {
     let backend = make_backend("bearer", Some("bearer-token-456"));
-    let header = build_auth_header(&backend);
+    let header = auth_header(&backend);
 
     assert!(header.is_some());
     let (name, value) = header.unwrap();
@@ -49,11 +82,242 @@ fn test_bearer_header() {
 #[test]
 fn test_missing_api_key() {
     let backend = make_backend("api_key", None);
-    assert!(build_auth_header(&backend).is_none());
+    assert!(auth_header(&backend).is_none());
 }
 
 #[test]
 fn test_empty_api_key() {
     let backend = make_backend("api_key", Some(""));
-    assert!(build_auth_header(&backend).is_none());
+    assert!(auth_header(&backend).is_none());
+}
+
+/// A command that prints `token` and appends a line to `count_file` each
+/// time it runs.
+fn counting_command(count_file: &std::path::Path, token: &str) -> String {
+    format!("echo run >> '{}'; echo {token}", count_file.display())
+}
+
+fn runs(count_file: &std::path::Path) -> usize {
+    std::fs::read_to_string(count_file).map_or(0, |s| s.lines().count())
+}
+
+#[test]
+fn test_bearer_from_command_is_cached() {
+    let dir = tempfile::tempdir().unwrap();
+    let count_file = dir.path().join("runs");
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some(counting_command(&count_file, "cmd-token"));
+
+    let (name, value) = auth_header(&backend).unwrap();
+    assert_eq!(name, "Authorization");
+    assert_eq!(value, "Bearer cmd-token");
+    assert_eq!(auth_header(&backend).unwrap().1, "Bearer cmd-token");
+    assert_eq!(runs(&count_file), 1, "second lookup within the TTL reuses the token");
+}
+
+#[test]
+fn test_command_token_expires_after_ttl() {
+    let dir = tempfile::tempdir().unwrap();
+    let count_file = dir.path().join("runs");
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some(counting_command(&count_file, "short-lived"));
+    backend.api_key_command_ttl_seconds = 0;
+
+    auth_header(&backend).unwrap();
+    auth_header(&backend).unwrap();
+    assert_eq!(runs(&count_file), 2);
+}
+
+#[test]
+fn test_api_key_takes_precedence_over_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let count_file = dir.path().join("runs");
+    let mut backend = make_backend("bearer", Some("static"));
+    backend.api_key_command = Some(counting_command(&count_file, "unused"));
+
+    assert_eq!(auth_header(&backend).unwrap().1, "Bearer static");
+    assert_eq!(runs(&count_file), 0);
+}
+
+#[test]
+fn test_failing_command_leaves_backend_unconfigured() {
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some("echo 'no session' >&2; exit 3".to_string());
+
+    match backend.resolve_credential() {
+        CredentialStatus::Unconfigured { reason } => {
+            assert!(reason.contains("no session"), "{reason}");
+        }
+        other => panic!("expected unconfigured, got {other:?}"),
+    }
+    assert!(auth_header(&backend).is_none());
+}
+
+#[test]
+fn test_hanging_command_times_out() {
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some("sleep 60; echo late".to_string());
+
+    let started = std::time::Instant::now();
+    match backend.resolve_credential() {
+        CredentialStatus::Unconfigured { reason } => {
+            assert!(reason.contains("timed out"), "{reason}");
+        }
+        other => panic!("expected unconfigured, got {other:?}"),
+    }
+    assert!(started.elapsed() < KEY_COMMAND_TIMEOUT + std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_command_with_output_beyond_a_pipe_buffer() {
+    let mut backend = make_backend("bearer", None);
+    // ~200 KiB of stderr, well past a 64 KiB pipe buffer, before the key.
+    backend.api_key_command = Some(
+        "head -c 204800 /dev/zero | tr '\\0' x >&2; echo chatty-token".to_string(),
+    );
+
+    let started = std::time::Instant::now();
+    assert_eq!(auth_header(&backend).unwrap().1, "Bearer chatty-token");
+    assert!(started.elapsed() < KEY_COMMAND_TIMEOUT);
+}
+
+#[test]
+fn test_is_configured_never_runs_the_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let count_file = dir.path().join("runs");
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some(counting_command(&count_file, "lazy-token"));
+
+    assert!(backend.is_configured(), "not run yet: assumed to work");
+    assert!(backend.credential_needs_command());
+    assert_eq!(runs(&count_file), 0);
+
+    auth_header(&backend).unwrap();
+    assert!(backend.is_configured());
+    assert!(!backend.credential_needs_command(), "cached");
+    assert_eq!(runs(&count_file), 1);
+}
+
+#[test]
+fn test_is_configured_reflects_the_last_command_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut backend = make_backend("bearer", None);
+    backend.api_key_command = Some(format!("echo nope >&2; exit 1 # {}", dir.path().display()));
+
+    assert!(backend.is_configured());
+    assert!(auth_header(&backend).is_none());
+    assert!(!backend.is_configured());
+    assert!(backend.credential_needs_command(), "failures are retried");
+}
+
+struct StaticSigner;
+
+impl RequestSigner for StaticSigner {
+    fn sign(&self, request: &SigningRequest<'_>) -> Result<Vec<AuthHeader>, String> {
+        Ok(vec![(
+            "x-signature".to_string(),
+            format!("{} {} {}", request.method, request.url, request.body.len()),
+        )])
+    }
+}
+
+fn signed_config(mock_url: &str) -> Config {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut backend = make_backend("signed", None);
+    backend.base_url = mock_url.to_string();
+    Config {
+        defaults: Defaults {
+            active: "test".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![backend],
+        ..Config::default()
+    }
+}
+
+#[tokio::test]
+async fn test_signed_backend_uses_registered_signer() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    let proxy = ProxyBuilder::new(signed_config(&mock.base_url()))
+        .signer("test", Arc::new(StaticSigner))
+        .start()
+        .await
+        .unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP proxy");
+    };
+
+    let resp = reqwest::Client::new()
+        .post(format!("http://{addr}/v1/messages"))
+        .header("authorization", "Bearer client-token")
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let captured = mock.captured_requests().await;
+    let expected = format!("POST {}/v1/messages 2", mock.base_url());
+    assert_eq!(header(&captured[0].headers, "x-signature"), Some(expected.as_str()));
+    assert!(header(&captured[0].headers, "authorization").is_none(), "client auth stripped");
+    proxy.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_signed_backend_without_signer_is_rejected() {
+    let mock = MockBackend::start().await;
+    let proxy = ProxyBuilder::new(signed_config(&mock.base_url())).start().await.unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP proxy");
+    };
+
+    let resp = reqwest::Client::new()
+        .post(format!("http://{addr}/v1/messages"))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 502);
+    assert!(mock.captured_requests().await.is_empty());
+    proxy.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_key_command_runs_off_the_async_workers() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    let mut config = signed_config(&mock.base_url());
+    config.backends[0].auth_type_str = "bearer".to_string();
+    config.backends[0].api_key_command = Some("sleep 1.5; echo slow-token".to_string());
+    let proxy = ProxyBuilder::new(config).start().await.unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP proxy");
+    };
+
+    let client = reqwest::Client::new();
+    let started = std::time::Instant::now();
+    let request = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client.post(format!("http://{addr}/v1/messages")).body("{}").send().await.unwrap()
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    // Single-threaded test runtime: a command run inline would stall the
+    // whole runtime, this health check included, until it exits.
+    let health = client.get(format!("http://{addr}/health")).send().await.unwrap();
+    assert!(health.status().is_success());
+    assert!(started.elapsed() < std::time::Duration::from_millis(1200), "{:?}", started.elapsed());
+
+    assert_eq!(request.await.unwrap().status(), 200);
+    let captured = mock.captured_requests().await;
+    assert_eq!(header(&captured[0].headers, "authorization"), Some("Bearer slow-token"));
+    proxy.shutdown().await.unwrap();
 }
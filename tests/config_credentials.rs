@@ -51,9 +51,21 @@ fn test_credential_resolution_passthrough() {
         base_url: "https://example.com".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
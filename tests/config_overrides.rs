@@ -0,0 +1,80 @@
+//! `ConfigOverrides` — command-line flags laid over a loaded config: given
+//! fields replace the file's values, everything else is left as loaded.
+
+use anyclaude::config::{Config, ConfigOverrides, DebugLogLevel};
+
+fn base() -> Config {
+    toml::from_str(
+        r#"
+[defaults]
+active = "primary"
+timeout_seconds = 120
+idle_timeout_seconds = 45
+
+[proxy]
+bind_addr = "127.0.0.1:4000"
+base_url = "http://127.0.0.1:4000"
+
+[debug_logging]
+level = "basic"
+
+[[backends]]
+name = "primary"
+display_name = "Primary"
+base_url = "https://primary.example.com"
+auth_type = "passthrough"
+
+[[backends]]
+name = "secondary"
+display_name = "Secondary"
+base_url = "https://secondary.example.com"
+auth_type = "passthrough"
+"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn no_overrides_leave_the_config_untouched() {
+    let mut config = base();
+    ConfigOverrides::default().apply(&mut config);
+    assert_eq!(
+        toml::to_string(&config).unwrap(),
+        toml::to_string(&base()).unwrap()
+    );
+}
+
+#[test]
+fn every_flag_replaces_its_field() {
+    let mut config = base();
+    ConfigOverrides {
+        backend: Some("secondary".to_string()),
+        bind_addr: Some("0.0.0.0:9000".to_string()),
+        timeout_seconds: Some(600),
+        debug_log: Some(DebugLogLevel::Full),
+    }
+    .apply(&mut config);
+
+    assert_eq!(config.defaults.active, "secondary");
+    assert_eq!(config.proxy.bind_addr, "0.0.0.0:9000");
+    assert_eq!(config.defaults.timeout_seconds, 600);
+    assert_eq!(config.debug_logging.level, DebugLogLevel::Full);
+}
+
+#[test]
+fn unspecified_fields_keep_their_file_values() {
+    let mut config = base();
+    ConfigOverrides {
+        timeout_seconds: Some(30),
+        ..ConfigOverrides::default()
+    }
+    .apply(&mut config);
+
+    assert_eq!(config.defaults.timeout_seconds, 30);
+    assert_eq!(config.defaults.active, "primary");
+    assert_eq!(config.defaults.idle_timeout_seconds, 45);
+    assert_eq!(config.proxy.bind_addr, "127.0.0.1:4000");
+    assert_eq!(config.proxy.base_url, "http://127.0.0.1:4000");
+    assert_eq!(config.debug_logging.level, DebugLogLevel::Basic);
+    assert_eq!(config.backends.len(), 2);
+}
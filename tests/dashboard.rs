@@ -0,0 +1,109 @@
+//! `/dashboard`: an HTML view of the metrics snapshot, served only when
+//! `proxy.dashboard` is on.
+
+mod common;
+
+use anyclaude::config::{Backend, Config, Defaults, ProxyConfig};
+use anyclaude::embed::{ProxyBuilder, RunningProxy};
+use anyclaude::proxy::ProxyAddr;
+use common::mock_backend::{MockBackend, MockResponse};
+
+async fn start(upstream: &str, dashboard: bool) -> (RunningProxy, String) {
+    start_with_token(upstream, dashboard, None).await
+}
+
+async fn start_with_token(
+    upstream: &str,
+    dashboard: bool,
+    token: Option<&str>,
+) -> (RunningProxy, String) {
+    let backend = |name: &str| Backend {
+        name: name.to_string(),
+        base_url: upstream.to_string(),
+        ..Backend::default()
+    };
+    let config = Config {
+        defaults: Defaults {
+            active: "alpha".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("127.0.0.1:{}", common::free_port()),
+            dashboard,
+            ..ProxyConfig::default()
+        },
+        backends: vec![backend("alpha"), backend("beta")],
+        ..Config::default()
+    };
+    let mut builder = ProxyBuilder::new(config);
+    if let Some(token) = token {
+        builder = builder.session_token(token);
+    }
+    let proxy = builder.start().await.unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP address");
+    };
+    (proxy, format!("http://{addr}"))
+}
+
+#[tokio::test]
+async fn dashboard_lists_every_configured_backend() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    let (proxy, url) = start(&mock.base_url(), true).await;
+
+    let client = reqwest::Client::new();
+    client.post(format!("{url}/v1/messages")).body("{}").send().await.unwrap();
+
+    let resp = client.get(format!("{url}/dashboard")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let content_type = resp.headers()["content-type"].to_str().unwrap().to_string();
+    assert!(content_type.starts_with("text/html"), "{content_type}");
+    let html = resp.text().await.unwrap();
+    assert!(html.contains("<td>alpha</td>"));
+    assert!(html.contains("<td>beta</td>"));
+    assert!(html.contains("Active backend: <strong>alpha</strong>"));
+    // The dashboard itself is not proxied.
+    assert_eq!(mock.captured_requests().await.len(), 1);
+
+    proxy.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn dashboard_is_off_by_default() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"upstream":true}"#)).await;
+    let (proxy, url) = start(&mock.base_url(), false).await;
+
+    let body = reqwest::get(format!("{url}/dashboard")).await.unwrap().text().await.unwrap();
+    assert!(body.contains("upstream"));
+
+    proxy.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn dashboard_requires_the_session_token() {
+    let mock = MockBackend::start().await;
+    let (proxy, url) = start_with_token(&mock.base_url(), true, Some("secret-token")).await;
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{url}/dashboard")).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+    let resp = client.get(format!("{url}/dashboard?token=wrong")).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+
+    // From a browser, as a query parameter...
+    let resp = client.get(format!("{url}/dashboard?token=secret-token")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    // ...or in the header the proxy routes use.
+    let resp = client
+        .get(format!("{url}/dashboard"))
+        .header("x-session-token", "secret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(mock.captured_requests().await.is_empty());
+
+    proxy.shutdown().await.unwrap();
+}
@@ -0,0 +1,127 @@
+//! `debug_logging.detail_sample_rate`: only a deterministic share of
+//! requests capture headers and bodies, while every request is still
+//! counted.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{
+    Backend, Config, ConfigStore, DebugLogLevel, DebugLoggingConfig, Defaults, ProxyConfig,
+};
+use anyclaude::metrics::{sampled_for_detail, DebugLogger, MetricsSnapshot};
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+const REQUESTS: usize = 3;
+
+async fn run_requests(rate: f64) -> MetricsSnapshot {
+    let mock = MockBackend::start().await;
+    for _ in 0..REQUESTS {
+        mock.enqueue_response(MockResponse::json(
+            r#"{"type":"message","content":[{"type":"text","text":"hi"}]}"#,
+        ))
+        .await;
+    }
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "mock".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "mock".to_string(),
+            display_name: "Mock".to_string(),
+            base_url: mock.base_url(),
+            auth_type_str: "passthrough".to_string(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test-detail-sampling.toml"));
+    let dir = tempfile::tempdir().unwrap();
+    let debug_logger = Arc::new(DebugLogger::new(DebugLoggingConfig {
+        level: DebugLogLevel::Full,
+        destination: anyclaude::config::DebugLogDestination::File,
+        file_path: dir.path().join("debug.log").to_string_lossy().into_owned(),
+        detail_sample_rate: rate,
+        ..DebugLoggingConfig::default()
+    }));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let hub = server.observability();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(addr, Duration::from_secs(5)).await;
+
+    let client = reqwest::Client::new();
+    for _ in 0..REQUESTS {
+        let resp = client
+            .post(format!("http://{addr}/v1/messages"))
+            .header("content-type", "application/json")
+            .body(r#"{"model":"claude-opus-4-6","max_tokens":16,"messages":[]}"#)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let _ = resp.bytes().await.unwrap();
+    }
+
+    for _ in 0..100 {
+        let snapshot = hub.snapshot();
+        if snapshot.recent.len() >= REQUESTS {
+            return snapshot;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("requests were not recorded");
+}
+
+#[tokio::test]
+async fn zero_rate_counts_requests_without_details() {
+    let snapshot = run_requests(0.0).await;
+    assert_eq!(snapshot.per_backend["mock"].total, REQUESTS as u64);
+    for record in &snapshot.recent {
+        assert!(record.request_meta.is_none(), "{:?}", record.request_meta);
+        assert!(record.response_meta.is_none());
+    }
+}
+
+#[tokio::test]
+async fn full_rate_captures_every_request() {
+    let snapshot = run_requests(1.0).await;
+    assert_eq!(snapshot.per_backend["mock"].total, REQUESTS as u64);
+    for record in &snapshot.recent {
+        let meta = record.request_meta.as_ref().expect("request meta");
+        assert_eq!(meta.path, "/v1/messages");
+        assert!(meta.body_preview.is_some());
+        assert!(record.response_meta.as_ref().is_some_and(|m| m.body_preview.is_some()));
+    }
+}
+
+#[test]
+fn sampling_is_deterministic_and_proportional() {
+    let ids: Vec<String> = (0..2000).map(|i| format!("req-{i}")).collect();
+    let picked: Vec<bool> = ids.iter().map(|id| sampled_for_detail(id, 0.25)).collect();
+    let again: Vec<bool> = ids.iter().map(|id| sampled_for_detail(id, 0.25)).collect();
+    assert_eq!(picked, again);
+
+    let share = picked.iter().filter(|&&p| p).count() as f64 / ids.len() as f64;
+    assert!((0.2..0.3).contains(&share), "sampled share {share}");
+
+    // A request in a smaller sample is also in every larger one.
+    for id in &ids {
+        if sampled_for_detail(id, 0.1) {
+            assert!(sampled_for_detail(id, 0.5));
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! The embedding API: a proxy built from a plain `Config`, either as a
+//! router the host serves itself or as a started server with a shutdown
+//! trigger.
+
+mod common;
+
+use anyclaude::config::{Backend, Config, Defaults, ProxyConfig};
+use anyclaude::embed::ProxyBuilder;
+use anyclaude::proxy::ProxyAddr;
+use common::mock_backend::{MockBackend, MockResponse};
+use tokio::net::TcpListener;
+
+fn config(upstream: &str) -> Config {
+    Config {
+        defaults: Defaults {
+            active: "mock".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("127.0.0.1:{}", common::free_port()),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "mock".to_string(),
+            base_url: upstream.to_string(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    }
+}
+
+async fn post_message(url: &str) -> String {
+    reqwest::Client::new()
+        .post(format!("{url}/v1/messages"))
+        .header("content-type", "application/json")
+        .body(r#"{"model":"claude-sonnet-4-5","max_tokens":8,"messages":[]}"#)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn started_proxy_forwards_and_shuts_down() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"embedded":true}"#)).await;
+
+    let proxy = ProxyBuilder::new(config(&mock.base_url())).start().await.unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP address");
+    };
+    assert_eq!(proxy.backend_state().get_active_backend(), "mock");
+
+    let body = post_message(&format!("http://{addr}")).await;
+    assert!(body.contains("embedded"));
+    assert_eq!(mock.captured_requests().await[0].path, "/v1/messages");
+
+    proxy.shutdown().await.unwrap();
+    assert!(reqwest::get(format!("http://{addr}/health")).await.is_err());
+}
+
+#[tokio::test]
+async fn router_serves_from_a_host_listener() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"routed":true}"#)).await;
+
+    let router = ProxyBuilder::new(config(&mock.base_url())).router().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let body = post_message(&format!("http://{addr}")).await;
+    assert!(body.contains("routed"));
+    assert_eq!(mock.captured_requests().await.len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_active_backend_is_rejected_at_build() {
+    let mut config = config("http://127.0.0.1:1");
+    config.defaults.active = "missing".to_string();
+    assert!(ProxyBuilder::new(config).router().is_err());
+}
@@ -0,0 +1,80 @@
+//! `ObservabilityHub::error_summary` — recent failed requests, counted by
+//! category, and the header segment built from it.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyclaude::metrics::{ErrorCategory, ErrorSeverity, ObservabilityHub};
+use anyclaude::ui::chrome_labels::{error_segment, CHROME_ERROR_COLOR, CHROME_WARNING_COLOR};
+use axum::body::Body;
+use axum::http::Request;
+
+fn finish(hub: &ObservabilityHub, status: Option<u16>, timed_out: bool) {
+    let req = Request::new(Body::empty());
+    let mut start = hub.start_request("req".to_string(), &req, "claude");
+    if timed_out {
+        start.span.mark_timed_out();
+    }
+    hub.finish_error(start.span, status);
+}
+
+#[test]
+fn counts_failures_by_category() {
+    let hub = ObservabilityHub::new(100);
+    finish(&hub, Some(200), false);
+    finish(&hub, Some(502), false);
+    finish(&hub, Some(500), false);
+    finish(&hub, Some(429), false);
+    finish(&hub, Some(404), false);
+    finish(&hub, None, true);
+
+    let summary = hub.error_summary(UNIX_EPOCH);
+    assert_eq!(summary.counts.get(&ErrorCategory::Upstream), Some(&2));
+    assert_eq!(summary.counts.get(&ErrorCategory::RateLimit), Some(&1));
+    assert_eq!(summary.counts.get(&ErrorCategory::Client), Some(&1));
+    assert_eq!(summary.counts.get(&ErrorCategory::Timeout), Some(&1));
+    assert_eq!(summary.total(), 5);
+    assert_eq!(summary.max_severity(), Some(ErrorSeverity::Error));
+    assert_eq!(summary.to_string(), "2 upstream, 1 timeout, 1 rate limit, 1 client");
+}
+
+#[test]
+fn only_failures_since_the_cutoff_are_counted() {
+    let hub = ObservabilityHub::new(100);
+    finish(&hub, Some(503), false);
+    let later = SystemTime::now() + Duration::from_secs(1);
+    assert!(hub.error_summary(later).is_empty());
+
+    let cutoff = SystemTime::now();
+    finish(&hub, Some(429), false);
+    let summary = hub.error_summary(cutoff);
+    assert_eq!(summary.to_string(), "1 rate limit");
+    assert_eq!(summary.max_severity(), Some(ErrorSeverity::Warning));
+}
+
+#[test]
+fn summary_covers_the_recent_request_ring() {
+    let hub = ObservabilityHub::new(2);
+    finish(&hub, Some(503), false);
+    finish(&hub, Some(500), false);
+    assert_eq!(hub.error_summary(UNIX_EPOCH).to_string(), "2 upstream");
+
+    // Failures pushed out of the ring are no longer counted.
+    finish(&hub, Some(200), false);
+    assert_eq!(hub.error_summary(UNIX_EPOCH).to_string(), "1 upstream");
+}
+
+#[test]
+fn segment_is_absent_without_errors_and_coloured_by_severity() {
+    let hub = ObservabilityHub::new(100);
+    finish(&hub, Some(200), false);
+    assert!(error_segment(&hub.error_summary(UNIX_EPOCH)).is_none());
+
+    finish(&hub, Some(400), false);
+    let warning = error_segment(&hub.error_summary(UNIX_EPOCH)).expect("segment");
+    assert_eq!(warning.text, "Errors: 1 client");
+    assert_eq!(warning.color, CHROME_WARNING_COLOR);
+
+    finish(&hub, Some(504), false);
+    let error = error_segment(&hub.error_summary(UNIX_EPOCH)).expect("segment");
+    assert_eq!(error.color, CHROME_ERROR_COLOR);
+}
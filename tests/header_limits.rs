@@ -0,0 +1,126 @@
+//! Incoming header limits: requests over `proxy.max_header_count` or
+//! `proxy.max_header_bytes` get a 431 before reaching any handler.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+async fn start(mock: &MockBackend, max_header_count: usize, max_header_bytes: usize) -> String {
+    let config = Config {
+        defaults: Defaults {
+            active: "test".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("127.0.0.1:{}", common::free_port()),
+            max_header_count,
+            max_header_bytes,
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "test".to_string(),
+            base_url: mock.base_url(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let mut server =
+        ProxyServer::new(config_store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn oversized_header_gets_431() {
+    let mock = MockBackend::start().await;
+    let url = start(&mock, 100, 8 * 1024).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{url}/v1/messages"))
+        .header("x-padding", "a".repeat(16 * 1024))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 431);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "headers_too_large");
+    assert!(mock.captured_requests().await.is_empty());
+}
+
+#[tokio::test]
+async fn too_many_headers_get_431() {
+    let mock = MockBackend::start().await;
+    let url = start(&mock, 10, 64 * 1024).await;
+
+    let mut req = reqwest::Client::new().get(format!("{url}/health"));
+    for i in 0..20 {
+        req = req.header(format!("x-extra-{i}"), "v");
+    }
+    let resp = req.send().await.unwrap();
+
+    assert_eq!(resp.status(), 431);
+}
+
+#[tokio::test]
+async fn headers_within_limits_pass() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    let url = start(&mock, 100, 64 * 1024).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{url}/v1/messages"))
+        .header("x-padding", "a".repeat(1024))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(mock.captured_requests().await.len(), 1);
+}
+
+#[tokio::test]
+async fn rejection_echoes_the_request_id() {
+    let mock = MockBackend::start().await;
+    let url = start(&mock, 100, 8 * 1024).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{url}/v1/messages"))
+        .header("x-request-id", "client-id-42")
+        .header("x-padding", "a".repeat(16 * 1024))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 431);
+    assert_eq!(resp.headers()["x-request-id"], "client-id-42");
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"]["request_id"], "client-id-42");
+
+    // Without a client id, the generated one is echoed.
+    let resp = client
+        .post(format!("{url}/v1/messages"))
+        .header("x-padding", "a".repeat(16 * 1024))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    let echoed = resp.headers()["x-request-id"].to_str().unwrap().to_string();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"]["request_id"], echoed.as_str());
+}
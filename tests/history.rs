@@ -19,6 +19,8 @@ fn make_entries(count: usize) -> Vec<HistoryEntry> {
                 Some(format!("backend-{}", i - 1))
             },
             to_backend: format!("backend-{}", i),
+            cwd: None,
+            title: None,
         })
         .collect()
 }
@@ -54,6 +56,7 @@ fn load_shows_dialog() {
     let mut s = HistoryDialogState::default();
     s.apply(HistoryIntent::Load {
         entries: make_entries(3),
+        capacity: 100,
     });
     assert!(s.is_visible());
 }
@@ -63,10 +66,60 @@ fn load_scrolls_to_end() {
     let mut s = HistoryDialogState::default();
     s.apply(HistoryIntent::Load {
         entries: make_entries(20),
+        capacity: 100,
     });
     assert_eq!(scroll_offset(&s), 20 - MAX_VISIBLE_ROWS);
 }
 
+#[test]
+fn load_keeps_only_the_newest_capacity_entries() {
+    let mut s = HistoryDialogState::default();
+    s.apply(HistoryIntent::Load {
+        entries: make_entries(50),
+        capacity: 30,
+    });
+    let HistoryDialogState::Visible { entries, .. } = &s else {
+        panic!("expected Visible");
+    };
+    assert_eq!(entries.len(), 30);
+    assert_eq!(entries[0].to_backend, "backend-20");
+    assert_eq!(entries[29].to_backend, "backend-49");
+}
+
+#[test]
+fn scroll_offset_stays_in_range_after_eviction() {
+    let mut s = HistoryDialogState::default();
+    s.apply(HistoryIntent::Load {
+        entries: make_entries(50),
+        capacity: 20,
+    });
+    assert_eq!(scroll_offset(&s), 20 - MAX_VISIBLE_ROWS);
+    for _ in 0..10 {
+        s.apply(HistoryIntent::PageDown);
+    }
+    assert_eq!(scroll_offset(&s), 20 - MAX_VISIBLE_ROWS);
+    for _ in 0..10 {
+        s.apply(HistoryIntent::PageUp);
+    }
+    assert_eq!(scroll_offset(&s), 0);
+}
+
+#[test]
+fn capacity_below_the_window_still_shows_every_kept_row() {
+    let mut s = HistoryDialogState::default();
+    s.apply(HistoryIntent::Load {
+        entries: make_entries(50),
+        capacity: 5,
+    });
+    assert_eq!(scroll_offset(&s), 0);
+    s.apply(HistoryIntent::ScrollDown);
+    assert_eq!(scroll_offset(&s), 0);
+    let HistoryDialogState::Visible { entries, .. } = &s else {
+        panic!("expected Visible");
+    };
+    assert_eq!(entries.len(), 5);
+}
+
 #[test]
 fn close_hides_dialog() {
     let mut s = HistoryDialogState::Visible {
@@ -99,6 +152,37 @@ fn scroll_down_clamps_at_max() {
     assert_eq!(scroll_offset(&s), max);
 }
 
+#[test]
+fn page_down_from_top_moves_one_page() {
+    let mut s = HistoryDialogState::Visible {
+        entries: make_entries(MAX_VISIBLE_ROWS * 3),
+        scroll_offset: 0,
+    };
+    s.apply(HistoryIntent::PageDown);
+    assert_eq!(scroll_offset(&s), MAX_VISIBLE_ROWS);
+}
+
+#[test]
+fn page_down_clamps_at_max() {
+    let entries = make_entries(MAX_VISIBLE_ROWS + 5);
+    let mut s = HistoryDialogState::Visible {
+        entries,
+        scroll_offset: 2,
+    };
+    s.apply(HistoryIntent::PageDown);
+    assert_eq!(scroll_offset(&s), 5);
+}
+
+#[test]
+fn page_up_clamps_at_first_entry() {
+    let mut s = HistoryDialogState::Visible {
+        entries: make_entries(MAX_VISIBLE_ROWS * 3),
+        scroll_offset: 3,
+    };
+    s.apply(HistoryIntent::PageUp);
+    assert_eq!(scroll_offset(&s), 0);
+}
+
 #[test]
 fn scroll_on_hidden_is_noop() {
     let mut s = HistoryDialogState::default();
@@ -0,0 +1,73 @@
+//! History persistence: entries round-trip through the JSONL store in
+//! order, the file keeps only the newest `capacity`, and free text is
+//! redacted like debug logs.
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyclaude::ui::history::{HistoryEntry, HistoryStore};
+
+fn entry(i: u64) -> HistoryEntry {
+    HistoryEntry {
+        timestamp: UNIX_EPOCH + Duration::from_secs(1_000 + i),
+        from_backend: (i > 0).then(|| format!("b{}", i - 1)),
+        to_backend: format!("b{i}"),
+        cwd: i.is_multiple_of(2).then(|| format!("/work/p{i}")),
+        title: None,
+    }
+}
+
+fn store(dir: &tempfile::TempDir, capacity: usize) -> HistoryStore {
+    HistoryStore::new(dir.path().join("history.jsonl"), capacity)
+}
+
+#[test]
+fn entries_round_trip_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let entries: Vec<HistoryEntry> = (0..5).map(entry).collect();
+
+    store(&dir, 10).save(&entries).unwrap();
+    assert_eq!(store(&dir, 10).load(), entries);
+}
+
+#[test]
+fn save_keeps_only_the_newest_capacity_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let entries: Vec<HistoryEntry> = (0..8).map(entry).collect();
+
+    store(&dir, 3).save(&entries).unwrap();
+    let raw = std::fs::read_to_string(dir.path().join("history.jsonl")).unwrap();
+    assert_eq!(raw.lines().count(), 3);
+    assert_eq!(store(&dir, 3).load(), entries[5..]);
+}
+
+#[test]
+fn load_caps_a_file_written_with_a_larger_capacity() {
+    let dir = tempfile::tempdir().unwrap();
+    let entries: Vec<HistoryEntry> = (0..6).map(entry).collect();
+
+    store(&dir, 10).save(&entries).unwrap();
+    assert_eq!(store(&dir, 2).load(), entries[4..]);
+}
+
+#[test]
+fn missing_file_loads_empty_and_bad_lines_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(store(&dir, 10).load().is_empty());
+
+    let good = serde_json::to_string(&entry(1)).unwrap();
+    std::fs::write(dir.path().join("history.jsonl"), format!("{{not json\n{good}\n")).unwrap();
+    assert_eq!(store(&dir, 10).load(), vec![entry(1)]);
+}
+
+#[test]
+fn saved_titles_are_redacted() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut e = entry(0);
+    e.title = Some("curl -H Bearer sk-secret-token".to_string());
+
+    store(&dir, 10).save(&[e]).unwrap();
+    let loaded = store(&dir, 10).load();
+    let title = loaded[0].title.as_deref().unwrap();
+    assert!(!title.contains("sk-secret-token"), "got: {title}");
+    assert!(title.starts_with("curl -H Bearer "));
+}
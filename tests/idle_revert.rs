@@ -0,0 +1,148 @@
+//! Inactivity auto-switch: a session left on a non-default backend goes
+//! back to `defaults.active` once no request has arrived for the timeout.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyclaude::backend::{BackendState, IdleRevert};
+use anyclaude::config::{Backend, Config, Defaults};
+use anyclaude::metrics::{ObservabilityHub, RequestOrigin};
+use anyclaude::proxy::router::TeammateMarker;
+use axum::body::Body;
+use axum::http::Request;
+
+const TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+fn state() -> BackendState {
+    let config = Config {
+        defaults: Defaults {
+            active: "default".to_string(),
+            idle_revert_minutes: 10,
+            ..Defaults::default()
+        },
+        backends: vec![
+            Backend {
+                name: "default".to_string(),
+                base_url: "http://127.0.0.1:1".to_string(),
+                ..Backend::default()
+            },
+            Backend {
+                name: "premium".to_string(),
+                base_url: "http://127.0.0.1:2".to_string(),
+                ..Backend::default()
+            },
+        ],
+        ..Config::default()
+    };
+    BackendState::from_config(config).unwrap()
+}
+
+#[test]
+fn switches_back_after_inactivity() {
+    let state = state();
+    state.switch_backend("premium").unwrap();
+    let start = Instant::now();
+    let idle = IdleRevert::new(state.clone(), TIMEOUT, start);
+
+    assert_eq!(idle.check(start + TIMEOUT - Duration::from_secs(1)), None);
+    assert_eq!(state.get_active_backend(), "premium");
+
+    assert_eq!(idle.check(start + TIMEOUT), Some("premium".to_string()));
+    assert_eq!(state.get_active_backend(), "default");
+}
+
+#[test]
+fn request_postpones_the_switch() {
+    let state = state();
+    state.switch_backend("premium").unwrap();
+    let start = Instant::now();
+    let idle = IdleRevert::new(state.clone(), TIMEOUT, start);
+
+    let request = start + Duration::from_secs(5 * 60);
+    idle.record_activity(request);
+
+    assert_eq!(idle.check(start + TIMEOUT), None);
+    assert_eq!(state.get_active_backend(), "premium");
+    assert_eq!(idle.check(request + TIMEOUT), Some("premium".to_string()));
+}
+
+#[test]
+fn never_fires_on_the_default_backend() {
+    let state = state();
+    let start = Instant::now();
+    let idle = IdleRevert::new(state.clone(), TIMEOUT, start);
+    let switches = state.get_switch_log().len();
+
+    assert_eq!(idle.check(start + TIMEOUT * 10), None);
+    assert_eq!(state.get_active_backend(), "default");
+    assert_eq!(state.get_switch_log().len(), switches);
+}
+
+#[test]
+fn manual_switch_restarts_the_clock() {
+    let state = state();
+    let start = Instant::now();
+    let idle = IdleRevert::new(state.clone(), TIMEOUT, start);
+
+    // Switched long after start: the idle time counts from the switch.
+    state.switch_backend("premium").unwrap();
+    let switched = start + TIMEOUT * 2;
+    assert_eq!(idle.check(switched), None);
+    assert_eq!(idle.check(switched + TIMEOUT - Duration::from_secs(1)), None);
+    assert_eq!(idle.check(switched + TIMEOUT), Some("premium".to_string()));
+}
+
+fn hub(idle: &Arc<IdleRevert>) -> ObservabilityHub {
+    ObservabilityHub::new(10).with_plugins(vec![idle.clone()])
+}
+
+#[test]
+fn teammate_requests_do_not_count_as_activity() {
+    let state = state();
+    state.switch_backend("premium").unwrap();
+    let start = Instant::now();
+    let idle = Arc::new(IdleRevert::new(state.clone(), TIMEOUT, start));
+    let hub = hub(&idle);
+
+    let mut req = Request::new(Body::empty());
+    req.extensions_mut().insert(TeammateMarker);
+    let mut request = hub.start_request("teammate".to_string(), &req, "premium");
+    request.span.set_origin(RequestOrigin::Teammate);
+    hub.finish_request(request.span);
+
+    assert_eq!(idle.check(start + TIMEOUT), Some("premium".to_string()));
+}
+
+#[test]
+fn finishing_a_main_request_counts_as_activity() {
+    let state = state();
+    state.switch_backend("premium").unwrap();
+    let idle = Arc::new(IdleRevert::new(state.clone(), TIMEOUT, Instant::now()));
+    let hub = hub(&idle);
+
+    let request = hub.start_request("main".to_string(), &Request::new(Body::empty()), "premium");
+    std::thread::sleep(Duration::from_millis(20));
+    let finishing = Instant::now();
+    hub.finish_request(request.span);
+
+    // Counted from the start alone, this is already 20ms past the timeout.
+    assert_eq!(idle.check(finishing + TIMEOUT - Duration::from_millis(1)), None);
+    assert_eq!(state.get_active_backend(), "premium");
+}
+
+#[test]
+fn streamed_chunks_count_as_activity() {
+    let state = state();
+    state.switch_backend("premium").unwrap();
+    let idle = Arc::new(IdleRevert::new(state.clone(), TIMEOUT, Instant::now()));
+    let hub = hub(&idle);
+
+    let request = hub.start_request("main".to_string(), &Request::new(Body::empty()), "premium");
+    std::thread::sleep(Duration::from_millis(20));
+    let chunk = Instant::now();
+    hub.stream_progress(&request.span);
+
+    // Counted from the start alone, this is already 20ms past the timeout.
+    assert_eq!(idle.check(chunk + TIMEOUT - Duration::from_millis(1)), None);
+    assert_eq!(state.get_active_backend(), "premium");
+}
@@ -5,7 +5,9 @@
 
 use anyclaude::ui::backend_switch::BackendSwitchIntent;
 use anyclaude::ui::history::HistoryIntent;
-use anyclaude::ui::input::{app_shortcut, backend_switch_nav, history_nav, settings_nav, AppShortcut};
+use anyclaude::ui::input::{
+    app_shortcut, backend_switch_nav, bound_app_shortcut, history_nav, settings_nav, AppShortcut, KeyChord,
+};
 use anyclaude::ui::settings::SettingsIntent;
 use winit::keyboard::{KeyCode, ModifiersState};
 
@@ -22,6 +24,53 @@ fn clipboard_is_on_cmd_not_ctrl() {
     assert_eq!(app_shortcut(KeyCode::KeyC, CTRL), None);
 }
 
+#[test]
+fn cmd_shift_c_copies_the_last_response() {
+    assert_eq!(
+        app_shortcut(KeyCode::KeyC, SUPER | ModifiersState::SHIFT),
+        Some(AppShortcut::CopyLastResponse)
+    );
+    assert_eq!(app_shortcut(KeyCode::KeyC, CTRL | ModifiersState::SHIFT), None);
+}
+
+#[test]
+fn key_chords_parse_modifiers_and_one_key() {
+    assert_eq!(KeyChord::parse("cmd+shift+c"), Some(KeyChord::COPY_LAST_RESPONSE));
+    assert_eq!(
+        KeyChord::parse(" Ctrl + Alt + Y "),
+        Some(KeyChord { code: KeyCode::KeyY, modifiers: CTRL | ModifiersState::ALT })
+    );
+    assert_eq!(
+        KeyChord::parse("super+7"),
+        Some(KeyChord { code: KeyCode::Digit7, modifiers: SUPER })
+    );
+    // Needs Cmd or Ctrl, one known key, and only known modifiers.
+    assert_eq!(KeyChord::parse("shift+c"), None);
+    assert_eq!(KeyChord::parse("c"), None);
+    assert_eq!(KeyChord::parse("ctrl+f1"), None);
+    assert_eq!(KeyChord::parse("hyper+c"), None);
+    assert_eq!(KeyChord::parse(""), None);
+}
+
+#[test]
+fn copy_last_response_moves_to_its_bound_chord() {
+    let chord = KeyChord::parse("ctrl+shift+y").unwrap();
+    assert_eq!(
+        bound_app_shortcut(KeyCode::KeyY, CTRL | ModifiersState::SHIFT, chord),
+        Some(AppShortcut::CopyLastResponse)
+    );
+    // The default chord no longer copies the response...
+    assert_eq!(bound_app_shortcut(KeyCode::KeyC, SUPER | ModifiersState::SHIFT, chord), None);
+    // ...and every other shortcut is unchanged.
+    assert_eq!(bound_app_shortcut(KeyCode::KeyC, SUPER, chord), Some(AppShortcut::CopySelection));
+    assert_eq!(bound_app_shortcut(KeyCode::KeyT, CTRL, chord), Some(AppShortcut::ToggleBackendPopup));
+    // With the default chord it is plain `app_shortcut`.
+    assert_eq!(
+        bound_app_shortcut(KeyCode::KeyC, SUPER | ModifiersState::SHIFT, KeyChord::COPY_LAST_RESPONSE),
+        Some(AppShortcut::CopyLastResponse)
+    );
+}
+
 #[test]
 fn features_are_on_ctrl() {
     assert_eq!(app_shortcut(KeyCode::KeyT, CTRL), Some(AppShortcut::ToggleBackendPopup));
@@ -66,6 +115,8 @@ fn backend_switch_navigation() {
 fn history_navigation() {
     assert!(matches!(history_nav(KeyCode::ArrowUp), Some(HistoryIntent::ScrollUp)));
     assert!(matches!(history_nav(KeyCode::ArrowDown), Some(HistoryIntent::ScrollDown)));
+    assert!(matches!(history_nav(KeyCode::PageUp), Some(HistoryIntent::PageUp)));
+    assert!(matches!(history_nav(KeyCode::PageDown), Some(HistoryIntent::PageDown)));
     assert!(history_nav(KeyCode::Enter).is_none());
 }
 
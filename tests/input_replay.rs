@@ -0,0 +1,119 @@
+//! `--record` / `--replay` input scripts: the JSON-lines format, recording
+//! through the session, and paced replay.
+
+use std::time::{Duration, Instant};
+
+use anyclaude::ui::gpu::replay::{InputRecorder, InputRecording, RecordedInput};
+
+fn event(delay_ms: u64, bytes: &[u8]) -> RecordedInput {
+    RecordedInput {
+        delay: Duration::from_millis(delay_ms),
+        bytes: bytes.to_vec(),
+    }
+}
+
+#[test]
+fn recording_round_trips_through_jsonl() {
+    let recording = InputRecording {
+        events: vec![event(0, b"hi\r"), event(250, b"\x1b[A"), event(5, &[0x1b, b'[', b'M', 0xff])],
+    };
+    let text = recording.to_jsonl();
+    assert!(text.starts_with(r#"{"delay_ms":0,"text":"hi\r"}"#), "{text}");
+    assert!(text.contains(r#""bytes":[27,91,77,255]"#), "{text}");
+    assert_eq!(InputRecording::parse(&text).unwrap(), recording);
+}
+
+#[test]
+fn hand_written_recording_parses_and_bad_lines_are_named() {
+    let parsed = InputRecording::parse("{\"delay_ms\":100,\"text\":\"\\u001b[B\"}\n\n").unwrap();
+    assert_eq!(parsed.events, vec![event(100, b"\x1b[B")]);
+
+    let err = InputRecording::parse("{\"delay_ms\":1,\"text\":\"a\"}\nnot json\n").unwrap_err();
+    assert!(err.starts_with("line 2:"), "{err}");
+}
+
+#[test]
+fn replay_delivers_every_write_with_its_gaps() {
+    let recording = InputRecording {
+        events: vec![event(0, b"a"), event(150, b"b"), event(100, b"c")],
+    };
+    let start = Instant::now();
+    let mut received = Vec::new();
+    recording.replay(|bytes| {
+        received.push((start.elapsed(), bytes.to_vec()));
+        true
+    });
+
+    let bytes: Vec<&[u8]> = received.iter().map(|(_, b)| b.as_slice()).collect();
+    assert_eq!(bytes, [b"a", b"b", b"c"]);
+    let gap = |i: usize| received[i].0 - received[i - 1].0;
+    let tolerance = Duration::from_millis(100);
+    assert!(gap(1) >= Duration::from_millis(150) && gap(1) < Duration::from_millis(150) + tolerance);
+    assert!(gap(2) >= Duration::from_millis(100) && gap(2) < Duration::from_millis(100) + tolerance);
+}
+
+#[test]
+fn replay_stops_once_the_child_is_gone() {
+    let recording = InputRecording {
+        events: vec![event(0, b"a"), event(0, b"b"), event(0, b"c")],
+    };
+    let mut sent = 0;
+    recording.replay(|_| {
+        sent += 1;
+        false
+    });
+    assert_eq!(sent, 1);
+}
+
+#[test]
+fn recorder_captures_gaps_between_writes() {
+    let mut recorder = InputRecorder::new();
+    recorder.record(b"x");
+    std::thread::sleep(Duration::from_millis(60));
+    recorder.record(b"y");
+
+    let events = &recorder.recording().events;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].bytes, b"y");
+    assert!(events[1].delay >= Duration::from_millis(60));
+}
+
+#[cfg(unix)]
+#[test]
+fn session_records_input_and_replay_reaches_a_child() {
+    use anyclaude::ui::gpu::pty::ChildPty;
+    use anyclaude::ui::gpu::session::Session;
+
+    let mut session = Session::new("/bin/sh".to_string(), Vec::new(), Vec::new());
+    session.recorder = Some(InputRecorder::new());
+    session.pty = Some(
+        ChildPty::spawn(
+            80,
+            24,
+            "/bin/sh".to_string(),
+            vec!["-c".to_string(), "stty -echo; read line; printf 'got:%s' \"$line\"".to_string()],
+            Vec::new(),
+            || {},
+            || {},
+        )
+        .expect("spawn /bin/sh"),
+    );
+    std::thread::sleep(Duration::from_millis(100));
+
+    let recording = InputRecording {
+        events: vec![event(0, b"rep"), event(50, b"lay\r")],
+    };
+    recording.replay(|bytes| session.write_input(bytes).is_ok());
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut out = Vec::new();
+    while !out.windows(10).any(|w| w == b"got:replay") && Instant::now() < deadline {
+        out.extend(session.pty.as_mut().unwrap().drain().concat());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(out.windows(10).any(|w| w == b"got:replay"), "got {:?}", String::from_utf8_lossy(&out));
+
+    let recorded = &session.recorder.as_ref().unwrap().recording().events;
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[1].bytes, b"lay\r");
+}
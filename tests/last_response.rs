@@ -0,0 +1,238 @@
+//! The last assistant response, as captured by the proxy for the
+//! Cmd+Shift+C "copy last response" shortcut: text blocks of the newest
+//! successful main-agent response win, failures, teammates and subagents
+//! never replace it, and there is nothing to copy before the first response.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{
+    AgentsConfig, Backend, Config, ConfigStore, DebugLoggingConfig, Defaults, ProxyConfig,
+};
+use anyclaude::metrics::{DebugLogger, ObservabilityHub};
+use anyclaude::proxy::ProxyServer;
+use anyclaude::sse::{parse_sse_events, ResponseTextCollector};
+use common::mock_backend::{MockBackend, MockResponse};
+
+fn reply(blocks: &[&str]) -> Vec<String> {
+    let mut events = vec![r#"{"type":"message_start","message":{"id":"msg_01","model":"m"}}"#.to_string()];
+    events.push(
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#
+            .to_string(),
+    );
+    events.push(
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"hmm"}}"#
+            .to_string(),
+    );
+    for (i, text) in blocks.iter().enumerate() {
+        let index = i + 1;
+        events.push(format!(
+            r#"{{"type":"content_block_start","index":{index},"content_block":{{"type":"text","text":""}}}}"#
+        ));
+        for word in text.split_inclusive(' ') {
+            events.push(format!(
+                r#"{{"type":"content_block_delta","index":{index},"delta":{{"type":"text_delta","text":"{word}"}}}}"#
+            ));
+        }
+        events.push(format!(r#"{{"type":"content_block_stop","index":{index}}}"#));
+    }
+    events.push(r#"{"type":"message_stop"}"#.to_string());
+    events
+}
+
+async fn start_proxy(mock: &MockBackend) -> (std::net::SocketAddr, ObservabilityHub) {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "mock".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "mock".to_string(),
+            display_name: "Mock".to_string(),
+            base_url: mock.base_url(),
+            auth_type_str: "passthrough".to_string(),
+            ..Backend::default()
+        }],
+        agents: Some(AgentsConfig {
+            teammate_backend: "mock".to_string(),
+            subagent_backend: Some("mock".to_string()),
+            team_backends: HashMap::new(),
+        }),
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test-last-response.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(DebugLoggingConfig::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let hub = server.observability();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(addr, Duration::from_secs(5)).await;
+    (addr, hub)
+}
+
+const MAIN_REQUEST: &str = r#"{"model":"claude-opus-4-6","stream":true,"max_tokens":16,"messages":[]}"#;
+
+async fn send(addr: std::net::SocketAddr) -> u16 {
+    send_to(addr, "/v1/messages", MAIN_REQUEST).await
+}
+
+async fn send_to(addr: std::net::SocketAddr, path: &str, body: &str) -> u16 {
+    let resp = reqwest::Client::new()
+        .post(format!("http://{addr}{path}"))
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap();
+    let status = resp.status().as_u16();
+    let _ = resp.bytes().await.unwrap();
+    status
+}
+
+/// Wait until `count` requests have been recorded by the hub.
+async fn settle(hub: &ObservabilityHub, count: usize) {
+    for _ in 0..100 {
+        if hub.snapshot().recent.len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("requests were not recorded");
+}
+
+#[test]
+fn collector_joins_text_blocks_and_skips_thinking() {
+    let body: String = reply(&["Hello there", "Second block"])
+        .iter()
+        .map(|e| format!("data: {e}\n\n"))
+        .collect();
+    let mut collector = ResponseTextCollector::new();
+    for event in parse_sse_events(body.as_bytes()) {
+        collector.record(&event);
+    }
+    assert_eq!(collector.text(), "Hello there\n\nSecond block");
+}
+
+#[tokio::test]
+async fn nothing_to_copy_before_any_response() {
+    let mock = MockBackend::start().await;
+    let (_, hub) = start_proxy(&mock).await;
+    assert_eq!(hub.last_response_text(), None);
+}
+
+#[tokio::test]
+async fn the_most_recent_response_wins() {
+    let mock = MockBackend::start().await;
+    let first = reply(&["First answer"]);
+    let second = reply(&["Second answer"]);
+    mock.enqueue_response(MockResponse::sse(&first.iter().map(String::as_str).collect::<Vec<_>>()))
+        .await;
+    mock.enqueue_response(MockResponse::sse(&second.iter().map(String::as_str).collect::<Vec<_>>()))
+        .await;
+    let (addr, hub) = start_proxy(&mock).await;
+
+    assert_eq!(send(addr).await, 200);
+    settle(&hub, 1).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("First answer"));
+
+    assert_eq!(send(addr).await, 200);
+    settle(&hub, 2).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("Second answer"));
+}
+
+#[tokio::test]
+async fn a_failed_response_keeps_the_previous_one() {
+    let mock = MockBackend::start().await;
+    let ok = reply(&["Kept"]);
+    mock.enqueue_response(MockResponse::sse(&ok.iter().map(String::as_str).collect::<Vec<_>>()))
+        .await;
+    let failed = reply(&["Error body"]);
+    mock.enqueue_response(MockResponse::sse_with_status(
+        500,
+        &failed.iter().map(String::as_str).collect::<Vec<_>>(),
+    ))
+    .await;
+    let (addr, hub) = start_proxy(&mock).await;
+
+    assert_eq!(send(addr).await, 200);
+    settle(&hub, 1).await;
+    send(addr).await;
+    settle(&hub, 2).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("Kept"));
+}
+
+#[test]
+fn collector_reads_text_blocks_of_a_json_message() {
+    let body = br#"{"type":"message","content":[
+        {"type":"thinking","thinking":"hmm","signature":"s"},
+        {"type":"text","text":"Hello there"},
+        {"type":"tool_use","id":"t","name":"x","input":{}},
+        {"type":"text","text":"Second block"}]}"#;
+    assert_eq!(ResponseTextCollector::from_message(body).text(), "Hello there\n\nSecond block");
+    assert_eq!(ResponseTextCollector::from_message(b"not json").text(), "");
+}
+
+#[tokio::test]
+async fn a_non_streaming_response_is_kept_too() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(
+        r#"{"type":"message","content":[{"type":"text","text":"Plain answer"}],"stop_reason":"end_turn"}"#,
+    ))
+    .await;
+    let (addr, hub) = start_proxy(&mock).await;
+
+    let body = r#"{"model":"claude-opus-4-6","max_tokens":16,"messages":[]}"#;
+    assert_eq!(send_to(addr, "/v1/messages", body).await, 200);
+    settle(&hub, 1).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("Plain answer"));
+}
+
+#[tokio::test]
+async fn teammate_and_subagent_responses_keep_the_main_one() {
+    let mock = MockBackend::start().await;
+    for text in ["Main answer", "Teammate answer", "Subagent answer"] {
+        let events = reply(&[text]);
+        mock.enqueue_response(MockResponse::sse(&events.iter().map(String::as_str).collect::<Vec<_>>()))
+            .await;
+    }
+    let (addr, hub) = start_proxy(&mock).await;
+
+    assert_eq!(send(addr).await, 200);
+    settle(&hub, 1).await;
+
+    assert_eq!(send_to(addr, "/teammate/alice@team/v1/messages", MAIN_REQUEST).await, 200);
+    settle(&hub, 2).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("Main answer"));
+
+    let registered = reqwest::Client::new()
+        .post(format!("http://{addr}/api/subagent-start"))
+        .json(&serde_json::json!({ "agent_id": "a1b2c3d4" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(registered.status().is_success());
+    let subagent_request = serde_json::json!({
+        "model": "claude-opus-4-6",
+        "stream": true,
+        "max_tokens": 16,
+        "messages": [{
+            "role": "user",
+            "content": "<system-reminder>SubagentStart hook additional context: \u{27E8}AC:a1b2c3d4\u{27E9}</system-reminder>"
+        }]
+    });
+    assert_eq!(send_to(addr, "/v1/messages", &subagent_request.to_string()).await, 200);
+    settle(&hub, 3).await;
+    assert_eq!(hub.last_response_text().as_deref(), Some("Main answer"));
+}
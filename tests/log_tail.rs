@@ -0,0 +1,107 @@
+//! The debug logger's in-memory tail and the `/api/log-tail` endpoint that
+//! serves it.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{
+    Backend, Config, ConfigStore, DebugLogDestination, DebugLogFormat, DebugLogLevel,
+    DebugLoggingConfig, Defaults, ProxyConfig,
+};
+use anyclaude::metrics::{DebugLogger, LOG_TAIL_CAPACITY};
+use anyclaude::proxy::ProxyServer;
+
+fn file_logger(dir: &tempfile::TempDir) -> DebugLogger {
+    DebugLogger::new(DebugLoggingConfig {
+        level: DebugLogLevel::Basic,
+        format: DebugLogFormat::Json,
+        destination: DebugLogDestination::File,
+        file_path: dir.path().join("debug.log").to_string_lossy().into_owned(),
+        ..DebugLoggingConfig::default()
+    })
+}
+
+fn log_lines(logger: &DebugLogger, count: usize) {
+    for i in 0..count {
+        logger.log_auxiliary("tail-test", None, None, Some(&format!("line {i}")), None);
+        // Events beyond the channel's capacity are dropped, not queued.
+        if i % 100 == 99 {
+            assert!(logger.flush(Duration::from_secs(5)));
+        }
+    }
+    assert!(logger.flush(Duration::from_secs(5)));
+}
+
+#[test]
+fn tail_returns_most_recent_lines_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = file_logger(&dir);
+    log_lines(&logger, 5);
+
+    let tail = logger.tail(3);
+    assert_eq!(tail.len(), 3);
+    for (line, i) in tail.iter().zip(2..) {
+        assert!(line.contains(&format!("\"line {i}\"")), "{line}");
+    }
+    assert_eq!(logger.tail(10).len(), 5, "fewer logged than requested");
+    assert!(logger.tail(0).is_empty());
+}
+
+#[test]
+fn tail_is_bounded() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = file_logger(&dir);
+    log_lines(&logger, LOG_TAIL_CAPACITY + 5);
+
+    let tail = logger.tail(usize::MAX);
+    assert_eq!(tail.len(), LOG_TAIL_CAPACITY);
+    assert!(tail[0].contains("\"line 5\""), "{}", tail[0]);
+}
+
+#[tokio::test]
+async fn log_tail_endpoint_serves_recent_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = Arc::new(file_logger(&dir));
+    log_lines(&logger, 4);
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "main".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "main".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth_type_str: "passthrough".to_string(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test-log-tail.toml"));
+    let mut server = ProxyServer::new(config_store.clone(), logger, None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(addr, Duration::from_secs(5)).await;
+
+    let body: serde_json::Value = reqwest::get(format!("http://{addr}/api/log-tail?lines=2"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let lines = body["lines"].as_array().expect("lines array");
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].as_str().unwrap().contains("\"line 2\""));
+    assert!(lines[1].as_str().unwrap().contains("\"line 3\""));
+}
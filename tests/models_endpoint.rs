@@ -0,0 +1,162 @@
+//! The synthetic `/v1/models` endpoint: lists client-facing model ids for
+//! the active backend's family mappings without contacting upstream.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyclaude::config::{AgentsConfig, Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::models::model_list;
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+use serde_json::Value;
+
+fn mapped_backend(base_url: &str) -> Backend {
+    Backend {
+        name: "mapped".to_string(),
+        display_name: "Mapped".to_string(),
+        base_url: base_url.to_string(),
+        auth_type_str: "passthrough".to_string(),
+        model_opus: Some("big".to_string()),
+        model_sonnet: Some("medium".to_string()),
+        model_haiku: Some("small".to_string()),
+        ..Backend::default()
+    }
+}
+
+async fn start(backend: Backend) -> (String, anyclaude::proxy::ProxyHandle) {
+    start_with(vec![backend], None).await
+}
+
+/// Serve `backends` with the first one active.
+async fn start_with(
+    backends: Vec<Backend>,
+    agents: Option<AgentsConfig>,
+) -> (String, anyclaude::proxy::ProxyHandle) {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: backends[0].name.clone(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends,
+        agents,
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    let handle = server.handle();
+    tokio::spawn(async move { let _ = server.run().await; });
+    (format!("http://{addr}"), handle)
+}
+
+#[tokio::test]
+async fn lists_client_models_for_all_mapped_families() {
+    let mock = MockBackend::start().await;
+    let (url, _handle) = start(mapped_backend(&mock.base_url())).await;
+
+    let list: Value = reqwest::get(format!("{url}/v1/models"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = list["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["claude-opus-4-6", "claude-sonnet-4-5", "claude-haiku-4-5"]);
+    assert_eq!(list["data"][0]["type"], "model");
+    assert_eq!(list["has_more"], false);
+    assert_eq!(list["first_id"], "claude-opus-4-6");
+    assert_eq!(list["last_id"], "claude-haiku-4-5");
+    assert!(mock.captured_requests().await.is_empty());
+}
+
+#[tokio::test]
+async fn unmapped_backend_forwards_upstream() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"data":[],"upstream":true}"#)).await;
+    let backend = Backend {
+        name: "plain".to_string(),
+        base_url: mock.base_url(),
+        ..Backend::default()
+    };
+    let (url, _handle) = start(backend).await;
+
+    let body = reqwest::get(format!("{url}/v1/models")).await.unwrap().text().await.unwrap();
+    assert!(body.contains("upstream"));
+    assert_eq!(mock.captured_requests().await[0].path, "/v1/models");
+}
+
+#[tokio::test]
+async fn teammate_route_lists_its_team_backend_models() {
+    let mock = MockBackend::start().await;
+    let plain = Backend {
+        name: "plain".to_string(),
+        base_url: mock.base_url(),
+        ..Backend::default()
+    };
+    let agents = AgentsConfig {
+        teammate_backend: "plain".to_string(),
+        subagent_backend: None,
+        team_backends: HashMap::from([("red".to_string(), "mapped".to_string())]),
+    };
+    let (url, _handle) = start_with(vec![plain, mapped_backend(&mock.base_url())], Some(agents)).await;
+
+    let list: Value = reqwest::get(format!("{url}/teammate/alice@red/v1/models"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["first_id"], "claude-opus-4-6");
+    assert!(mock.captured_requests().await.is_empty());
+}
+
+#[tokio::test]
+async fn teammate_route_forwards_for_unmapped_backend() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"data":[],"upstream":true}"#)).await;
+    let backend = Backend {
+        name: "plain".to_string(),
+        base_url: mock.base_url(),
+        ..Backend::default()
+    };
+    let (url, _handle) = start(backend).await;
+
+    let body = reqwest::get(format!("{url}/teammate/alice/v1/models"))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(body.contains("upstream"));
+    assert_eq!(mock.captured_requests().await[0].path, "/v1/models");
+}
+
+#[test]
+fn partial_mapping_lists_only_mapped_families() {
+    let backend = Backend {
+        model_haiku: Some("small".to_string()),
+        ..Backend::default()
+    };
+    let list = model_list(&backend).unwrap();
+    assert_eq!(list.data.len(), 1);
+    assert_eq!(list.data[0].id, "claude-haiku-4-5");
+    assert!(list.data[0].display_name.contains("small"));
+    assert!(model_list(&Backend::default()).is_none());
+}
@@ -7,16 +7,21 @@
 
 mod common;
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use axum::body::Body;
 use axum::http::{header::CONTENT_TYPE, Method, Request};
 
 use anyclaude::backend::{BackendState, AgentRegistry};
-use anyclaude::config::{Backend, Config, DebugLogDestination, DebugLogFormat, DebugLogLevel, DebugLoggingConfig, Defaults};
-use anyclaude::metrics::{DebugLogger, ObservabilityHub, RequestRecord, RequestSpan};
+use anyclaude::config::{
+    Backend, Config, DebugLogDestination, DebugLogFormat, DebugLogLevel, DebugLoggingConfig, Defaults,
+    ResponseNormalization,
+};
+use anyclaude::metrics::{DebugLogger, ObservabilityHub, RequestOrigin, RequestRecord, RequestSpan};
 use anyclaude::proxy::pipeline::{self, PipelineContext, PipelineConfig};
+use anyclaude::proxy::error::ProxyError;
 use anyclaude::proxy::pool::PoolConfig;
 use anyclaude::proxy::thinking::TransformerRegistry;
 use anyclaude::proxy::timeout::TimeoutConfig;
@@ -36,8 +41,17 @@ fn create_integration_config(mock_base_url: &str) -> Config {
             idle_timeout_seconds: 60,
             pool_idle_timeout_seconds: 90,
             pool_max_idle_per_host: 8,
+            warmup_connections: false,
             max_retries: 1, // Low for faster tests
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         backends: vec![
             Backend {
@@ -46,9 +60,21 @@ fn create_integration_config(mock_base_url: &str) -> Config {
                 base_url: mock_base_url.to_string(),
                 auth_type_str: "passthrough".to_string(),
                 api_key: None,
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: None,
                 thinking_budget_tokens: None,
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: None,
                 model_sonnet: Some("mock-sonnet".to_string()),
                 model_haiku: Some("mock-haiku".to_string()),
@@ -66,9 +92,12 @@ fn create_pipeline_context() -> PipelineContext {
         completed_at: None,
         latency_ms: None,
         ttfb_ms: None,
+        connect_ms: None,
         backend: String::new(),
+        origin: RequestOrigin::Main,
         status: None,
         timed_out: false,
+        cancelled: false,
         request_bytes: 0,
         response_bytes: 0,
         request_analysis: None,
@@ -76,6 +105,7 @@ fn create_pipeline_context() -> PipelineContext {
         routing_decision: None,
         request_meta: None,
         response_meta: None,
+        sse_events: None,
     };
     let span = RequestSpan::new(record);
     let debug_config = DebugLoggingConfig {
@@ -87,6 +117,7 @@ fn create_pipeline_context() -> PipelineContext {
         header_preview: false,
         full_body: false,
         pretty_print: false,
+        detail_sample_rate: 1.0,
         rotation: Default::default(),
     };
     let debug_logger = Arc::new(DebugLogger::new(debug_config));
@@ -153,6 +184,33 @@ async fn test_main_pipeline_json_request() {
     assert_eq!(body["model"], "mock-sonnet"); // Should be rewritten to backend's model
 }
 
+#[tokio::test]
+async fn test_main_pipeline_records_routing_decision() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let config = create_integration_config(&mock.base_url());
+    let backend_state = BackendState::from_config(config).unwrap();
+    let pipeline_config = create_pipeline_config(backend_state);
+    let mut ctx = create_pipeline_context();
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/v1/messages")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"model": "claude-3-sonnet", "messages": []}"#))
+        .unwrap();
+    pipeline::execute_pipeline(req, &pipeline_config, &mut ctx, None, None)
+        .await
+        .unwrap();
+
+    let decisions = ctx.observability.routing_decisions();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].request_id, "integration-test-id");
+    assert_eq!(decisions[0].path, "/v1/messages");
+    assert_eq!(decisions[0].reason, "active backend");
+}
+
 #[tokio::test]
 async fn test_main_pipeline_streaming_request() {
     let mock = MockBackend::start().await;
@@ -286,9 +344,21 @@ async fn test_pipeline_with_backend_override() {
         base_url: mock.base_url(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: Some("override-model".to_string()),
         model_haiku: None,
@@ -406,6 +476,184 @@ async fn test_pipeline_thinking_compat_conversion() {
     assert_eq!(body["thinking"]["budget_tokens"], 5000);
 }
 
+async fn run_with_bypass(mock: &MockBackend, path: &str) -> serde_json::Value {
+    let mut config = create_integration_config(&mock.base_url());
+    config.backends[0].thinking_compat = Some(true);
+    config.backends[0].thinking_budget_tokens = Some(5000);
+    config.proxy.bypass_paths = vec!["/v1/messages/count_tokens".to_string()];
+
+    let backend_state = BackendState::from_config(config).unwrap();
+    let pipeline_config = create_pipeline_config(backend_state);
+    let mut ctx = create_pipeline_context();
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"model": "claude-sonnet-4", "thinking": {"type": "adaptive"}, "messages": []}"#))
+        .unwrap();
+
+    pipeline::execute_pipeline(req, &pipeline_config, &mut ctx, None, None)
+        .await
+        .unwrap();
+
+    let captured = mock.captured_requests().await;
+    serde_json::from_slice(&captured.last().unwrap().body).unwrap()
+}
+
+#[tokio::test]
+async fn test_pipeline_passthrough_follows_config_reload() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let mut config = create_integration_config(&mock.base_url());
+    config.backends[0].thinking_compat = Some(true);
+    let backend_state = BackendState::from_config(config.clone()).unwrap();
+    let pipeline_config = create_pipeline_config(backend_state.clone());
+
+    config.proxy.passthrough = true;
+    backend_state.update_config(config).unwrap();
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/v1/messages")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"model": "claude-sonnet-4", "thinking": {"type": "adaptive"}, "messages": []}"#))
+        .unwrap();
+    pipeline::execute_pipeline(req, &pipeline_config, &mut create_pipeline_context(), None, None)
+        .await
+        .unwrap();
+
+    let captured = mock.captured_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&captured[0].body).unwrap();
+    assert_eq!(body["thinking"]["type"], "adaptive");
+    assert_eq!(body["model"], "claude-sonnet-4");
+}
+
+#[tokio::test]
+async fn test_pipeline_bypass_paths_follow_config_reload() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let mut config = create_integration_config(&mock.base_url());
+    config.backends[0].thinking_compat = Some(true);
+    let backend_state = BackendState::from_config(config.clone()).unwrap();
+    let pipeline_config = create_pipeline_config(backend_state.clone());
+
+    config.proxy.bypass_paths = vec!["/v1/messages".to_string()];
+    backend_state.update_config(config).unwrap();
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/v1/messages")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"model": "claude-sonnet-4", "thinking": {"type": "adaptive"}, "messages": []}"#))
+        .unwrap();
+    pipeline::execute_pipeline(req, &pipeline_config, &mut create_pipeline_context(), None, None)
+        .await
+        .unwrap();
+
+    let captured = mock.captured_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&captured[0].body).unwrap();
+    assert_eq!(body["thinking"]["type"], "adaptive");
+}
+
+#[tokio::test]
+async fn test_pipeline_bypass_path_skips_thinking_and_transforms() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"input_tokens": 3}"#)).await;
+
+    let body = run_with_bypass(&mock, "/v1/messages/count_tokens").await;
+
+    assert_eq!(body["thinking"]["type"], "adaptive");
+    assert_eq!(body["model"], "claude-sonnet-4");
+}
+
+#[tokio::test]
+async fn test_pipeline_messages_still_transformed_with_bypass_paths() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let body = run_with_bypass(&mock, "/v1/messages").await;
+
+    assert_eq!(body["thinking"]["type"], "enabled");
+    assert_eq!(body["model"], "mock-sonnet");
+}
+
+/// Upstream SSE that every response stage would rewrite: a backend stop
+/// reason, missing usage, a stream cut off before `message_stop`, and a
+/// pause long enough for keep-alives.
+const REWRITABLE_SSE: [&str; 2] = [
+    "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4\"}}\n\n",
+    "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"stop\"},\"usage\":{}}\n\n",
+];
+
+/// Send a request to `path` on a `force_buffer`, `response_normalization`
+/// and `match_client_stream` backend, with keep-alives on, and return the
+/// body the client receives. A non-`stream` request makes the backend's
+/// SSE a candidate for reshaping into JSON.
+async fn relay_rewritable_stream(path: &str, passthrough: bool, stream: bool) -> Vec<u8> {
+    use http_body_util::BodyExt;
+
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&REWRITABLE_SSE, 150)).await;
+
+    let mut config = create_integration_config(&mock.base_url());
+    config.proxy.passthrough = passthrough;
+    config.proxy.bypass_paths = vec!["/v1/raw".to_string()];
+    let backend = &mut config.backends[0];
+    backend.force_buffer = true;
+    backend.match_client_stream = true;
+    backend.response_normalization = Some(ResponseNormalization {
+        stop_reasons: HashMap::from([("stop".to_string(), "end_turn".to_string())]),
+        fill_usage_defaults: true,
+    });
+
+    let backend_state = BackendState::from_config(config).unwrap();
+    let mut pipeline_config = create_pipeline_config(backend_state);
+    pipeline_config.timeout_config.keepalive = Some(Duration::from_millis(40));
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(
+            r#"{{"model": "claude-sonnet-4", "stream": {stream}, "messages": []}}"#
+        )))
+        .unwrap();
+    let response = pipeline::execute_pipeline(
+        req,
+        &pipeline_config,
+        &mut create_pipeline_context(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    response.into_body().collect().await.unwrap().to_bytes().to_vec()
+}
+
+#[tokio::test]
+async fn test_pipeline_bypass_path_relays_response_unchanged() {
+    for stream in [true, false] {
+        let body = relay_rewritable_stream("/v1/raw/messages", false, stream).await;
+        assert_eq!(String::from_utf8(body).unwrap(), REWRITABLE_SSE.concat());
+
+        // The same stream on a regular path is rewritten.
+        let body = relay_rewritable_stream("/v1/messages", false, stream).await;
+        assert_ne!(String::from_utf8(body).unwrap(), REWRITABLE_SSE.concat());
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_passthrough_relays_response_unchanged() {
+    for stream in [true, false] {
+        let body = relay_rewritable_stream("/v1/messages", true, stream).await;
+        assert_eq!(String::from_utf8(body).unwrap(), REWRITABLE_SSE.concat());
+    }
+}
+
 // =============================================================================
 // Integration Test: Headers Processing
 // =============================================================================
@@ -532,6 +780,64 @@ async fn test_pipeline_malformed_json_body() {
     assert_eq!(captured[0].body, b"{\"invalid json");
 }
 
+async fn run_validated(mock: &MockBackend, path: &str, body: &'static str) -> Result<axum::http::Response<Body>, ProxyError> {
+    let config = create_integration_config(&mock.base_url());
+    let backend_state = BackendState::from_config(config).unwrap();
+    let mut pipeline_config = create_pipeline_config(backend_state);
+    pipeline_config.validate_json = true;
+    let mut ctx = create_pipeline_context();
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+
+    pipeline::execute_pipeline(req, &pipeline_config, &mut ctx, None, None).await
+}
+
+#[tokio::test]
+async fn test_pipeline_validate_json_rejects_truncated_body() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let err = run_validated(&mock, "/v1/messages", r#"{"model": "claude-3-sonnet", "messages": ["#)
+        .await
+        .expect_err("truncated body is rejected");
+
+    assert_eq!(err.status_code(), 400);
+    let message = err.to_string();
+    assert!(message.contains("Malformed JSON request body"), "{message}");
+    assert!(message.contains("line 1 column"), "parse location in {message}");
+    assert!(mock.captured_requests().await.is_empty(), "nothing forwarded upstream");
+}
+
+#[tokio::test]
+async fn test_pipeline_validate_json_passes_valid_body() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    let response = run_validated(&mock, "/v1/messages", r#"{"model": "claude-3-sonnet", "messages": []}"#)
+        .await
+        .expect("valid body is forwarded");
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(mock.captured_requests().await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_pipeline_validate_json_skips_non_messages_paths() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"ok": true}"#)).await;
+
+    run_validated(&mock, "/v1/other", r#"{"invalid json"#)
+        .await
+        .expect("non-messages path is not validated");
+
+    assert_eq!(mock.captured_requests().await[0].body, b"{\"invalid json");
+}
+
 #[tokio::test]
 async fn test_pipeline_unconfigured_backend() {
     let mut config = create_integration_config("http://127.0.0.1:59999");
@@ -26,9 +26,21 @@ fn create_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -48,12 +60,30 @@ fn config_with_teams(
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 1,
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
         terminal: TerminalConfig::default(),
         debug_logging: DebugLoggingConfig::default(),
@@ -106,7 +136,7 @@ async fn main_pipeline_routes_to_active_backend() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -136,7 +166,7 @@ async fn teammate_pipeline_routes_to_teammate_backend() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -165,7 +195,7 @@ async fn teammate_path_stripped_before_forwarding() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -192,7 +222,7 @@ async fn teammate_preserves_query_string() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -224,7 +254,7 @@ async fn teammates_with_trailing_s_routes_to_main_not_teammate() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -256,7 +286,7 @@ async fn bare_teammate_prefix_returns_404() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -322,7 +352,7 @@ async fn concurrent_main_and_teammate_route_correctly() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
     let h = Arc::new(h);
@@ -381,7 +411,7 @@ async fn teammate_requests_dont_increment_thinking_session() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
     let debug_logger = Arc::new(DebugLogger::new(Default::default()));
@@ -445,7 +475,7 @@ async fn teammate_sse_streaming_works() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let h = TestHarness::start(config).await;
 
@@ -487,7 +517,7 @@ async fn backend_switch_doesnt_affect_teammate_routing() {
             create_backend("teammate", &mock_teammate.base_url()),
         ],
         &format!("127.0.0.1:{}", common::free_port()),
-        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None }),
+        Some(AgentsConfig { teammate_backend: "teammate".to_string(), subagent_backend: None, team_backends: HashMap::new() }),
     );
     let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
     let debug_logger = Arc::new(DebugLogger::new(Default::default()));
@@ -525,3 +555,84 @@ async fn backend_switch_doesnt_affect_teammate_routing() {
 
     drop(handle);
 }
+
+#[tokio::test]
+async fn teammate_team_routes_to_team_backend() {
+    let mock_main = MockBackend::start().await;
+    let mock_default = MockBackend::start().await;
+    let mock_a = MockBackend::start().await;
+    let mock_b = MockBackend::start().await;
+    mock_a.enqueue_response(MockResponse::json(r#"{"from":"team-a"}"#)).await;
+    mock_b.enqueue_response(MockResponse::json(r#"{"from":"team-b"}"#)).await;
+    mock_default.enqueue_response(MockResponse::json(r#"{"from":"default"}"#)).await;
+
+    let config = config_with_teams(
+        vec![
+            create_backend("main", &mock_main.base_url()),
+            create_backend("teammate", &mock_default.base_url()),
+            create_backend("backend-a", &mock_a.base_url()),
+            create_backend("backend-b", &mock_b.base_url()),
+        ],
+        &format!("127.0.0.1:{}", common::free_port()),
+        Some(AgentsConfig {
+            teammate_backend: "teammate".to_string(),
+            subagent_backend: None,
+            team_backends: HashMap::from([
+                ("teamA".to_string(), "backend-a".to_string()),
+                ("teamB".to_string(), "backend-b".to_string()),
+            ]),
+        }),
+    );
+    let h = TestHarness::start(config).await;
+
+    for (agent, expected) in [
+        ("alice@teamA", "team-a"),
+        ("bob@teamB", "team-b"),
+        ("carol@teamC", "default"),
+    ] {
+        let resp = h.client
+            .post(h.url(&format!("/teammate/{agent}/v1/messages")))
+            .body("{}")
+            .send().await.unwrap();
+        assert!(resp.text().await.unwrap().contains(expected), "{agent}");
+    }
+
+    assert_eq!(mock_a.captured_requests().await[0].path, "/v1/messages");
+    assert_eq!(mock_b.captured_requests().await.len(), 1);
+    assert_eq!(mock_default.captured_requests().await.len(), 1);
+    assert_eq!(mock_main.captured_requests().await.len(), 0);
+}
+
+#[tokio::test]
+async fn teammate_start_registers_team_backend() {
+    let mock_default = MockBackend::start().await;
+    let mock_a = MockBackend::start().await;
+
+    let config = config_with_teams(
+        vec![
+            create_backend("teammate", &mock_default.base_url()),
+            create_backend("backend-a", &mock_a.base_url()),
+        ],
+        &format!("127.0.0.1:{}", common::free_port()),
+        Some(AgentsConfig {
+            teammate_backend: "teammate".to_string(),
+            subagent_backend: None,
+            team_backends: HashMap::from([("teamA".to_string(), "backend-a".to_string())]),
+        }),
+    );
+    let h = TestHarness::start(config).await;
+
+    let resp: serde_json::Value = h.client
+        .post(h.url("/api/teammate-start"))
+        .json(&serde_json::json!({"agent_id": "alice@teamA"}))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(resp["backend"], "backend-a");
+
+    let resp: serde_json::Value = h.client
+        .post(h.url("/api/teammate-start"))
+        .json(&serde_json::json!({"agent_id": "bob@teamZ"}))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(resp["backend"], "teammate");
+}
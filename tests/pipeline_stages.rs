@@ -11,13 +11,16 @@ use axum::body::Body;
 use axum::http::{header::{AUTHORIZATION, CONTENT_TYPE}, HeaderMap, Method, Request};
 use serde_json::json;
 
-use anyclaude::backend::{BackendState, AgentRegistry};
+use anyclaude::backend::{AgentRegistry, BackendBalancer, BackendState};
 use anyclaude::config::{Backend, Config, DebugLogDestination, DebugLogFormat, DebugLogLevel, DebugLoggingConfig, Defaults};
-use anyclaude::metrics::{BackendOverride, DebugLogger, ObservabilityHub, RequestRecord, RequestSpan};
+use anyclaude::metrics::{
+    BackendOverride, DebugLogger, ObservabilityHub, RequestOrigin, RequestRecord, RequestSpan,
+};
 use anyclaude::proxy::pipeline::{self, PipelineContext, PipelineConfig};
 use anyclaude::proxy::pool::PoolConfig;
 use anyclaude::proxy::thinking::TransformerRegistry;
 use anyclaude::proxy::timeout::TimeoutConfig;
+use anyclaude::sse::parse_sse_events;
 
 // =============================================================================
 // Test Helpers
@@ -32,8 +35,17 @@ fn create_test_config() -> Config {
             idle_timeout_seconds: 60,
             pool_idle_timeout_seconds: 90,
             pool_max_idle_per_host: 8,
+            warmup_connections: false,
             max_retries: 3,
             retry_backoff_base_ms: 100,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         backends: vec![
             Backend {
@@ -42,9 +54,21 @@ fn create_test_config() -> Config {
                 base_url: "http://127.0.0.1:9999".to_string(),
                 auth_type_str: "passthrough".to_string(),
                 api_key: None,
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: None,
                 thinking_budget_tokens: None,
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: None,
                 model_sonnet: Some("test-sonnet".to_string()),
                 model_haiku: None,
@@ -55,9 +79,21 @@ fn create_test_config() -> Config {
                 base_url: "https://api.anthropic.com".to_string(),
                 auth_type_str: "api_key".to_string(),
                 api_key: Some("test-api-key".to_string()),
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: Some(false),
                 thinking_budget_tokens: None,
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: None,
                 model_sonnet: None,
                 model_haiku: None,
@@ -68,9 +104,21 @@ fn create_test_config() -> Config {
                 base_url: "https://openrouter.ai/api".to_string(),
                 auth_type_str: "bearer".to_string(),
                 api_key: Some("openrouter-key".to_string()),
+                api_key_command: None,
+                api_key_command_ttl_seconds: 300,
                 pricing: None,
                 thinking_compat: Some(true),
                 thinking_budget_tokens: Some(5000),
+                supports_thinking: true,
+                thinking_registry_enabled: true,
+                supports_cache_control: true,
+                response_normalization: None,
+                usage_mapping: None,
+                force_buffer: false,
+                match_client_stream: false,
+                max_concurrent: None,
+                tags: Vec::new(),
+                weight: 1,
                 model_opus: Some("openrouter-opus".to_string()),
                 model_sonnet: Some("openrouter-sonnet".to_string()),
                 model_haiku: Some("openrouter-haiku".to_string()),
@@ -88,9 +136,12 @@ fn create_test_context() -> PipelineContext {
         completed_at: None,
         latency_ms: None,
         ttfb_ms: None,
+        connect_ms: None,
         backend: String::new(),
+        origin: RequestOrigin::Main,
         status: None,
         timed_out: false,
+        cancelled: false,
         request_bytes: 0,
         response_bytes: 0,
         request_analysis: None,
@@ -98,6 +149,7 @@ fn create_test_context() -> PipelineContext {
         routing_decision: None,
         request_meta: None,
         response_meta: None,
+        sse_events: None,
     };
     let span = RequestSpan::new(record);
     let debug_config = DebugLoggingConfig {
@@ -109,6 +161,7 @@ fn create_test_context() -> PipelineContext {
         header_preview: false,
         full_body: false,
         pretty_print: false,
+        detail_sample_rate: 1.0,
         rotation: Default::default(),
     };
     let debug_logger = Arc::new(DebugLogger::new(debug_config));
@@ -368,6 +421,97 @@ fn test_resolve_backend_direct_backend_name() {
     assert_eq!(backend.name, "openrouter");
 }
 
+#[test]
+fn test_resolve_backend_weighted_pool() {
+    let mut config = create_test_config();
+    config.defaults.balance_tag = Some("pool".to_string());
+    for backend in &mut config.backends {
+        backend.tags = vec!["pool".to_string()];
+    }
+    // Only "anthropic" carries weight, so every pick lands there.
+    config.backends[0].weight = 0;
+    config.backends[2].weight = 0;
+    let backend_state = BackendState::from_config(config)
+        .unwrap()
+        .with_balancer(BackendBalancer::seeded(3));
+    let registry = AgentRegistry::new();
+    let mut ctx = create_test_context();
+    let parsed_body = Some(json!({"model": "claude-3"}));
+
+    let backend = pipeline::resolve_backend(
+        &backend_state,
+        None,
+        None,
+        parsed_body.as_ref(),
+        &registry,
+        &mut ctx,
+    ).unwrap();
+
+    assert_eq!(backend.name, "anthropic");
+    let decision = ctx.span.record_mut().routing_decision.clone().unwrap();
+    assert_eq!(decision.backend, "anthropic");
+    assert_eq!(decision.reason, "weighted pool 'pool'");
+}
+
+#[test]
+fn test_weighted_pool_keeps_thinking_across_main_agent_requests() {
+    let mut config = create_test_config();
+    config.defaults.balance_tag = Some("pool".to_string());
+    // A 50/50 pool of two native backends.
+    config.backends[0].tags = vec!["pool".to_string()];
+    config.backends[1].tags = vec!["pool".to_string()];
+    let backend_state = BackendState::from_config(config)
+        .unwrap()
+        .with_balancer(BackendBalancer::seeded(3));
+    let registry = AgentRegistry::new();
+    let transformer_registry = Arc::new(TransformerRegistry::new());
+    let mut messages = Vec::new();
+
+    for turn in 0..20 {
+        let mut ctx = create_test_context();
+        messages.push(json!({"role": "user", "content": format!("turn {turn}")}));
+        let body = json!({
+            "model": "claude-3",
+            "metadata": {"user_id": "user_abc_account__session_1234"},
+            "messages": messages
+        });
+        let backend = pipeline::resolve_backend(
+            &backend_state,
+            None,
+            None,
+            Some(&body),
+            &registry,
+            &mut ctx,
+        ).unwrap();
+        let session = pipeline::create_thinking(&transformer_registry, &backend, &mut ctx);
+        let (result, _, _) = pipeline::transform_body(
+            serde_json::to_vec(&body).unwrap(),
+            Some(body),
+            &backend,
+            session.as_ref(),
+            &mut ctx,
+        ).unwrap();
+
+        let sent: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        let kept = sent["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|m| m["role"] == "assistant" && m["content"][0]["type"] == "thinking")
+            .count();
+        assert_eq!(kept, turn, "turn {turn} lost earlier thinking blocks");
+
+        let content = json!([
+            {"type": "thinking", "thinking": format!("thought {turn}"), "signature": format!("sig-{turn}")},
+            {"type": "text", "text": "ok"}
+        ]);
+        session
+            .unwrap()
+            .register_from_response(&serde_json::to_vec(&json!({"content": content})).unwrap());
+        messages.push(json!({"role": "assistant", "content": content}));
+    }
+}
+
 #[test]
 fn test_resolve_backend_missing_backend() {
     let config = create_test_config();
@@ -572,6 +716,63 @@ fn test_create_thinking_always_creates_session() {
     assert!(session.is_some());
 }
 
+/// Run stages 3-4 for a request carrying a thinking block the registry
+/// has never seen.
+fn transform_unregistered_thinking(backend: &Backend) -> serde_json::Value {
+    let transformer_registry = Arc::new(TransformerRegistry::new());
+    let mut ctx = create_test_context();
+    let session = pipeline::create_thinking(&transformer_registry, backend, &mut ctx);
+    let body = json!({
+        "model": "claude-sonnet-4-5",
+        "messages": [{
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "from another backend", "signature": "sig"},
+                {"type": "text", "text": "Answer"}
+            ]
+        }]
+    });
+    let (result, _, _) = pipeline::transform_body(
+        serde_json::to_vec(&body).unwrap(),
+        Some(body),
+        backend,
+        session.as_ref(),
+        &mut ctx,
+    )
+    .unwrap();
+    serde_json::from_slice(&result).unwrap()
+}
+
+#[test]
+fn test_thinking_registry_disabled_backend_forwards_blocks_unchanged() {
+    let backend = Backend {
+        name: "no-registry".to_string(),
+        thinking_registry_enabled: false,
+        ..Backend::default()
+    };
+    let mut ctx = create_test_context();
+    let registry = Arc::new(TransformerRegistry::new());
+    assert!(pipeline::create_thinking(&registry, &backend, &mut ctx).is_none());
+
+    let result = transform_unregistered_thinking(&backend);
+    let content = result["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(content.len(), 2);
+    assert_eq!(content[0]["thinking"], "from another backend");
+    assert_eq!(content[0]["signature"], "sig");
+}
+
+#[test]
+fn test_thinking_registry_enabled_backend_filters_unregistered_blocks() {
+    let backend = Backend {
+        name: "with-registry".to_string(),
+        ..Backend::default()
+    };
+    let result = transform_unregistered_thinking(&backend);
+    let content = result["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(content.len(), 1);
+    assert_eq!(content[0]["type"], "text");
+}
+
 // =============================================================================
 // Stage 4: transform_body tests
 // =============================================================================
@@ -586,9 +787,21 @@ fn test_transform_body_no_json() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: Some("mapped-sonnet".to_string()),
         model_haiku: None,
@@ -621,9 +834,21 @@ fn test_transform_body_model_rewrite() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: Some("mapped-sonnet".to_string()),
         model_haiku: None,
@@ -658,9 +883,21 @@ fn test_transform_body_thinking_compat_adaptive_to_enabled() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(true), // Enable thinking compat
         thinking_budget_tokens: Some(8000),
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -676,7 +913,8 @@ fn test_transform_body_thinking_compat_adaptive_to_enabled() {
 
     let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
     assert_eq!(result_json["thinking"]["type"], "enabled");
-    assert_eq!(result_json["thinking"]["budget_tokens"], 8000);
+    // The configured 8000 doesn't fit under max_tokens = 4096.
+    assert_eq!(result_json["thinking"]["budget_tokens"], 4095);
 }
 
 #[test]
@@ -694,9 +932,21 @@ fn test_transform_body_thinking_compat_no_adaptive() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(true),
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -715,6 +965,402 @@ fn test_transform_body_thinking_compat_no_adaptive() {
     assert_eq!(result_json["thinking"]["budget_tokens"], 5000);
 }
 
+#[test]
+fn test_transform_body_budget_overrides_enabled_thinking() {
+    let body_json = json!({
+        "model": "claude-3-sonnet",
+        "thinking": {"type": "enabled", "budget_tokens": 32000},
+        "max_tokens": 64000
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert_eq!(result_json["thinking"]["type"], "enabled");
+    assert_eq!(result_json["thinking"]["budget_tokens"], 2048);
+}
+
+#[test]
+fn test_transform_body_budget_enables_thinking_when_absent() {
+    let body_json = json!({"model": "claude-3-sonnet", "max_tokens": 8192});
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert_eq!(result_json["thinking"], json!({"type": "enabled", "budget_tokens": 2048}));
+}
+
+#[test]
+fn test_transform_body_budget_capped_below_max_tokens() {
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    for body_json in [
+        json!({"model": "claude-3-haiku", "max_tokens": 1500}),
+        json!({
+            "model": "claude-3-haiku",
+            "max_tokens": 1500,
+            "thinking": {"type": "enabled", "budget_tokens": 4096}
+        }),
+    ] {
+        let body_bytes = serde_json::to_vec(&body_json).unwrap();
+        let (result, _, _) = pipeline::transform_body(
+            body_bytes,
+            Some(body_json),
+            &backend,
+            None,
+            &mut ctx,
+        ).unwrap();
+
+        let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+        assert_eq!(result_json["thinking"]["type"], "enabled");
+        assert_eq!(result_json["thinking"]["budget_tokens"], 1499);
+    }
+}
+
+#[test]
+fn test_transform_body_budget_skips_thinking_below_minimum() {
+    let body_json = json!({"model": "claude-3-haiku", "max_tokens": 512});
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes.clone(),
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    // A 511-token budget would be rejected upstream
+    assert_eq!(result, body_bytes, "body should pass through unchanged");
+}
+
+#[test]
+fn test_transform_body_budget_skips_thinking_with_forced_tool_choice() {
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    for tool_choice in [json!({"type": "any"}), json!({"type": "tool", "name": "Bash"})] {
+        let body_json = json!({
+            "model": "claude-3-haiku",
+            "max_tokens": 8192,
+            "tool_choice": tool_choice
+        });
+        let body_bytes = serde_json::to_vec(&body_json).unwrap();
+        let (result, _, _) = pipeline::transform_body(
+            body_bytes.clone(),
+            Some(body_json),
+            &backend,
+            None,
+            &mut ctx,
+        ).unwrap();
+
+        assert_eq!(result, body_bytes, "body should pass through unchanged");
+    }
+}
+
+#[test]
+fn test_transform_body_budget_drops_converted_thinking_below_minimum() {
+    let body_json = json!({
+        "model": "claude-3-haiku",
+        "max_tokens": 512,
+        "thinking": {"type": "adaptive"}
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_compat: Some(true),
+        thinking_budget_tokens: Some(8000),
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    // Neither the configured 8000 nor the capped 511 would be accepted
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert!(result_json.get("thinking").is_none(), "thinking removed");
+}
+
+#[test]
+fn test_transform_body_budget_skips_thinking_with_sampling_params() {
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    for (param, value) in [("temperature", json!(0.2)), ("top_k", json!(40)), ("top_p", json!(0.9))] {
+        let mut body_json = json!({"model": "claude-3-haiku", "max_tokens": 8192});
+        body_json[param] = value;
+        let body_bytes = serde_json::to_vec(&body_json).unwrap();
+        let (result, _, _) = pipeline::transform_body(
+            body_bytes.clone(),
+            Some(body_json),
+            &backend,
+            None,
+            &mut ctx,
+        ).unwrap();
+
+        assert_eq!(result, body_bytes, "{} set: body should pass through unchanged", param);
+    }
+}
+
+#[test]
+fn test_transform_body_budget_skips_thinking_after_unthought_tool_use() {
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+    let tool_turn = |first: serde_json::Value| {
+        json!({
+            "model": "claude-3-haiku",
+            "max_tokens": 8192,
+            "messages": [
+                {"role": "user", "content": "list files"},
+                {"role": "assistant", "content": [
+                    first,
+                    {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "ls"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "a.txt"}
+                ]}
+            ]
+        })
+    };
+
+    let body_json = tool_turn(json!({"type": "text", "text": "Listing."}));
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes.clone(),
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+    assert_eq!(result, body_bytes, "body should pass through unchanged");
+
+    let body_json = tool_turn(json!({"type": "thinking", "thinking": "ls", "signature": "sig"}));
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert_eq!(result_json["thinking"], json!({"type": "enabled", "budget_tokens": 2048}));
+}
+
+#[test]
+fn test_transform_body_budget_leaves_disabled_thinking_alone() {
+    let body_json = json!({
+        "model": "claude-3-haiku",
+        "max_tokens": 512,
+        "thinking": {"type": "disabled"}
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_budget_tokens: Some(2048),
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes.clone(),
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    assert_eq!(result, body_bytes, "body should pass through unchanged");
+}
+
+#[test]
+fn test_transform_body_strips_thinking_for_non_thinking_backend() {
+    let body_json = json!({
+        "model": "claude-3-sonnet",
+        "thinking": {"type": "adaptive"},
+        "messages": []
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        thinking_compat: Some(true),
+        thinking_budget_tokens: Some(2048),
+        supports_thinking: false,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert!(result_json.get("thinking").is_none(), "thinking config removed");
+    assert_eq!(result_json["messages"], json!([]));
+}
+
+#[test]
+fn test_transform_body_strips_thinking_blocks_for_non_thinking_backend() {
+    let body_json = json!({
+        "model": "claude-3-sonnet",
+        "messages": [
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "hmm", "signature": "sig"},
+                {"type": "redacted_thinking", "data": "opaque"},
+                {"type": "text", "text": "hello"}
+            ]},
+            {"role": "user", "content": "again"}
+        ]
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        supports_thinking: false,
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    assert_eq!(
+        result_json["messages"][1]["content"],
+        json!([{"type": "text", "text": "hello"}])
+    );
+}
+
+fn cache_control_body() -> serde_json::Value {
+    json!({
+        "model": "claude-3-sonnet",
+        "system": [{"type": "text", "text": "sys", "cache_control": {"type": "ephemeral"}}],
+        "tools": [{
+            "name": "lookup",
+            "input_schema": {"type": "object", "properties": {"cache_control": {"type": "string"}}},
+            "cache_control": {"type": "ephemeral"}
+        }],
+        "messages": [{
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "hi", "cache_control": {"type": "ephemeral"}},
+                {"type": "tool_result", "tool_use_id": "t1", "content": [
+                    {"type": "text", "text": "out", "cache_control": {"type": "ephemeral"}}
+                ]}
+            ]
+        }]
+    })
+}
+
+#[test]
+fn test_transform_body_strips_cache_control_for_unsupported_backend() {
+    let body_json = cache_control_body();
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        supports_cache_control: false,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        ..Backend::default()
+    };
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes,
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    let result_json: serde_json::Value = serde_json::from_slice(&result).unwrap();
+    let text = serde_json::to_string(&result_json).unwrap();
+    assert_eq!(text.matches("\"cache_control\"").count(), 1, "only the schema property survives");
+    assert!(result_json["tools"][0]["input_schema"]["properties"]["cache_control"].is_object());
+    assert_eq!(result_json["messages"][0]["content"][1]["content"][0]["text"], "out");
+}
+
+#[test]
+fn test_transform_body_preserves_cache_control_for_supporting_backend() {
+    let body_json = cache_control_body();
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+    let mut ctx = create_test_context();
+    let backend = Backend::default();
+
+    let (result, _, _) = pipeline::transform_body(
+        body_bytes.clone(),
+        Some(body_json),
+        &backend,
+        None,
+        &mut ctx,
+    ).unwrap();
+
+    assert_eq!(result, body_bytes, "body forwarded unchanged");
+}
+
 #[test]
 fn test_transform_body_no_thinking_compat() {
     // Backend with thinking_compat disabled should not convert
@@ -730,9 +1376,21 @@ fn test_transform_body_no_thinking_compat() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(false), // Disabled
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -802,6 +1460,98 @@ fn test_transform_body_thinking_filtering() {
     assert_eq!(result, body_bytes);
 }
 
+#[test]
+fn test_transform_body_records_thinking_filter_counters() {
+    let transformer_registry = Arc::new(TransformerRegistry::new());
+    let mut ctx = create_test_context();
+    let backend = Backend {
+        name: "test".to_string(),
+        ..Backend::default()
+    };
+    let session = transformer_registry.begin_request(&backend.name, ctx.debug_logger.clone());
+    let response = json!({"content": [{"type": "thinking", "thinking": "kept"}]});
+    session.register_from_response(&serde_json::to_vec(&response).unwrap());
+
+    let body_json = json!({
+        "model": "claude-3-sonnet",
+        "messages": [
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "kept"},
+                {"type": "thinking", "thinking": "stale"}
+            ]}
+        ]
+    });
+    for _ in 0..2 {
+        pipeline::transform_body(
+            serde_json::to_vec(&body_json).unwrap(),
+            Some(body_json.clone()),
+            &backend,
+            Some(&session),
+            &mut ctx,
+        ).unwrap();
+    }
+
+    let snapshot = ctx.observability.snapshot();
+    let counters = snapshot.per_backend["test"].thinking_filter;
+    assert_eq!(counters.requests, 2);
+    assert_eq!(counters.confirmed, 1, "confirmed once, then already confirmed");
+    assert_eq!(counters.filtered, 2, "stale block stripped from both requests");
+    assert_eq!(counters.old_session_removed, 0);
+}
+
+#[test]
+fn test_transform_body_dry_run_counts_match_but_body_is_untouched() {
+    let backend = Backend {
+        name: "test".to_string(),
+        thinking_compat: Some(true),
+        ..Backend::default()
+    };
+    let body_json = json!({
+        "model": "claude-3-sonnet",
+        "thinking": {"type": "adaptive"},
+        "messages": [
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "kept", "signature": "s"},
+                {"type": "thinking", "thinking": "stale", "signature": ""}
+            ]}
+        ]
+    });
+    let body_bytes = serde_json::to_vec(&body_json).unwrap();
+
+    // Same registry history, once applied and once as a dry run.
+    let run = |dry_run: bool| {
+        let registry = Arc::new(TransformerRegistry::new().with_dry_run(dry_run));
+        let mut ctx = create_test_context();
+        let session = registry.begin_request(&backend.name, ctx.debug_logger.clone());
+        assert_eq!(session.is_dry_run(), dry_run);
+        let response = json!({"content": [{"type": "thinking", "thinking": "kept"}]});
+        session.register_from_response(&serde_json::to_vec(&response).unwrap());
+        let (result, _, _) = pipeline::transform_body(
+            body_bytes.clone(),
+            Some(body_json.clone()),
+            &backend,
+            Some(&session),
+            &mut ctx,
+        ).unwrap();
+        let counters = ctx.observability.snapshot().per_backend["test"].thinking_filter;
+        (result, counters)
+    };
+
+    let (applied, applied_counters) = run(false);
+    let (dry, dry_counters) = run(true);
+
+    let applied: serde_json::Value = serde_json::from_slice(&applied).unwrap();
+    assert_eq!(applied["thinking"]["type"], "enabled");
+    assert_eq!(applied["messages"][1]["content"].as_array().unwrap().len(), 1);
+
+    assert_eq!(dry, body_bytes, "dry run forwards the original body");
+    assert_eq!(dry_counters, applied_counters, "dry run computes the same counts");
+    assert_eq!(dry_counters.confirmed, 1);
+    assert_eq!(dry_counters.filtered, 1);
+}
+
 #[test]
 fn test_transform_body_budget_calculation_from_max_tokens() {
     // When thinking_budget_tokens is not configured, calculate from max_tokens
@@ -818,9 +1568,21 @@ fn test_transform_body_budget_calculation_from_max_tokens() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(true),
         thinking_budget_tokens: None, // Not configured - should use max_tokens - 1
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -853,9 +1615,21 @@ fn test_transform_body_budget_default_when_no_max_tokens() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(true),
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -886,7 +1660,8 @@ fn test_build_headers_basic() {
     let mut ctx = create_test_context();
     let backend = Backend::default(); // passthrough auth
 
-    let result = pipeline::build_headers(&headers, &backend, &mut ctx).unwrap();
+    let credential = backend.resolve_credential();
+    let result = pipeline::build_headers(&headers, &backend, &credential, &mut ctx).unwrap();
 
     // Should contain our custom headers
     assert!(result.iter().any(|(k, _)| k == "content-type"));
@@ -911,15 +1686,28 @@ fn test_build_headers_strips_auth_for_own_credentials() {
         base_url: "http://test".to_string(),
         auth_type_str: "bearer".to_string(),
         api_key: Some("backend-api-key".to_string()),
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
     };
 
-    let result = pipeline::build_headers(&headers, &backend, &mut ctx).unwrap();
+    let credential = backend.resolve_credential();
+    let result = pipeline::build_headers(&headers, &backend, &credential, &mut ctx).unwrap();
 
     // Count auth headers - should only have the backend's auth, not client's
     let auth_count = result.iter().filter(|(k, _)| k.eq_ignore_ascii_case("authorization")).count();
@@ -944,15 +1732,28 @@ fn test_build_headers_passthrough_keeps_auth() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(), // passthrough
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
     };
 
-    let result = pipeline::build_headers(&headers, &backend, &mut ctx).unwrap();
+    let credential = backend.resolve_credential();
+    let result = pipeline::build_headers(&headers, &backend, &credential, &mut ctx).unwrap();
 
     // Should keep client auth headers in passthrough mode
     let auth_header = result.iter().find(|(k, _)| k.eq_ignore_ascii_case("authorization"));
@@ -973,15 +1774,28 @@ fn test_build_headers_patches_anthropic_beta() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(true), // needs thinking compat
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
     };
 
-    let result = pipeline::build_headers(&headers, &backend, &mut ctx).unwrap();
+    let credential = backend.resolve_credential();
+    let result = pipeline::build_headers(&headers, &backend, &credential, &mut ctx).unwrap();
 
     let beta_header = result.iter().find(|(k, _)| k.eq_ignore_ascii_case("anthropic-beta"));
     assert!(beta_header.is_some());
@@ -1010,15 +1824,28 @@ fn test_build_headers_no_anthropic_beta_patch_for_anthropic() {
         base_url: "https://api.anthropic.com".to_string(),
         auth_type_str: "api_key".to_string(),
         api_key: Some("key".to_string()),
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: Some(false), // no thinking compat
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
     };
 
-    let result = pipeline::build_headers(&headers, &backend, &mut ctx).unwrap();
+    let credential = backend.resolve_credential();
+    let result = pipeline::build_headers(&headers, &backend, &credential, &mut ctx).unwrap();
 
     let beta_header = result.iter().find(|(k, _)| k.eq_ignore_ascii_case("anthropic-beta"));
     assert!(beta_header.is_some());
@@ -1107,9 +1934,21 @@ fn test_corner_case_model_family_detection_case_sensitive() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: Some("openrouter-opus".to_string()),
         model_sonnet: Some("openrouter-sonnet".to_string()),
         model_haiku: Some("openrouter-haiku".to_string()),
@@ -1147,9 +1986,21 @@ fn test_corner_case_budget_tokens_with_small_max_tokens() {
             base_url: "http://test".to_string(),
             auth_type_str: "passthrough".to_string(),
             api_key: None,
+            api_key_command: None,
+            api_key_command_ttl_seconds: 300,
             pricing: None,
             thinking_compat: Some(true),
             thinking_budget_tokens: None,
+            supports_thinking: true,
+            thinking_registry_enabled: true,
+            supports_cache_control: true,
+            response_normalization: None,
+            usage_mapping: None,
+            force_buffer: false,
+            match_client_stream: false,
+            max_concurrent: None,
+            tags: Vec::new(),
+            weight: 1,
             model_opus: None,
             model_sonnet: None,
             model_haiku: None,
@@ -1214,9 +2065,21 @@ fn test_corner_case_empty_model_string() {
         base_url: "http://test".to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: Some("mapped-sonnet".to_string()),
         model_haiku: None,
@@ -1235,3 +2098,129 @@ fn test_corner_case_empty_model_string() {
     assert_eq!(result_json["model"], "");
     assert!(mapping.is_none());
 }
+
+// =============================================================================
+// Unsigned thinking: signatures dropped once a backend is seen without them
+// =============================================================================
+
+fn thinking_stream(signature: Option<&str>) -> Vec<u8> {
+    let mut events = vec![
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#.to_string(),
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Hmm"}}"#.to_string(),
+    ];
+    if let Some(sig) = signature {
+        events.push(format!(
+            r#"{{"type":"content_block_delta","index":0,"delta":{{"type":"signature_delta","signature":"{sig}"}}}}"#
+        ));
+    }
+    events.push(r#"{"type":"content_block_stop","index":0}"#.to_string());
+    events.iter().map(|e| format!("data: {e}\n\n")).collect::<String>().into_bytes()
+}
+
+fn signed_thinking_request() -> serde_json::Value {
+    json!({
+        "model": "claude-sonnet-4-5",
+        "messages": [{
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "Hmm", "signature": "client-sig"},
+                {"type": "text", "text": "Answer"}
+            ]
+        }]
+    })
+}
+
+/// Stream one response from `backend`, then transform the next request.
+fn next_request_after(response: &[u8], backend: &str) -> serde_json::Value {
+    let registry = Arc::new(TransformerRegistry::new());
+    let backend = Backend {
+        name: backend.to_string(),
+        ..Backend::default()
+    };
+    let mut ctx = create_test_context();
+
+    let session = registry.begin_request(&backend.name, ctx.debug_logger.clone());
+    let mut registration = session.sse_registration();
+    registration.feed(&parse_sse_events(response));
+    registration.finish();
+
+    let session = registry.begin_request(&backend.name, ctx.debug_logger.clone());
+    let body = signed_thinking_request();
+    let (result, _, _) = pipeline::transform_body(
+        serde_json::to_vec(&body).unwrap(),
+        Some(body),
+        &backend,
+        Some(&session),
+        &mut ctx,
+    )
+    .unwrap();
+    serde_json::from_slice(&result).unwrap()
+}
+
+#[test]
+fn test_unsigned_thinking_response_drops_signatures_on_next_request() {
+    let result = next_request_after(&thinking_stream(None), "glm");
+    let block = &result["messages"][0]["content"][0];
+    assert_eq!(block["thinking"], "Hmm");
+    assert!(block.get("signature").is_none());
+}
+
+#[test]
+fn test_signed_thinking_response_keeps_signatures() {
+    let result = next_request_after(&thinking_stream(Some("sig")), "anthropic");
+    assert_eq!(result["messages"][0]["content"][0]["signature"], "client-sig");
+}
+
+#[test]
+fn test_unsigned_thinking_only_affects_that_backend() {
+    let registry = Arc::new(TransformerRegistry::new());
+    let logger = create_test_context().debug_logger;
+
+    let glm = registry.begin_request("glm", logger.clone());
+    let mut registration = glm.sse_registration();
+    registration.feed(&parse_sse_events(&thinking_stream(None)));
+    registration.finish();
+
+    assert!(registry.begin_request("glm", logger.clone()).drops_signatures());
+    assert!(!registry.begin_request("anthropic", logger).drops_signatures());
+}
+
+#[test]
+fn test_unsigned_thinking_mark_resets_on_backend_switch() {
+    let registry = Arc::new(TransformerRegistry::new());
+    let logger = create_test_context().debug_logger;
+
+    let glm = registry.begin_request("glm", logger.clone());
+    let mut registration = glm.sse_registration();
+    registration.feed(&parse_sse_events(&thinking_stream(None)));
+    registration.finish();
+    assert!(registry.returns_unsigned_thinking("glm"));
+
+    // Away and back: the mark is dropped and glm is observed afresh.
+    registry.begin_request("anthropic", logger.clone());
+    assert!(!registry.returns_unsigned_thinking("glm"));
+    assert!(!registry.begin_request("glm", logger.clone()).drops_signatures());
+
+    // A switch from the IPC path clears it too.
+    let glm = registry.begin_request("glm", logger);
+    let mut registration = glm.sse_registration();
+    registration.feed(&parse_sse_events(&thinking_stream(None)));
+    registration.finish();
+    registry.notify_backend_switch("anthropic");
+    assert!(!registry.returns_unsigned_thinking("glm"));
+}
+
+#[test]
+fn test_stream_cut_before_block_stop_does_not_mark_backend() {
+    let registry = Arc::new(TransformerRegistry::new());
+    let logger = create_test_context().debug_logger;
+
+    let session = registry.begin_request("glm", logger.clone());
+    let mut registration = session.sse_registration();
+    let full = thinking_stream(None);
+    let cut = String::from_utf8(full).unwrap().replace(r#"data: {"type":"content_block_stop","index":0}"#, "");
+    registration.feed(&parse_sse_events(cut.as_bytes()));
+    registration.finish();
+
+    assert!(!registry.begin_request("glm", logger).drops_signatures());
+}
@@ -25,6 +25,8 @@ fn app_with_history(n: usize, scroll_offset: usize) -> AppState {
             timestamp: UNIX_EPOCH + Duration::from_secs(1_000 + i as u64),
             from_backend: if i == 0 { None } else { Some(format!("backend{}", i - 1)) },
             to_backend: format!("backend{i}"),
+            cwd: None,
+            title: None,
         })
         .collect();
     state.history = HistoryDialogState::Visible { entries, scroll_offset };
@@ -141,3 +143,45 @@ fn empty_history_shows_the_italic_placeholder_not_a_list() {
         other => panic!("expected an italic placeholder Text, got {other:?}"),
     }
 }
+
+/// Every Text string under `id`, depth-first.
+fn texts(tree: &RetainedTree, id: NodeId) -> Vec<String> {
+    let mut out = Vec::new();
+    if let NodeKind::Text(style) = &tree.node(id).kind {
+        out.push(style.text.clone());
+    }
+    for &child in &tree.node(id).children {
+        out.extend(texts(tree, child));
+    }
+    out
+}
+
+#[test]
+fn rows_show_title_and_cwd_when_captured() {
+    let mut state = app_with_history(0, 0);
+    state.history = HistoryDialogState::Visible {
+        entries: vec![
+            HistoryEntry {
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_000),
+                from_backend: None,
+                to_backend: "bare".to_string(),
+                cwd: None,
+                title: None,
+            },
+            HistoryEntry {
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_001),
+                from_backend: Some("bare".to_string()),
+                to_backend: "glm".to_string(),
+                cwd: Some("/work/project".to_string()),
+                title: Some("fix tests".to_string()),
+            },
+        ],
+        scroll_offset: 0,
+    };
+    let (tree, root) = laid_out(&state);
+    let rows = texts(&tree, body_children(&tree, root)[2]);
+
+    // Newest first.
+    assert_eq!(rows[0], "1001  ·  bare  →  glm  ·  fix tests  ·  /work/project");
+    assert_eq!(rows[1], "1000  ·  (initial)  →  bare");
+}
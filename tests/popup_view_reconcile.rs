@@ -41,7 +41,13 @@ fn app_with_settings(fields: Vec<SettingsFieldSnapshot>) -> AppState {
 fn app_with_history(n: usize) -> AppState {
     let mut state = AppState::new("sid".to_string(), Instant::now(), (80, 24));
     let entries = (0..n)
-        .map(|i| HistoryEntry { timestamp: UNIX_EPOCH, from_backend: None, to_backend: format!("b{i}") })
+        .map(|i| HistoryEntry {
+            timestamp: UNIX_EPOCH,
+            from_backend: None,
+            to_backend: format!("b{i}"),
+            cwd: None,
+            title: None,
+        })
         .collect::<Vec<_>>();
     state.history = HistoryDialogState::Visible { entries, scroll_offset: 0 };
     state
@@ -23,12 +23,30 @@ fn test_config_with_backends(backends: Vec<Backend>, bind_addr: &str) -> Config
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 1,
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
 
         terminal: TerminalConfig::default(),
@@ -46,9 +64,21 @@ fn create_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -197,3 +227,95 @@ async fn test_list_backends() {
     assert!(backends.contains(&"alpha".to_string()));
     assert!(backends.contains(&"beta".to_string()));
 }
+
+/// Two backends, `alpha` active, with `x-anyclaude-backend` overrides on.
+async fn start_with_backend_header(
+    alpha: &MockBackend,
+    beta: &MockBackend,
+) -> (std::net::SocketAddr, anyclaude::backend::BackendState, anyclaude::metrics::ObservabilityHub) {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut config = test_config_with_backends(
+        vec![
+            create_backend("alpha", &alpha.base_url()),
+            create_backend("beta", &beta.base_url()),
+        ],
+        &bind_addr,
+    );
+    config.proxy.backend_header = Some("x-anyclaude-backend".to_string());
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let backend_state = server.backend_state();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+    (proxy_addr, backend_state, observability)
+}
+
+#[tokio::test]
+async fn test_backend_header_routes_single_request() {
+    let mock_alpha = MockBackend::start().await;
+    let mock_beta = MockBackend::start().await;
+    mock_alpha.enqueue_response(MockResponse::json(r#"{"from": "alpha"}"#)).await;
+    mock_beta.enqueue_response(MockResponse::json(r#"{"from": "beta"}"#)).await;
+    let (proxy_addr, backend_state, observability) =
+        start_with_backend_header(&mock_alpha, &mock_beta).await;
+
+    let client = Client::new();
+    let resp = client
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .header("x-anyclaude-backend", "beta")
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.text().await.unwrap().contains("beta"));
+
+    let beta_requests = mock_beta.captured_requests().await;
+    assert_eq!(beta_requests.len(), 1);
+    assert!(!beta_requests[0]
+        .headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("x-anyclaude-backend")));
+    assert_eq!(backend_state.get_active_backend(), "alpha");
+    let decision = observability.routing_decisions().pop().unwrap();
+    assert_eq!(decision.backend, "beta");
+    assert_eq!(decision.reason, "header override");
+
+    // Without the header the active backend still serves.
+    let resp = client
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.text().await.unwrap().contains("alpha"));
+}
+
+#[tokio::test]
+async fn test_backend_header_with_unknown_backend_is_rejected() {
+    let mock_alpha = MockBackend::start().await;
+    let mock_beta = MockBackend::start().await;
+    let (proxy_addr, backend_state, _observability) =
+        start_with_backend_header(&mock_alpha, &mock_beta).await;
+
+    let resp = Client::new()
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .header("x-anyclaude-backend", "gamma")
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "invalid_request");
+    assert!(body["error"]["message"].as_str().unwrap().contains("Unknown backend 'gamma'"));
+
+    assert!(mock_alpha.captured_requests().await.is_empty());
+    assert!(mock_beta.captured_requests().await.is_empty());
+    assert_eq!(backend_state.get_active_backend(), "alpha");
+}
@@ -0,0 +1,110 @@
+//! Per-backend `max_concurrent` limiting: serialization, queue timeout,
+//! and the bounded wait queue.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Defaults};
+use anyclaude::proxy::concurrency::ConcurrencyLimiter;
+use anyclaude::proxy::error::ProxyError;
+use axum::http::StatusCode;
+use tokio::time::Instant;
+
+fn limited_backend(max_concurrent: usize) -> Backend {
+    Backend {
+        name: "limited".to_string(),
+        max_concurrent: Some(max_concurrent),
+        tags: Vec::new(),
+        weight: 1,
+        ..Backend::default()
+    }
+}
+
+#[tokio::test]
+async fn unlimited_backend_needs_no_permit() {
+    let limiter = ConcurrencyLimiter::default();
+    let permit = limiter.acquire(&Backend::default()).await.unwrap();
+    assert!(permit.is_none());
+}
+
+#[tokio::test]
+async fn max_concurrent_one_serializes_requests() {
+    let limiter = Arc::new(ConcurrencyLimiter::new(Duration::from_secs(5), 8));
+    let backend = limited_backend(1);
+    let hold = Duration::from_millis(100);
+
+    let run = |limiter: Arc<ConcurrencyLimiter>, backend: Backend| async move {
+        let _permit = limiter.acquire(&backend).await.unwrap();
+        let start = Instant::now();
+        tokio::time::sleep(hold).await;
+        (start, Instant::now())
+    };
+    let (a, b) = tokio::join!(
+        tokio::spawn(run(limiter.clone(), backend.clone())),
+        tokio::spawn(run(limiter.clone(), backend.clone())),
+    );
+    let (mut first, mut second) = (a.unwrap(), b.unwrap());
+    if second.0 < first.0 {
+        std::mem::swap(&mut first, &mut second);
+    }
+    assert!(second.0 >= first.1, "second request started before the first released its slot");
+}
+
+#[tokio::test]
+async fn queue_timeout_fires_when_saturated() {
+    let limiter = ConcurrencyLimiter::new(Duration::from_millis(50), 8);
+    let backend = limited_backend(1);
+    let _held = limiter.acquire(&backend).await.unwrap();
+
+    let started = Instant::now();
+    let err = limiter.acquire(&backend).await.err().expect("should time out");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(matches!(err, ProxyError::BackendBusy { limit: 1, .. }), "{err}");
+    assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(err.to_string().contains("limited"));
+}
+
+#[tokio::test]
+async fn full_queue_rejects_immediately() {
+    let limiter = ConcurrencyLimiter::new(Duration::from_secs(5), 0);
+    let backend = limited_backend(1);
+    let _held = limiter.acquire(&backend).await.unwrap();
+
+    let started = Instant::now();
+    let err = limiter.acquire(&backend).await.err().expect("queue is full");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(err.to_string().contains("queue full"), "{err}");
+}
+
+#[tokio::test]
+async fn reloaded_queue_limits_apply_to_the_next_request() {
+    let limiter = ConcurrencyLimiter::new(Duration::from_secs(5), 8);
+    let backend = limited_backend(1);
+    let _held = limiter.acquire(&backend).await.unwrap();
+
+    limiter.set_queue_limits(&Defaults {
+        max_queued_requests: 0,
+        ..Defaults::default()
+    });
+    let started = Instant::now();
+    let err = limiter.acquire(&backend).await.err().expect("queue is full");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(err.to_string().contains("queue full"), "{err}");
+}
+
+#[tokio::test]
+async fn released_permit_admits_a_queued_request() {
+    let limiter = Arc::new(ConcurrencyLimiter::new(Duration::from_secs(5), 8));
+    let backend = limited_backend(1);
+    let held = limiter.acquire(&backend).await.unwrap();
+
+    let waiter = tokio::spawn({
+        let limiter = limiter.clone();
+        let backend = backend.clone();
+        async move { limiter.acquire(&backend).await.map(|p| p.is_some()) }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!waiter.is_finished());
+    drop(held);
+    assert!(waiter.await.unwrap().unwrap());
+}
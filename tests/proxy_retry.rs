@@ -23,12 +23,30 @@ fn test_config(backend: Backend, bind_addr: &str) -> Config {
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 2,
             retry_backoff_base_ms: 50,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
 
         terminal: TerminalConfig::default(),
@@ -46,9 +64,21 @@ fn create_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -153,3 +183,71 @@ async fn test_slow_response_succeeds() {
     let body = resp.text().await.unwrap();
     assert!(body.contains("slow"));
 }
+
+/// Start a proxy with a 1s request timeout in front of `mock`, which
+/// answers every attempt slower than that.
+async fn start_timing_out(mock: &MockBackend, retry_non_idempotent: bool) -> String {
+    for _ in 0..3 {
+        mock.enqueue_response(MockResponse::json(r#"{"late": true}"#).with_delay(1500)).await;
+    }
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    config.defaults.timeout_seconds = 1;
+    config.defaults.retry_non_idempotent = retry_non_idempotent;
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    format!("http://{}/v1/messages", proxy_addr)
+}
+
+#[tokio::test]
+async fn test_timed_out_post_not_retried_by_default() {
+    let mock = MockBackend::start().await;
+    let url = start_timing_out(&mock, false).await;
+
+    let resp = Client::new().post(&url).body("{}").send().await.unwrap();
+
+    assert_eq!(resp.status(), 504);
+    assert_eq!(mock.captured_requests().await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_timed_out_get_is_retried() {
+    let mock = MockBackend::start().await;
+    let url = start_timing_out(&mock, false).await;
+
+    let resp = Client::new().get(&url).send().await.unwrap();
+
+    assert_eq!(resp.status(), 504);
+    assert_eq!(mock.captured_requests().await.len(), 3);
+}
+
+#[tokio::test]
+async fn test_post_with_idempotency_key_is_retried() {
+    let mock = MockBackend::start().await;
+    let url = start_timing_out(&mock, false).await;
+
+    Client::new()
+        .post(&url)
+        .header("Idempotency-Key", "req-1")
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(mock.captured_requests().await.len(), 3);
+}
+
+#[tokio::test]
+async fn test_post_retried_when_opted_in() {
+    let mock = MockBackend::start().await;
+    let url = start_timing_out(&mock, true).await;
+
+    Client::new().post(&url).body("{}").send().await.unwrap();
+
+    assert_eq!(mock.captured_requests().await.len(), 3);
+}
@@ -0,0 +1,97 @@
+//! Shutdown callbacks: registered work runs once, after connections drain.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{DebugLogDestination, DebugLogFormat, DebugLogLevel, DebugLoggingConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::shutdown::ShutdownManager;
+
+fn counting_callback(manager: &ShutdownManager) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    manager.on_shutdown(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    calls
+}
+
+#[tokio::test]
+async fn callback_runs_exactly_once() {
+    let manager = ShutdownManager::new();
+    let calls = counting_callback(&manager);
+
+    manager.wait_for_connections(Duration::from_secs(1)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    manager.wait_for_connections(Duration::from_secs(1)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn callback_waits_for_active_connections() {
+    let manager = Arc::new(ShutdownManager::new());
+    let calls = counting_callback(&manager);
+    manager.increment_connections();
+
+    let waiter = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.wait_for_connections(Duration::from_secs(5)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    manager.decrement_connections();
+    waiter.await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn callback_still_runs_when_the_drain_times_out() {
+    let manager = ShutdownManager::new();
+    let calls = counting_callback(&manager);
+    manager.increment_connections();
+
+    manager.wait_for_connections(Duration::from_millis(150)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn blocking_callback_leaves_the_runtime_free() {
+    // Single-threaded runtime: a callback run inline would starve the task
+    // it waits on.
+    let manager = ShutdownManager::new();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let answered = Arc::new(AtomicUsize::new(0));
+    let seen = answered.clone();
+    manager.on_shutdown(move || {
+        if rx.recv_timeout(Duration::from_secs(2)).is_ok() {
+            seen.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let _ = tx.send(());
+    });
+
+    manager.wait_for_connections(Duration::from_secs(1)).await;
+    assert_eq!(answered.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn debug_logger_flush_writes_queued_events() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("debug.log");
+    let logger = DebugLogger::new(DebugLoggingConfig {
+        level: DebugLogLevel::Basic,
+        format: DebugLogFormat::Json,
+        destination: DebugLogDestination::File,
+        file_path: path.to_string_lossy().into_owned(),
+        ..DebugLoggingConfig::default()
+    });
+
+    logger.log_auxiliary("shutdown-test", None, None, Some("queued"), None);
+    assert!(logger.flush(Duration::from_secs(5)));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("shutdown-test"));
+}
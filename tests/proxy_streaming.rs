@@ -3,7 +3,8 @@
 mod common;
 
 use anyclaude::config::{
-    Backend, Config, ConfigStore, DebugLoggingConfig, Defaults, ProxyConfig, TerminalConfig,
+    Backend, Config, ConfigStore, DebugLogDestination, DebugLogFormat, DebugLogLevel,
+    DebugLoggingConfig, Defaults, ProxyConfig, TerminalConfig,
 };
 use anyclaude::metrics::DebugLogger;
 use anyclaude::proxy::ProxyServer;
@@ -23,12 +24,30 @@ fn test_config(backend: Backend, bind_addr: &str) -> Config {
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 1,
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
 
         terminal: TerminalConfig::default(),
@@ -46,9 +65,21 @@ fn create_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -139,3 +170,392 @@ async fn test_json_response_passthrough() {
     let body = resp.text().await.unwrap();
     assert!(body.contains("Hello"));
 }
+
+async fn start_with_keepalive(mock: &MockBackend, keepalive_seconds: u32) -> std::net::SocketAddr {
+    start_with(mock, |config| config.defaults.sse_keepalive_seconds = keepalive_seconds).await
+}
+
+async fn start_with(mock: &MockBackend, adjust: impl FnOnce(&mut Config)) -> std::net::SocketAddr {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    adjust(&mut config);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+    proxy_addr
+}
+
+async fn stream_body(proxy_addr: std::net::SocketAddr) -> String {
+    let resp = Client::new()
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .body(r#"{"stream": true}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    resp.text().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_sse_keepalive_fills_upstream_stall_between_events() {
+    let start = "event: message_start\ndata: {\"type\":\"message_start\"}\n\n";
+    let stop = "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&[start, stop], 2500)).await;
+    let proxy_addr = start_with_keepalive(&mock, 1).await;
+
+    let body = stream_body(proxy_addr).await;
+    let keepalive = std::str::from_utf8(anyclaude::metrics::SSE_KEEPALIVE).unwrap();
+    assert!(body.matches(keepalive).count() >= 1, "no keep-alive in {body:?}");
+    assert!(body.starts_with(start), "keep-alive must follow the first event: {body:?}");
+    assert_eq!(body.replace(keepalive, ""), format!("{start}{stop}"));
+}
+
+#[tokio::test]
+async fn test_sse_keepalive_never_splits_an_event() {
+    // The upstream stalls halfway through an event; a comment there would
+    // corrupt it, so the stream must come through byte-for-byte.
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(
+        &["data: {\"type\":", "\"message_stop\"}\n\n"],
+        2500,
+    )).await;
+    let proxy_addr = start_with_keepalive(&mock, 1).await;
+
+    let body = stream_body(proxy_addr).await;
+    assert_eq!(body, "data: {\"type\":\"message_stop\"}\n\n");
+}
+
+#[tokio::test]
+async fn test_sse_keepalive_disabled_by_default() {
+    let start = "data: {\"type\":\"message_start\"}\n\n";
+    let stop = "data: {\"type\":\"message_stop\"}\n\n";
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&[start, stop], 1500)).await;
+    let proxy_addr = start_with_keepalive(&mock, 0).await;
+
+    assert_eq!(stream_body(proxy_addr).await, format!("{start}{stop}"));
+}
+
+#[tokio::test]
+async fn test_sse_event_histogram_recorded_on_request_record() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse(&[
+        r#"{"type":"message_start","message":{}}"#,
+        r#"{"type":"ping"}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"a"}}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"b"}}"#,
+        r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"}}"#,
+        r#"{"type":"message_stop"}"#,
+    ])).await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    stream_body(proxy_addr).await;
+
+    let recent = observability.snapshot().recent;
+    let histogram = recent
+        .iter()
+        .find_map(|r| r.sse_events.clone())
+        .expect("streamed request has an SSE histogram");
+    assert_eq!(histogram.count("message_start"), 1);
+    assert_eq!(histogram.count("ping"), 1);
+    assert_eq!(histogram.count("content_block_delta"), 2);
+    assert_eq!(histogram.count("text_delta"), 2);
+    assert_eq!(histogram.count("message_delta"), 1);
+    assert_eq!(histogram.count("message_stop"), 1);
+}
+
+#[tokio::test]
+async fn test_sse_truncated_stream_ends_with_error_event() {
+    let start = "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{}}\n\n";
+    let delta = "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0}\n\n";
+    let mock = MockBackend::start().await;
+    // Upstream closes after message_start without ever sending message_stop.
+    mock.enqueue_response(MockResponse::sse_chunked(&[start, delta], 0)).await;
+    let proxy_addr = start_with_keepalive(&mock, 0).await;
+
+    let body = stream_body(proxy_addr).await;
+    assert!(body.starts_with(&format!("{start}{delta}")), "upstream events relayed: {body:?}");
+    let events = anyclaude::sse::parse_sse_events(body.as_bytes());
+    let last = events.last().expect("stream has events");
+    assert_eq!(last.event_type, "error");
+    assert_eq!(last.data["error"]["type"], "api_error");
+    assert!(body.ends_with("\n\n"), "error event is terminated: {body:?}");
+}
+
+#[tokio::test]
+async fn test_sse_complete_stream_gets_no_error_event() {
+    let start = "data: {\"type\":\"message_start\"}\n\n";
+    let stop = "data: {\"type\":\"message_stop\"}\n\n";
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&[start, stop], 0)).await;
+    let proxy_addr = start_with_keepalive(&mock, 0).await;
+
+    assert_eq!(stream_body(proxy_addr).await, format!("{start}{stop}"));
+}
+
+/// Chunks as the client receives them, for asserting on frame boundaries.
+async fn stream_chunks(proxy_addr: std::net::SocketAddr) -> Vec<String> {
+    let mut resp = Client::new()
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .body(r#"{"stream": true}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let mut chunks = Vec::new();
+    while let Some(chunk) = resp.chunk().await.unwrap() {
+        chunks.push(String::from_utf8(chunk.to_vec()).unwrap());
+    }
+    chunks
+}
+
+const SPLIT_FRAMES: &[&str] = &[
+    "event: message_start\r\ndata: {\"type\":\"message_start\",\"mess",
+    "age\":{}}\r\n\r\nevent: content_block_delta\r\ndata: {\"type\":\"content_block_delta\",",
+    "\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"hi\"}}\r\n\r\ndata: {\"type\":\"message_stop\"}",
+];
+
+#[tokio::test]
+async fn test_force_buffer_reframes_split_events() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(SPLIT_FRAMES, 100)).await;
+    let proxy_addr = start_with(&mock, |config| config.backends[0].force_buffer = true).await;
+
+    let chunks = stream_chunks(proxy_addr).await;
+    for chunk in &chunks {
+        assert!(chunk.ends_with("\n\n"), "chunk ends mid-event: {chunk:?}");
+        assert!(!chunk.contains('\r'), "CRLF normalized: {chunk:?}");
+    }
+    let events = anyclaude::sse::parse_sse_events(chunks.concat().as_bytes());
+    let types: Vec<_> = events.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(types, ["message_start", "content_block_delta", "message_stop"]);
+    assert_eq!(events[1].data["delta"]["text"], "hi");
+}
+
+#[tokio::test]
+async fn test_without_force_buffer_split_frames_are_relayed_as_sent() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(SPLIT_FRAMES, 100)).await;
+    let proxy_addr = start_with(&mock, |_| {}).await;
+
+    let body = stream_chunks(proxy_addr).await.concat();
+    assert!(body.starts_with(&SPLIT_FRAMES.concat()), "relayed byte-for-byte: {body:?}");
+}
+
+#[tokio::test]
+async fn test_client_disconnect_cancels_the_upstream_stream() {
+    let ping = "event: ping\ndata: {\"type\":\"ping\"}\n\n";
+    let mut pieces = vec!["event: message_start\ndata: {\"type\":\"message_start\",\"message\":{}}\n\n"];
+    pieces.extend(std::iter::repeat_n(ping, 30));
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&pieces, 100)).await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    let mut resp = Client::new()
+        .post(format!("http://{}/v1/messages", proxy_addr))
+        .body(r#"{"stream": true}"#)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.chunk().await.unwrap().is_some());
+    drop(resp);
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let record = loop {
+        if let Some(record) = observability.snapshot().recent.into_iter().next() {
+            break record;
+        }
+        assert!(std::time::Instant::now() < deadline, "request never finished");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert!(record.cancelled);
+    assert_eq!(observability.snapshot().per_backend["test"].cancelled, 1);
+
+    // The upstream body stops being pulled once the proxy lets go of it.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let sent = mock.chunks_sent();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(mock.chunks_sent(), sent);
+    assert!(sent < pieces.len(), "all {sent} pieces were sent");
+}
+
+#[tokio::test]
+async fn test_completed_stream_is_not_cancelled() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse(&[
+        r#"{"type":"message_start","message":{}}"#,
+        r#"{"type":"message_stop"}"#,
+    ])).await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    stream_body(proxy_addr).await;
+
+    let recent = observability.snapshot().recent;
+    assert!(!recent[0].cancelled);
+}
+
+#[tokio::test]
+async fn test_thinking_stream_stats_are_logged() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse(&[
+        r#"{"type":"message_start","message":{}}"#,
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Hmm"}}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig"}}"#,
+        r#"{"type":"content_block_stop","index":0}"#,
+        r#"{"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+        r#"{"type":"content_block_stop","index":1}"#,
+        r#"{"type":"message_stop"}"#,
+    ])).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("debug.log");
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    config.debug_logging = DebugLoggingConfig {
+        level: DebugLogLevel::Verbose,
+        format: DebugLogFormat::Json,
+        destination: DebugLogDestination::File,
+        file_path: path.to_string_lossy().into_owned(),
+        ..DebugLoggingConfig::default()
+    };
+    let debug_logger = Arc::new(DebugLogger::new(config.debug_logging.clone()));
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger.clone(), None).unwrap();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    stream_body(proxy_addr).await;
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let line = loop {
+        assert!(debug_logger.flush(Duration::from_secs(5)));
+        let log = std::fs::read_to_string(&path).unwrap_or_default();
+        if let Some(line) = log.lines().find(|l| l.contains("thinking_stats")) {
+            break line.to_string();
+        }
+        assert!(std::time::Instant::now() < deadline, "no thinking stats logged");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert!(line.contains("backend='test'"), "{line}");
+    assert!(line.contains("request_id="), "{line}");
+    assert!(
+        line.contains("1 blocks (0 redacted), 1 deltas, 1 sig_deltas, 1 stops, signatures: found"),
+        "{line}"
+    );
+}
+
+#[tokio::test]
+async fn test_split_data_line_with_noise_is_counted_once() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(
+        &[
+            "garbage before the stream\n\nevent: message_start\ndata: {\"type\":\"mess",
+            "age_start\",\"message\":{}}\n\nevent: message_stop\ndata: {\"type\":",
+            "\"message_stop\"}\n\ntrailing noise\n",
+        ],
+        20,
+    ))
+    .await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    let body = stream_body(proxy_addr).await;
+    assert!(body.contains("message_stop"));
+
+    let recent = observability.snapshot().recent;
+    let histogram = recent[0].sse_events.as_ref().unwrap();
+    assert_eq!(histogram.count("message_start"), 1);
+    assert_eq!(histogram.count("message_stop"), 1);
+    assert_eq!(histogram.iter().count(), 2);
+}
+
+#[tokio::test]
+async fn test_trailing_event_without_blank_line_reaches_every_consumer() {
+    let mock = MockBackend::start().await;
+    // The final event ends without its blank line: only the end-of-stream
+    // flush of the stream's single decoder dispatches it.
+    mock.enqueue_response(MockResponse::sse_chunked(
+        &[
+            "data: {\"type\":\"message_start\",\"message\":{}}\n\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"Hi\"}}\n\n",
+            "data: {\"type\":\"message_stop\"}",
+        ],
+        0,
+    ))
+    .await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = test_config(create_backend("test", &mock.base_url()), &bind_addr);
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let observability = server.observability();
+    let (proxy_addr, _base_url) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(proxy_addr, Duration::from_secs(5)).await;
+
+    let body = stream_body(proxy_addr).await;
+    assert!(!body.contains("api_error"), "message_stop seen, no error event: {body:?}");
+
+    let recent = observability.snapshot().recent;
+    let histogram = recent[0].sse_events.as_ref().unwrap();
+    assert_eq!(histogram.count("message_start"), 1);
+    assert_eq!(histogram.count("content_block_start"), 1);
+    assert_eq!(histogram.count("message_stop"), 1);
+    assert_eq!(observability.last_response_text().as_deref(), Some("Hi"));
+}
@@ -30,8 +30,17 @@ fn test_from_defaults() {
         idle_timeout_seconds: 90,
         pool_idle_timeout_seconds: 120,
         pool_max_idle_per_host: 4,
+        warmup_connections: false,
         max_retries: 2,
         retry_backoff_base_ms: 150,
+        retry_non_idempotent: false,
+        switch_log_capacity: 500,
+        routing_log_capacity: 200,
+        queue_timeout_seconds: 30,
+        max_queued_requests: 64,
+        sse_keepalive_seconds: 0,
+        idle_revert_minutes: 0,
+        balance_tag: None,
     };
 
     let config = TimeoutConfig::from(&defaults);
@@ -0,0 +1,112 @@
+//! Serving the proxy over a Unix domain socket (`bind_addr = "unix:/path"`).
+#![cfg(unix)]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::{ProxyAddr, ProxyServer};
+use common::mock_backend::{MockBackend, MockResponse};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+fn unix_config(socket: &Path, backend_url: &str) -> ConfigStore {
+    let backend = Backend {
+        name: "test".to_string(),
+        display_name: "Test".to_string(),
+        base_url: backend_url.to_string(),
+        ..Backend::default()
+    };
+    let config = Config {
+        defaults: Defaults {
+            active: "test".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("unix:{}", socket.display()),
+            ..ProxyConfig::default()
+        },
+        backends: vec![backend],
+        ..Config::default()
+    };
+    ConfigStore::new(config, PathBuf::from("/tmp/test.toml"))
+}
+
+/// One HTTP/1.1 exchange over the socket; returns the raw response.
+async fn request(socket: &Path, raw: &str) -> String {
+    let mut stream = UnixStream::connect(socket).await.unwrap();
+    stream.write_all(raw.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+#[tokio::test]
+async fn messages_round_trip_over_unix_socket() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(r#"{"content":[{"type":"text","text":"over the socket"}]}"#))
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("proxy.sock");
+    let config_store = unix_config(&socket, &mock.base_url());
+    let mut server =
+        ProxyServer::new(config_store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    assert_eq!(server.bind(&config_store).await.unwrap(), ProxyAddr::Unix(socket.clone()));
+    let handle = server.handle();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+
+    let body = r#"{"model":"claude-sonnet-4","stream":false}"#;
+    let response = request(
+        &socket,
+        &format!(
+            "POST /v1/messages HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("over the socket"));
+    assert_eq!(mock.captured_requests().await.len(), 1);
+
+    let health = request(&socket, "GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await;
+    assert!(health.starts_with("HTTP/1.1 200"), "{health}");
+
+    // Shutdown removes the socket file.
+    handle.shutdown();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while socket.exists() && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(!socket.exists());
+}
+
+#[tokio::test]
+async fn stale_socket_file_is_replaced() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("proxy.sock");
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    assert!(socket.exists());
+
+    let config_store = unix_config(&socket, "http://127.0.0.1:9");
+    let mut server =
+        ProxyServer::new(config_store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    assert!(server.bind(&config_store).await.is_ok());
+}
+
+#[tokio::test]
+async fn try_bind_rejects_a_unix_bind_addr() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_store = unix_config(&dir.path().join("proxy.sock"), "http://127.0.0.1:9");
+    let mut server =
+        ProxyServer::new(config_store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    let err = server.try_bind(&config_store).await.unwrap_err();
+    assert!(err.to_string().contains("TCP"));
+}
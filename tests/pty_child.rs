@@ -0,0 +1,247 @@
+//! `ChildPty` lifecycle: output delivery and exit detection on a real PTY.
+#![cfg(unix)]
+
+use std::io::{self, Read};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyclaude::ui::chrome_labels::{child_exit_banner, pty_read_error_banner};
+use anyclaude::ui::gpu::pty::{pump_output, wait_for_teardowns, ChildPty};
+
+fn spawn_sh(script: &str, exit_tx: mpsc::Sender<()>) -> ChildPty {
+    ChildPty::spawn(
+        80,
+        24,
+        "/bin/sh".to_string(),
+        vec!["-c".to_string(), script.to_string()],
+        Vec::new(),
+        || {},
+        move || {
+            let _ = exit_tx.send(());
+        },
+    )
+    .expect("spawn /bin/sh")
+}
+
+#[test]
+fn child_exit_is_signalled_with_its_code() {
+    let (tx, rx) = mpsc::channel();
+    let mut pty = spawn_sh("printf done; exit 7", tx);
+
+    rx.recv_timeout(Duration::from_secs(10)).expect("on_exit fired");
+    assert_eq!(pty.poll_exit(), Some(7));
+    // Reported once; later polls stay quiet.
+    assert_eq!(pty.poll_exit(), None);
+
+    // The final output is still delivered after exit.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut out = Vec::new();
+    while !out.windows(4).any(|w| w == b"done") && Instant::now() < deadline {
+        out.extend(pty.drain().concat());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(out.windows(4).any(|w| w == b"done"), "got {out:?}");
+}
+
+#[test]
+fn running_child_has_no_exit_status() {
+    let (tx, rx) = mpsc::channel();
+    let mut pty = spawn_sh("sleep 5", tx);
+    assert_eq!(pty.poll_exit(), None);
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn exit_banner_names_the_code_on_its_own_line() {
+    let banner = child_exit_banner(127);
+    assert!(banner.starts_with("\r\n"));
+    assert!(banner.ends_with("\r\n"));
+    assert!(banner.contains("exited with code 127"));
+}
+
+fn pid_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn respawn_replaces_the_child_and_tears_down_the_old_one() {
+    let (tx, rx) = mpsc::channel();
+    let mut pty = spawn_sh("sleep 30", tx);
+    let old_pid = pty.pid().expect("child pid");
+    assert!(pid_alive(old_pid));
+
+    pty.respawn().expect("respawn");
+    let new_pid = pty.pid().expect("new child pid");
+    assert_ne!(new_pid, old_pid);
+    assert!(pid_alive(new_pid));
+    assert_eq!(pty.poll_exit(), None, "fresh child is running");
+
+    // The old child was signalled; its exit must not surface on the new session.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while pid_alive(old_pid) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!pid_alive(old_pid), "old child still running");
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+    assert_eq!(pty.poll_exit(), None);
+}
+
+#[test]
+fn respawned_child_still_delivers_output_and_exit() {
+    let (tx, rx) = mpsc::channel();
+    let mut pty = spawn_sh("read line; printf \"got:$line\"; exit 4", tx);
+    pty.respawn().expect("respawn");
+
+    pty.write(b"hi\n").unwrap();
+    rx.recv_timeout(Duration::from_secs(10)).expect("on_exit fired for the new child");
+    assert_eq!(pty.poll_exit(), Some(4));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut out = Vec::new();
+    while !out.windows(6).any(|w| w == b"got:hi") && Instant::now() < deadline {
+        out.extend(pty.drain().concat());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(out.windows(6).any(|w| w == b"got:hi"), "got {out:?}");
+}
+
+#[test]
+fn terminate_lets_a_trapping_child_exit_gracefully() {
+    let (tx, rx) = mpsc::channel();
+    // The trap runs once the foreground sleep (same process group) dies.
+    let mut pty = spawn_sh("trap 'exit 0' TERM; while :; do sleep 0.05; done", tx);
+    pty.set_shutdown_grace(Duration::from_secs(5));
+    std::thread::sleep(Duration::from_millis(200));
+
+    assert!(pty.terminate(libc::SIGTERM), "child exited on its own");
+    rx.recv_timeout(Duration::from_secs(5)).expect("on_exit fired");
+    assert_eq!(pty.poll_exit(), Some(0), "the trap's exit code, not a kill");
+}
+
+#[test]
+fn terminate_kills_a_child_that_ignores_the_signal_after_the_grace() {
+    let (tx, rx) = mpsc::channel();
+    let mut pty = spawn_sh("trap '' TERM; sleep 30", tx);
+    pty.set_shutdown_grace(Duration::from_millis(200));
+    let pid = pty.pid().expect("child pid");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let started = Instant::now();
+    assert!(!pty.terminate(libc::SIGTERM));
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    rx.recv_timeout(Duration::from_secs(5)).expect("killed child reported");
+    assert!(!pid_alive(pid));
+}
+
+#[test]
+fn drop_returns_at_once_and_the_kill_follows_in_the_background() {
+    let (tx, _rx) = mpsc::channel();
+    let mut pty = spawn_sh("trap '' TERM; sleep 30", tx);
+    pty.set_shutdown_grace(Duration::from_millis(300));
+    let pid = pty.pid().expect("child pid");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let started = Instant::now();
+    drop(pty);
+    assert!(started.elapsed() < Duration::from_millis(300), "drop waited out the grace");
+
+    // Another test may be joining the same teardown; poll the pid too.
+    wait_for_teardowns();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while pid_alive(pid) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!pid_alive(pid));
+}
+
+#[test]
+fn shutdown_signals_now_without_firing_on_exit() {
+    let (tx, rx) = mpsc::channel();
+    let pty = spawn_sh("trap 'exit 0' INT; while :; do sleep 0.05; done", tx);
+    let pid = pty.pid().expect("child pid");
+    std::thread::sleep(Duration::from_millis(200));
+
+    pty.shutdown(libc::SIGINT);
+    wait_for_teardowns();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while pid_alive(pid) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!pid_alive(pid));
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err(), "on_exit stays quiet");
+}
+
+/// Yields its chunks, then fails every read with `error`.
+struct FailingReader {
+    chunks: Vec<&'static [u8]>,
+    error: fn() -> io::Error,
+}
+
+impl Read for FailingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.chunks.is_empty() {
+            return Err((self.error)());
+        }
+        let chunk = self.chunks.remove(0);
+        buf[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+#[test]
+fn read_error_is_reported_after_delivering_output() {
+    let mut reader = FailingReader {
+        chunks: vec![b"hello"],
+        error: || io::Error::new(io::ErrorKind::BrokenPipe, "pty gone"),
+    };
+    let mut out = Vec::new();
+    let err = pump_output(&mut reader, |bytes| {
+        out.extend_from_slice(bytes);
+        true
+    })
+    .expect_err("read error surfaces");
+    assert_eq!(out, b"hello");
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn eof_and_hangup_end_the_pump_cleanly() {
+    let mut eof: &[u8] = b"bye";
+    assert!(pump_output(&mut eof, |_| true).is_ok());
+
+    let mut hangup = FailingReader {
+        chunks: Vec::new(),
+        error: || io::Error::from_raw_os_error(libc::EIO),
+    };
+    assert!(pump_output(&mut hangup, |_| true).is_ok());
+}
+
+#[test]
+fn interrupted_reads_are_retried() {
+    struct Interrupted(u32);
+    impl Read for Interrupted {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            self.0 += 1;
+            if self.0 == 1 {
+                Err(io::ErrorKind::Interrupted.into())
+            } else {
+                Ok(0)
+            }
+        }
+    }
+    let mut reader = Interrupted(0);
+    assert!(pump_output(&mut reader, |_| true).is_ok());
+    assert_eq!(reader.0, 2);
+}
+
+#[test]
+fn read_error_banner_names_the_error() {
+    let banner = pty_read_error_banner("Input/output error");
+    assert!(banner.starts_with("\r\n"));
+    assert!(banner.contains("terminal output lost: Input/output error"));
+}
@@ -0,0 +1,57 @@
+//! PTY size fallback: a failing size query is retried, then the
+//! configured default is used; a successful query always wins.
+
+use std::cell::Cell;
+
+use anyclaude::config::TerminalConfig;
+use anyclaude::ui::gpu::pty::{query_size, DEFAULT_PTY_SIZE};
+
+#[test]
+fn configured_default_is_used_when_the_query_keeps_failing() {
+    let calls = Cell::new(0);
+    let size = query_size(
+        || {
+            calls.set(calls.get() + 1);
+            Err::<(u16, u16), _>("no tty")
+        },
+        (200, 60),
+    );
+    assert_eq!(size, (200, 60));
+    assert!(calls.get() > 1, "query was not retried");
+}
+
+#[test]
+fn successful_query_overrides_the_default() {
+    assert_eq!(query_size(|| Ok::<_, ()>((132, 43)), (200, 60)), (132, 43));
+}
+
+#[test]
+fn transient_failure_recovers_on_retry() {
+    let calls = Cell::new(0);
+    let size = query_size(
+        || {
+            calls.set(calls.get() + 1);
+            if calls.get() == 1 {
+                Err("starting up")
+            } else {
+                Ok((120, 40))
+            }
+        },
+        (80, 24),
+    );
+    assert_eq!(size, (120, 40));
+}
+
+#[test]
+fn zero_size_counts_as_a_failure() {
+    assert_eq!(query_size(|| Ok::<_, ()>((0, 0)), (100, 30)), (100, 30));
+}
+
+#[test]
+fn terminal_config_defaults_to_80_by_24() {
+    let terminal = TerminalConfig::default();
+    assert_eq!((terminal.default_cols, terminal.default_rows), DEFAULT_PTY_SIZE);
+
+    let parsed: TerminalConfig = toml::from_str("default_cols = 160\ndefault_rows = 48").unwrap();
+    assert_eq!((parsed.default_cols, parsed.default_rows), (160, 48));
+}
@@ -0,0 +1,114 @@
+//! Request ids: a client-supplied correlation header is reused and echoed,
+//! otherwise a UUID is generated.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::{DebugLogger, ObservabilityHub};
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+/// Start a proxy in front of a mock returning one JSON response. Returns the
+/// proxy URL and its observability hub.
+async fn start(mock: &MockBackend, request_id_header: &str) -> (String, ObservabilityHub) {
+    mock.enqueue_response(MockResponse::json(r#"{"content":[]}"#)).await;
+    let config = Config {
+        defaults: Defaults {
+            active: "test".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("127.0.0.1:{}", common::free_port()),
+            request_id_header: request_id_header.to_string(),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "test".to_string(),
+            base_url: mock.base_url(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let mut server =
+        ProxyServer::new(config_store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    let hub = server.observability();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (format!("http://{}/v1/messages", addr), hub)
+}
+
+#[tokio::test]
+async fn client_request_id_is_reused_and_echoed() {
+    let mock = MockBackend::start().await;
+    let (url, hub) = start(&mock, "x-request-id").await;
+
+    let resp = reqwest::Client::new()
+        .post(url)
+        .header("x-request-id", "abc")
+        .body(r#"{"model":"claude-sonnet-4"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.headers()["x-request-id"], "abc");
+    assert_eq!(hub.routing_decisions()[0].request_id, "abc");
+}
+
+#[tokio::test]
+async fn missing_request_id_gets_a_generated_uuid() {
+    let mock = MockBackend::start().await;
+    let (url, hub) = start(&mock, "x-request-id").await;
+
+    let resp = reqwest::Client::new()
+        .post(url)
+        .body(r#"{"model":"claude-sonnet-4"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    let echoed = resp.headers()["x-request-id"].to_str().unwrap().to_string();
+    assert!(uuid::Uuid::parse_str(&echoed).is_ok(), "{echoed}");
+    assert_eq!(hub.routing_decisions()[0].request_id, echoed);
+}
+
+#[tokio::test]
+async fn request_id_header_name_is_configurable() {
+    let mock = MockBackend::start().await;
+    let (url, _hub) = start(&mock, "x-correlation-id").await;
+
+    let resp = reqwest::Client::new()
+        .post(url)
+        .header("x-correlation-id", "trace-7")
+        .header("x-request-id", "ignored")
+        .body(r#"{"model":"claude-sonnet-4"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.headers()["x-correlation-id"], "trace-7");
+}
+
+#[tokio::test]
+async fn unusable_client_id_is_replaced() {
+    let mock = MockBackend::start().await;
+    let (url, _hub) = start(&mock, "x-request-id").await;
+
+    let resp = reqwest::Client::new()
+        .post(url)
+        .header("x-request-id", "a".repeat(500))
+        .body(r#"{"model":"claude-sonnet-4"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    let echoed = resp.headers()["x-request-id"].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(echoed).is_ok(), "{echoed}");
+}
@@ -0,0 +1,71 @@
+//! Per-request timing breakdown: connect time, time to first byte and
+//! total, recorded on each `RequestRecord`.
+
+mod common;
+
+use anyclaude::config::{Backend, Config, Defaults, ProxyConfig};
+use anyclaude::embed::ProxyBuilder;
+use anyclaude::proxy::ProxyAddr;
+use common::mock_backend::{MockBackend, MockResponse};
+
+const DELAY_MS: u64 = 300;
+
+#[tokio::test]
+async fn delayed_response_shows_in_ttfb_not_connect() {
+    let mock = MockBackend::start().await;
+    for _ in 0..2 {
+        mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#).with_delay(DELAY_MS)).await;
+    }
+    let config = Config {
+        defaults: Defaults {
+            active: "slow".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: format!("127.0.0.1:{}", common::free_port()),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "slow".to_string(),
+            base_url: mock.base_url(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let proxy = ProxyBuilder::new(config).start().await.unwrap();
+    let ProxyAddr::Tcp(addr) = proxy.addr().clone() else {
+        panic!("expected a TCP address");
+    };
+
+    let client = reqwest::Client::new();
+    for _ in 0..2 {
+        let resp = client
+            .post(format!("http://{addr}/v1/messages"))
+            .body("{}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        resp.text().await.unwrap();
+    }
+
+    let snapshot = proxy.observability().snapshot();
+    let (first, reused) = (&snapshot.recent[0], &snapshot.recent[1]);
+
+    let timing = first.timing();
+    let connect = timing.connect_ms.expect("first request opens a connection");
+    let ttfb = timing.ttfb_ms.unwrap();
+    assert!(ttfb >= DELAY_MS, "ttfb {ttfb}ms shorter than the {DELAY_MS}ms delay");
+    assert!(connect < DELAY_MS / 2, "connect took {connect}ms");
+    assert!(timing.total_ms.unwrap() >= ttfb);
+
+    // The second request rides the pooled connection.
+    assert_eq!(reused.timing().connect_ms, None);
+    assert!(reused.timing().ttfb_ms.unwrap() >= DELAY_MS);
+
+    let metrics = &snapshot.per_backend["slow"];
+    assert_eq!(metrics.avg_connect_ms, connect as f64);
+    assert!(metrics.avg_ttfb_ms >= DELAY_MS as f64);
+
+    proxy.shutdown().await.unwrap();
+}
@@ -0,0 +1,124 @@
+//! Tests for per-backend response normalization: stop reason mapping and
+//! usage defaults, for both non-streaming JSON and SSE streams.
+
+use std::collections::HashMap;
+
+use anyclaude::config::ResponseNormalization;
+use anyclaude::proxy::model_rewrite::{make_response_normalizer, normalize_response};
+use axum::body::Bytes;
+use serde_json::{json, Value};
+
+fn rules() -> ResponseNormalization {
+    ResponseNormalization {
+        stop_reasons: HashMap::from([("stop".to_string(), "end_turn".to_string())]),
+        fill_usage_defaults: true,
+    }
+}
+
+fn sse_data(chunk: &Bytes) -> Vec<Value> {
+    String::from_utf8_lossy(chunk)
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|payload| serde_json::from_str(payload).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_response_missing_output_tokens_gets_zero() {
+    let body = Bytes::from(
+        json!({
+            "type": "message",
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 12}
+        })
+        .to_string(),
+    );
+
+    let result: Value = serde_json::from_slice(&normalize_response(&body, &rules())).unwrap();
+    assert_eq!(result["usage"]["output_tokens"], 0);
+    assert_eq!(result["usage"]["input_tokens"], 12);
+}
+
+#[test]
+fn json_response_maps_backend_stop_reason() {
+    let body = Bytes::from(
+        json!({
+            "type": "message",
+            "stop_reason": "stop",
+            "usage": {"input_tokens": 1, "output_tokens": 2}
+        })
+        .to_string(),
+    );
+
+    let result: Value = serde_json::from_slice(&normalize_response(&body, &rules())).unwrap();
+    assert_eq!(result["stop_reason"], "end_turn");
+}
+
+#[test]
+fn json_response_already_normal_is_untouched() {
+    let body = Bytes::from(
+        "{\"type\":\"message\",\"stop_reason\":\"end_turn\",\"usage\":{\"input_tokens\":1,\"output_tokens\":2}}",
+    );
+    assert_eq!(normalize_response(&body, &rules()), body);
+}
+
+#[test]
+fn sse_message_delta_maps_stop_reason_and_fills_usage() {
+    let chunk = Bytes::from(
+        "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"stop\"},\"usage\":{}}\n\n",
+    );
+    let mut normalizer = make_response_normalizer(rules());
+
+    let result = normalizer(chunk);
+    let events = sse_data(&result);
+    assert_eq!(events[0]["delta"]["stop_reason"], "end_turn");
+    assert_eq!(events[0]["usage"]["output_tokens"], 0);
+    assert!(String::from_utf8_lossy(&result).starts_with("event: message_delta\n"));
+}
+
+#[test]
+fn sse_other_events_pass_through() {
+    let chunk = Bytes::from(
+        "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"stop\"}}\n\n",
+    );
+    let mut normalizer = make_response_normalizer(rules());
+    assert_eq!(normalizer(chunk.clone()), chunk);
+}
+
+#[test]
+fn sse_event_split_across_chunks_is_normalized() {
+    let event = "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"stop\"},\"usage\":{}}\n\n";
+    let mut normalizer = make_response_normalizer(rules());
+
+    // Split inside the data line: nothing of it goes out until it's whole.
+    let split = event.find("stop_reason").unwrap();
+    let mut out = normalizer(Bytes::from(&event[..split])).to_vec();
+    assert_eq!(out, b"event: message_delta\n");
+    out.extend_from_slice(&normalizer(Bytes::from(&event[split..])));
+    out.extend_from_slice(&normalizer(Bytes::new()));
+
+    let events = sse_data(&Bytes::from(out));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["delta"]["stop_reason"], "end_turn");
+    assert_eq!(events[0]["usage"]["output_tokens"], 0);
+}
+
+#[test]
+fn sse_unterminated_tail_is_released_at_end_of_stream() {
+    let mut normalizer = make_response_normalizer(rules());
+    assert!(normalizer(Bytes::from("data: {\"type\":\"ping\"")).is_empty());
+    assert_eq!(normalizer(Bytes::new()), Bytes::from("data: {\"type\":\"ping\""));
+}
+
+#[test]
+fn sse_chunk_starting_with_blank_line_keeps_it() {
+    let mut normalizer = make_response_normalizer(rules());
+    let chunk = Bytes::from(
+        "\nevent: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"stop\"},\"usage\":{\"output_tokens\":3}}\n\n",
+    );
+
+    let result = normalizer(chunk);
+    let text = String::from_utf8_lossy(&result);
+    assert!(text.starts_with("\nevent: message_delta\n"), "got {:?}", text);
+    assert_eq!(sse_data(&result)[0]["delta"]["stop_reason"], "end_turn");
+}
@@ -18,6 +18,18 @@ fn mapping(backend: &str, original: &str) -> ModelMapping {
     }
 }
 
+#[test]
+fn mapping_resolves_from_backend_config() {
+    let backend = Backend {
+        model_opus: Some("glm-4.7".to_string()),
+        ..Backend::default()
+    };
+    let m = ModelMapping::resolve(&backend, "claude-opus-4-6").unwrap();
+    assert_eq!(m.backend, "glm-4.7");
+    assert_eq!(m.original, "claude-opus-4-6");
+    assert!(ModelMapping::resolve(&backend, "claude-haiku-4-5").is_none());
+}
+
 // ---------------------------------------------------------------------------
 // Unit tests: make_reverse_model_rewriter (SSE streaming)
 // ---------------------------------------------------------------------------
@@ -215,6 +227,29 @@ fn rewriter_handles_malformed_sse_data_line() {
     assert_eq!(result.as_ref(), chunk.as_ref());
 }
 
+#[test]
+fn rewriter_rewrites_message_start_split_across_chunks() {
+    let event = "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"model\":\"glm-5\"}}\n\n";
+    let (head, tail) = event.split_at(40);
+    let mut rewriter = make_reverse_model_rewriter(mapping("glm-5", "claude-opus-4-6"));
+
+    let first = rewriter(Bytes::from(head));
+    assert_eq!(first.as_ref(), b"event: message_start\n", "partial data line is held back");
+    let second = rewriter(Bytes::from(tail));
+    let text = String::from_utf8_lossy(&second);
+    assert!(text.starts_with("data: {"), "held bytes come out first: {text}");
+    assert!(text.contains("claude-opus-4-6"));
+    assert!(text.ends_with("\n\n"));
+}
+
+#[test]
+fn rewriter_releases_held_bytes_on_end_of_stream() {
+    let mut rewriter = make_reverse_model_rewriter(mapping("glm-5", "claude-opus-4-6"));
+    assert!(rewriter(Bytes::from("data: {\"type\":")).is_empty());
+    let flushed = rewriter(Bytes::new());
+    assert_eq!(flushed.as_ref(), b"data: {\"type\":");
+}
+
 // ---------------------------------------------------------------------------
 // Unit tests: reverse_model_in_response (non-streaming JSON)
 // ---------------------------------------------------------------------------
@@ -317,12 +352,30 @@ fn test_config(backend: Backend, bind_addr: &str) -> Config {
             idle_timeout_seconds: 30,
             pool_idle_timeout_seconds: 30,
             pool_max_idle_per_host: 2,
+            warmup_connections: false,
             max_retries: 1,
             retry_backoff_base_ms: 10,
+            retry_non_idempotent: false,
+            switch_log_capacity: 500,
+            routing_log_capacity: 200,
+            queue_timeout_seconds: 30,
+            max_queued_requests: 64,
+            sse_keepalive_seconds: 0,
+            idle_revert_minutes: 0,
+            balance_tag: None,
         },
         proxy: ProxyConfig {
             bind_addr: bind_addr.to_string(),
             base_url: format!("http://{}", bind_addr),
+            passthrough: false,
+            thinking_dry_run: false,
+            validate_json: false,
+            request_id_header: "x-request-id".to_string(),
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            backend_header: None,
+            dashboard: false,
+            bypass_paths: Vec::new(),
         },
         terminal: TerminalConfig::default(),
         debug_logging: DebugLoggingConfig::default(),
@@ -345,9 +398,21 @@ fn create_backend_with_model_map(
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: model_opus.map(String::from),
         model_sonnet: model_sonnet.map(String::from),
         model_haiku: model_haiku.map(String::from),
@@ -361,9 +426,21 @@ fn create_passthrough_backend(name: &str, base_url: &str) -> Backend {
         base_url: base_url.to_string(),
         auth_type_str: "passthrough".to_string(),
         api_key: None,
+        api_key_command: None,
+        api_key_command_ttl_seconds: 300,
         pricing: None,
         thinking_compat: None,
         thinking_budget_tokens: None,
+        supports_thinking: true,
+        thinking_registry_enabled: true,
+        supports_cache_control: true,
+        response_normalization: None,
+        usage_mapping: None,
+        force_buffer: false,
+        match_client_stream: false,
+        max_concurrent: None,
+        tags: Vec::new(),
+        weight: 1,
         model_opus: None,
         model_sonnet: None,
         model_haiku: None,
@@ -663,6 +740,8 @@ async fn integration_error_response_with_model_field_is_rewritten() {
         headers: vec![("content-type".to_string(), "application/json".to_string())],
         body: r#"{"type":"error","model":"glm-5","error":{"type":"invalid_request_error","message":"bad request"}}"#.into(),
         delay_ms: 0,
+        chunks: Vec::new(),
+        chunk_pause_ms: 0,
     }).await;
 
     let bind_addr = format!("127.0.0.1:{}", common::free_port());
@@ -687,3 +766,95 @@ async fn integration_error_response_with_model_field_is_rewritten() {
     assert_eq!(json["model"], "claude-opus-4-6", "error response model should be reverse-mapped");
     assert_eq!(json["error"]["message"], "bad request", "error content should be preserved");
 }
+
+#[tokio::test]
+async fn integration_passthrough_mode_relays_bodies_untouched() {
+    let mock = MockBackend::start().await;
+
+    mock.enqueue_response(MockResponse::json(
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"glm-5","content":[{"type":"text","text":"Hello"}],"stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":5}}"#,
+    )).await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let mut backend = create_backend_with_model_map("test", &mock.base_url(), Some("glm-5"), None, None);
+    backend.thinking_compat = Some(true);
+    backend.thinking_budget_tokens = Some(2048);
+    let mut config = test_config(backend, &bind_addr);
+    config.proxy.passthrough = true;
+    let (_addr, proxy_url, _handle) = start_proxy(config).await;
+
+    // Adaptive thinking and an unregistered thinking block would both be
+    // rewritten by the transform stage; passthrough must leave them alone.
+    let request_body = r#"{"model":"claude-opus-4-6","thinking":{"type":"adaptive"},"max_tokens":1024,"messages":[{"role":"user","content":"hi"},{"role":"assistant","content":[{"type":"thinking","thinking":"unseen","signature":"sig"},{"type":"text","text":"ok"}]},{"role":"user","content":"again"}]}"#;
+    let client = Client::new();
+    let resp = client
+        .post(format!("{}/v1/messages", proxy_url))
+        .header("content-type", "application/json")
+        .body(request_body)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = serde_json::from_str(&resp.text().await.unwrap()).unwrap();
+    assert_eq!(json["model"], "glm-5", "response model must not be reverse-mapped");
+
+    let requests = mock.captured_requests().await;
+    assert_eq!(
+        requests[0].body.as_slice(),
+        request_body.as_bytes(),
+        "request body must be forwarded byte-for-byte"
+    );
+}
+
+#[tokio::test]
+async fn integration_long_sse_stream_is_rewritten_without_buffering() {
+    let start = "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\",\"model\":\"glm-5\",\"role\":\"assistant\",\"content\":[]}}\n\n";
+    let (start_head, start_tail) = start.split_at(60);
+    let delta = "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"chunk\"}}\n\n";
+    let mut pieces = vec![start_head, start_tail];
+    pieces.extend(std::iter::repeat_n(delta, 40));
+    pieces.push("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n");
+
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse_chunked(&pieces, 50)).await;
+
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let backend = create_backend_with_model_map("test", &mock.base_url(), Some("glm-5"), None, None);
+    let (_addr, proxy_url, _handle) = start_proxy(test_config(backend, &bind_addr)).await;
+
+    let mut resp = Client::new()
+        .post(format!("{}/v1/messages", proxy_url))
+        .header("content-type", "application/json")
+        .body(r#"{"model":"claude-opus-4-6","stream":true,"max_tokens":1024,"messages":[{"role":"user","content":"hi"}]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    // The rewritten message_start reaches the client while most of the
+    // upstream body has not even been sent yet.
+    let mut received = Vec::new();
+    while !String::from_utf8_lossy(&received).contains("message_stop") {
+        let chunk = resp.chunk().await.unwrap().expect("stream ended early");
+        received.extend_from_slice(&chunk);
+        if String::from_utf8_lossy(&received).contains("claude-opus-4-6") {
+            break;
+        }
+    }
+    assert!(
+        mock.chunks_sent() < pieces.len() / 2,
+        "message_start arrived only after {} of {} pieces",
+        mock.chunks_sent(),
+        pieces.len()
+    );
+
+    while let Some(chunk) = resp.chunk().await.unwrap() {
+        received.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8(received).unwrap();
+    assert!(body.starts_with("event: message_start\ndata: {"), "got: {body}");
+    assert!(!body.contains("\"glm-5\""));
+    assert_eq!(body.matches("\"text\":\"chunk\"").count(), 40);
+    assert!(body.ends_with("data: {\"type\":\"message_stop\"}\n\n"));
+}
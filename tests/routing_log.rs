@@ -0,0 +1,50 @@
+//! The observability hub's routing log: recent routing decisions kept
+//! newest-first and bounded by `defaults.routing_log_capacity`.
+
+use std::time::SystemTime;
+
+use anyclaude::metrics::{ObservabilityHub, RoutingLogEntry};
+
+fn entry(request_id: &str, backend: &str) -> RoutingLogEntry {
+    RoutingLogEntry {
+        request_id: request_id.to_string(),
+        backend: backend.to_string(),
+        reason: "active backend".to_string(),
+        path: "/v1/messages".to_string(),
+        timestamp: SystemTime::now(),
+    }
+}
+
+fn ids(hub: &ObservabilityHub) -> Vec<String> {
+    hub.routing_decisions()
+        .into_iter()
+        .map(|e| e.request_id)
+        .collect()
+}
+
+#[test]
+fn routing_decisions_are_returned_newest_first() {
+    let hub = ObservabilityHub::new(10);
+    hub.record_routing(entry("req-1", "claude"));
+    hub.record_routing(entry("req-2", "glm"));
+    hub.record_routing(entry("req-3", "claude"));
+
+    assert_eq!(ids(&hub), ["req-3", "req-2", "req-1"]);
+    assert_eq!(hub.routing_decisions()[1].backend, "glm");
+}
+
+#[test]
+fn routing_log_caps_at_configured_size() {
+    let hub = ObservabilityHub::new(1000).with_routing_log_capacity(2);
+    for i in 1..=5 {
+        hub.record_routing(entry(&format!("req-{i}"), "claude"));
+    }
+
+    assert_eq!(ids(&hub), ["req-5", "req-4"]);
+}
+
+#[test]
+fn routing_log_entry_renders_on_one_line() {
+    let line = entry("req-9", "glm").to_string();
+    assert!(line.ends_with("req-9 /v1/messages → glm (active backend)"), "{line}");
+}
@@ -0,0 +1,111 @@
+//! `Server-Timing` on proxied responses: the proxy's own time and the
+//! upstream's are reported separately, so a slow backend shows up as
+//! upstream time rather than proxy overhead.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Config, ConfigStore, DebugLoggingConfig, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+/// `name;dur=N` entries of a `Server-Timing` value, by name.
+fn parse_server_timing(value: &str) -> HashMap<String, f64> {
+    value
+        .split(',')
+        .filter_map(|metric| {
+            let (name, params) = metric.trim().split_once(';')?;
+            let dur = params.trim().strip_prefix("dur=")?.parse().ok()?;
+            Some((name.to_string(), dur))
+        })
+        .collect()
+}
+
+async fn start_proxy(mock: &MockBackend) -> std::net::SocketAddr {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "mock".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "mock".to_string(),
+            display_name: "Mock".to_string(),
+            base_url: mock.base_url(),
+            auth_type_str: "passthrough".to_string(),
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test-server-timing.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(DebugLoggingConfig::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    common::wait_for_server(addr, Duration::from_secs(5)).await;
+    addr
+}
+
+async fn timing_of(addr: std::net::SocketAddr, stream: bool) -> HashMap<String, f64> {
+    let resp = reqwest::Client::new()
+        .post(format!("http://{addr}/v1/messages"))
+        .header("content-type", "application/json")
+        .body(format!(r#"{{"model":"claude-opus-4-6","stream":{stream},"max_tokens":16,"messages":[]}}"#))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let header = resp
+        .headers()
+        .get("server-timing")
+        .expect("server-timing header")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let _ = resp.bytes().await.unwrap();
+    parse_server_timing(&header)
+}
+
+#[tokio::test]
+async fn delayed_backend_is_reported_as_upstream_time() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(
+        MockResponse::json(r#"{"type":"message","content":[{"type":"text","text":"hi"}]}"#)
+            .with_delay(200),
+    )
+    .await;
+    let addr = start_proxy(&mock).await;
+
+    let metrics = timing_of(addr, false).await;
+    let upstream = metrics["upstream"];
+    let proxy = metrics["proxy"];
+    assert!(upstream >= 200.0, "upstream {upstream}ms");
+    assert!(proxy >= 0.0 && proxy < upstream, "proxy {proxy}ms vs upstream {upstream}ms");
+}
+
+#[tokio::test]
+async fn streaming_responses_carry_the_header_too() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(
+        MockResponse::sse(&[r#"{"type":"message_start","message":{"id":"m"}}"#, r#"{"type":"message_stop"}"#])
+            .with_delay(100),
+    )
+    .await;
+    let addr = start_proxy(&mock).await;
+
+    let metrics = timing_of(addr, true).await;
+    assert!(metrics["upstream"] >= 100.0);
+    assert!(metrics.contains_key("proxy"));
+}
@@ -0,0 +1,58 @@
+//! `Session::resize`: the single path that keeps the PTY master and the
+//! emulator grid at the same size.
+#![cfg(unix)]
+
+use anyclaude::ui::gpu::pty::ChildPty;
+use anyclaude::ui::gpu::session::Session;
+use term_core::{create_emulator, TerminalEmulator};
+
+fn session_with_child() -> Session {
+    let mut session = Session::new("/bin/sh".to_string(), Vec::new(), Vec::new());
+    session.pty = Some(
+        ChildPty::spawn(
+            80,
+            24,
+            "/bin/sh".to_string(),
+            vec!["-c".to_string(), "sleep 5".to_string()],
+            Vec::new(),
+            || {},
+            || {},
+        )
+        .expect("spawn /bin/sh"),
+    );
+    session.emulator = Some(create_emulator(80, 24, 100));
+    session
+}
+
+fn emulator_size(emu: &dyn TerminalEmulator) -> (usize, usize) {
+    let snapshot = emu.snapshot();
+    let cols = snapshot.visible_iter().next().map_or(0, |row| row.cells.len());
+    (cols, snapshot.visible_rows)
+}
+
+#[test]
+fn resize_updates_master_and_surface() {
+    let mut session = session_with_child();
+
+    session.resize(120, 40);
+
+    assert_eq!(session.pty.as_ref().unwrap().size(), (120, 40));
+    assert_eq!(emulator_size(session.emulator.as_deref().unwrap()), (120, 40));
+}
+
+#[test]
+fn resize_clamps_zero_size_on_both_sides() {
+    let mut session = session_with_child();
+
+    session.resize(0, 0);
+
+    assert_eq!(session.pty.as_ref().unwrap().size(), (1, 1));
+    assert_eq!(emulator_size(session.emulator.as_deref().unwrap()), (1, 1));
+}
+
+#[test]
+fn resize_without_a_running_child_is_a_no_op() {
+    let mut session = Session::new("/bin/sh".to_string(), Vec::new(), Vec::new());
+    session.resize(100, 30);
+    assert!(session.pty.is_none() && session.emulator.is_none());
+}
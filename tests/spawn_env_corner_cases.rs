@@ -755,3 +755,53 @@ fn spawn_env_preserves_https() {
         "HTTPS should be preserved"
     );
 }
+
+// =============================================================================
+// NO-PROXY MODE
+// =============================================================================
+
+/// `--no-proxy` drops everything that points the child at the proxy, so
+/// claude uses the user's own ANTHROPIC_BASE_URL and nothing needs to listen.
+#[test]
+fn without_proxy_strips_proxy_env_and_keeps_the_rest() {
+    let args: Vec<String> = vec!["--model".into(), "opus".into()];
+    let params = build_spawn_params(
+        &args,
+        "http://127.0.0.1:4000",
+        "test-session-token",
+        &ClaudeSettingsManager::new(),
+        None,
+        None,
+    )
+    .without_proxy();
+
+    assert!(params.env.iter().all(|(k, _)| k != "ANTHROPIC_BASE_URL"));
+    assert!(params.env.iter().all(|(_, v)| !v.contains("test-session-token")));
+    assert!(params.env.iter().all(|(_, v)| !v.contains("127.0.0.1:4000")));
+    assert!(params.args.iter().all(|a| !a.contains("127.0.0.1")), "no subagent hooks");
+    assert!(params.args.contains(&"--model".to_string()));
+}
+
+#[test]
+fn without_proxy_keeps_unrelated_custom_headers() {
+    let mut params = build_spawn_params(
+        &[],
+        "http://127.0.0.1:4000",
+        "test-session-token",
+        &ClaudeSettingsManager::new(),
+        None,
+        None,
+    );
+    params
+        .env
+        .push(("ANTHROPIC_CUSTOM_HEADERS".into(), "x-team:alpha".into()));
+
+    let params = params.without_proxy();
+    let headers: Vec<_> = params
+        .env
+        .iter()
+        .filter(|(k, _)| k == "ANTHROPIC_CUSTOM_HEADERS")
+        .map(|(_, v)| v.as_str())
+        .collect();
+    assert_eq!(headers, ["x-team:alpha"]);
+}
@@ -1,7 +1,8 @@
 mod common;
 
 use anyclaude::sse::{
-    analyze_thinking_stream, count_thinking_events, parse_sse_events, ThinkingStreamStats,
+    analyze_thinking_stream, count_thinking_events, parse_sse_data, parse_sse_events,
+    parse_sse_payloads, split_sse_frames, SseEventDecoder, SseEventHistogram, ThinkingStreamStats, MAX_SSE_LINE_BYTES,
 };
 
 #[test]
@@ -452,3 +453,215 @@ data: {\"type\":\"content_block_stop\",\"index\":0}\n";
     assert_eq!(stats.thinking_stops, 1);
     assert!(stats.has_signatures); // found in both places
 }
+
+#[test]
+fn decoder_holds_partial_lines_until_completed() {
+    let mut decoder = SseEventDecoder::new();
+    assert!(decoder.push(b"event: message_start\ndata: {\"type\":\"mess").is_empty());
+    let events = decoder.push(b"age_start\"}\n\ndata: {\"type\":\"ping\"}");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "message_start");
+
+    // A stream ending without a trailing newline is flushed by finish().
+    let events = decoder.finish();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "ping");
+    assert!(decoder.finish().is_empty());
+}
+
+#[test]
+fn decoder_matches_batch_parse_for_any_split() {
+    let sse = "data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"привет\"}}\n\
+data:{\"type\":\"message_stop\"}\n".as_bytes();
+    let batch: Vec<String> = parse_sse_events(sse)
+        .into_iter()
+        .map(|e| e.data.to_string())
+        .collect();
+
+    for split in 0..=sse.len() {
+        let mut decoder = SseEventDecoder::new();
+        let mut got: Vec<String> = Vec::new();
+        got.extend(decoder.push(&sse[..split]).into_iter().map(|e| e.data.to_string()));
+        got.extend(decoder.push(&sse[split..]).into_iter().map(|e| e.data.to_string()));
+        got.extend(decoder.finish().into_iter().map(|e| e.data.to_string()));
+        assert_eq!(got, batch, "split at {split}");
+    }
+}
+
+const HISTOGRAM_STREAM: &[u8] = b"\
+event: message_start\ndata: {\"type\":\"message_start\",\"message\":{}}\n\n\
+event: ping\ndata: {\"type\":\"ping\"}\n\n\
+data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"thinking\"}}\n\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"a\"}}\n\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"b\"}}\n\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"signature_delta\",\"signature\":\"s\"}}\n\n\
+data: {\"type\":\"content_block_stop\",\"index\":0}\n\n\
+data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"text\"}}\n\n\
+data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"text_delta\",\"text\":\"hi\"}}\n\n\
+data: {\"type\":\"ping\"}\n\n\
+data: {\"type\":\"content_block_stop\",\"index\":1}\n\n\
+data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"}}\n\n\
+data: {\"type\":\"message_stop\"}\n\n";
+
+#[test]
+fn histogram_counts_every_event_type_and_delta_type() {
+    let histogram = SseEventHistogram::from_events(&parse_sse_events(HISTOGRAM_STREAM));
+    let counts: Vec<(&str, u64)> = histogram.iter().collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("content_block_delta", 4),
+            ("content_block_start", 2),
+            ("content_block_stop", 2),
+            ("message_delta", 1),
+            ("message_start", 1),
+            ("message_stop", 1),
+            ("ping", 2),
+            ("signature_delta", 1),
+            ("text_delta", 1),
+            ("thinking_delta", 2),
+        ]
+    );
+    // message_delta carries a delta without a type; it is not broken down.
+    assert_eq!(histogram.count("input_json_delta"), 0);
+    assert_eq!(
+        histogram.to_string(),
+        "content_block_delta=4 content_block_start=2 content_block_stop=2 message_delta=1 \
+message_start=1 message_stop=1 ping=2 signature_delta=1 text_delta=1 thinking_delta=2"
+    );
+    assert_eq!(SseEventHistogram::new().to_string(), "-");
+}
+
+#[test]
+fn histogram_from_decoder_matches_batch_parse() {
+    let batch = SseEventHistogram::from_events(&parse_sse_events(HISTOGRAM_STREAM));
+    let mut decoder = SseEventDecoder::new();
+    let mut incremental = SseEventHistogram::new();
+    for chunk in HISTOGRAM_STREAM.chunks(7) {
+        for event in decoder.push(chunk) {
+            incremental.record(&event);
+        }
+    }
+    for event in decoder.finish() {
+        incremental.record(&event);
+    }
+    assert_eq!(incremental, batch);
+}
+
+#[test]
+fn split_sse_frames_yields_one_terminated_frame_per_event() {
+    let body = b"event: ping\r\ndata: {\"type\":\"ping\"}\r\n\r\n\n\ndata: {\"type\":\"message_stop\"}";
+    let frames: Vec<String> = split_sse_frames(body)
+        .into_iter()
+        .map(|f| String::from_utf8(f).unwrap())
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            "event: ping\ndata: {\"type\":\"ping\"}\n\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        ]
+    );
+    assert!(split_sse_frames(b"\n\n").is_empty());
+}
+
+fn over_long_line() -> Vec<u8> {
+    let mut line = b"data: {\"type\":\"content_block_delta\",\"text\":\"".to_vec();
+    line.resize(MAX_SSE_LINE_BYTES + 16, b'x');
+    line
+}
+
+#[test]
+fn parse_keeps_every_event_of_a_normal_buffer() {
+    let sse = b"data: {\"type\":\"message_start\"}\n\ndata: {\"type\":\"ping\"}\n\ndata: {\"type\":\"message_stop\"}\n\n";
+    let types: Vec<_> = parse_sse_events(sse).into_iter().map(|e| e.event_type).collect();
+    assert_eq!(types, ["message_start", "ping", "message_stop"]);
+}
+
+#[test]
+fn parse_skips_an_over_long_line() {
+    let mut sse = b"data: {\"type\":\"message_start\"}\n".to_vec();
+    sse.extend(over_long_line());
+    sse.extend_from_slice(b"\"}\ndata: {\"type\":\"message_stop\"}\n");
+
+    let types: Vec<_> = parse_sse_events(&sse).into_iter().map(|e| e.event_type).collect();
+    assert_eq!(types, ["message_start", "message_stop"]);
+}
+
+#[test]
+fn decoder_drops_an_over_long_line_instead_of_buffering_it() {
+    let mut decoder = SseEventDecoder::new();
+    assert_eq!(decoder.push(b"data: {\"type\":\"message_start\"}\n\n").len(), 1);
+
+    for chunk in over_long_line().chunks(64 * 1024) {
+        assert!(decoder.push(chunk).is_empty());
+        assert!(decoder.pending_len() <= MAX_SSE_LINE_BYTES);
+    }
+    assert_eq!(decoder.pending_len(), 0);
+
+    // The tail of the skipped line is discarded; the next event parses.
+    let events = decoder.push(b"xxx\"}\ndata: {\"type\":\"message_stop\"}\n\n");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "message_stop");
+}
+
+#[test]
+fn multi_line_data_is_joined_into_one_event() {
+    let sse = b"event: message_start\ndata: {\"type\": \"message_start\",\ndata:  \"message\": {\"id\": \"m1\"}}\n\n";
+    let events = parse_sse_events(sse);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "message_start");
+    assert_eq!(events[0].data["message"]["id"], "m1");
+}
+
+#[test]
+fn multi_line_data_split_across_chunks_decodes_on_blank_line() {
+    let mut decoder = SseEventDecoder::new();
+    assert!(decoder.push(b"data: {\"type\":\n").is_empty());
+    assert!(decoder.push(b"data: \"ping\"}\n").is_empty());
+    let events = decoder.push(b"\n");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "ping");
+}
+
+#[test]
+fn data_lines_that_are_json_alone_still_join_until_the_blank_line() {
+    assert_eq!(parse_sse_data(b"data: 1\ndata: 2\n\ndata:3\n\n"), ["1\n2", "3"]);
+
+    let mut decoder = SseEventDecoder::new();
+    assert!(decoder.push_payloads(b"data: 1\n").is_empty(), "held until the blank line");
+    assert!(decoder.push_payloads(b"data: 2\n").is_empty());
+    assert_eq!(decoder.push_payloads(b"\n").len(), 2);
+}
+
+#[test]
+fn data_line_json_on_its_own_waits_for_its_continuation() {
+    let sse = b"data: {\"type\": \"ping\"}\ndata: \n\n";
+    assert_eq!(parse_sse_payloads(sse), [serde_json::json!({"type": "ping"})]);
+    assert_eq!(parse_sse_data(sse), ["{\"type\": \"ping\"}\n"]);
+}
+
+#[test]
+fn id_and_retry_fields_are_ignored() {
+    let sse = b"id: 42\nretry: 3000\nevent: ping\ndata: {\"type\": \"ping\"}\n\nid: 43\ndata: {\"type\": \"message_stop\"}\n\n";
+    let types: Vec<_> = parse_sse_events(sse).into_iter().map(|e| e.event_type).collect();
+    assert_eq!(types, ["ping", "message_stop"]);
+}
+
+#[test]
+fn decoder_skips_interleaved_noise_and_counts_split_events_once() {
+    let mut decoder = SseEventDecoder::new();
+    let mut types = Vec::new();
+    for chunk in [
+        &b"HTTP noise\n: ok\n\nevent: message_start\nda"[..],
+        b"ta: {\"type\":\"message_st",
+        b"art\"}\n\n<html>oops</html>\ndata: [DONE]\n\n",
+        b"data: {\"type\":\"message_stop\"}\n\ntrailing junk",
+    ] {
+        types.extend(decoder.push(chunk).into_iter().map(|e| e.event_type));
+    }
+    types.extend(decoder.finish().into_iter().map(|e| e.event_type));
+
+    assert_eq!(types, ["message_start", "message_stop"]);
+    assert_eq!(decoder.discarded_lines(), 3);
+}
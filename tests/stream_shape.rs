@@ -0,0 +1,218 @@
+//! Stream-shape matching for backends that ignore the request's `stream`
+//! flag: SSE folded into JSON for non-streaming clients, JSON replayed as
+//! SSE for streaming ones.
+
+mod common;
+
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::metrics::SSE_KEEPALIVE;
+use anyclaude::proxy::stream_shape::{buffer_sse, json_to_sse, sse_to_json};
+use axum::body::Bytes;
+use futures::StreamExt;
+use anyclaude::proxy::{ProxyHandle, ProxyServer};
+use anyclaude::sse::parse_sse_payloads;
+use common::mock_backend::{MockBackend, MockResponse};
+use serde_json::{json, Value};
+
+const STREAM: &[&str] = &[
+    r#"{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","model":"m","content":[],"stop_reason":null,"usage":{"input_tokens":9,"output_tokens":1}}}"#,
+    r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+    r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+    r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#,
+    r#"{"type":"content_block_stop","index":0}"#,
+    r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"tu_1","name":"Read","input":{}}}"#,
+    r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"path\":"}}"#,
+    r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"a.rs\"}"}}"#,
+    r#"{"type":"content_block_stop","index":1}"#,
+    r#"{"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":7}}"#,
+    r#"{"type":"message_stop"}"#,
+];
+
+fn message() -> Value {
+    json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "model": "m",
+        "content": [
+            {"type": "thinking", "thinking": "hmm", "signature": "sig"},
+            {"type": "text", "text": "Hello"},
+            {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {"path": "a.rs"}}
+        ],
+        "stop_reason": "tool_use",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 9, "output_tokens": 7}
+    })
+}
+
+fn sse_body(events: &[&str]) -> String {
+    events.iter().map(|e| format!("data: {e}\n\n")).collect()
+}
+
+#[test]
+fn stream_folds_into_message() {
+    let json: Value = serde_json::from_slice(&sse_to_json(sse_body(STREAM).as_bytes()).unwrap()).unwrap();
+    assert_eq!(json["content"][0], json!({"type": "text", "text": "Hello"}));
+    assert_eq!(json["content"][1]["input"], json!({"path": "a.rs"}));
+    assert_eq!(json["stop_reason"], "tool_use");
+    assert_eq!(json["usage"], json!({"input_tokens": 9, "output_tokens": 7}));
+}
+
+#[test]
+fn non_message_stream_is_not_folded() {
+    assert!(sse_to_json(b"data: {\"type\":\"ping\"}\n\n").is_none());
+}
+
+#[test]
+fn message_replays_as_events_and_folds_back() {
+    let sse = json_to_sse(message().to_string().as_bytes()).unwrap();
+    let types: Vec<String> = parse_sse_payloads(&sse)
+        .iter()
+        .map(|p| p["type"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(types.first().map(String::as_str), Some("message_start"));
+    assert_eq!(types.last().map(String::as_str), Some("message_stop"));
+    assert_eq!(types.iter().filter(|t| *t == "content_block_start").count(), 3);
+
+    let round_trip: Value = serde_json::from_slice(&sse_to_json(&sse).unwrap()).unwrap();
+    assert_eq!(round_trip, message());
+}
+
+#[test]
+fn error_body_is_not_replayed() {
+    assert!(json_to_sse(br#"{"type":"error","error":{"type":"overloaded_error"}}"#).is_none());
+}
+
+async fn start(mock: &MockBackend) -> (String, ProxyHandle) {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "odd".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![Backend {
+            name: "odd".to_string(),
+            base_url: mock.base_url(),
+            match_client_stream: true,
+            ..Backend::default()
+        }],
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(Default::default()));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    let handle = server.handle();
+    tokio::spawn(async move { let _ = server.run().await; });
+    (format!("http://{addr}"), handle)
+}
+
+async fn post(url: &str, stream: bool) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{url}/v1/messages"))
+        .json(&json!({"model": "m", "stream": stream, "messages": []}))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn non_streaming_client_gets_json_from_streaming_backend() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse(STREAM)).await;
+    let (url, _handle) = start(&mock).await;
+
+    let resp = post(&url, false).await;
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["type"], "message");
+    assert_eq!(body["content"][0]["text"], "Hello");
+}
+
+#[tokio::test]
+async fn streaming_client_gets_sse_from_json_backend() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(&message().to_string())).await;
+    let (url, _handle) = start(&mock).await;
+
+    let resp = post(&url, true).await;
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    let body = resp.bytes().await.unwrap();
+    assert!(body.starts_with(b"event: message_start\n"));
+    let folded: Value = serde_json::from_slice(&sse_to_json(&body).unwrap()).unwrap();
+    assert_eq!(folded, message());
+}
+
+#[tokio::test]
+async fn matching_shapes_pass_through() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::sse(STREAM)).await;
+    let (url, _handle) = start(&mock).await;
+
+    let resp = post(&url, true).await;
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    assert_eq!(parse_sse_payloads(&resp.bytes().await.unwrap()).len(), STREAM.len());
+}
+
+fn chunks(pieces: &[&'static str], delay: Duration) -> impl futures::Stream<Item = Result<Bytes, Infallible>> + Unpin {
+    futures::stream::iter(pieces.to_vec())
+        .then(move |piece| async move {
+            tokio::time::sleep(delay).await;
+            Ok(Bytes::from_static(piece.as_bytes()))
+        })
+        .boxed()
+}
+
+#[tokio::test]
+async fn buffered_stream_beats_while_reading_then_replays_events() {
+    let pieces = ["data: {\"type\":\"message_start\"}\r\n\r\ndata: {\"ty", "pe\":\"message_stop\"}\n\n"];
+    let out: Vec<Bytes> = buffer_sse(
+        chunks(&pieces, Duration::from_millis(30)),
+        "test".to_string(),
+        1024,
+        Duration::from_millis(10),
+    )
+    .map(Result::unwrap)
+    .collect()
+    .await;
+
+    let beats = out.iter().take_while(|chunk| chunk.as_ref() == SSE_KEEPALIVE).count();
+    assert!(beats >= 1, "no keep-alive while buffering: {out:?}");
+    assert_eq!(
+        &out[beats..],
+        [
+            Bytes::from_static(b"data: {\"type\":\"message_start\"}\n\n"),
+            Bytes::from_static(b"data: {\"type\":\"message_stop\"}\n\n"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn buffered_stream_over_the_limit_ends_with_an_error_event() {
+    let pieces = ["data: {\"type\":\"message_start\"}\n\n", "data: {\"type\":\"message_stop\"}\n\n"];
+    let out: Vec<Bytes> = buffer_sse(
+        chunks(&pieces, Duration::ZERO),
+        "test".to_string(),
+        40,
+        Duration::from_secs(60),
+    )
+    .map(Result::unwrap)
+    .collect()
+    .await;
+
+    assert_eq!(out.len(), 1, "{out:?}");
+    let payloads = parse_sse_payloads(&out[0]);
+    assert_eq!(payloads[0]["type"], "error");
+    assert!(payloads[0]["error"]["message"].as_str().unwrap().contains("40 bytes"));
+}
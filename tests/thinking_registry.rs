@@ -2,9 +2,13 @@
 
 mod common;
 
-use anyclaude::proxy::thinking::{fast_hash, safe_suffix, safe_truncate, ThinkingRegistry};
-use anyclaude::sse::parse_sse_events;
+use anyclaude::proxy::thinking::{
+    block_hash, fast_hash, normalize_thinking_blocks, safe_suffix, safe_truncate, BlockKind,
+    FilterOutcome, SseBlockAccumulator, ThinkingRegistry,
+};
+use anyclaude::sse::{parse_sse_events, SseEventDecoder};
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::time::Duration;
 
 // ========================================================================
@@ -131,7 +135,7 @@ data: {\"type\":\"content_block_stop\",\"index\":0}\n";
     assert_eq!(registry.block_count(), 1);
 
     // Verify the registered block matches the full accumulated text
-    let hash = fast_hash("Hello world");
+    let hash = block_hash(BlockKind::Thinking, "Hello world");
     assert!(registry.blocks.contains_key(&hash));
 }
 
@@ -170,6 +174,76 @@ data: {\"type\":\"content_block_stop\",\"index\":2}\n";
     assert_eq!(registry.block_count(), 2, "should register 2 thinking blocks");
 }
 
+const MIXED_SSE_STREAM: &[u8] = "\
+data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"thinking\",\"thinking\":\"\"}}\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Thought é A\"}}\n\
+data: {\"type\":\"content_block_stop\",\"index\":0}\n\
+data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"redacted_thinking\",\"data\":\"opaque\"}}\n\
+data: {\"type\":\"content_block_stop\",\"index\":1}\n\
+data: {\"type\":\"content_block_start\",\"index\":2,\"content_block\":{\"type\":\"thinking\",\"thinking\":\"\"}}\n\
+data: {\"type\":\"content_block_delta\",\"index\":2,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Truncated B\"}}".as_bytes();
+
+/// Feed `stream` in `chunk_size`-byte fragments through the incremental path.
+fn register_incrementally(registry: &mut ThinkingRegistry, stream: &[u8], chunk_size: usize) {
+    let session = registry.current_session();
+    let mut decoder = SseEventDecoder::new();
+    let mut accumulator = SseBlockAccumulator::new();
+    for chunk in stream.chunks(chunk_size) {
+        for event in decoder.push(chunk) {
+            if let Some((kind, content)) = accumulator.feed(&event) {
+                registry.register_block(kind, &content, session);
+            }
+        }
+    }
+    for event in decoder.finish() {
+        if let Some((kind, content)) = accumulator.feed(&event) {
+            registry.register_block(kind, &content, session);
+        }
+    }
+    for (kind, content) in accumulator.finish() {
+        registry.register_block(kind, &content, session);
+    }
+}
+
+#[test]
+fn test_incremental_sse_registration_matches_batch() {
+    let mut batch = ThinkingRegistry::new();
+    batch.on_backend_switch("anthropic");
+    batch.register_from_sse_stream(&parse_sse_events(MIXED_SSE_STREAM), batch.current_session());
+    let expected: HashSet<u64> = batch.blocks.keys().copied().collect();
+    assert_eq!(expected.len(), 3);
+    assert!(expected.contains(&block_hash(BlockKind::Thinking, "Thought é A")));
+    assert!(expected.contains(&block_hash(BlockKind::Redacted, "opaque")));
+    assert!(expected.contains(&block_hash(BlockKind::Thinking, "Truncated B")));
+
+    // Fragment sizes that split lines, JSON tokens and the multi-byte char.
+    for chunk_size in [1, 2, 7, 64, MIXED_SSE_STREAM.len()] {
+        let mut incremental = ThinkingRegistry::new();
+        incremental.on_backend_switch("anthropic");
+        register_incrementally(&mut incremental, MIXED_SSE_STREAM, chunk_size);
+        let got: HashSet<u64> = incremental.blocks.keys().copied().collect();
+        assert_eq!(got, expected, "chunk_size={chunk_size}");
+    }
+}
+
+#[test]
+fn test_accumulator_emits_thinking_block_on_its_stop() {
+    let mut accumulator = SseBlockAccumulator::new();
+    let events = parse_sse_events(MIXED_SSE_STREAM);
+
+    let completed: Vec<_> = events.iter().map(|e| accumulator.feed(e)).collect();
+    assert_eq!(completed[0], None);
+    assert_eq!(completed[1], None);
+    assert_eq!(completed[2], Some((BlockKind::Thinking, "Thought é A".to_string())));
+    assert_eq!(completed[3], Some((BlockKind::Redacted, "opaque".to_string())));
+    assert!(completed[4..].iter().all(Option::is_none));
+
+    assert_eq!(
+        accumulator.finish(),
+        vec![(BlockKind::Thinking, "Truncated B".to_string())]
+    );
+}
+
 #[test]
 fn test_sse_stream_registered_blocks_match_request() {
     let mut registry = ThinkingRegistry::new();
@@ -189,6 +263,43 @@ data: {\"type\":\"content_block_stop\",\"index\":0}\n";
     assert_eq!(removed, 0, "SSE-registered block should match request block");
 }
 
+#[test]
+fn test_thinking_and_redacted_with_same_payload_do_not_collide() {
+    let mut registry = ThinkingRegistry::new();
+    registry.on_backend_switch("anthropic");
+
+    let response = json!({
+        "content": [
+            {"type": "thinking", "thinking": "same-payload"},
+            {"type": "redacted_thinking", "data": "same-payload"}
+        ]
+    });
+    registry.register_from_response(
+        &serde_json::to_vec(&response).unwrap(),
+        registry.current_session(),
+    );
+    assert_eq!(registry.block_count(), 2, "both kinds tracked independently");
+    assert!(registry
+        .blocks
+        .contains_key(&block_hash(BlockKind::Thinking, "same-payload")));
+    assert!(registry
+        .blocks
+        .contains_key(&block_hash(BlockKind::Redacted, "same-payload")));
+
+    // A request carrying only the redacted block must not keep a thinking
+    // block alive (or vice versa) through a shared hash.
+    let mut request = json!({
+        "messages": [
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "other"},
+                {"type": "redacted_thinking", "data": "same-payload"}
+            ]}
+        ]
+    });
+    assert_eq!(registry.filter_request(&mut request), 1, "unregistered thinking removed");
+}
+
 #[test]
 fn test_register_deduplication() {
     let mut registry = ThinkingRegistry::new();
@@ -916,3 +1027,100 @@ fn test_interleaved_opus_haiku_workflow() {
     );
     assert_eq!(registry.block_count(), 2);
 }
+
+#[test]
+fn test_filter_outcome_reports_each_decision() {
+    let mut registry = ThinkingRegistry::with_orphan_threshold(Duration::ZERO);
+    registry.on_backend_switch("anthropic");
+    let session = registry.current_session();
+    registry.register_from_response(&make_response_with_thinking(&["A", "B", "C"]), session);
+
+    // A and B confirmed, C orphaned, the unknown block stripped.
+    let mut request = make_request_with_thinking(&["A", "B", "unknown"]);
+    assert_eq!(
+        registry.filter_request_outcome(&mut request),
+        FilterOutcome {
+            confirmed: 2,
+            orphaned_removed: 1,
+            filtered: 1,
+            ..FilterOutcome::default()
+        }
+    );
+
+    // B dropped out of the history: evicted as confirmed-but-unused.
+    let mut request = make_request_with_thinking(&["A"]);
+    assert_eq!(
+        registry.filter_request_outcome(&mut request),
+        FilterOutcome {
+            unused_removed: 1,
+            ..FilterOutcome::default()
+        }
+    );
+
+    // A backend switch invalidates A; it is evicted and stripped.
+    registry.on_backend_switch("openrouter");
+    let mut request = make_request_with_thinking(&["A"]);
+    assert_eq!(
+        registry.filter_request_outcome(&mut request),
+        FilterOutcome {
+            old_session_removed: 1,
+            filtered: 1,
+            ..FilterOutcome::default()
+        }
+    );
+}
+
+#[test]
+fn test_normalize_removes_empty_signature_field() {
+    let mut body = json!({
+        "messages": [{"role": "assistant", "content": [
+            {"type": "thinking", "thinking": "a", "signature": ""},
+            {"type": "thinking", "thinking": "b", "signature": "sig-b"},
+            {"type": "text", "text": "ok", "signature": ""}
+        ]}]
+    });
+    assert_eq!(normalize_thinking_blocks(&mut body), 1);
+    let content = &body["messages"][0]["content"];
+    assert!(content[0].get("signature").is_none(), "empty signature removed entirely");
+    assert_eq!(content[1]["signature"], "sig-b");
+    assert_eq!(content[2]["signature"], "", "non-thinking blocks untouched");
+}
+
+#[test]
+fn test_normalize_collapses_identical_thinking_blocks_within_a_message() {
+    let mut body = json!({
+        "messages": [
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "same", "signature": "s"},
+                {"type": "text", "text": "one"},
+                {"type": "thinking", "thinking": "same", "signature": "s"},
+                {"type": "thinking", "thinking": "same", "signature": "other"}
+            ]},
+            {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "same", "signature": "s"}
+            ]}
+        ]
+    });
+    assert_eq!(normalize_thinking_blocks(&mut body), 1);
+    let first = body["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(first.len(), 3, "exact duplicate dropped, differing signature kept");
+    assert_eq!(first[1]["text"], "one");
+    assert_eq!(body["messages"][1]["content"].as_array().unwrap().len(), 1, "other messages untouched");
+}
+
+#[test]
+fn test_normalize_empty_signature_then_dedup() {
+    // A block that lost its signature on one switch duplicates one that
+    // had it blanked on another; both normalize to the same block.
+    let mut body = json!({
+        "messages": [{"role": "assistant", "content": [
+            {"type": "thinking", "thinking": "t", "signature": ""},
+            {"type": "thinking", "thinking": "t"}
+        ]}]
+    });
+    assert_eq!(normalize_thinking_blocks(&mut body), 2);
+    assert_eq!(
+        body["messages"][0]["content"],
+        json!([{"type": "thinking", "thinking": "t"}])
+    );
+}
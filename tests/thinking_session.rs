@@ -8,6 +8,7 @@
 
 use anyclaude::metrics::DebugLogger;
 use anyclaude::proxy::thinking::TransformerRegistry;
+use anyclaude::sse::SseEventDecoder;
 use std::sync::Arc;
 
 fn make_registry() -> Arc<TransformerRegistry> {
@@ -571,3 +572,29 @@ fn filter_null_body() {
     let filtered = session.filter(&mut body);
     assert_eq!(filtered, 0);
 }
+
+#[test]
+fn sse_registration_registers_each_block_as_it_completes() {
+    let reg = make_registry();
+    let logger = make_logger();
+    let session = reg.begin_request("claude", logger);
+    let mut registration = session.sse_registration();
+    let mut decoder = SseEventDecoder::new();
+
+    registration.feed(&decoder.push(b"data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"thinking\",\"thinking\":\"\"}}\n\n"));
+    registration.feed(&decoder.push(b"data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"early\"}}\n\ndata: {\"type\":\"content_block_st"));
+    assert_eq!(reg.thinking_cache_stats().total, 0, "not complete yet");
+
+    registration.feed(&decoder.push(b"op\",\"index\":0}\n\n"));
+    assert_eq!(reg.thinking_cache_stats().total, 1, "registered before the stream ends");
+
+    // A block cut off before its stop is registered when the stream finishes.
+    registration.feed(&decoder.push(b"data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"thinking\",\"thinking\":\"late\"}}\n\n"));
+    assert_eq!(reg.thinking_cache_stats().total, 1);
+    registration.finish();
+    assert_eq!(reg.thinking_cache_stats().total, 2);
+
+    let session2 = reg.begin_request("claude", make_logger());
+    let mut body = request_with_thinking("sig", "early");
+    assert_eq!(session2.filter(&mut body), 0, "incrementally registered block survives filter");
+}
@@ -0,0 +1,187 @@
+//! Usage totals split by request origin: requests through `/teammate/*`
+//! count toward the teammate totals, everything else toward main.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyclaude::config::{
+    AgentsConfig, Backend, Config, ConfigStore, DebugLogLevel, DebugLoggingConfig, Defaults,
+    ProxyConfig,
+};
+use anyclaude::metrics::{
+    DebugLogger, MetricsSnapshot, ObservabilityHub, RequestOrigin, ResponseAnalysis, UsageTotals,
+};
+use anyclaude::proxy::ProxyServer;
+use axum::body::Body;
+use axum::http::Request;
+use common::mock_backend::{MockBackend, MockResponse};
+
+fn analysis(input: u64, output: u64, cost: f64) -> ResponseAnalysis {
+    ResponseAnalysis {
+        summary: String::new(),
+        input_tokens: Some(input),
+        output_tokens: Some(output),
+        stop_reason: None,
+        cost_usd: Some(cost),
+    }
+}
+
+fn finish(hub: &ObservabilityHub, origin: RequestOrigin, usage: Option<ResponseAnalysis>) {
+    let req = Request::new(Body::empty());
+    let mut start = hub.start_request("req".to_string(), &req, "claude");
+    start.span.set_origin(origin);
+    start.span.record_mut().response_analysis = usage;
+    hub.finish_request(start.span);
+}
+
+#[test]
+fn origins_are_totalled_separately() {
+    let hub = ObservabilityHub::new(10);
+    finish(&hub, RequestOrigin::Main, Some(analysis(100, 10, 0.5)));
+    finish(&hub, RequestOrigin::Teammate, Some(analysis(7, 3, 0.25)));
+    finish(&hub, RequestOrigin::Teammate, None);
+
+    let snapshot = hub.snapshot();
+    assert_eq!(
+        snapshot.per_origin[&RequestOrigin::Main],
+        UsageTotals { requests: 1, input_tokens: 100, output_tokens: 10, cost_usd: 0.5 }
+    );
+    assert_eq!(
+        snapshot.per_origin[&RequestOrigin::Teammate],
+        UsageTotals { requests: 2, input_tokens: 7, output_tokens: 3, cost_usd: 0.25 }
+    );
+}
+
+#[test]
+fn requests_default_to_main_origin() {
+    let hub = ObservabilityHub::new(10);
+    let req = Request::new(Body::empty());
+    let start = hub.start_request("req".to_string(), &req, "claude");
+    hub.finish_request(start.span);
+
+    let snapshot = hub.snapshot();
+    assert_eq!(snapshot.recent[0].origin, RequestOrigin::Main);
+    assert!(!snapshot.per_origin.contains_key(&RequestOrigin::Teammate));
+}
+
+fn backend(name: &str, base_url: &str) -> Backend {
+    Backend {
+        name: name.to_string(),
+        display_name: name.to_string(),
+        base_url: base_url.to_string(),
+        auth_type_str: "passthrough".to_string(),
+        ..Backend::default()
+    }
+}
+
+/// Start a proxy on `mock` with a `main` and a `cheap` teammate backend,
+/// logging at `level`.
+async fn start_proxy(mock: &MockBackend, level: DebugLogLevel) -> (String, ObservabilityHub) {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: "main".to_string(),
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            base_url: format!("http://{bind_addr}"),
+            ..ProxyConfig::default()
+        },
+        backends: vec![backend("main", &mock.base_url()), backend("cheap", &mock.base_url())],
+        agents: Some(AgentsConfig {
+            teammate_backend: "cheap".to_string(),
+            subagent_backend: None,
+            team_backends: HashMap::new(),
+        }),
+        ..Config::default()
+    };
+    let config_store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let debug_logger = Arc::new(DebugLogger::new(DebugLoggingConfig {
+        level,
+        ..DebugLoggingConfig::default()
+    }));
+    let mut server = ProxyServer::new(config_store.clone(), debug_logger, None).unwrap();
+    let hub = server.observability();
+    let (addr, _) = server.try_bind(&config_store).await.unwrap();
+    let _handle = server.handle();
+    tokio::spawn(async move { let _ = server.run().await; });
+    (addr.to_string(), hub)
+}
+
+/// Wait until the hub has recorded `count` finished requests.
+async fn wait_for_requests(hub: &ObservabilityHub, count: usize) -> MetricsSnapshot {
+    let mut snapshot = hub.snapshot();
+    for _ in 0..50 {
+        if snapshot.recent.len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        snapshot = hub.snapshot();
+    }
+    snapshot
+}
+
+#[tokio::test]
+async fn teammate_route_is_tagged_and_counted_as_teammate() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(
+        r#"{"type":"message","stop_reason":"end_turn","usage":{"input_tokens":12,"output_tokens":4}}"#,
+    ))
+    .await;
+    let (addr, hub) = start_proxy(&mock, DebugLogLevel::Verbose).await;
+
+    let body = reqwest::Client::new()
+        .post(format!("http://{addr}/teammate/alice@team/v1/messages"))
+        .body("{}")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(body.contains("end_turn"));
+
+    let snapshot = wait_for_requests(&hub, 1).await;
+    assert_eq!(snapshot.recent[0].origin, RequestOrigin::Teammate);
+    let teammate = snapshot.per_origin[&RequestOrigin::Teammate];
+    assert_eq!((teammate.requests, teammate.input_tokens, teammate.output_tokens), (1, 12, 4));
+    assert!(!snapshot.per_origin.contains_key(&RequestOrigin::Main));
+}
+
+#[tokio::test]
+async fn usage_is_counted_with_debug_logging_off() {
+    let mock = MockBackend::start().await;
+    mock.enqueue_response(MockResponse::json(
+        r#"{"type":"message","stop_reason":"end_turn","usage":{"input_tokens":12,"output_tokens":4}}"#,
+    ))
+    .await;
+    mock.enqueue_response(MockResponse::sse(&[
+        r#"{"type":"message_start","message":{"usage":{"input_tokens":30,"output_tokens":1}}}"#,
+        r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":6}}"#,
+        r#"{"type":"message_stop"}"#,
+    ]))
+    .await;
+    let (addr, hub) = start_proxy(&mock, DebugLoggingConfig::default().level).await;
+
+    let client = reqwest::Client::new();
+    for body in ["{}", r#"{"stream":true}"#] {
+        client
+            .post(format!("http://{addr}/v1/messages"))
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+    }
+
+    let snapshot = wait_for_requests(&hub, 2).await;
+    let main = snapshot.per_origin[&RequestOrigin::Main];
+    assert_eq!((main.requests, main.input_tokens, main.output_tokens), (2, 42, 10));
+}
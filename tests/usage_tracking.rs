@@ -0,0 +1,93 @@
+//! Tests for streamed token accounting: Anthropic's default usage shape
+//! and per-backend usage mappings.
+
+use anyclaude::config::{BackendPricing, UsageMapping};
+use anyclaude::metrics::{ResponseParser, UsageTracker};
+use anyclaude::sse::SseEventDecoder;
+
+const ANTHROPIC_STREAM: &str = concat!(
+    "event: message_start\n",
+    "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":25,\"output_tokens\":1}}}\n\n",
+    "event: content_block_delta\n",
+    "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"hi\"}}\n\n",
+    "event: message_delta\n",
+    "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":15}}\n\n",
+    "event: message_stop\n",
+    "data: {\"type\":\"message_stop\"}\n\n",
+);
+
+const OPENAI_STREAM: &str = concat!(
+    "data: {\"object\":\"chat.completion.chunk\",\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\n",
+    "data: {\"object\":\"chat.completion.chunk\",\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+    "data: {\"object\":\"chat.completion.chunk\",\"choices\":[],\"usage\":{\"prompt_tokens\":40,\"completion_tokens\":7}}\n\n",
+    "data: [DONE]\n\n",
+);
+
+fn openai_mapping() -> UsageMapping {
+    UsageMapping {
+        event: None,
+        input_tokens: "/usage/prompt_tokens".to_string(),
+        output_tokens: "/usage/completion_tokens".to_string(),
+    }
+}
+
+#[test]
+fn anthropic_stream_counts_by_default() {
+    let analysis = ResponseParser::new().parse_sse(ANTHROPIC_STREAM.as_bytes(), None);
+    assert_eq!(analysis.input_tokens, Some(25));
+    assert_eq!(analysis.output_tokens, Some(15));
+    assert_eq!(analysis.stop_reason.as_deref(), Some("end_turn"));
+}
+
+#[test]
+fn openai_final_usage_chunk_is_counted_with_mapping() {
+    let mapping = openai_mapping();
+    let analysis = ResponseParser::new().parse_sse(OPENAI_STREAM.as_bytes(), Some(&mapping));
+    assert_eq!(analysis.input_tokens, Some(40));
+    assert_eq!(analysis.output_tokens, Some(7));
+}
+
+#[test]
+fn openai_stream_without_mapping_counts_nothing() {
+    let analysis = ResponseParser::new().parse_sse(OPENAI_STREAM.as_bytes(), None);
+    assert_eq!(analysis.input_tokens, None);
+    assert_eq!(analysis.output_tokens, None);
+}
+
+#[test]
+fn mapping_event_filters_by_type() {
+    let mapping = UsageMapping {
+        event: Some("message_stop".to_string()),
+        input_tokens: "/usage/input_tokens".to_string(),
+        output_tokens: "/usage/output_tokens".to_string(),
+    };
+    let stream = concat!(
+        "data: {\"type\":\"message_delta\",\"usage\":{\"input_tokens\":1,\"output_tokens\":1}}\n\n",
+        "data: {\"type\":\"message_stop\",\"usage\":{\"input_tokens\":9,\"output_tokens\":3}}\n\n",
+    );
+    let analysis = ResponseParser::new().parse_sse(stream.as_bytes(), Some(&mapping));
+    assert_eq!(analysis.input_tokens, Some(9));
+    assert_eq!(analysis.output_tokens, Some(3));
+}
+
+#[test]
+fn tracker_handles_chunks_split_mid_line_and_prices_usage() {
+    let pricing = BackendPricing {
+        input_per_million: 1_000_000.0,
+        output_per_million: 2_000_000.0,
+    };
+    let mut tracker = UsageTracker::new(Some(openai_mapping()), Some(pricing));
+    let mut decoder = SseEventDecoder::new();
+    for chunk in OPENAI_STREAM.as_bytes().chunks(7) {
+        for payload in decoder.push_payloads(chunk) {
+            tracker.observe(&payload);
+        }
+    }
+    for payload in decoder.finish_payloads() {
+        tracker.observe(&payload);
+    }
+    let analysis = tracker.finish();
+    assert_eq!(analysis.input_tokens, Some(40));
+    assert_eq!(analysis.output_tokens, Some(7));
+    assert_eq!(analysis.cost_usd, Some(54.0));
+}
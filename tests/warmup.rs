@@ -0,0 +1,116 @@
+//! Startup warmup: with `defaults.warmup_connections` the proxy reaches
+//! each backend before any client request arrives; failures don't stop it.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyclaude::config::{Backend, Config, ConfigStore, Defaults, ProxyConfig};
+use anyclaude::metrics::DebugLogger;
+use anyclaude::proxy::ProxyServer;
+use common::mock_backend::{MockBackend, MockResponse};
+
+async fn start(backends: Vec<Backend>, warmup: bool) -> String {
+    let bind_addr = format!("127.0.0.1:{}", common::free_port());
+    let config = Config {
+        defaults: Defaults {
+            active: backends[0].name.clone(),
+            warmup_connections: warmup,
+            ..Defaults::default()
+        },
+        proxy: ProxyConfig {
+            bind_addr: bind_addr.clone(),
+            ..ProxyConfig::default()
+        },
+        backends,
+        ..Config::default()
+    };
+    let store = ConfigStore::new(config, PathBuf::from("/tmp/test.toml"));
+    let mut server =
+        ProxyServer::new(store.clone(), Arc::new(DebugLogger::new(Default::default())), None).unwrap();
+    let (addr, _) = server.try_bind(&store).await.unwrap();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    format!("http://{addr}")
+}
+
+fn backend(name: &str, base_url: String) -> Backend {
+    Backend {
+        name: name.to_string(),
+        base_url,
+        ..Backend::default()
+    }
+}
+
+/// Wait until `mock` has seen `count` requests, or give up after 5s.
+async fn wait_for_requests(mock: &MockBackend, count: usize) -> Vec<common::mock_backend::CapturedRequest> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let captured = mock.captured_requests().await;
+        if captured.len() >= count || Instant::now() > deadline {
+            return captured;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn warmup_reaches_every_backend_before_any_client_request() {
+    let first = MockBackend::start().await;
+    let second = MockBackend::start().await;
+    // The mock only captures non-root paths, so give the base URLs one.
+    start(
+        vec![
+            backend("first", format!("{}/api", first.base_url())),
+            backend("second", format!("{}/api", second.base_url())),
+        ],
+        true,
+    )
+    .await;
+
+    for mock in [&first, &second] {
+        let captured = wait_for_requests(mock, 1).await;
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0].method, "HEAD");
+        assert_eq!(captured[0].path, "/api");
+    }
+}
+
+#[tokio::test]
+async fn no_warmup_by_default() {
+    let mock = MockBackend::start().await;
+    let url = start(vec![backend("only", format!("{}/api", mock.base_url()))], false).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(mock.captured_requests().await.is_empty());
+
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    reqwest::Client::new().post(format!("{url}/v1/messages")).body("{}").send().await.unwrap();
+    assert_eq!(mock.captured_requests().await[0].method, "POST");
+}
+
+#[tokio::test]
+async fn unreachable_backend_does_not_block_startup() {
+    let mock = MockBackend::start().await;
+    let dead = format!("http://127.0.0.1:{}", common::free_port());
+    let url = start(
+        vec![
+            backend("live", format!("{}/api", mock.base_url())),
+            backend("dead", dead),
+        ],
+        true,
+    )
+    .await;
+
+    assert_eq!(wait_for_requests(&mock, 1).await[0].method, "HEAD");
+    mock.enqueue_response(MockResponse::json(r#"{"ok":true}"#)).await;
+    let resp = reqwest::Client::new()
+        .post(format!("{url}/v1/messages"))
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}